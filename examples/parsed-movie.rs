@@ -13,14 +13,11 @@ fn main() {
     println!("Sound Plugin: {}", movie.plugin_info.sound_plugin);
     println!("Input Plugin: {}", movie.plugin_info.input_plugin);
     println!("RSP Plugin: {}", movie.plugin_info.rsp_plugin);
-    println!(
-        "Controller flags: {:#?}",
-        movie.recording_info.controller_flags
-    );
+    println!("Controller flags: {:#?}", movie.controller_flags());
 
     // Find a nice starting point for the frames.
     let first_frame = movie
-        .inputs
+        .samples()
         .iter()
         .position(|input| input.a_btn() && input.b_btn() && input.axis() != (0, 0))
         .unwrap_or(0);
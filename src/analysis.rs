@@ -0,0 +1,944 @@
+//! Comparisons between movies.
+//!
+//! [`compare`] aligns two movies of the same game against the [`Markers`] they share
+//! and reports the time gained or lost in each segment between consecutive markers,
+//! as published in the comparison tables of improved runs. [`compare_many`] does
+//! the same across any number of revisions, for improvement history tables.
+//!
+//! [`common_prefix_frames`] and [`first_divergence`] find where two input tracks
+//! stop matching, for comparing branches of a run or bisecting a desync.
+//! [`approx_differing_frames`] lists the frames that differ beyond a tolerance on the
+//! stick axes, ignoring calibration noise, and [`diff_matrix`] counts the differing
+//! frames in buckets over the whole movies, to draw where two revisions diverge.
+//!
+//! [`button_timeline`] lists when each button of a controller is pressed and
+//! released, for video overlays and input charts.
+//!
+//! [`neutral_ports`] finds controllers that never leave the neutral state, which
+//! [`Movie::drop_unused_controllers`] removes.
+//!
+//! [`desync_risk`] lists the header fields that must match for a movie to sync on
+//! another setup, scored by how often they are the cause of a desync.
+//!
+//! [`report`] bundles statistics and lint findings into a [`MovieReport`], exported as
+//! JSON or CSV for CI pipelines to archive per commit.
+//!
+//! [`Markers`]: crate::markers::Markers
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display},
+    ops::Range,
+};
+
+use crate::{
+    ControllerButton, Movie, MovieError,
+    lint::{AxisConstraints, AxisIssue},
+    parsed::ExtendedFlags,
+    raw::{ControllerFlags, ControllerState, MovieStartType},
+    track::{FrameIndex, InputTrack},
+};
+
+/// The comparison of a segment between two consecutive shared markers.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SegmentComparison {
+    /// The marker starting the segment, or `None` for the start of the movies.
+    pub start: Option<String>,
+    /// The marker ending the segment, or `None` for the end of the movies.
+    pub end: Option<String>,
+    /// The length of the segment in the baseline movie, in frames.
+    pub baseline_frames: usize,
+    /// The length of the segment in the candidate movie, in frames.
+    pub candidate_frames: usize,
+    /// The length of the segment in the baseline movie, in seconds.
+    pub baseline_seconds: f64,
+    /// The length of the segment in the candidate movie, in seconds.
+    pub candidate_seconds: f64,
+    /// The time saved by the candidate in this segment, in seconds. Negative if
+    /// the candidate is slower.
+    pub seconds_saved: f64,
+    /// The time saved by the candidate up to the end of this segment, in seconds.
+    pub total_seconds_saved: f64,
+}
+
+/// A timeline comparing two movies segment by segment.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Comparison {
+    /// The compared segments, in order.
+    pub segments: Vec<SegmentComparison>,
+    /// The labels of markers that could not be aligned, because they are missing
+    /// from one of the movies or appear out of order.
+    pub unmatched: Vec<String>,
+}
+
+impl Comparison {
+    /// Returns the total time saved by the candidate, in seconds.
+    pub fn seconds_saved(&self) -> f64 {
+        self.segments
+            .last()
+            .map_or(0.0, |segment| segment.total_seconds_saved)
+    }
+}
+
+/// Compares a candidate movie against a baseline, aligning them on their shared markers.
+///
+/// Markers are taken in the baseline's frame order. A shared marker is skipped if it
+/// appears before the previously aligned marker in the candidate, or past the end of
+/// either movie.
+pub fn compare(baseline: &Movie, candidate: &Movie) -> Comparison {
+    let (aligned, unmatched) = align(baseline, candidate);
+
+    let baseline_timing = baseline.timing();
+    let candidate_timing = candidate.timing();
+
+    let mut boundaries = vec![(None, 0, 0)];
+    boundaries.extend(aligned.into_iter().map(|(label, a, b)| (Some(label), a, b)));
+    boundaries.push((None, baseline.frame_count(), candidate.frame_count()));
+
+    let mut segments = Vec::with_capacity(boundaries.len() - 1);
+    let mut total_seconds_saved = 0.0;
+
+    for pair in boundaries.windows(2) {
+        let (start, baseline_start, candidate_start) = &pair[0];
+        let (end, baseline_end, candidate_end) = &pair[1];
+
+        let baseline_seconds = baseline_timing
+            .frame_to_duration(*baseline_end)
+            .saturating_sub(baseline_timing.frame_to_duration(*baseline_start))
+            .as_secs_f64();
+        let candidate_seconds = candidate_timing
+            .frame_to_duration(*candidate_end)
+            .saturating_sub(candidate_timing.frame_to_duration(*candidate_start))
+            .as_secs_f64();
+
+        let seconds_saved = baseline_seconds - candidate_seconds;
+        total_seconds_saved += seconds_saved;
+
+        segments.push(SegmentComparison {
+            start: start.clone(),
+            end: end.clone(),
+            baseline_frames: baseline_end.saturating_sub(*baseline_start),
+            candidate_frames: candidate_end.saturating_sub(*candidate_start),
+            baseline_seconds,
+            candidate_seconds,
+            seconds_saved,
+            total_seconds_saved,
+        });
+    }
+
+    Comparison {
+        segments,
+        unmatched,
+    }
+}
+
+/// The lengths of a segment between two consecutive shared markers across revisions.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SegmentHistory {
+    /// The marker starting the segment, or `None` for the start of the movies.
+    pub start: Option<String>,
+    /// The marker ending the segment, or `None` for the end of the movies.
+    pub end: Option<String>,
+    /// The length of the segment in each revision, in frames.
+    pub frames: Vec<usize>,
+    /// The change in length from the previous revision, in frames. Negative if the
+    /// revision is faster. Always 0 for the first revision.
+    pub deltas: Vec<i64>,
+}
+
+/// The segment lengths of several revisions of a run, as returned by [`compare_many`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct History {
+    /// The compared segments, in order.
+    pub segments: Vec<SegmentHistory>,
+    /// The length of each revision, in frames.
+    pub total_frames: Vec<usize>,
+    /// The labels of markers that could not be aligned, because they are missing
+    /// from one of the revisions or appear out of order.
+    pub unmatched: Vec<String>,
+}
+
+/// Compares revisions of the same run segment by segment, aligning them on the
+/// markers shared by every revision.
+///
+/// Markers are taken in the first revision's frame order. A shared marker is
+/// skipped if it appears before the previously aligned marker in any revision.
+pub fn compare_many(movies: &[&Movie]) -> History {
+    let Some(first) = movies.first() else {
+        return History::default();
+    };
+
+    let mut unmatched = BTreeSet::new();
+    let mut boundaries = vec![(None, vec![0; movies.len()])];
+
+    for marker in first.markers().iter() {
+        let frames = movies
+            .iter()
+            .map(|movie| movie.markers().get(&marker.label))
+            .collect::<Option<Vec<_>>>();
+        let last = &boundaries[boundaries.len() - 1].1;
+
+        match frames {
+            Some(frames) if frames.iter().zip(last).all(|(frame, last)| frame >= last) => {
+                boundaries.push((Some(marker.label), frames));
+            }
+            _ => {
+                unmatched.insert(marker.label);
+            }
+        }
+    }
+
+    for movie in &movies[1..] {
+        for marker in movie.markers().iter() {
+            if first.markers().get(&marker.label).is_none() {
+                unmatched.insert(marker.label);
+            }
+        }
+    }
+
+    let total_frames: Vec<usize> = movies.iter().map(|movie| movie.frame_count()).collect();
+    boundaries.push((None, total_frames.clone()));
+
+    let segments = boundaries
+        .windows(2)
+        .map(|pair| {
+            let (start, starts) = &pair[0];
+            let (end, ends) = &pair[1];
+            let frames: Vec<usize> = ends
+                .iter()
+                .zip(starts)
+                .map(|(end, start)| end.saturating_sub(*start))
+                .collect();
+            let deltas = (0..frames.len())
+                .map(|i| match i {
+                    0 => 0,
+                    _ => frames[i] as i64 - frames[i - 1] as i64,
+                })
+                .collect();
+
+            SegmentHistory {
+                start: start.clone(),
+                end: end.clone(),
+                frames,
+                deltas,
+            }
+        })
+        .collect();
+
+    History {
+        segments,
+        total_frames,
+        unmatched: unmatched.into_iter().collect(),
+    }
+}
+
+/// Returns the number of leading frames with the same state in both tracks.
+///
+/// The tracks may have different lengths; the result is at most the shorter length.
+pub fn common_prefix_frames(a: &InputTrack, b: &InputTrack) -> usize {
+    a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count()
+}
+
+/// Returns the first frame at which the tracks differ, or `None` if they are identical.
+///
+/// If one track is a prefix of the other, they diverge at the end of the shorter one.
+pub fn first_divergence(a: &InputTrack, b: &InputTrack) -> Option<FrameIndex> {
+    let prefix = common_prefix_frames(a, b);
+    (prefix < a.len().max(b.len())).then_some(prefix)
+}
+
+/// Returns the frames on which two movies differ, treating controller states as equal
+/// if they match with [`ControllerState::approx_eq`].
+///
+/// Frames past the end of the shorter movie, and ports missing from one of the
+/// movies, count as differing.
+pub fn approx_differing_frames(a: &Movie, b: &Movie, axis_tolerance: u8) -> Vec<FrameIndex> {
+    let frame_count = a.frame_count().max(b.frame_count());
+    let port_count = a.controller_count().max(b.controller_count());
+
+    (0..frame_count)
+        .filter(|&frame| {
+            (0..port_count).any(|port| {
+                match (a.port_state(frame, port), b.port_state(frame, port)) {
+                    (Some(a), Some(b)) => !a.approx_eq(b, axis_tolerance),
+                    (a, b) => a != b,
+                }
+            })
+        })
+        .collect()
+}
+
+/// The characters [`DiffMatrix`] is drawn with, from no difference to every frame
+/// differing.
+const DIFF_SHADES: &[u8] = b" .:-=+*#%@";
+
+/// The number of differing frames in consecutive buckets of frames, for each
+/// controller port. See [`diff_matrix`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffMatrix {
+    /// The number of frames per bucket. The last bucket may be shorter.
+    pub bucket: usize,
+    /// The number of frames compared, the length of the longer movie.
+    pub frame_count: usize,
+    /// The number of differing frames in each bucket, for each port.
+    pub ports: Vec<Vec<usize>>,
+    /// The number of frames in each bucket on which any port differs.
+    pub combined: Vec<usize>,
+}
+
+impl DiffMatrix {
+    /// Returns the number of buckets.
+    pub fn len(&self) -> usize {
+        self.combined.len()
+    }
+
+    /// Returns `true` if there are no buckets, i.e. both movies are empty.
+    pub fn is_empty(&self) -> bool {
+        self.combined.is_empty()
+    }
+
+    /// Returns the frames of a bucket.
+    pub fn bucket_frames(&self, bucket: usize) -> Range<FrameIndex> {
+        let start = (bucket * self.bucket).min(self.frame_count);
+        start..(start + self.bucket).min(self.frame_count)
+    }
+
+    /// Returns the buckets in which any port differs.
+    pub fn differing_buckets(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&bucket| self.combined[bucket] > 0)
+            .collect()
+    }
+}
+
+/// Draws one line per port, then a line for all ports, with one character per bucket
+/// shaded by the fraction of differing frames. Any difference is drawn at least as `.`.
+impl Display for DiffMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let strip = |counts: &[usize]| -> String {
+            counts
+                .iter()
+                .enumerate()
+                .map(|(bucket, &count)| {
+                    let len = self.bucket_frames(bucket).len().max(1);
+                    let top = DIFF_SHADES.len() - 1;
+                    let shade = match count {
+                        0 => 0,
+                        _ => (count * top).div_ceil(len).clamp(1, top),
+                    };
+                    DIFF_SHADES[shade] as char
+                })
+                .collect()
+        };
+
+        for (port, counts) in self.ports.iter().enumerate() {
+            writeln!(f, "port {port} |{}|", strip(counts))?;
+        }
+        writeln!(f, "all    |{}|", strip(&self.combined))
+    }
+}
+
+/// Counts the frames on which two movies differ, in buckets of `bucket` frames, for
+/// each controller port and for all ports together.
+///
+/// Frames past the end of the shorter movie, and ports missing from one of the
+/// movies, count as differing. A bucket of 0 frames is treated as 1.
+pub fn diff_matrix(a: &Movie, b: &Movie, bucket: usize) -> DiffMatrix {
+    let bucket = bucket.max(1);
+    let frame_count = a.frame_count().max(b.frame_count());
+    let port_count = a.controller_count().max(b.controller_count());
+    let buckets = frame_count.div_ceil(bucket);
+
+    let mut ports = vec![vec![0; buckets]; port_count];
+    let mut combined = vec![0; buckets];
+    for frame in 0..frame_count {
+        let mut differs = false;
+        for (port, counts) in ports.iter_mut().enumerate() {
+            if a.port_state(frame, port) != b.port_state(frame, port) {
+                counts[frame / bucket] += 1;
+                differs = true;
+            }
+        }
+
+        if differs {
+            combined[frame / bucket] += 1;
+        }
+    }
+
+    DiffMatrix {
+        bucket,
+        frame_count,
+        ports,
+        combined,
+    }
+}
+
+/// A press of a button, from the frame it is pressed on to the frame it is released on.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ButtonPress {
+    /// The first frame on which the button is held.
+    pub press_frame: FrameIndex,
+    /// The first frame on which the button is no longer held, or the frame count if it
+    /// is held until the end.
+    pub release_frame: FrameIndex,
+}
+
+/// The presses of every button of a controller. See [`button_timeline`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ButtonTimeline {
+    /// The controller port.
+    pub port: usize,
+    /// The number of frames of the movie.
+    pub frame_count: usize,
+    /// The presses of each button that is pressed at least once, in order.
+    pub buttons: BTreeMap<ControllerButton, Vec<ButtonPress>>,
+}
+
+impl ButtonTimeline {
+    /// Returns the presses of a button, in order.
+    pub fn presses(&self, button: ControllerButton) -> &[ButtonPress] {
+        self.buttons.get(&button).map_or(&[], Vec::as_slice)
+    }
+
+    /// Serializes the timeline to pretty-printed JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, MovieError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parses a timeline from JSON.
+    #[cfg(feature = "json")]
+    pub fn from_json(s: &str) -> Result<Self, MovieError> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
+
+/// Lists, for each button of a controller, the frames it is pressed and released on.
+pub fn button_timeline(movie: &Movie, port: usize) -> Result<ButtonTimeline, MovieError> {
+    movie.check_port(port)?;
+
+    let frame_count = movie.frame_count();
+    let mut buttons: BTreeMap<ControllerButton, Vec<ButtonPress>> = BTreeMap::new();
+    let mut held: BTreeMap<ControllerButton, FrameIndex> = BTreeMap::new();
+    for frame in 0..frame_count {
+        let state = movie.port_state(frame, port).copied().unwrap_or_default();
+        for button in ControllerButton::iter() {
+            match (state.is_set(button), held.get(&button)) {
+                (true, None) => {
+                    held.insert(button, frame);
+                }
+                (false, Some(&press_frame)) => {
+                    held.remove(&button);
+                    buttons.entry(button).or_default().push(ButtonPress {
+                        press_frame,
+                        release_frame: frame,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for (button, press_frame) in held {
+        buttons.entry(button).or_default().push(ButtonPress {
+            press_frame,
+            release_frame: frame_count,
+        });
+    }
+
+    Ok(ButtonTimeline {
+        port,
+        frame_count,
+        buttons,
+    })
+}
+
+/// Returns the controller ports whose state is neutral on every frame, such as
+/// controllers accidentally enabled while recording.
+pub fn neutral_ports(movie: &Movie) -> Vec<usize> {
+    let count = movie.controller_count();
+    (0..count)
+        .filter(|&port| {
+            movie
+                .samples()
+                .iter()
+                .skip(port)
+                .step_by(count)
+                .all(|&state| state == ControllerState::default())
+        })
+        .collect()
+}
+
+impl Movie {
+    /// Removes the controllers whose state is neutral on every frame, returning the
+    /// ports that were removed.
+    ///
+    /// The presence and pak flags of the removed controllers are cleared, and the
+    /// remaining controllers keep their order. At least one controller is kept, so if
+    /// every controller is neutral, the first one stays.
+    pub fn drop_unused_controllers(&mut self) -> Vec<usize> {
+        if self.controller_count() == 0 {
+            return Vec::new();
+        }
+
+        let mut unused = neutral_ports(self);
+        if unused.len() == self.controller_count() {
+            unused.remove(0);
+        }
+        if unused.is_empty() {
+            return unused;
+        }
+
+        let count = self.controller_count();
+        let inputs = std::mem::take(&mut self.inputs)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !unused.contains(&(index % count)))
+            .map(|(_, state)| state)
+            .collect();
+
+        // Ports are numbered among the present controllers, so map them to the
+        // physical controllers they stand for.
+        let flags = u32::from(self.controller_flags());
+        let physical: Vec<u32> = (0..4).filter(|bit| flags & (1 << bit) != 0).collect();
+        let cleared = unused
+            .iter()
+            .map(|&port| 0x111 << physical[port])
+            .fold(0, |mask, bits| mask | bits);
+
+        self.set_controller_flags(ControllerFlags::from(flags & !cleared))
+            .expect("the movie has no inputs while its flags change");
+        self.inputs = inputs;
+
+        unused
+    }
+
+    /// Returns `true` if both movies have the same controllers and frames, treating
+    /// controller states as equal if they match with [`ControllerState::approx_eq`].
+    /// Header fields are not compared.
+    pub fn inputs_approx_eq(&self, other: &Movie, axis_tolerance: u8) -> bool {
+        self.controller_count() == other.controller_count()
+            && self.inputs.len() == other.inputs.len()
+            && self
+                .inputs
+                .iter()
+                .zip(&other.inputs)
+                .all(|(a, b)| a.approx_eq(b, axis_tolerance))
+    }
+}
+
+/// How likely a header field is to cause a desync on another setup.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum RiskLevel {
+    /// The field is known to be safe.
+    None,
+    /// The field rarely matters, but is worth matching when a movie desyncs.
+    Low,
+    /// The field must be matched for the movie to sync in many games.
+    Medium,
+    /// The field is a common cause of desyncs and must be matched exactly.
+    High,
+}
+
+impl RiskLevel {
+    /// Returns the weight of the level in [`DesyncRisk::score`].
+    pub fn weight(self) -> u32 {
+        match self {
+            RiskLevel::None => 0,
+            RiskLevel::Low => 1,
+            RiskLevel::Medium => 3,
+            RiskLevel::High => 5,
+        }
+    }
+}
+
+impl Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskLevel::None => write!(f, "NONE"),
+            RiskLevel::Low => write!(f, "LOW"),
+            RiskLevel::Medium => write!(f, "MEDIUM"),
+            RiskLevel::High => write!(f, "HIGH"),
+        }
+    }
+}
+
+/// A single entry of a [`DesyncRisk`] checklist.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RiskItem {
+    /// A short name identifying the field checked.
+    pub name: &'static str,
+    /// How likely the field is to cause a desync.
+    pub level: RiskLevel,
+    /// What has to match for the movie to sync.
+    pub message: String,
+}
+
+/// The fields of a movie that must match between setups, as returned by
+/// [`desync_risk`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DesyncRisk {
+    /// All fields checked, in order.
+    pub items: Vec<RiskItem>,
+}
+
+impl DesyncRisk {
+    /// Returns the sum of the [`RiskLevel::weight`] of every item. Zero means the
+    /// movie is expected to sync on any common setup.
+    pub fn score(&self) -> u32 {
+        self.items.iter().map(|item| item.level.weight()).sum()
+    }
+
+    /// Returns the highest risk level of the checklist.
+    pub fn highest(&self) -> RiskLevel {
+        self.items
+            .iter()
+            .map(|item| item.level)
+            .max()
+            .unwrap_or(RiskLevel::None)
+    }
+
+    /// Returns an iterator over the items at or above the given level.
+    pub fn at_least(&self, level: RiskLevel) -> impl Iterator<Item = &RiskItem> {
+        self.items.iter().filter(move |item| item.level >= level)
+    }
+
+    /// Records a checked field.
+    fn push(&mut self, name: &'static str, level: RiskLevel, message: impl Into<String>) {
+        self.items.push(RiskItem {
+            name,
+            level,
+            message: message.into(),
+        });
+    }
+}
+
+impl Display for DesyncRisk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for item in &self.items {
+            writeln!(f, "[{}] {}: {}", item.level, item.name, item.message)?;
+        }
+
+        write!(f, "Score: {}", self.score())
+    }
+}
+
+/// The plugins most movies are recorded with, which every TAS setup is expected to
+/// have. Names are matched by prefix, ignoring the version.
+const STANDARD_PLUGINS: [(&str, &[&str]); 4] = [
+    ("Video plugin", &["Jabo's Direct3D8"]),
+    ("Sound plugin", &["Jabo's DirectSound", "Azimer's"]),
+    ("Input plugin", &["TAS Input"]),
+    ("RSP plugin", &["RSP emulation Plugin"]),
+];
+
+/// Returns a checklist of the header fields known to cause desyncs when a movie is
+/// played back on a different setup than it was recorded on.
+///
+/// The fields checked are the plugins, the start type and the companion files it
+/// requires, the WiiVC emulation flag, the controller accessories and the refresh
+/// rate. The checklist only reads the header; use [`verify`](crate::verify::verify)
+/// to check the actual ROM and companion files.
+pub fn desync_risk(movie: &Movie) -> DesyncRisk {
+    let mut risk = DesyncRisk::default();
+
+    let plugins = &movie.plugin_info;
+    let names = [
+        &plugins.video_plugin,
+        &plugins.sound_plugin,
+        &plugins.input_plugin,
+        &plugins.rsp_plugin,
+    ];
+    for ((field, standard), name) in STANDARD_PLUGINS.into_iter().zip(names) {
+        let name = name.to_string();
+        let name = name.trim_end();
+        if name.is_empty() {
+            risk.push(
+                field,
+                RiskLevel::Medium,
+                "plugin was not recorded, the original setup is unknown",
+            );
+        } else if standard.iter().any(|prefix| name.starts_with(prefix)) {
+            risk.push(field, RiskLevel::None, format!("standard plugin {name:?}"));
+        } else if field == "RSP plugin" {
+            risk.push(
+                field,
+                RiskLevel::High,
+                format!("RSP timing affects lag in many games, {name:?} is required"),
+            );
+        } else {
+            risk.push(
+                field,
+                RiskLevel::Low,
+                format!("non-standard plugin {name:?}, use it if the movie desyncs"),
+            );
+        }
+    }
+
+    match movie.recording_info.start_type {
+        MovieStartType::PowerOn => {
+            risk.push("Start type", RiskLevel::None, "movie starts from power-on")
+        }
+        MovieStartType::EEPROM => risk.push(
+            "Start type",
+            RiskLevel::Medium,
+            "movie starts from EEPROM, the exact save file is required",
+        ),
+        MovieStartType::Snapshot => risk.push(
+            "Start type",
+            RiskLevel::High,
+            "movie starts from a snapshot, the exact savestate and a compatible emulator version are required",
+        ),
+    }
+
+    match movie.metadata.extended_flags {
+        ExtendedFlags::ExtendedFlagsV1 {
+            wiivc_emulation_mode: true,
+        } => risk.push(
+            "WiiVC mode",
+            RiskLevel::Medium,
+            "recorded with WiiVC emulation, which changes timing in some games",
+        ),
+        _ => risk.push("WiiVC mode", RiskLevel::None, "WiiVC emulation is off"),
+    }
+
+    let flags = movie.controller_flags();
+    let paks = [
+        (
+            flags.controller_01_has_mempak(),
+            flags.controller_01_has_rumblepak(),
+        ),
+        (
+            flags.controller_02_has_mempak(),
+            flags.controller_02_has_rumblepak(),
+        ),
+        (
+            flags.controller_03_has_mempak(),
+            flags.controller_03_has_rumblepak(),
+        ),
+        (
+            flags.controller_04_has_mempak(),
+            flags.controller_04_has_rumblepak(),
+        ),
+    ];
+    let mempaks = paks.iter().filter(|(mempak, _)| *mempak).count();
+    let rumblepaks = paks.iter().filter(|(_, rumblepak)| *rumblepak).count();
+    if mempaks > 0 {
+        risk.push(
+            "Controller paks",
+            RiskLevel::Medium,
+            format!("{mempaks} memory pak(s), whose contents are not stored in the movie"),
+        );
+    } else if rumblepaks > 0 {
+        risk.push(
+            "Controller paks",
+            RiskLevel::Low,
+            format!("{rumblepaks} rumble pak(s), which change what games detect"),
+        );
+    } else {
+        risk.push("Controller paks", RiskLevel::None, "no accessories");
+    }
+
+    let timing = movie.timing();
+    if movie.recording_info.vis_per_second == 0 {
+        risk.push(
+            "VI/s",
+            RiskLevel::Low,
+            format!(
+                "refresh rate not recorded, assuming {} VI/s",
+                timing.vis_per_second()
+            ),
+        );
+    } else if timing.is_off_region() {
+        risk.push(
+            "VI/s",
+            RiskLevel::High,
+            format!(
+                "{} VI/s does not match the {:?} ROM region",
+                timing.vis_per_second(),
+                timing.region()
+            ),
+        );
+    } else {
+        risk.push(
+            "VI/s",
+            RiskLevel::None,
+            format!("{} VI/s", timing.vis_per_second()),
+        );
+    }
+
+    risk
+}
+
+/// Returns the shared markers that appear in the same order in both sets, with their
+/// frames in each, and the labels of the remaining markers.
+fn align(baseline: &Movie, candidate: &Movie) -> (Vec<(String, usize, usize)>, Vec<String>) {
+    let (baseline_end, candidate_end) = (baseline.frame_count(), candidate.frame_count());
+    let (baseline, candidate) = (baseline.markers(), candidate.markers());
+    let mut aligned = Vec::new();
+    let mut unmatched = BTreeSet::new();
+    let mut last_candidate_frame = 0;
+
+    for marker in baseline.iter() {
+        match candidate.get(&marker.label) {
+            Some(frame)
+                if frame >= last_candidate_frame
+                    && frame <= candidate_end
+                    && marker.frame <= baseline_end =>
+            {
+                last_candidate_frame = frame;
+                aligned.push((marker.label, marker.frame, frame));
+            }
+            _ => {
+                unmatched.insert(marker.label);
+            }
+        }
+    }
+
+    for marker in candidate.iter() {
+        if baseline.get(&marker.label).is_none() {
+            unmatched.insert(marker.label);
+        }
+    }
+
+    (aligned, unmatched.into_iter().collect())
+}
+
+/// The shortest run of neutral frames listed in [`MovieReport::idle_ranges`]: half a
+/// second at 60 frames per second.
+pub const REPORT_MIN_IDLE_FRAMES: usize = 30;
+
+/// Statistics and lint findings of a movie, for archiving. See [`report`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MovieReport {
+    /// The number of frames.
+    pub frame_count: usize,
+    /// The number of controllers present.
+    pub controller_count: usize,
+    /// The number of vertical interrupts.
+    pub vertical_interrupts: u32,
+    /// The length of the movie in seconds, from its VI count.
+    pub duration_seconds: f64,
+    /// The number of rerecords.
+    pub rerecord_count: u32,
+    /// The number of times each button is pressed, over every controller. Buttons
+    /// that are never pressed are omitted.
+    pub press_counts: BTreeMap<ControllerButton, usize>,
+    /// The runs of at least [`REPORT_MIN_IDLE_FRAMES`] frames on which every
+    /// controller is neutral, in order.
+    pub idle_ranges: Vec<Range<FrameIndex>>,
+    /// The samples whose stick position breaks the default [`AxisConstraints`].
+    pub axis_issues: Vec<AxisIssue>,
+}
+
+impl MovieReport {
+    /// Returns the number of frames in [`MovieReport::idle_ranges`].
+    pub fn idle_frames(&self) -> usize {
+        self.idle_ranges.iter().map(ExactSizeIterator::len).sum()
+    }
+
+    /// Serializes the report to pretty-printed JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, MovieError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parses a report from JSON.
+    #[cfg(feature = "json")]
+    pub fn from_json(s: &str) -> Result<Self, MovieError> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// Formats the report as CSV, with a `metric,value` header and one metric per row.
+    ///
+    /// Press counts are listed as `presses.<button>`, and idle ranges and axis issues
+    /// are summarized by their counts.
+    pub fn to_csv(&self) -> String {
+        let mut rows = vec![
+            ("frame_count".to_string(), self.frame_count.to_string()),
+            (
+                "controller_count".to_string(),
+                self.controller_count.to_string(),
+            ),
+            (
+                "vertical_interrupts".to_string(),
+                self.vertical_interrupts.to_string(),
+            ),
+            (
+                "duration_seconds".to_string(),
+                self.duration_seconds.to_string(),
+            ),
+            (
+                "rerecord_count".to_string(),
+                self.rerecord_count.to_string(),
+            ),
+        ];
+        rows.extend(
+            self.press_counts
+                .iter()
+                .map(|(button, count)| (format!("presses.{button:?}"), count.to_string())),
+        );
+        rows.push((
+            "idle_ranges".to_string(),
+            self.idle_ranges.len().to_string(),
+        ));
+        rows.push(("idle_frames".to_string(), self.idle_frames().to_string()));
+        rows.push((
+            "axis_issues".to_string(),
+            self.axis_issues.len().to_string(),
+        ));
+
+        let mut csv = String::from("metric,value\n");
+        for (metric, value) in rows {
+            csv.push_str(&format!("{metric},{value}\n"));
+        }
+
+        csv
+    }
+}
+
+/// Builds the statistics and lint findings of a movie.
+pub fn report(movie: &Movie) -> MovieReport {
+    let mut press_counts = BTreeMap::new();
+    for port in 0..movie.controller_count() {
+        let timeline = button_timeline(movie, port).expect("the port is present");
+        for (button, presses) in timeline.buttons {
+            *press_counts.entry(button).or_default() += presses.len();
+        }
+    }
+
+    let mut idle_ranges: Vec<Range<FrameIndex>> = Vec::new();
+    for (frame, states) in movie.frames().enumerate() {
+        if states
+            .iter()
+            .any(|&state| state != ControllerState::default())
+        {
+            continue;
+        }
+
+        match idle_ranges.last_mut() {
+            Some(last) if last.end == frame => last.end += 1,
+            _ => idle_ranges.push(frame..frame + 1),
+        }
+    }
+    idle_ranges.retain(|range| range.len() >= REPORT_MIN_IDLE_FRAMES);
+
+    MovieReport {
+        frame_count: movie.frame_count(),
+        controller_count: movie.controller_count(),
+        vertical_interrupts: movie.recording_info.vertical_interrupts,
+        duration_seconds: movie.duration().as_secs_f64(),
+        rerecord_count: movie.recording_info.rerecord_count,
+        press_counts,
+        idle_ranges,
+        axis_issues: movie.lint_axes(&AxisConstraints::new()),
+    }
+}
@@ -0,0 +1,82 @@
+//! Free-text notes attached to movie frames.
+
+use std::ops::RangeBounds;
+
+use crate::bookmarks::Bookmarks;
+
+/// A map from frame index to a free-text note.
+///
+/// Annotations are not part of the M64 format. They are persisted alongside the
+/// movie in a [`Sidecar`](crate::sidecar::Sidecar) file.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Annotations {
+    /// The notes, keyed by frame index.
+    notes: Bookmarks<String>,
+}
+
+impl Annotations {
+    /// Creates an empty set of annotations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attaches a note to a frame, returning the note it replaced, if any.
+    pub fn insert<S: Into<String>>(&mut self, frame: usize, note: S) -> Option<String> {
+        self.notes.insert(frame, note.into())
+    }
+
+    /// Returns the note attached to a frame.
+    pub fn get(&self, frame: usize) -> Option<&str> {
+        self.notes.get(frame).map(String::as_str)
+    }
+
+    /// Removes the note attached to a frame, returning it.
+    pub fn remove(&mut self, frame: usize) -> Option<String> {
+        self.notes.remove(frame)
+    }
+
+    /// Returns an iterator over the notes within a range of frames, in frame order.
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> impl Iterator<Item = (usize, &str)> {
+        self.notes
+            .range(range)
+            .map(|(frame, note)| (frame, note.as_str()))
+    }
+
+    /// Returns the last note at or before a frame.
+    pub fn nearest_before(&self, frame: usize) -> Option<(usize, &str)> {
+        self.notes
+            .nearest_before(frame)
+            .map(|(frame, note)| (frame, note.as_str()))
+    }
+
+    /// Returns an iterator over all notes, in frame order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.range(..)
+    }
+
+    /// Returns the number of annotated frames.
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    /// Returns `true` if no frame is annotated.
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Removes all notes.
+    pub fn clear(&mut self) {
+        self.notes.clear();
+    }
+}
+
+impl<S: Into<String>> FromIterator<(usize, S)> for Annotations {
+    fn from_iter<I: IntoIterator<Item = (usize, S)>>(iter: I) -> Self {
+        Annotations {
+            notes: iter
+                .into_iter()
+                .map(|(frame, note)| (frame, note.into()))
+                .collect(),
+        }
+    }
+}
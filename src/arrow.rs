@@ -0,0 +1,86 @@
+//! Export of movie inputs as Apache Arrow record batches and Parquet files.
+//!
+//! Every controller sample becomes one row with the columns:
+//!
+//! | Column    | Type     | Description                                           |
+//! |-----------|----------|-------------------------------------------------------|
+//! | `frame`   | `UInt64` | The frame index.                                      |
+//! | `port`    | `UInt8`  | The controller port, starting at 0.                  |
+//! | `buttons` | `UInt16` | The button bits, as encoded in the M64 file.          |
+//! | `x`       | `Int8`   | The analog x-axis value.                              |
+//! | `y`       | `Int8`   | The analog y-axis value.                              |
+//!
+//! The files can be loaded directly with polars or pandas.
+
+use std::{fs::File, io::Write, path::Path, sync::Arc};
+
+use arrow_array::{Int8Array, RecordBatch, UInt8Array, UInt16Array, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+
+use crate::{Movie, MovieError, raw::ControllerState};
+
+/// Returns the schema of the record batches produced by this module.
+pub fn schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("frame", DataType::UInt64, false),
+        Field::new("port", DataType::UInt8, false),
+        Field::new("buttons", DataType::UInt16, false),
+        Field::new("x", DataType::Int8, false),
+        Field::new("y", DataType::Int8, false),
+    ]))
+}
+
+/// Converts samples interleaved by controller, as in [`Movie::samples`], to a record
+/// batch with one row per sample.
+pub fn record_batch(
+    samples: &[ControllerState],
+    controller_count: usize,
+) -> Result<RecordBatch, MovieError> {
+    let controller_count = controller_count.max(1);
+    let rows = 0..samples.len();
+
+    let columns: Vec<Arc<dyn arrow_array::Array>> = vec![
+        Arc::new(
+            rows.clone()
+                .map(|i| (i / controller_count) as u64)
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(
+            rows.map(|i| (i % controller_count) as u8)
+                .collect::<UInt8Array>(),
+        ),
+        Arc::new(
+            samples
+                .iter()
+                .map(|&state| u32::from(state) as u16)
+                .collect::<UInt16Array>(),
+        ),
+        Arc::new(samples.iter().map(|s| s.x_axis()).collect::<Int8Array>()),
+        Arc::new(samples.iter().map(|s| s.y_axis()).collect::<Int8Array>()),
+    ];
+
+    Ok(RecordBatch::try_new(schema(), columns)?)
+}
+
+impl Movie {
+    /// Converts the inputs of the movie to a record batch. See the
+    /// [module documentation](crate::arrow) for the columns.
+    pub fn to_record_batch(&self) -> Result<RecordBatch, MovieError> {
+        record_batch(self.samples(), self.controller_count())
+    }
+
+    /// Writes the inputs of the movie as a Parquet file.
+    pub fn write_parquet<W: Write + Send>(&self, writer: W) -> Result<(), MovieError> {
+        let batch = self.to_record_batch()?;
+        let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+
+    /// Writes the inputs of the movie to a Parquet file at the path.
+    pub fn to_parquet_file<P: AsRef<Path>>(&self, path: P) -> Result<(), MovieError> {
+        self.write_parquet(File::create(path)?)
+    }
+}
@@ -0,0 +1,153 @@
+//! Command-line utilities for working with Mupen64 movie files.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+#[cfg(feature = "cache")]
+use m64_movie::cache::SummaryCache;
+use m64_movie::{
+    BinReadExt, Movie, MovieError,
+    summary::MovieSummary,
+    verify::{self, Companions},
+};
+
+/// Command-line utilities for Mupen64 movie files.
+#[derive(Debug, Parser)]
+#[command(name = "m64", version, about)]
+struct Cli {
+    /// The command to run.
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// The available commands.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the header information of a movie.
+    Info {
+        /// The movie file to read, or `-` to read it from standard input.
+        movie: PathBuf,
+        /// A summary cache file, reused to skip parsing movies seen before.
+        #[cfg(feature = "cache")]
+        #[arg(long)]
+        cache: Option<PathBuf>,
+    },
+    /// Verify a movie against a ROM and the companion files it starts from.
+    Verify {
+        /// The movie file to verify.
+        movie: PathBuf,
+        /// The ROM image the movie is played back with.
+        #[arg(long)]
+        rom: PathBuf,
+        /// The savestate the movie starts from.
+        #[arg(long)]
+        st: Option<PathBuf>,
+        /// The save file (EEPROM, SRAM, FlashRAM) the movie starts from.
+        #[arg(long)]
+        save: Option<PathBuf>,
+    },
+}
+
+/// The path of a summary cache file, which can only be given with the `cache` feature.
+#[cfg(feature = "cache")]
+type CachePath = PathBuf;
+/// The path of a summary cache file, which can only be given with the `cache` feature.
+#[cfg(not(feature = "cache"))]
+type CachePath = std::convert::Infallible;
+
+/// Runs the `info` command, reading the movie from standard input if its path is `-`.
+fn run_info(movie: PathBuf, cache: Option<CachePath>) -> Result<bool, MovieError> {
+    let summary = if movie.as_os_str() == "-" {
+        Movie::from_reader(io::stdin().lock())?.summary()
+    } else if let Some(cache) = cache {
+        cached_summary(movie, cache)?
+    } else {
+        Movie::from_reader(BufReader::new(File::open(movie)?))?.summary()
+    };
+
+    println!(
+        "ROM:          {} ({:08X})",
+        summary.rom_name, summary.rom_crc32
+    );
+    println!("Author:       {}", summary.author);
+    println!("Description:  {}", summary.description);
+    println!("Start type:   {}", summary.start_type);
+    println!("Controllers:  {}", summary.controller_count);
+    println!("Frames:       {}", summary.frame_count);
+    println!("VIs:          {}", summary.vertical_interrupts);
+    println!("VI/s:         {}", summary.vis_per_second);
+    println!("Rerecords:    {}", summary.rerecord_count);
+    println!("Duration:     {:.3}s", summary.duration.as_secs_f64());
+
+    Ok(true)
+}
+
+/// Returns the summary of a movie through the cache stored at `cache`, saving it
+/// afterwards.
+#[cfg(feature = "cache")]
+fn cached_summary(movie: PathBuf, cache: CachePath) -> Result<MovieSummary, MovieError> {
+    let mut cache = SummaryCache::open(cache)?;
+    let summary = cache.summary_of_file(movie)?;
+    cache.save()?;
+    Ok(summary)
+}
+
+/// Without the `cache` feature, no cache path can be given.
+#[cfg(not(feature = "cache"))]
+fn cached_summary(_movie: PathBuf, cache: CachePath) -> Result<MovieSummary, MovieError> {
+    match cache {}
+}
+
+/// Runs the `verify` command, returning whether verification passed.
+fn run_verify(
+    movie: PathBuf,
+    rom: PathBuf,
+    st: Option<PathBuf>,
+    save: Option<PathBuf>,
+) -> Result<bool, MovieError> {
+    let movie = Movie::from_file(movie)?;
+    let rom = fs::read(rom)?;
+    let savestate = st.map(fs::read).transpose()?;
+    let save = save.map(fs::read).transpose()?;
+
+    let companions = Companions {
+        savestate: savestate.as_deref(),
+        save: save.as_deref(),
+    };
+
+    let report = verify::verify(&movie, &rom, &companions)?;
+    println!("{}", report);
+
+    Ok(report.passed())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        #[cfg(feature = "cache")]
+        Command::Info { movie, cache } => run_info(movie, cache),
+        #[cfg(not(feature = "cache"))]
+        Command::Info { movie } => run_info(movie, None),
+        Command::Verify {
+            movie,
+            rom,
+            st,
+            save,
+        } => run_verify(movie, rom, st, save),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Attribution of frames to the author or tool that last changed them.
+//!
+//! A [`Blame`] tags each frame of a movie with who last modified it, so collaborative
+//! projects can answer "who changed frames 10000 to 10200?". A
+//! [`MovieEditor`](crate::edit::MovieEditor) keeps it up to date with
+//! [`MovieEditor::track_blame`](crate::edit::MovieEditor::track_blame), and a
+//! [`Bundle`](crate::bundle::Bundle) persists it.
+//!
+//! Blame is stored as text, listing the inclusive ranges of attributed frames:
+//!
+//! ```text
+//! # m64-movie blame
+//! frames 7416
+//! 0-999 Alice
+//! 1000-1199 bruteforcer
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+};
+
+use crate::{BlameError, MovieError, counters::MAX_FRAME_COUNT};
+
+/// The comment written at the top of every blame file.
+const BLAME_HEADER: &str = "# m64-movie blame";
+
+/// A range of consecutive frames attributed to the same author.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BlameRange<'a> {
+    /// The frames of the range.
+    pub frames: Range<usize>,
+    /// Who last changed the frames, or `None` if they are unattributed.
+    pub author: Option<&'a str>,
+}
+
+/// The author or tool that last changed each frame of a movie.
+#[derive(Debug, Clone, Default)]
+pub struct Blame {
+    /// The distinct author names.
+    authors: Vec<String>,
+    /// The index of the author of each frame in `authors`, if attributed.
+    frames: Vec<Option<usize>>,
+}
+
+impl Blame {
+    /// Creates a blame for `frames` unattributed frames.
+    pub fn new(frames: usize) -> Self {
+        Blame {
+            authors: Vec::new(),
+            frames: vec![None; frames],
+        }
+    }
+
+    /// Returns the number of frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if there are no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns who last changed a frame, or `None` if it is unattributed or does not exist.
+    pub fn author_of(&self, frame: usize) -> Option<&str> {
+        let author = (*self.frames.get(frame)?)?;
+        Some(&self.authors[author])
+    }
+
+    /// Attributes a range of frames to an author. Frames past the end are ignored.
+    pub fn attribute(&mut self, frames: Range<usize>, author: &str) {
+        let author = self.intern(author);
+        let end = frames.end.min(self.frames.len());
+        for frame in frames.start.min(end)..end {
+            self.frames[frame] = Some(author);
+        }
+    }
+
+    /// Inserts `count` frames attributed to an author before frame `at`, clamped to
+    /// the end.
+    pub fn insert(&mut self, at: usize, count: usize, author: &str) {
+        let author = self.intern(author);
+        let at = at.min(self.frames.len());
+        self.frames
+            .splice(at..at, std::iter::repeat_n(Some(author), count));
+    }
+
+    /// Removes a range of frames, clamped to the end.
+    pub fn remove(&mut self, frames: Range<usize>) {
+        let end = frames.end.min(self.frames.len());
+        self.frames.drain(frames.start.min(end)..end);
+    }
+
+    /// Shortens or extends the blame to `frames` frames, leaving new frames unattributed.
+    pub fn resize(&mut self, frames: usize) {
+        self.frames.resize(frames, None);
+    }
+
+    /// Returns the runs of frames with the same author within a range, in order.
+    pub fn ranges(&self, frames: Range<usize>) -> Vec<BlameRange<'_>> {
+        let end = frames.end.min(self.frames.len());
+        let mut ranges: Vec<BlameRange<'_>> = Vec::new();
+
+        for frame in frames.start.min(end)..end {
+            let author = self.author_of(frame);
+            match ranges.last_mut() {
+                Some(last) if last.author == author => last.frames.end = frame + 1,
+                _ => ranges.push(BlameRange {
+                    frames: frame..frame + 1,
+                    author,
+                }),
+            }
+        }
+
+        ranges
+    }
+
+    /// Parses a blame from its text representation.
+    ///
+    /// The frame count is capped at [`MAX_FRAME_COUNT`], the most frames a movie can
+    /// have. Use [`Blame::parse_bounded`] when the blame belongs to a known movie.
+    pub fn parse(text: &str) -> Result<Self, MovieError> {
+        Blame::parse_bounded(text, MAX_FRAME_COUNT)
+    }
+
+    /// Parses a blame from its text representation, rejecting a frame count above
+    /// `max_frames`, e.g. the frame count of the movie it belongs to.
+    pub fn parse_bounded(text: &str, max_frames: usize) -> Result<Self, MovieError> {
+        let mut blame = Blame::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim_start();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || BlameError::InvalidLine {
+                line: line_number,
+                text: line.to_string(),
+            };
+
+            if let Some(frames) = line.strip_prefix("frames ") {
+                let frames = frames.trim().parse().map_err(|_| invalid())?;
+                if frames > max_frames.min(MAX_FRAME_COUNT) {
+                    return Err(invalid().into());
+                }
+
+                blame.resize(frames);
+                continue;
+            }
+
+            let (range, author) = line.split_once(' ').ok_or_else(invalid)?;
+            let (start, end) = range.split_once('-').unwrap_or((range, range));
+            let start: usize = start.parse().map_err(|_| invalid())?;
+            let end: usize = end.parse().map_err(|_| invalid())?;
+            if end < start || end >= blame.len() {
+                return Err(invalid().into());
+            }
+
+            blame.attribute(start..end + 1, author);
+        }
+
+        Ok(blame)
+    }
+
+    /// Returns the index of an author, adding it if needed.
+    fn intern(&mut self, author: &str) -> usize {
+        match self.authors.iter().position(|known| known == author) {
+            Some(index) => index,
+            None => {
+                self.authors.push(author.to_string());
+                self.authors.len() - 1
+            }
+        }
+    }
+}
+
+/// Blames are equal if they attribute every frame to the same author.
+impl PartialEq for Blame {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && (0..self.len()).all(|frame| self.author_of(frame) == other.author_of(frame))
+    }
+}
+
+impl Eq for Blame {}
+
+impl Display for Blame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", BLAME_HEADER)?;
+        writeln!(f, "frames {}", self.len())?;
+
+        for range in self.ranges(0..self.len()) {
+            if let Some(author) = range.author {
+                let author = author.replace('\n', " ");
+                writeln!(
+                    f,
+                    "{}-{} {}",
+                    range.frames.start,
+                    range.frames.end - 1,
+                    author
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+//! Values attached to frames, with fast lookup of the nearest one.
+//!
+//! [`Bookmarks`] is the frame-indexed map shared by
+//! [`Annotations`](crate::annotations::Annotations), [`Markers`](crate::markers::Markers),
+//! [`LagMask`](crate::lag::LagMask) and editor integrations, e.g. to find the latest cached emulator state at or before
+//! an edited frame:
+//!
+//! ```
+//! use m64_movie::bookmarks::Bookmarks;
+//!
+//! let mut greenzone = Bookmarks::new();
+//! greenzone.insert(0, "power-on");
+//! greenzone.insert(600, "after the intro");
+//! greenzone.insert(1200, "first star");
+//!
+//! assert_eq!(greenzone.nearest_before(1000), Some((600, &"after the intro")));
+//!
+//! // An edit at frame 1000 invalidates the states after it.
+//! greenzone.split_off(1001);
+//! assert_eq!(greenzone.nearest_after(601), None);
+//! ```
+
+use std::{collections::BTreeMap, ops::RangeBounds};
+
+use crate::track::FrameIndex;
+
+/// A map from frame index to a value, sorted by frame.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Bookmarks<T> {
+    /// The values, keyed by frame index.
+    values: BTreeMap<FrameIndex, T>,
+}
+
+impl<T> Bookmarks<T> {
+    /// Creates an empty set of bookmarks.
+    pub fn new() -> Self {
+        Bookmarks {
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Attaches a value to a frame, returning the value it replaced, if any.
+    pub fn insert(&mut self, frame: FrameIndex, value: T) -> Option<T> {
+        self.values.insert(frame, value)
+    }
+
+    /// Returns the value attached to a frame.
+    pub fn get(&self, frame: FrameIndex) -> Option<&T> {
+        self.values.get(&frame)
+    }
+
+    /// Returns a mutable reference to the value attached to a frame.
+    pub fn get_mut(&mut self, frame: FrameIndex) -> Option<&mut T> {
+        self.values.get_mut(&frame)
+    }
+
+    /// Returns `true` if a value is attached to a frame.
+    pub fn contains(&self, frame: FrameIndex) -> bool {
+        self.values.contains_key(&frame)
+    }
+
+    /// Removes the value attached to a frame, returning it.
+    pub fn remove(&mut self, frame: FrameIndex) -> Option<T> {
+        self.values.remove(&frame)
+    }
+
+    /// Returns the last bookmark at or before a frame.
+    pub fn nearest_before(&self, frame: FrameIndex) -> Option<(FrameIndex, &T)> {
+        self.values
+            .range(..=frame)
+            .next_back()
+            .map(|(&frame, value)| (frame, value))
+    }
+
+    /// Returns the first bookmark at or after a frame.
+    pub fn nearest_after(&self, frame: FrameIndex) -> Option<(FrameIndex, &T)> {
+        self.values
+            .range(frame..)
+            .next()
+            .map(|(&frame, value)| (frame, value))
+    }
+
+    /// Returns an iterator over the bookmarks within a range of frames, in frame order.
+    pub fn range<R: RangeBounds<FrameIndex>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (FrameIndex, &T)> {
+        self.values
+            .range(range)
+            .map(|(&frame, value)| (frame, value))
+    }
+
+    /// Returns an iterator over all bookmarks, in frame order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (FrameIndex, &T)> {
+        self.range(..)
+    }
+
+    /// Returns an iterator over the bookmarked frames, in order.
+    pub fn frames(&self) -> impl DoubleEndedIterator<Item = FrameIndex> + '_ {
+        self.values.keys().copied()
+    }
+
+    /// Removes the bookmarks at or after a frame, returning them.
+    pub fn split_off(&mut self, frame: FrameIndex) -> Bookmarks<T> {
+        Bookmarks {
+            values: self.values.split_off(&frame),
+        }
+    }
+
+    /// Returns the number of bookmarks.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if there are no bookmarks.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Removes all bookmarks.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
+impl<T> Default for Bookmarks<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(FrameIndex, T)> for Bookmarks<T> {
+    fn from_iter<I: IntoIterator<Item = (FrameIndex, T)>>(iter: I) -> Self {
+        Bookmarks {
+            values: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Extend<(FrameIndex, T)> for Bookmarks<T> {
+    fn extend<I: IntoIterator<Item = (FrameIndex, T)>>(&mut self, iter: I) {
+        self.values.extend(iter);
+    }
+}
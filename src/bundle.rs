@@ -0,0 +1,553 @@
+//! Single-file archives of everything needed to sync a movie.
+//!
+//! A [`Bundle`] holds a movie together with the savestate or save files it starts
+//! from, a [`RomManifest`] identifying the ROM it was recorded on, and its
+//! [sidecar](crate::sidecar), so a run can be shared as one file instead of a
+//! hand-assembled zip.
+//!
+//! Bundles are stored in a small little-endian binary format:
+//!
+//! | Field       | Type                         |
+//! |-------------|------------------------------|
+//! | Magic       | `M64B\x1A`                   |
+//! | Version     | `u16`, currently 1           |
+//! | Entry count | `u32`                        |
+//! | Entries     | one per file, see below      |
+//!
+//! Each entry is a null-terminated UTF-8 name, a `u32` length and the file's bytes.
+//! The movie is stored as `movie.m64`, its savestate as `movie.st`, its sidecar as
+//! `movie.m64.sidecar`, its [blame](crate::blame) as `movie.blame`, the ROM manifest
+//! as `rom.manifest`, and save files under `save/`.
+//!
+//! [`manifest`] lists the hashes of a movie and its companion files with the ROM,
+//! format and tool versions they were made with, for archiving published runs. It
+//! can be written as TOML with the `toml` feature, or JSON with the `json` feature.
+
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::Cursor,
+    path::Path,
+};
+
+use binrw::{BinRead, BinWrite, NullString, binrw};
+
+use crate::{
+    BinReadExt, BinWriteExt, BundleError, Movie, MovieError,
+    blame::Blame,
+    hash::sha256,
+    sidecar::Sidecar,
+    verify::{Companions, RomFormat, RomHeader},
+};
+
+/// The current version of the bundle file format.
+const BUNDLE_VERSION: u16 = 1;
+
+/// The name of the movie entry.
+const MOVIE_ENTRY: &str = "movie.m64";
+
+/// The name of the savestate entry.
+const SAVESTATE_ENTRY: &str = "movie.st";
+
+/// The name of the sidecar entry.
+const SIDECAR_ENTRY: &str = "movie.m64.sidecar";
+
+/// The name of the blame entry.
+const BLAME_ENTRY: &str = "movie.blame";
+
+/// The name of the ROM manifest entry.
+const ROM_ENTRY: &str = "rom.manifest";
+
+/// The prefix of save file entries.
+const SAVE_PREFIX: &str = "save/";
+
+/// A file stored in a bundle.
+#[binrw]
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[brw(little)]
+struct Entry {
+    /// The name of the file.
+    #[br(try_map = |s: NullString| String::try_from(s))]
+    #[bw(map = |s: &String| NullString::from(s.as_str()))]
+    name: String,
+    /// The length of the file in bytes.
+    #[br(temp)]
+    #[bw(calc = data.len() as u32)]
+    len: u32,
+    /// The contents of the file.
+    #[br(count = len)]
+    data: Vec<u8>,
+}
+
+/// The files of a bundle, as stored.
+#[binrw]
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[brw(little, magic = b"M64B\x1A")]
+struct Archive {
+    /// The version of the bundle file format.
+    #[br(assert(version == BUNDLE_VERSION, "Unsupported bundle version: {}", version))]
+    #[bw(calc = BUNDLE_VERSION)]
+    version: u16,
+    /// The number of files.
+    #[br(temp)]
+    #[bw(calc = entries.len() as u32)]
+    entry_count: u32,
+    /// The files.
+    #[br(count = entry_count)]
+    entries: Vec<Entry>,
+}
+
+/// The identity of the ROM a movie was recorded on.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RomManifest {
+    /// The internal name of the ROM.
+    pub name: String,
+    /// The first ROM checksum (CRC1), as stored in the movie header.
+    pub crc32: u32,
+    /// The country code of the ROM, as stored in the movie header.
+    pub country: u16,
+    /// The size of the ROM in bytes.
+    pub size: usize,
+    /// The SHA-256 digest of the whole ROM in big-endian (.z64) byte order, as 64
+    /// lowercase hexadecimal digits.
+    pub sha256: String,
+}
+
+impl RomManifest {
+    /// Describes a ROM image in any [`RomFormat`].
+    pub fn from_rom(rom: &[u8]) -> Result<Self, MovieError> {
+        let header = RomHeader::from_bytes(rom)?;
+
+        Ok(RomManifest {
+            name: header.name,
+            crc32: header.crc32,
+            country: header.country,
+            size: rom.len(),
+            sha256: sha256(&header.format.to_big_endian(rom)),
+        })
+    }
+
+    /// Returns `true` if a ROM image in any [`RomFormat`] is the one described.
+    pub fn matches(&self, rom: &[u8]) -> bool {
+        let Some(format) = RomFormat::detect(rom) else {
+            return false;
+        };
+
+        rom.len() == self.size && sha256(&format.to_big_endian(rom)) == self.sha256
+    }
+
+    /// Parses a manifest from its text representation.
+    fn parse(text: &str) -> Result<Self, BundleError> {
+        let invalid = || BundleError::InvalidEntry(ROM_ENTRY.to_string());
+        let mut manifest = RomManifest {
+            name: String::new(),
+            crc32: 0,
+            country: 0,
+            size: 0,
+            sha256: String::new(),
+        };
+
+        for line in text.lines() {
+            let (key, value) = line.split_once(' ').ok_or_else(invalid)?;
+            match key {
+                "name" => manifest.name = value.to_string(),
+                "crc32" => {
+                    manifest.crc32 = u32::from_str_radix(value, 16).map_err(|_| invalid())?
+                }
+                "country" => {
+                    manifest.country = u16::from_str_radix(value, 16).map_err(|_| invalid())?
+                }
+                "size" => manifest.size = value.parse().map_err(|_| invalid())?,
+                "sha256" => {
+                    if value.len() != 64 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+                        return Err(invalid());
+                    }
+                    manifest.sha256 = value.to_ascii_lowercase();
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(manifest)
+    }
+}
+
+impl Display for RomManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "name {}", self.name)?;
+        writeln!(f, "crc32 {:08X}", self.crc32)?;
+        writeln!(f, "country {:04X}", self.country)?;
+        writeln!(f, "size {}", self.size)?;
+        writeln!(f, "sha256 {}", self.sha256)
+    }
+}
+
+/// A save file (EEPROM, SRAM or FlashRAM) stored in a bundle.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SaveFile {
+    /// The file name, e.g. `SUPER MARIO 64.eep`.
+    pub name: String,
+    /// The contents of the file.
+    pub data: Vec<u8>,
+}
+
+/// A movie and the files needed to sync it. See the [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bundle {
+    /// The movie, with its annotations, markers and lag frames.
+    pub movie: Movie,
+    /// The savestate the movie starts from, if any.
+    pub savestate: Option<Vec<u8>>,
+    /// The save files the movie starts from.
+    pub save_files: Vec<SaveFile>,
+    /// The ROM the movie was recorded on, if known.
+    pub rom: Option<RomManifest>,
+    /// Who last changed each frame of the movie, if tracked.
+    pub blame: Option<Blame>,
+}
+
+impl Bundle {
+    /// Creates a bundle holding only a movie.
+    pub fn new(movie: Movie) -> Self {
+        Bundle {
+            movie,
+            savestate: None,
+            save_files: Vec::new(),
+            rom: None,
+            blame: None,
+        }
+    }
+
+    /// Returns the bundle with a savestate.
+    pub fn with_savestate(mut self, savestate: Vec<u8>) -> Self {
+        self.savestate = Some(savestate);
+        self
+    }
+
+    /// Returns the bundle with a save file added.
+    pub fn with_save_file<S: Into<String>>(mut self, name: S, data: Vec<u8>) -> Self {
+        self.save_files.push(SaveFile {
+            name: name.into(),
+            data,
+        });
+        self
+    }
+
+    /// Returns the bundle with the blame of the movie.
+    pub fn with_blame(mut self, blame: Blame) -> Self {
+        self.blame = Some(blame);
+        self
+    }
+
+    /// Returns the bundle with the manifest of a ROM image in any [`RomFormat`].
+    pub fn with_rom(mut self, rom: &[u8]) -> Result<Self, MovieError> {
+        self.rom = Some(RomManifest::from_rom(rom)?);
+        Ok(self)
+    }
+
+    /// Returns the companion files of the movie, for [`verify`](crate::verify::verify).
+    /// The first save file is used as the save.
+    pub fn companions(&self) -> Companions<'_> {
+        Companions {
+            savestate: self.savestate.as_deref(),
+            save: self.save_files.first().map(|file| file.data.as_slice()),
+        }
+    }
+
+    /// Builds a bundle from its stored files.
+    fn from_archive(archive: Archive) -> Result<Self, MovieError> {
+        let mut movie = None;
+        let mut sidecar = None;
+        let mut savestate = None;
+        let mut save_files: Vec<SaveFile> = Vec::new();
+        let mut rom = None;
+        let mut blame = None;
+
+        for Entry { name, data } in archive.entries {
+            let duplicate = match name.as_str() {
+                MOVIE_ENTRY => movie.replace(Movie::from_bytes(&data)?).is_some(),
+                SAVESTATE_ENTRY => savestate.replace(data).is_some(),
+                SIDECAR_ENTRY => sidecar.replace(text(&name, &data)?).is_some(),
+                BLAME_ENTRY => blame.replace(text(&name, &data)?).is_some(),
+                ROM_ENTRY => rom
+                    .replace(RomManifest::parse(&text(&name, &data)?)?)
+                    .is_some(),
+                _ => match name.strip_prefix(SAVE_PREFIX) {
+                    Some(file) => {
+                        let duplicate = save_files.iter().any(|save| save.name == file);
+                        save_files.push(SaveFile {
+                            name: file.to_string(),
+                            data,
+                        });
+                        duplicate
+                    }
+                    None => return Err(BundleError::UnknownEntry(name).into()),
+                },
+            };
+
+            if duplicate {
+                return Err(BundleError::DuplicateEntry(name).into());
+            }
+        }
+
+        let mut movie = movie.ok_or(BundleError::MissingMovie)?;
+        // The sidecar and the blame are parsed last, so their frames can be checked
+        // against the movie.
+        let blame = blame
+            .map(|blame| Blame::parse_bounded(&blame, movie.frame_count()))
+            .transpose()?;
+        let sidecar = sidecar
+            .map(|sidecar| Sidecar::parse_bounded(&sidecar, movie.frame_count()))
+            .transpose()?;
+        if let Some(sidecar) = sidecar {
+            *movie.annotations_mut() = sidecar.annotations;
+            *movie.markers_mut() = sidecar.markers;
+            movie.set_lag_mask(sidecar.lag_mask);
+        }
+
+        Ok(Bundle {
+            movie,
+            savestate,
+            save_files,
+            rom,
+            blame,
+        })
+    }
+
+    /// Returns the archival [`Manifest`] of the bundle, including the ROM hash if
+    /// known. Every file stored in the bundle is hashed under its entry name.
+    pub fn manifest(&self) -> Result<Manifest, MovieError> {
+        let mut manifest = manifest(&self.movie, &Companions::default())?;
+        manifest.rom.sha256 = self.rom.as_ref().map(|rom| rom.sha256.clone());
+        manifest.files = self
+            .to_archive()?
+            .entries
+            .iter()
+            .map(|entry| FileHash::new(&entry.name, &entry.data))
+            .collect();
+        Ok(manifest)
+    }
+
+    /// Collects the files to store.
+    fn to_archive(&self) -> Result<Archive, MovieError> {
+        let mut entries = vec![Entry {
+            name: MOVIE_ENTRY.to_string(),
+            data: self.movie.to_bytes()?,
+        }];
+
+        let sidecar = self.movie.sidecar();
+        if sidecar != Sidecar::default() {
+            entries.push(Entry {
+                name: SIDECAR_ENTRY.to_string(),
+                data: sidecar.to_string().into_bytes(),
+            });
+        }
+
+        if let Some(blame) = &self.blame {
+            entries.push(Entry {
+                name: BLAME_ENTRY.to_string(),
+                data: blame.to_string().into_bytes(),
+            });
+        }
+
+        if let Some(savestate) = &self.savestate {
+            entries.push(Entry {
+                name: SAVESTATE_ENTRY.to_string(),
+                data: savestate.clone(),
+            });
+        }
+
+        entries.extend(self.save_files.iter().map(|file| Entry {
+            name: format!("{SAVE_PREFIX}{}", file.name),
+            data: file.data.clone(),
+        }));
+
+        if let Some(rom) = &self.rom {
+            entries.push(Entry {
+                name: ROM_ENTRY.to_string(),
+                data: rom.to_string().into_bytes(),
+            });
+        }
+
+        Ok(Archive { entries })
+    }
+}
+
+/// Decodes a text entry.
+fn text(name: &str, data: &[u8]) -> Result<String, BundleError> {
+    String::from_utf8(data.to_vec()).map_err(|_| BundleError::InvalidEntry(name.to_string()))
+}
+
+impl BinReadExt for Bundle {
+    type Error = MovieError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Bundle::from_archive(Archive::read(&mut Cursor::new(bytes))?)
+    }
+
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Self::Error> {
+        Bundle::from_archive(Archive::read(&mut File::open(path)?)?)
+    }
+}
+
+impl BinWriteExt for Bundle {
+    type Error = MovieError;
+
+    fn to_bytes(&self) -> Result<Vec<u8>, Self::Error> {
+        let mut cursor = Cursor::new(Vec::new());
+        self.to_archive()?.write(&mut cursor)?;
+        Ok(cursor.into_inner())
+    }
+
+    fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Self::Error> {
+        let mut file = File::create(path)?;
+        self.to_archive()?.write(&mut file)?;
+        Ok(())
+    }
+}
+
+/// The hash of a file listed in a [`Manifest`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileHash {
+    /// The name of the file.
+    pub name: String,
+    /// The size of the file in bytes.
+    pub size: usize,
+    /// The SHA-256 digest of the file, as 64 lowercase hexadecimal digits.
+    pub sha256: String,
+}
+
+impl FileHash {
+    /// Hashes the contents of a file.
+    fn new(name: &str, data: &[u8]) -> Self {
+        FileHash {
+            name: name.to_string(),
+            size: data.len(),
+            sha256: sha256(data),
+        }
+    }
+}
+
+/// The ROM a movie was recorded on, as listed in a [`Manifest`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestRom {
+    /// The internal name of the ROM.
+    pub name: String,
+    /// The first ROM checksum (CRC1), as stored in the movie header.
+    pub crc32: u32,
+    /// The country code of the ROM, as stored in the movie header.
+    pub country: u16,
+    /// The SHA-256 digest of the whole ROM, if known. See [`RomManifest::sha256`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub sha256: Option<String>,
+}
+
+/// The versions of the format and tools a movie was made with, as listed in a
+/// [`Manifest`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestVersions {
+    /// The version of the M64 format.
+    pub format: u32,
+    /// The extended version of the M64 format.
+    pub extended_format: u8,
+    /// The version of this crate, which produced the manifest.
+    pub m64_movie: String,
+    /// The video plugin the movie was recorded with.
+    pub video_plugin: String,
+    /// The sound plugin the movie was recorded with.
+    pub sound_plugin: String,
+    /// The input plugin the movie was recorded with.
+    pub input_plugin: String,
+    /// The RSP plugin the movie was recorded with.
+    pub rsp_plugin: String,
+}
+
+/// An archival record of a movie and its companion files, as returned by [`manifest`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    /// The number of frames of the movie.
+    pub frame_count: usize,
+    /// The number of vertical interrupts of the movie.
+    pub vertical_interrupts: u32,
+    /// The number of rerecords of the movie.
+    pub rerecord_count: u32,
+    /// The ROM the movie was recorded on.
+    pub rom: ManifestRom,
+    /// The versions of the format and tools.
+    pub versions: ManifestVersions,
+    /// The hashes of the movie and its companion files.
+    pub files: Vec<FileHash>,
+}
+
+impl Manifest {
+    /// Serializes the manifest to TOML.
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> Result<String, MovieError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Parses a manifest from TOML.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, MovieError> {
+        Ok(toml::from_str(s)?)
+    }
+
+    /// Serializes the manifest to pretty-printed JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, MovieError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parses a manifest from JSON.
+    #[cfg(feature = "json")]
+    pub fn from_json(s: &str) -> Result<Self, MovieError> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
+
+/// Builds the archival manifest of a movie and its companion files.
+///
+/// The movie is hashed as written by [`BinWriteExt::to_bytes`], under the entry names
+/// used by [`Bundle`]. The save file is listed as `save`.
+pub fn manifest(movie: &Movie, companions: &Companions) -> Result<Manifest, MovieError> {
+    let mut files = vec![FileHash::new(MOVIE_ENTRY, &movie.to_bytes()?)];
+    if let Some(savestate) = companions.savestate {
+        files.push(FileHash::new(SAVESTATE_ENTRY, savestate));
+    }
+    if let Some(save) = companions.save {
+        files.push(FileHash::new("save", save));
+    }
+
+    let plugins = &movie.plugin_info;
+    let plugin = |name: &dyn Display| name.to_string().trim_end().to_string();
+
+    Ok(Manifest {
+        frame_count: movie.frame_count(),
+        vertical_interrupts: movie.recording_info.vertical_interrupts,
+        rerecord_count: movie.recording_info.rerecord_count,
+        rom: ManifestRom {
+            name: movie.game_info.rom_name.to_string().trim_end().to_string(),
+            crc32: movie.game_info.rom_crc32,
+            country: movie.game_info.rom_country,
+            sha256: None,
+        },
+        versions: ManifestVersions {
+            format: movie.metadata.version,
+            extended_format: movie.metadata.extended_version,
+            m64_movie: env!("CARGO_PKG_VERSION").to_string(),
+            video_plugin: plugin(&plugins.video_plugin),
+            sound_plugin: plugin(&plugins.sound_plugin),
+            input_plugin: plugin(&plugins.input_plugin),
+            rsp_plugin: plugin(&plugins.rsp_plugin),
+        },
+        files,
+    })
+}
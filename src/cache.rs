@@ -0,0 +1,183 @@
+//! Memoized movie summaries keyed by file content.
+//!
+//! Scanning a large collection of movies parses every file, even when most have
+//! not changed since the last scan. A [`SummaryCache`] keys each [`MovieSummary`] by
+//! the FNV-1a hash of the file bytes, so unchanged files are hashed but not parsed
+//! again. The cache lives in memory, and can be loaded from and saved to a JSON file
+//! to be reused across runs.
+//!
+//! ```no_run
+//! use m64_movie::cache::SummaryCache;
+//!
+//! # fn main() -> Result<(), m64_movie::MovieError> {
+//! let mut cache = SummaryCache::open("summaries.json")?;
+//! let summary = cache.summary_of_file("movies/1key.m64")?;
+//! println!("{} frames", summary.frame_count);
+//! cache.save()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BinReadExt, Movie, MovieError, catalog::Collection, hash::fnv1a, summary::MovieSummary,
+};
+
+/// The version of the on-disk cache format. Files of another version are ignored.
+const CACHE_VERSION: u32 = 1;
+
+/// The on-disk form of a [`SummaryCache`].
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    /// The version of the format.
+    version: u32,
+    /// The summaries, keyed by the hash of the file bytes as 16 hex digits.
+    entries: BTreeMap<String, MovieSummary>,
+}
+
+/// A cache of movie summaries, keyed by the FNV-1a hash of the movie bytes.
+#[derive(Debug, Clone, Default)]
+pub struct SummaryCache {
+    /// The summaries, keyed by content hash.
+    entries: HashMap<u64, MovieSummary>,
+    /// The file the cache is saved to, if any.
+    path: Option<PathBuf>,
+    /// The number of lookups answered from the cache.
+    hits: usize,
+    /// The number of lookups that parsed the movie.
+    misses: usize,
+}
+
+impl SummaryCache {
+    /// Creates an empty in-memory cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a cache stored at `path`, starting empty if the file does not exist or
+    /// was written by another version of the format.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MovieError> {
+        let path = path.as_ref();
+        let entries = match fs::read_to_string(path) {
+            Ok(json) => {
+                let file: CacheFile = serde_json::from_str(&json)?;
+                if file.version == CACHE_VERSION {
+                    file.entries
+                        .into_iter()
+                        .filter_map(|(key, summary)| {
+                            u64::from_str_radix(&key, 16)
+                                .ok()
+                                .map(|hash| (hash, summary))
+                        })
+                        .collect()
+                } else {
+                    HashMap::new()
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(SummaryCache {
+            entries,
+            path: Some(path.to_path_buf()),
+            ..Self::default()
+        })
+    }
+
+    /// Returns the summary of an encoded movie, parsing it only if no movie with the
+    /// same bytes has been seen.
+    pub fn summary(&mut self, bytes: &[u8]) -> Result<MovieSummary, MovieError> {
+        let hash = fnv1a(bytes);
+        if let Some(summary) = self.entries.get(&hash) {
+            self.hits += 1;
+            return Ok(summary.clone());
+        }
+
+        let summary = Movie::from_bytes(bytes)?.summary();
+        self.misses += 1;
+        self.entries.insert(hash, summary.clone());
+        Ok(summary)
+    }
+
+    /// Returns the summary of the movie stored at `path`. See [`SummaryCache::summary`].
+    pub fn summary_of_file<P: AsRef<Path>>(&mut self, path: P) -> Result<MovieSummary, MovieError> {
+        self.summary(&fs::read(path)?)
+    }
+
+    /// Builds a collection of the movies stored at `paths`, in order.
+    pub fn collect<I, P>(&mut self, paths: I) -> Result<Collection, MovieError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut collection = Collection::new();
+        for path in paths {
+            let path = path.as_ref();
+            collection.push(path, self.summary_of_file(path)?);
+        }
+
+        Ok(collection)
+    }
+
+    /// Returns the number of cached summaries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no summaries are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of lookups answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Returns the number of lookups that parsed the movie.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Removes every cached summary.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the file the cache was opened from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Saves the cache to the file it was opened from. An in-memory cache is not saved.
+    pub fn save(&self) -> Result<(), MovieError> {
+        match &self.path {
+            Some(path) => self.save_to(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Saves the cache to `path` as JSON.
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), MovieError> {
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            entries: self
+                .entries
+                .iter()
+                .map(|(hash, summary)| (format!("{hash:016x}"), summary.clone()))
+                .collect(),
+        };
+
+        fs::write(path, serde_json::to_string(&file)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,430 @@
+//! Querying collections of movies by their [`MovieSummary`].
+//!
+//! A [`Collection`] is filtered and sorted by chaining predicates and sort keys
+//! from this module:
+//!
+//! ```
+//! use m64_movie::catalog::{Collection, by_rom_crc, duration};
+//!
+//! # let collection = Collection::new();
+//! let runs = collection.filter(by_rom_crc(0x0E3D_AA4E)).sort_by(duration);
+//! ```
+//!
+//! A [`SearchIndex`] finds movies by the text of their headers instead, such as a
+//! word from the description. [`index`] builds one from the movies in a directory.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{BinReadExt, Movie, MovieError, raw::MovieStartType, summary::MovieSummary};
+
+/// A movie in a [`Collection`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CatalogEntry {
+    /// Where the movie is stored.
+    pub path: PathBuf,
+    /// The summary of the movie.
+    pub summary: MovieSummary,
+}
+
+/// An ordered collection of movies.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Collection {
+    /// The movies, in order.
+    entries: Vec<CatalogEntry>,
+}
+
+impl Collection {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a movie to the end of the collection.
+    pub fn push(&mut self, path: impl Into<PathBuf>, summary: MovieSummary) {
+        self.entries.push(CatalogEntry {
+            path: path.into(),
+            summary,
+        });
+    }
+
+    /// Returns the number of movies in the collection.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the collection is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the movies, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &CatalogEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns the first movie, if any.
+    pub fn first(&self) -> Option<&CatalogEntry> {
+        self.entries.first()
+    }
+
+    /// Keeps only the movies matching the predicate.
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&MovieSummary) -> bool,
+    {
+        self.entries.retain(|entry| predicate(&entry.summary));
+        self
+    }
+
+    /// Sorts the movies by a key, in ascending order. Movies with equal keys keep their order.
+    pub fn sort_by<K, F>(mut self, mut key: F) -> Self
+    where
+        K: Ord,
+        F: FnMut(&MovieSummary) -> K,
+    {
+        self.entries.sort_by_key(|entry| key(&entry.summary));
+        self
+    }
+
+    /// Reverses the order of the movies.
+    pub fn reverse(mut self) -> Self {
+        self.entries.reverse();
+        self
+    }
+
+    /// Keeps only the first `n` movies.
+    pub fn take(mut self, n: usize) -> Self {
+        self.entries.truncate(n);
+        self
+    }
+}
+
+impl FromIterator<CatalogEntry> for Collection {
+    fn from_iter<I: IntoIterator<Item = CatalogEntry>>(iter: I) -> Self {
+        Collection {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for Collection {
+    type Item = CatalogEntry;
+    type IntoIter = std::vec::IntoIter<CatalogEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// Matches movies recorded on the ROM with the given CRC.
+pub fn by_rom_crc(crc32: u32) -> impl Fn(&MovieSummary) -> bool {
+    move |summary| summary.rom_crc32 == crc32
+}
+
+/// Matches movies whose author name contains the given text, ignoring case.
+pub fn by_author(author: &str) -> impl Fn(&MovieSummary) -> bool {
+    let author = author.to_lowercase();
+    move |summary| summary.author.to_lowercase().contains(&author)
+}
+
+/// Matches movies with the given start type.
+pub fn by_start_type(start_type: MovieStartType) -> impl Fn(&MovieSummary) -> bool {
+    move |summary| summary.start_type == start_type
+}
+
+/// Matches movies with the given number of controllers.
+pub fn by_controller_count(count: usize) -> impl Fn(&MovieSummary) -> bool {
+    move |summary| summary.controller_count == count
+}
+
+/// Matches movies no longer than the given duration.
+pub fn shorter_than(max: Duration) -> impl Fn(&MovieSummary) -> bool {
+    move |summary| summary.duration <= max
+}
+
+/// Sorts by duration.
+pub fn duration(summary: &MovieSummary) -> Duration {
+    summary.duration
+}
+
+/// Sorts by frame count.
+pub fn frame_count(summary: &MovieSummary) -> usize {
+    summary.frame_count
+}
+
+/// Sorts by rerecord count.
+pub fn rerecord_count(summary: &MovieSummary) -> u32 {
+    summary.rerecord_count
+}
+
+/// Sorts by author name.
+pub fn author(summary: &MovieSummary) -> String {
+    summary.author.clone()
+}
+
+/// A text field of a movie header covered by a [`SearchIndex`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IndexField {
+    /// The author name.
+    Author,
+    /// The movie description.
+    Description,
+    /// The internal name of the ROM.
+    RomName,
+    /// The name of one of the plugins.
+    Plugin,
+}
+
+/// The searchable text of a movie.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexEntry {
+    /// Where the movie is stored.
+    pub path: PathBuf,
+    /// The author name.
+    pub author: String,
+    /// The movie description.
+    pub description: String,
+    /// The internal name of the ROM.
+    pub rom_name: String,
+    /// The names of the video, sound, input and RSP plugins.
+    pub plugins: Vec<String>,
+}
+
+impl IndexEntry {
+    /// Builds the entry of a movie stored at `path`.
+    pub fn from_movie(path: impl Into<PathBuf>, movie: &Movie) -> Self {
+        let plugins = &movie.plugin_info;
+        IndexEntry {
+            path: path.into(),
+            author: movie.recording_info.author_name.to_string(),
+            description: movie.recording_info.description.to_string(),
+            rom_name: movie.game_info.rom_name.to_string(),
+            plugins: [
+                &plugins.video_plugin,
+                &plugins.sound_plugin,
+                &plugins.input_plugin,
+                &plugins.rsp_plugin,
+            ]
+            .into_iter()
+            .map(ToString::to_string)
+            .collect(),
+        }
+    }
+
+    /// Returns the indexed fields with their text.
+    fn fields(&self) -> impl Iterator<Item = (IndexField, &str)> {
+        [
+            (IndexField::Author, self.author.as_str()),
+            (IndexField::Description, self.description.as_str()),
+            (IndexField::RomName, self.rom_name.as_str()),
+        ]
+        .into_iter()
+        .chain(
+            self.plugins
+                .iter()
+                .map(|plugin| (IndexField::Plugin, plugin.as_str())),
+        )
+    }
+}
+
+/// A movie matching a search, with the field it matched in.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SearchHit<'a> {
+    /// The matching movie.
+    pub entry: &'a IndexEntry,
+    /// The field the query matched in.
+    pub field: IndexField,
+    /// The number of character edits needed for the query to match, 0 for an exact
+    /// match.
+    pub distance: usize,
+}
+
+/// An in-memory text index over the headers of a collection of movies.
+///
+/// With the `serde` feature, the index can be serialized to persist it between runs.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchIndex {
+    /// The indexed movies.
+    entries: Vec<IndexEntry>,
+    /// The files that could not be parsed as movies while indexing.
+    skipped: Vec<PathBuf>,
+}
+
+impl SearchIndex {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a movie to the index.
+    pub fn push(&mut self, entry: IndexEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Returns the number of indexed movies.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no movies are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the indexed movies.
+    pub fn iter(&self) -> impl Iterator<Item = &IndexEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns the files that could not be parsed as movies while indexing.
+    pub fn skipped(&self) -> &[PathBuf] {
+        &self.skipped
+    }
+
+    /// Returns the movies with a field containing the query, ignoring case, in index
+    /// order. Each movie is returned once, for the first field it matches in.
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_>> {
+        let query = query.to_lowercase();
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .fields()
+                    .find(|(_, text)| text.to_lowercase().contains(&query))
+                    .map(|(field, _)| SearchHit {
+                        entry,
+                        field,
+                        distance: 0,
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the movies with a field matching every word of the query, tolerating
+    /// typos, closest matches first.
+    ///
+    /// Words are compared ignoring case. A query word matches a word of the field if
+    /// they differ by at most one edit per four characters of the query word, so short
+    /// words must match exactly.
+    pub fn search_fuzzy(&self, query: &str) -> Vec<SearchHit<'_>> {
+        let query = words(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .fields()
+                    .filter_map(|(field, text)| {
+                        let text = words(text);
+                        query
+                            .iter()
+                            .map(|word| {
+                                text.iter()
+                                    .map(|candidate| edit_distance(word, candidate))
+                                    .filter(|&distance| distance <= word.chars().count() / 4)
+                                    .min()
+                            })
+                            .sum::<Option<usize>>()
+                            .map(|distance| (field, distance))
+                    })
+                    .min_by_key(|&(_, distance)| distance)
+                    .map(|(field, distance)| SearchHit {
+                        entry,
+                        field,
+                        distance,
+                    })
+            })
+            .collect();
+
+        hits.sort_by_key(|hit| hit.distance);
+        hits
+    }
+}
+
+impl FromIterator<IndexEntry> for SearchIndex {
+    fn from_iter<I: IntoIterator<Item = IndexEntry>>(iter: I) -> Self {
+        SearchIndex {
+            entries: iter.into_iter().collect(),
+            skipped: Vec::new(),
+        }
+    }
+}
+
+/// Builds a search index over the `.m64` files in a directory and its
+/// subdirectories, in path order.
+///
+/// Files that cannot be parsed are skipped and listed in [`SearchIndex::skipped`].
+/// Errors reading the directory itself are returned.
+pub fn index<P: AsRef<Path>>(dir: P) -> Result<SearchIndex, MovieError> {
+    let mut paths = Vec::new();
+    collect_movie_paths(dir.as_ref(), &mut paths)?;
+    paths.sort();
+
+    let mut index = SearchIndex::new();
+    for path in paths {
+        match Movie::from_file(&path) {
+            Ok(movie) => index.push(IndexEntry::from_movie(path, &movie)),
+            Err(_) => index.skipped.push(path),
+        }
+    }
+
+    Ok(index)
+}
+
+/// Appends the paths of the `.m64` files in a directory and its subdirectories.
+///
+/// Symbolic links to directories are not followed, so a link cycle cannot recurse
+/// forever.
+fn collect_movie_paths(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), MovieError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_movie_paths(&path, paths)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("m64"))
+        {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits text into lowercase words of letters and digits.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Returns the Levenshtein distance between two words, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
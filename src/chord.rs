@@ -0,0 +1,116 @@
+//! Patterns matching controller states.
+//!
+//! A [`Chord`] describes a combination of held buttons and stick positions, such as
+//! "C-up with the stick held up", and [`Movie::replace_states`] rewrites every sample
+//! matching it:
+//!
+//! ```
+//! use m64_movie::{ControllerButton, chord::Chord, raw::ControllerState};
+//!
+//! let chord = Chord::new().with(ControllerButton::CUp).y(1..=i8::MAX);
+//! assert!(chord.matches(&ControllerState::build().c_up().stick(0, 80).done()));
+//! assert!(!chord.matches(&ControllerState::build().c_up().stick(0, -80).done()));
+//! ```
+
+use std::ops::RangeInclusive;
+
+use crate::{ControllerButton, Movie, raw::ControllerState, selection::FrameSelection};
+
+/// A combination of buttons and stick positions a controller state may match.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Chord {
+    /// The mask of the buttons that must be held.
+    buttons: u16,
+    /// Whether no other button may be held.
+    exclusive: bool,
+    /// The x-axis values matched, or `None` for any.
+    x: Option<RangeInclusive<i8>>,
+    /// The y-axis values matched, or `None` for any.
+    y: Option<RangeInclusive<i8>>,
+}
+
+impl Chord {
+    /// Creates a chord matching every state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the chord requiring a button to be held.
+    pub fn with(mut self, button: ControllerButton) -> Self {
+        self.buttons |= button.mask();
+        self
+    }
+
+    /// Returns the chord requiring no buttons to be held besides its own, if `exclusive`.
+    pub fn exclusive(mut self, exclusive: bool) -> Self {
+        self.exclusive = exclusive;
+        self
+    }
+
+    /// Returns the chord requiring the x-axis to be within a range.
+    pub fn x(mut self, range: RangeInclusive<i8>) -> Self {
+        self.x = Some(range);
+        self
+    }
+
+    /// Returns the chord requiring the y-axis to be within a range.
+    pub fn y(mut self, range: RangeInclusive<i8>) -> Self {
+        self.y = Some(range);
+        self
+    }
+
+    /// Returns the buttons the chord requires, in the order of [`ControllerButton::ALL`].
+    pub fn buttons(&self) -> Vec<ControllerButton> {
+        ControllerButton::iter()
+            .filter(|button| self.buttons & button.mask() != 0)
+            .collect()
+    }
+
+    /// Returns `true` if a controller state matches the chord.
+    pub fn matches(&self, state: &ControllerState) -> bool {
+        let held = (u32::from(*state) & 0xFFFF) as u16;
+        let buttons = if self.exclusive {
+            held == self.buttons
+        } else {
+            held & self.buttons == self.buttons
+        };
+
+        buttons
+            && self.x.as_ref().is_none_or(|x| x.contains(&state.x_axis()))
+            && self.y.as_ref().is_none_or(|y| y.contains(&state.y_axis()))
+    }
+}
+
+/// Creates a chord requiring the buttons to be held.
+impl FromIterator<ControllerButton> for Chord {
+    fn from_iter<I: IntoIterator<Item = ControllerButton>>(iter: I) -> Self {
+        iter.into_iter().fold(Chord::new(), Chord::with)
+    }
+}
+
+impl Movie {
+    /// Replaces every sample of the selected frames matching a chord, on every
+    /// controller, returning the number of samples replaced. Frames past the end of the
+    /// movie are ignored.
+    pub fn replace_states(
+        &mut self,
+        matcher: &Chord,
+        replacement: ControllerState,
+        selection: &FrameSelection,
+    ) -> usize {
+        let count = self.controller_count();
+        let selection = selection.intersect(&FrameSelection::all(self));
+
+        let mut replaced = 0;
+        for range in selection.ranges() {
+            for state in &mut self.inputs[range.start * count..range.end * count] {
+                if matcher.matches(state) {
+                    *state = replacement;
+                    replaced += 1;
+                }
+            }
+        }
+
+        replaced
+    }
+}
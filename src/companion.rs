@@ -0,0 +1,272 @@
+//! The companion files a movie needs to start, by its [`MovieStartType`].
+//!
+//! Mupen64 names a savestate after the movie it belongs to, e.g. `run.m64` starts from
+//! `run.st`, while save files are named after the internal name of the ROM, e.g.
+//! `SUPER MARIO 64.eep`. [`StartRequirements`] encodes these conventions, and
+//! [`Movie::locate_companions`] finds the files next to a movie.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+use crate::{Movie, raw::MovieStartType};
+
+/// The extensions of a savestate, in the order they are looked up.
+pub const SAVESTATE_EXTENSIONS: &[&str] = &["st", "savestate"];
+
+/// The extension of an EEPROM save file.
+pub const EEPROM_EXTENSION: &str = "eep";
+
+/// The extension of an SRAM save file.
+pub const SRAM_EXTENSION: &str = "sra";
+
+/// The extension of a FlashRAM save file.
+pub const FLASHRAM_EXTENSION: &str = "fla";
+
+/// The extension of a memory pak file.
+pub const MEMPAK_EXTENSION: &str = "mpk";
+
+/// What a companion file is named after.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NamedAfter {
+    /// The file stem of the movie, e.g. `run` for `run.m64`.
+    Movie,
+    /// The internal name of the ROM, e.g. `SUPER MARIO 64`.
+    Rom,
+}
+
+/// A kind of file a movie may start from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CompanionKind {
+    /// A savestate, for movies starting from a snapshot.
+    Savestate,
+    /// An EEPROM save file.
+    Eeprom,
+    /// An SRAM save file.
+    Sram,
+    /// A FlashRAM save file.
+    FlashRam,
+    /// The contents of the memory paks.
+    Mempak,
+}
+
+impl CompanionKind {
+    /// Every kind of companion file.
+    pub const ALL: [CompanionKind; 5] = [
+        CompanionKind::Savestate,
+        CompanionKind::Eeprom,
+        CompanionKind::Sram,
+        CompanionKind::FlashRam,
+        CompanionKind::Mempak,
+    ];
+
+    /// The kinds of save file a game may use. A game only uses one of them.
+    pub const SAVES: [CompanionKind; 3] = [
+        CompanionKind::Eeprom,
+        CompanionKind::Sram,
+        CompanionKind::FlashRam,
+    ];
+
+    /// Returns the extensions of the file, in the order they are looked up.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            CompanionKind::Savestate => SAVESTATE_EXTENSIONS,
+            CompanionKind::Eeprom => &[EEPROM_EXTENSION],
+            CompanionKind::Sram => &[SRAM_EXTENSION],
+            CompanionKind::FlashRam => &[FLASHRAM_EXTENSION],
+            CompanionKind::Mempak => &[MEMPAK_EXTENSION],
+        }
+    }
+
+    /// Returns what the file is named after.
+    pub fn named_after(self) -> NamedAfter {
+        match self {
+            CompanionKind::Savestate => NamedAfter::Movie,
+            _ => NamedAfter::Rom,
+        }
+    }
+
+    /// Returns the candidate file names, in the order they are looked up, for a movie
+    /// with the given file stem recorded on a ROM with the given internal name.
+    pub fn file_names(self, movie_stem: &str, rom_name: &str) -> Vec<String> {
+        let stem = match self.named_after() {
+            NamedAfter::Movie => movie_stem,
+            NamedAfter::Rom => rom_name.trim(),
+        };
+
+        self.extensions()
+            .iter()
+            .map(|extension| format!("{stem}.{extension}"))
+            .collect()
+    }
+}
+
+impl Display for CompanionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CompanionKind::Savestate => "savestate",
+            CompanionKind::Eeprom => "EEPROM save",
+            CompanionKind::Sram => "SRAM save",
+            CompanionKind::FlashRam => "FlashRAM save",
+            CompanionKind::Mempak => "memory pak",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// The companion files a movie needs to start.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StartRequirements {
+    /// How the movie begins.
+    pub start_type: MovieStartType,
+    /// The files that must all be present.
+    pub all_of: Vec<CompanionKind>,
+    /// The files of which at least one must be present, if any are listed.
+    pub any_of: Vec<CompanionKind>,
+}
+
+impl StartRequirements {
+    /// Returns the requirements of a start type, regardless of the controller paks.
+    ///
+    /// A snapshot needs its savestate, and an EEPROM start needs the save file of the
+    /// game, whichever kind it uses. A power-on start needs nothing.
+    pub fn for_start_type(start_type: MovieStartType) -> Self {
+        let (all_of, any_of) = match start_type {
+            MovieStartType::Snapshot => (vec![CompanionKind::Savestate], Vec::new()),
+            MovieStartType::EEPROM => (Vec::new(), CompanionKind::SAVES.to_vec()),
+            MovieStartType::PowerOn => (Vec::new(), Vec::new()),
+        };
+
+        StartRequirements {
+            start_type,
+            all_of,
+            any_of,
+        }
+    }
+
+    /// Returns `true` if nothing is needed besides the movie and the ROM.
+    pub fn is_empty(&self) -> bool {
+        self.all_of.is_empty() && self.any_of.is_empty()
+    }
+
+    /// Returns `true` if the given kinds of file meet the requirements.
+    pub fn is_satisfied_by(&self, present: &[CompanionKind]) -> bool {
+        self.all_of.iter().all(|kind| present.contains(kind))
+            && (self.any_of.is_empty() || self.any_of.iter().any(|kind| present.contains(kind)))
+    }
+}
+
+/// A companion file found next to a movie.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Companion {
+    /// The kind of file.
+    pub kind: CompanionKind,
+    /// Where the file is stored.
+    pub path: PathBuf,
+}
+
+/// The companion files found for a movie. See [`Movie::locate_companions`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LocatedCompanions {
+    /// The requirements the files were looked up for.
+    pub requirements: StartRequirements,
+    /// The files found, in the order they were looked up.
+    pub found: Vec<Companion>,
+}
+
+impl LocatedCompanions {
+    /// Returns the path of the first file found of a kind.
+    pub fn get(&self, kind: CompanionKind) -> Option<&Path> {
+        self.found
+            .iter()
+            .find(|companion| companion.kind == kind)
+            .map(|companion| companion.path.as_path())
+    }
+
+    /// Returns `true` if the files found meet the requirements.
+    pub fn is_complete(&self) -> bool {
+        let present: Vec<_> = self.found.iter().map(|companion| companion.kind).collect();
+        self.requirements.is_satisfied_by(&present)
+    }
+
+    /// Returns the kinds of file that are required but were not found. If none of
+    /// the alternative save files were found, they are all listed.
+    pub fn missing(&self) -> Vec<CompanionKind> {
+        let mut missing: Vec<_> = self
+            .requirements
+            .all_of
+            .iter()
+            .copied()
+            .filter(|&kind| self.get(kind).is_none())
+            .collect();
+
+        if !self
+            .requirements
+            .any_of
+            .iter()
+            .any(|&kind| self.get(kind).is_some())
+        {
+            missing.extend(&self.requirements.any_of);
+        }
+
+        missing
+    }
+}
+
+impl Movie {
+    /// Returns the companion files the movie needs to start.
+    ///
+    /// Besides the requirements of its start type, a movie starting from EEPROM with a
+    /// memory pak plugged in needs the memory pak contents, which are otherwise
+    /// cleared on power-on.
+    pub fn start_requirements(&self) -> StartRequirements {
+        let mut requirements = StartRequirements::for_start_type(self.recording_info.start_type);
+
+        let flags = self.controller_flags();
+        let has_mempak = flags.controller_01_has_mempak()
+            || flags.controller_02_has_mempak()
+            || flags.controller_03_has_mempak()
+            || flags.controller_04_has_mempak();
+        if has_mempak && self.recording_info.start_type == MovieStartType::EEPROM {
+            requirements.all_of.push(CompanionKind::Mempak);
+        }
+
+        requirements
+    }
+
+    /// Looks up the companion files the movie needs in the directory of the movie
+    /// stored at `movie_path`, following the naming conventions of Mupen64.
+    ///
+    /// Only files that exist are returned. Use [`LocatedCompanions::missing`] to find
+    /// which required files were not found.
+    pub fn locate_companions<P: AsRef<Path>>(&self, movie_path: P) -> LocatedCompanions {
+        let movie_path = movie_path.as_ref();
+        let dir = movie_path.parent().unwrap_or(Path::new(""));
+        let stem = movie_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        let rom_name = self.game_info.rom_name.to_string();
+
+        let requirements = self.start_requirements();
+        let found = requirements
+            .all_of
+            .iter()
+            .chain(&requirements.any_of)
+            .filter_map(|&kind| {
+                kind.file_names(&stem, &rom_name)
+                    .into_iter()
+                    .map(|name| dir.join(name))
+                    .find(|path| path.is_file())
+                    .map(|path| Companion { kind, path })
+            })
+            .collect();
+
+        LocatedCompanions {
+            requirements,
+            found,
+        }
+    }
+}
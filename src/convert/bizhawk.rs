@@ -0,0 +1,235 @@
+//! BizHawk input logs.
+//!
+//! BizHawk movies (`.bk2`) are zip archives, but inputs are often exchanged as just
+//! the `Input Log.txt` inside them. Each frame is one pipe-delimited line, with a
+//! group for the console buttons followed by one group per controller:
+//!
+//! ```text
+//! [Input]
+//! LogKey:#Reset|Power|#P1 X Axis|P1 Y Axis|P1 A Up|...|P1 R|
+//! |..|    0,    0,......S...........|
+//! [/Input]
+//! ```
+//!
+//! A button is pressed if its column holds any character other than `.`. The
+//! columns are described by an [`InputLogLayout`].
+
+use crate::{ControllerButton, ConvertError, Movie, MovieError, raw::ControllerState};
+
+/// A button column of an input log.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LogButton {
+    /// The name of the button in the log key, without the controller prefix.
+    pub name: String,
+    /// The character written when the button is pressed.
+    pub mnemonic: char,
+    /// The controller button the column maps to, or `None` if it has no M64 equivalent.
+    pub button: Option<ControllerButton>,
+}
+
+impl LogButton {
+    /// Creates a button column.
+    pub fn new(name: &str, mnemonic: char, button: Option<ControllerButton>) -> Self {
+        LogButton {
+            name: name.to_string(),
+            mnemonic,
+            button,
+        }
+    }
+}
+
+/// The columns of an input log.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InputLogLayout {
+    /// The console buttons, written in the first group of every line.
+    pub console: Vec<LogButton>,
+    /// The buttons of each controller, written after its axes.
+    pub buttons: Vec<LogButton>,
+    /// The number of controllers.
+    pub controllers: usize,
+}
+
+impl InputLogLayout {
+    /// Returns the layout BizHawk uses for N64 movies with the given number of controllers.
+    ///
+    /// The analog stick directions BizHawk logs as buttons (`A Up` to `A Right`) have
+    /// no M64 equivalent. They are ignored when parsing and never written.
+    pub fn n64(controllers: usize) -> Self {
+        use ControllerButton::*;
+
+        InputLogLayout {
+            console: vec![
+                LogButton::new("Reset", 'r', None),
+                LogButton::new("Power", 'P', None),
+            ],
+            buttons: vec![
+                LogButton::new("A Up", 'U', None),
+                LogButton::new("A Down", 'D', None),
+                LogButton::new("A Left", 'L', None),
+                LogButton::new("A Right", 'R', None),
+                LogButton::new("DPad U", 'U', Some(DPadUp)),
+                LogButton::new("DPad D", 'D', Some(DPadDown)),
+                LogButton::new("DPad L", 'L', Some(DPadLeft)),
+                LogButton::new("DPad R", 'R', Some(DPadRight)),
+                LogButton::new("Start", 'S', Some(Start)),
+                LogButton::new("Z", 'Z', Some(Z)),
+                LogButton::new("B", 'B', Some(B)),
+                LogButton::new("A", 'A', Some(A)),
+                LogButton::new("C Up", 'u', Some(CUp)),
+                LogButton::new("C Down", 'd', Some(CDown)),
+                LogButton::new("C Left", 'l', Some(CLeft)),
+                LogButton::new("C Right", 'r', Some(CRight)),
+                LogButton::new("L", 'L', Some(TriggerLeft)),
+                LogButton::new("R", 'R', Some(TriggerRight)),
+            ],
+            controllers,
+        }
+    }
+
+    /// Returns the `LogKey` line describing the columns, without the `LogKey:` prefix.
+    pub fn log_key(&self) -> String {
+        let mut key = String::new();
+        if !self.console.is_empty() {
+            key.push('#');
+            for button in &self.console {
+                key.push_str(&button.name);
+                key.push('|');
+            }
+        }
+
+        for port in 1..=self.controllers {
+            key.push_str(&format!("#P{port} X Axis|P{port} Y Axis|"));
+            for button in &self.buttons {
+                key.push_str(&format!("P{port} {}|", button.name));
+            }
+        }
+
+        key
+    }
+
+    /// Parses the frame lines of an input log into controller states, interleaved by
+    /// controller as in [`Movie::samples`].
+    ///
+    /// The `[Input]` and `[/Input]` markers, the `LogKey` line and blank lines are
+    /// skipped, so both a whole `Input Log.txt` and bare frame lines are accepted.
+    pub fn parse(&self, text: &str) -> Result<Vec<ControllerState>, MovieError> {
+        let mut samples = Vec::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty()
+                || line == "[Input]"
+                || line == "[/Input]"
+                || line.starts_with("LogKey:")
+            {
+                continue;
+            }
+
+            self.parse_line(line, &mut samples)
+                .map_err(|reason| ConvertError::InvalidLine {
+                    line: index + 1,
+                    reason,
+                })?;
+        }
+
+        Ok(samples)
+    }
+
+    /// Parses a single frame line, appending one state per controller.
+    fn parse_line(&self, line: &str, samples: &mut Vec<ControllerState>) -> Result<(), String> {
+        let groups = line
+            .strip_prefix('|')
+            .and_then(|line| line.strip_suffix('|'))
+            .ok_or("frame lines must start and end with '|'")?;
+        let mut groups = groups.split('|');
+
+        if !self.console.is_empty() {
+            let console = groups.next().unwrap_or_default();
+            if console.chars().count() != self.console.len() {
+                return Err(format!(
+                    "expected {} console buttons, found {console:?}",
+                    self.console.len()
+                ));
+            }
+        }
+
+        for port in 1..=self.controllers {
+            let group = groups
+                .next()
+                .ok_or_else(|| format!("missing controller {port}"))?;
+            samples.push(self.parse_controller(group)?);
+        }
+
+        match groups.next() {
+            Some(extra) => Err(format!("unexpected group {extra:?}")),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses the group of a single controller: its axes followed by its buttons.
+    fn parse_controller(&self, group: &str) -> Result<ControllerState, String> {
+        let mut fields = group.splitn(3, ',');
+        let mut axis = || {
+            let value = fields.next().unwrap_or_default().trim();
+            value
+                .parse::<i8>()
+                .map_err(|_| format!("invalid axis value {value:?}"))
+        };
+        let (x, y) = (axis()?, axis()?);
+
+        let buttons = fields.next().unwrap_or_default();
+        if buttons.chars().count() != self.buttons.len() {
+            return Err(format!(
+                "expected {} buttons, found {buttons:?}",
+                self.buttons.len()
+            ));
+        }
+
+        let mut state = ControllerState::default();
+        state.set_axis(x, y);
+        for (column, c) in self.buttons.iter().zip(buttons.chars()) {
+            if let (Some(button), true) = (column.button, c != '.') {
+                state.set(button);
+            }
+        }
+
+        Ok(state)
+    }
+
+    /// Writes controller states as an input log, including the `[Input]` markers and
+    /// the `LogKey` line. The states are interleaved by controller as in
+    /// [`Movie::samples`]; a trailing partial frame is ignored.
+    pub fn render(&self, samples: &[ControllerState]) -> String {
+        let mut log = format!("[Input]\nLogKey:{}\n", self.log_key());
+
+        if self.controllers != 0 {
+            for frame in samples.chunks_exact(self.controllers) {
+                log.push('|');
+                if !self.console.is_empty() {
+                    log.extend(self.console.iter().map(|_| '.'));
+                    log.push('|');
+                }
+
+                for state in frame {
+                    log.push_str(&format!("{:5},{:5},", state.x_axis(), state.y_axis()));
+                    log.extend(self.buttons.iter().map(|column| match column.button {
+                        Some(button) if state.is_set(button) => column.mnemonic,
+                        _ => '.',
+                    }));
+                    log.push('|');
+                }
+                log.push('\n');
+            }
+        }
+
+        log.push_str("[/Input]\n");
+        log
+    }
+}
+
+impl Movie {
+    /// Exports the inputs of the movie as a BizHawk input log.
+    pub fn to_bizhawk_input_log(&self) -> String {
+        InputLogLayout::n64(self.controller_count()).render(self.samples())
+    }
+}
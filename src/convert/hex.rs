@@ -0,0 +1,164 @@
+//! Raw hexadecimal inputs.
+//!
+//! Inputs are often exchanged in forum posts and resync notes as the hexadecimal
+//! value of each sample: the `BUTTONS` mask of the zilmar plugin specification,
+//! followed by the x and y axis bytes, e.g. `0080_7F00` for A held with the stick
+//! fully right. See [`ControllerState::to_hex`].
+//!
+//! A block of frames is written one frame per line, with the frame index followed by
+//! one sample per controller:
+//!
+//! ```text
+//! # frames 100..102
+//! 100: 0080_7F00
+//! 101: 0000_7F00
+//! ```
+//!
+//! Blank lines and lines starting with `#` are ignored.
+
+use std::ops::Range;
+
+use crate::{
+    ConvertError, FrameError, Movie, MovieError, raw::ControllerState, selection::FrameSelection,
+    track::FrameIndex,
+};
+
+impl ControllerState {
+    /// Formats the state as `BBBB_XXYY`: the `BUTTONS` mask, then the x and y axis
+    /// bytes, in uppercase hexadecimal.
+    pub fn to_hex(&self) -> String {
+        let value = u32::from(*self);
+        format!(
+            "{:04X}_{:02X}{:02X}",
+            value & 0xFFFF,
+            (value >> 16) & 0xFF,
+            value >> 24
+        )
+    }
+
+    /// Parses a state formatted by [`ControllerState::to_hex`]. The underscore is
+    /// optional, and digits may be in either case.
+    pub fn from_hex(s: &str) -> Result<Self, MovieError> {
+        let digits: String = s.trim().chars().filter(|&c| c != '_').collect();
+        if digits.len() != 8 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ConvertError::InvalidHex(s.to_string()).into());
+        }
+
+        let parse = |range: Range<usize>| u32::from_str_radix(&digits[range], 16).unwrap();
+        let value = parse(0..4) | parse(4..6) << 16 | parse(6..8) << 24;
+        Ok(ControllerState::from(value))
+    }
+}
+
+/// Parses a block of frames, returning the frame indices with their samples, in the
+/// order they appear.
+pub fn parse_hex_block(text: &str) -> Result<Vec<(FrameIndex, Vec<ControllerState>)>, MovieError> {
+    let mut frames = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |reason: String| ConvertError::InvalidLine {
+            line: index + 1,
+            reason,
+        };
+
+        let (frame, samples) = line
+            .split_once(':')
+            .ok_or_else(|| invalid("expected a frame index followed by ':'".to_string()))?;
+        let frame = frame
+            .trim()
+            .parse()
+            .map_err(|_| invalid(format!("invalid frame index {:?}", frame.trim())))?;
+        let samples = samples
+            .split_whitespace()
+            .map(|sample| {
+                ControllerState::from_hex(sample)
+                    .map_err(|_| invalid(format!("invalid sample {sample:?}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        frames.push((frame, samples));
+    }
+
+    Ok(frames)
+}
+
+impl Movie {
+    /// Formats a range of frames as a hexadecimal block.
+    pub fn to_hex_block(&self, frames: Range<FrameIndex>) -> Result<String, MovieError> {
+        self.to_hex_block_in(&frames.into())
+    }
+
+    /// Formats the selected frames as a hexadecimal block, with one `# frames` line
+    /// listing the selected ranges.
+    pub fn to_hex_block_in(&self, selection: &FrameSelection) -> Result<String, MovieError> {
+        let frame_count = self.frame_count();
+        if let Some(last) = selection.ranges().last()
+            && last.end > frame_count
+        {
+            return Err(FrameError::OutOfBounds {
+                frame: last.end,
+                frame_count,
+            }
+            .into());
+        }
+
+        let ranges: Vec<String> = selection
+            .ranges()
+            .iter()
+            .map(|range| format!("{}..{}", range.start, range.end))
+            .collect();
+        let mut block = format!("# frames {}\n", ranges.join(", "));
+        for frame in selection.frames() {
+            let samples: Vec<String> = self
+                .frame(frame)
+                .unwrap_or_default()
+                .iter()
+                .map(ControllerState::to_hex)
+                .collect();
+            block.push_str(&format!("{frame}: {}\n", samples.join(" ")));
+        }
+
+        Ok(block)
+    }
+
+    /// Replaces the frames listed in a hexadecimal block, returning the number of
+    /// frames replaced.
+    ///
+    /// Every frame must exist and list one sample per controller. Otherwise an error
+    /// is returned and the movie is unchanged.
+    pub fn import_hex_block(&mut self, text: &str) -> Result<usize, MovieError> {
+        let frames = parse_hex_block(text)?;
+
+        let frame_count = self.frame_count();
+        let controller_count = self.controller_count();
+        for (frame, samples) in &frames {
+            if *frame >= frame_count {
+                return Err(FrameError::OutOfBounds {
+                    frame: *frame,
+                    frame_count,
+                }
+                .into());
+            }
+            if samples.len() != controller_count {
+                return Err(FrameError::PartialFrame {
+                    samples: samples.len(),
+                    controller_count,
+                }
+                .into());
+            }
+        }
+
+        for (frame, samples) in &frames {
+            if let Some(target) = self.frame_mut(*frame) {
+                target.copy_from_slice(samples);
+            }
+        }
+
+        Ok(frames.len())
+    }
+}
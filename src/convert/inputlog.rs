@@ -0,0 +1,125 @@
+//! Input logs dumped by input plugins during playback.
+//!
+//! Some input plugins can log every controller poll they answer, which shows what the
+//! emulator actually received. Each line holds the frame, the controller port and
+//! the raw 32-bit sample, in hexadecimal with an optional `0x` prefix:
+//!
+//! ```text
+//! # frame port sample
+//! 0 0 0x00000000
+//! 1 0 0x00000080
+//! ```
+//!
+//! [`Movie::first_log_mismatch`] compares a log against the inputs of the movie, to
+//! find where a desynced playback started receiving something else.
+
+use std::fmt::{self, Display};
+
+use crate::{ConvertError, Movie, MovieError, raw::ControllerState, track::FrameIndex};
+
+/// A controller poll answered by an input plugin.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InputPoll {
+    /// The frame of the poll.
+    pub frame: FrameIndex,
+    /// The controller port polled.
+    pub port: usize,
+    /// The state returned to the emulator.
+    pub state: ControllerState,
+}
+
+/// Parses an input log, keeping the polls in order.
+pub fn parse_input_log(text: &str) -> Result<Vec<InputPoll>, MovieError> {
+    let mut polls = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |reason: String| ConvertError::InvalidLine {
+            line: index + 1,
+            reason,
+        };
+
+        let mut fields = line.split_whitespace();
+        let (Some(frame), Some(port), Some(sample), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid("expected a frame, a port and a sample".to_string()).into());
+        };
+
+        let frame = frame
+            .parse()
+            .map_err(|_| invalid(format!("invalid frame {frame:?}")))?;
+        let port = port
+            .parse()
+            .map_err(|_| invalid(format!("invalid port {port:?}")))?;
+        let digits = sample
+            .strip_prefix("0x")
+            .or_else(|| sample.strip_prefix("0X"))
+            .unwrap_or(sample);
+        let sample = u32::from_str_radix(digits, 16)
+            .map_err(|_| invalid(format!("invalid sample {sample:?}")))?;
+
+        polls.push(InputPoll {
+            frame,
+            port,
+            state: ControllerState::from(sample),
+        });
+    }
+
+    Ok(polls)
+}
+
+/// A poll where the emulator received something other than the movie's input.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LogMismatch {
+    /// The frame of the poll.
+    pub frame: FrameIndex,
+    /// The controller port polled.
+    pub port: usize,
+    /// The state in the movie, or `None` if the movie has no such frame or port.
+    pub expected: Option<ControllerState>,
+    /// The state the emulator received.
+    pub received: ControllerState,
+}
+
+impl Display for LogMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "frame {}, port {}: ", self.frame, self.port)?;
+        match self.expected {
+            Some(expected) => write!(f, "expected {}", expected.to_hex())?,
+            None => write!(f, "expected no input")?,
+        }
+        write!(f, ", received {}", self.received.to_hex())
+    }
+}
+
+impl Movie {
+    /// Returns the earliest poll of an input log that differs from the movie, by
+    /// frame then port, or `None` if the emulator received the movie's inputs.
+    ///
+    /// Neutral polls past the end of the movie are ignored, as plugins return a
+    /// neutral state once the movie is over.
+    pub fn first_log_mismatch(&self, polls: &[InputPoll]) -> Option<LogMismatch> {
+        polls
+            .iter()
+            .filter_map(|poll| {
+                let expected = self.port_state(poll.frame, poll.port).copied();
+                let matches = match expected {
+                    Some(expected) => expected == poll.state,
+                    None => poll.state == ControllerState::default(),
+                };
+
+                (!matches).then_some(LogMismatch {
+                    frame: poll.frame,
+                    port: poll.port,
+                    expected,
+                    received: poll.state,
+                })
+            })
+            .min_by_key(|mismatch| (mismatch.frame, mismatch.port))
+    }
+}
@@ -0,0 +1,173 @@
+//! Timestamped keyboard recordings.
+//!
+//! A human playthrough recorded as key events can be turned into a starting point for
+//! a TAS. Each line of a key log holds the time of the event in milliseconds, `down`
+//! or `up`, and the key:
+//!
+//! ```text
+//! # time down|up key
+//! 0 down Right
+//! 120 down X
+//! 180 up X
+//! ```
+//!
+//! [`KeyBindings`] map keys to buttons and stick directions, and
+//! [`KeyBindings::to_states`] quantizes the events to frames using the [`Timing`] of
+//! a movie. A frame holds the keys held when it starts, plus the keys pressed during
+//! it, so taps shorter than a frame are kept.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
+
+use crate::{ControllerButton, ConvertError, MovieError, raw::ControllerState, timing::Timing};
+
+/// A key press or release.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KeyEvent {
+    /// When the event happened, from the start of the recording.
+    pub time: Duration,
+    /// The key.
+    pub key: String,
+    /// `true` if the key was pressed, `false` if it was released.
+    pub pressed: bool,
+}
+
+/// Parses a key log, returning the events sorted by time. Events at the same time
+/// keep their order.
+pub fn parse_key_log(text: &str) -> Result<Vec<KeyEvent>, MovieError> {
+    let mut events = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |reason: String| ConvertError::InvalidLine {
+            line: index + 1,
+            reason,
+        };
+
+        let mut fields = line.split_whitespace();
+        let (Some(time), Some(action), Some(key), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid("expected a time, `down` or `up`, and a key".to_string()).into());
+        };
+
+        let time = time
+            .parse()
+            .map_err(|_| invalid(format!("invalid time {time:?}")))?;
+        let pressed = match action {
+            "down" => true,
+            "up" => false,
+            _ => return Err(invalid(format!("expected `down` or `up`, found {action:?}")).into()),
+        };
+
+        events.push(KeyEvent {
+            time: Duration::from_millis(time),
+            key: key.to_string(),
+            pressed,
+        });
+    }
+
+    events.sort_by_key(|event| event.time);
+    Ok(events)
+}
+
+/// What a key does on the controller.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum KeyBinding {
+    /// The key holds a button.
+    Button(ControllerButton),
+    /// The key pushes the stick. The pushes of keys held together are added up.
+    Stick {
+        /// The x-axis push.
+        x: i8,
+        /// The y-axis push.
+        y: i8,
+    },
+}
+
+/// The bindings of keys to a controller.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct KeyBindings {
+    /// The binding of each key.
+    bindings: BTreeMap<String, KeyBinding>,
+}
+
+impl KeyBindings {
+    /// Creates bindings without any key bound.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds a key, replacing its previous binding.
+    pub fn bind<S: Into<String>>(mut self, key: S, binding: KeyBinding) -> Self {
+        self.bindings.insert(key.into(), binding);
+        self
+    }
+
+    /// Returns the binding of a key.
+    pub fn get(&self, key: &str) -> Option<KeyBinding> {
+        self.bindings.get(key).copied()
+    }
+
+    /// Returns the controller state with the given keys held. Unbound keys are
+    /// ignored, and the stick is clamped to ±127 on each axis.
+    pub fn state<'a, I>(&self, keys: I) -> ControllerState
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut state = ControllerState::default();
+        let (mut x, mut y) = (0i32, 0i32);
+
+        for binding in keys.into_iter().filter_map(|key| self.get(key)) {
+            match binding {
+                KeyBinding::Button(button) => state.set(button),
+                KeyBinding::Stick { x: dx, y: dy } => {
+                    x += dx as i32;
+                    y += dy as i32;
+                }
+            }
+        }
+
+        state.set_x_axis(x.clamp(-127, 127) as i8);
+        state.set_y_axis(y.clamp(-127, 127) as i8);
+        state
+    }
+
+    /// Quantizes key events to one controller state per frame, up to the frame of the
+    /// last event.
+    pub fn to_states(&self, events: &[KeyEvent], timing: &Timing) -> Vec<ControllerState> {
+        let frames = events
+            .iter()
+            .map(|event| timing.duration_to_frame(event.time) + 1)
+            .max()
+            .unwrap_or(0);
+
+        let mut states = Vec::with_capacity(frames);
+        let mut events = events.iter().peekable();
+        let mut held = BTreeSet::new();
+
+        for frame in 0..frames {
+            let end = timing.frame_to_duration(frame + 1);
+            let mut active = held.clone();
+
+            while let Some(event) = events.next_if(|event| event.time < end) {
+                if event.pressed {
+                    held.insert(event.key.as_str());
+                    active.insert(event.key.as_str());
+                } else {
+                    held.remove(event.key.as_str());
+                }
+            }
+
+            states.push(self.state(active));
+        }
+
+        states
+    }
+}
@@ -0,0 +1,7 @@
+//! Conversion of movie inputs to and from the formats of other emulators.
+
+pub mod bizhawk;
+pub mod hex;
+pub mod inputlog;
+pub mod keylog;
+pub mod pj64;
@@ -0,0 +1,74 @@
+//! Project64 rerecording movies (`.pjm`).
+//!
+//! The Project64 rerecording builds record movies in a layout derived from version 3
+//! of the M64 format: the header fields sit at the same offsets, and inputs use the
+//! same `BUTTONS` encoding of the zilmar plugin specification. The differences are:
+//!
+//! - the signature is `PJM ` instead of `M64\x1A`, followed by version 1;
+//! - the extended version, flags and data (0x016, 0x017 and 0x024 to 0x044) are
+//!   reserved and always zero.
+//!
+//! Converting a movie to PJM therefore drops its extended data.
+
+use crate::{
+    BinReadExt, BinWriteExt, ConvertError, M64_MAGIC, Movie, MovieError, raw::m64::HEADER_LEN,
+};
+
+/// The signature at the start of every PJM file.
+pub const PJM_MAGIC: [u8; 4] = *b"PJM ";
+
+/// The only PJM version supported.
+const PJM_VERSION: u32 = 1;
+
+/// The M64 version PJM headers are converted to.
+const M64_VERSION: u32 = 3;
+
+/// The header ranges that are reserved in PJM, but hold extended fields in M64.
+const EXTENDED_RANGES: [std::ops::Range<usize>; 2] = [0x016..0x018, 0x024..0x044];
+
+/// Replaces the signature and version of a header, and clears its extended fields.
+fn convert_header(bytes: &mut [u8], magic: [u8; 4], version: u32) {
+    bytes[..4].copy_from_slice(&magic);
+    bytes[4..8].copy_from_slice(&version.to_le_bytes());
+    for range in EXTENDED_RANGES {
+        bytes[range].fill(0);
+    }
+}
+
+impl Movie {
+    /// Reads a Project64 rerecording movie.
+    pub fn from_pjm_bytes(bytes: &[u8]) -> Result<Self, MovieError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(ConvertError::InvalidHeader(format!(
+                "PJM files have a {HEADER_LEN}-byte header, found {} bytes",
+                bytes.len()
+            ))
+            .into());
+        }
+        if bytes[..4] != PJM_MAGIC {
+            return Err(ConvertError::InvalidHeader(format!(
+                "expected the PJM signature, found {:02X?}",
+                &bytes[..4]
+            ))
+            .into());
+        }
+
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version != PJM_VERSION {
+            return Err(
+                ConvertError::InvalidHeader(format!("unsupported PJM version {version}")).into(),
+            );
+        }
+
+        let mut bytes = bytes.to_vec();
+        convert_header(&mut bytes, M64_MAGIC, M64_VERSION);
+        Movie::from_bytes(&bytes)
+    }
+
+    /// Encodes the movie as a Project64 rerecording movie, dropping its extended data.
+    pub fn to_pjm_bytes(&self) -> Result<Vec<u8>, MovieError> {
+        let mut bytes = self.to_bytes()?;
+        convert_header(&mut bytes, PJM_MAGIC, PJM_VERSION);
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,55 @@
+//! The sample movies shipped with the crate.
+//!
+//! The movies the crate is tested against are embedded in the library, so downstream
+//! crates and fuzzers can exercise real files without keeping their own copies.
+//!
+//! ```
+//! use m64_movie::corpus::iter_bundled_movies;
+//!
+//! for bundled in iter_bundled_movies() {
+//!     let movie = bundled.parse().unwrap();
+//!     println!("{}: {} frames", bundled.name, movie.frame_count());
+//! }
+//! ```
+
+use crate::{BinReadExt, Movie, MovieError};
+
+/// A sample movie embedded in the crate.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BundledMovie {
+    /// The file name of the movie.
+    pub name: &'static str,
+    /// The bytes of the movie file.
+    pub bytes: &'static [u8],
+}
+
+impl BundledMovie {
+    /// Parses the movie.
+    pub fn parse(&self) -> Result<Movie, MovieError> {
+        Movie::from_bytes(self.bytes)
+    }
+}
+
+/// Creates a [`BundledMovie`] from a file of the `movies` directory.
+macro_rules! bundled {
+    ($name:literal) => {
+        BundledMovie {
+            name: $name,
+            bytes: include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/", $name)),
+        }
+    };
+}
+
+/// Every sample movie, sorted by name.
+pub const BUNDLED_MOVIES: &[BundledMovie] =
+    &[bundled!("120 star tas (2012).m64"), bundled!("1key.m64")];
+
+/// Returns an iterator over every sample movie, sorted by name.
+pub fn iter_bundled_movies() -> impl Iterator<Item = &'static BundledMovie> {
+    BUNDLED_MOVIES.iter()
+}
+
+/// Returns the sample movie with the given file name.
+pub fn bundled_movie(name: &str) -> Option<&'static BundledMovie> {
+    BUNDLED_MOVIES.iter().find(|movie| movie.name == name)
+}
@@ -0,0 +1,158 @@
+//! Checked updates of the header counters.
+//!
+//! The counters of the header are 32-bit fields. The counters of [`RecordingInfo`]
+//! can only be updated through the methods of this module, which report an overflow
+//! as a [`CounterError`] instead of wrapping, leaving the movie unchanged. This is
+//! mostly relevant to absurdly long movies, and to rerecord counts carried over from
+//! tools that count past 32 bits.
+//!
+//! Movies with extended version 1 store the high word of the rerecord count in their
+//! [`ExtendedData`]. [`Movie::add_rerecords`] carries into it and
+//! [`Movie::set_rerecord_count`] sets both words, so their rerecord count only
+//! overflows at 64 bits.
+//!
+//! The number of input samples is not stored in the movie but derived from its inputs
+//! when it is written, which fails if the movie has more frames than the header can
+//! declare.
+
+use crate::{
+    CounterError, FrameError, MovieError,
+    parsed::{ExtendedData, Movie, RecordingInfo},
+    raw::ControllerState,
+};
+
+/// The largest number of frames a movie can declare.
+pub const MAX_FRAME_COUNT: usize = u32::MAX as usize;
+
+/// Returns a counter as a `u32`, or an overflow error naming it.
+fn checked(counter: &'static str, value: u64) -> Result<u32, CounterError> {
+    u32::try_from(value).map_err(|_| CounterError::Overflow { counter, value })
+}
+
+impl RecordingInfo {
+    /// Returns the number of vertical interrupts in the movie.
+    pub fn vertical_interrupts(&self) -> u32 {
+        self.vertical_interrupts
+    }
+
+    /// Returns the low word of the number of rerecords in the movie. See
+    /// [`Movie::rerecord_count`] for the whole count.
+    pub fn rerecord_count(&self) -> u32 {
+        self.rerecord_count
+    }
+
+    /// Adds VIs to the VI count, returning the new count.
+    pub fn add_vertical_interrupts(&mut self, count: u64) -> Result<u32, CounterError> {
+        self.set_vertical_interrupts(self.vertical_interrupts as u64 + count)?;
+        Ok(self.vertical_interrupts)
+    }
+
+    /// Sets the VI count.
+    pub fn set_vertical_interrupts(&mut self, count: u64) -> Result<(), CounterError> {
+        self.vertical_interrupts = checked("vertical_interrupts", count)?;
+        Ok(())
+    }
+
+    /// Adds rerecords to the low word of the rerecord count, returning the new count.
+    ///
+    /// Unlike [`Movie::add_rerecords`], this does not carry into the high word.
+    pub fn add_rerecords(&mut self, count: u64) -> Result<u32, CounterError> {
+        self.set_rerecord_count(self.rerecord_count as u64 + count)?;
+        Ok(self.rerecord_count)
+    }
+
+    /// Sets the rerecord count.
+    pub fn set_rerecord_count(&mut self, count: u64) -> Result<(), CounterError> {
+        self.rerecord_count = checked("rerecord_count", count)?;
+        Ok(())
+    }
+}
+
+impl Movie {
+    /// Returns the rerecord count, including its high word for extended version 1
+    /// movies.
+    pub fn rerecord_count(&self) -> u64 {
+        let high = match self.metadata.extended_data {
+            ExtendedData::ExtendedDataV1 {
+                rerecord_count_high,
+                ..
+            } => rerecord_count_high,
+            ExtendedData::ExtendedDataV0 => 0,
+        };
+        (high as u64) << 32 | self.recording_info.rerecord_count as u64
+    }
+
+    /// Sets the rerecord count.
+    ///
+    /// For extended version 1 movies, the high word is stored in the [`ExtendedData`].
+    /// Other movies only have the 32-bit count of [`RecordingInfo`].
+    pub fn set_rerecord_count(&mut self, count: u64) -> Result<(), CounterError> {
+        let ExtendedData::ExtendedDataV1 {
+            rerecord_count_high,
+            ..
+        } = &mut self.metadata.extended_data
+        else {
+            return self.recording_info.set_rerecord_count(count);
+        };
+
+        *rerecord_count_high = (count >> 32) as u32;
+        self.recording_info.rerecord_count = count as u32;
+        Ok(())
+    }
+
+    /// Adds rerecords to the rerecord count, returning the new count.
+    ///
+    /// For extended version 1 movies, the count carries into the high word of the
+    /// [`ExtendedData`]. Other movies only have the 32-bit count of [`RecordingInfo`].
+    pub fn add_rerecords(&mut self, count: u64) -> Result<u64, CounterError> {
+        let total = self.rerecord_count() as u128 + count as u128;
+        let ExtendedData::ExtendedDataV1 {
+            rerecord_count_high,
+            ..
+        } = &mut self.metadata.extended_data
+        else {
+            return self.recording_info.add_rerecords(count).map(u64::from);
+        };
+
+        *rerecord_count_high = checked("extended_data.rerecord_count_high", (total >> 32) as u64)?;
+        self.recording_info.rerecord_count = total as u32;
+        Ok(total as u64)
+    }
+
+    /// Appends input samples, which must make up whole frames, to the movie.
+    ///
+    /// Fails without changing the movie if the samples do not make up whole frames, or
+    /// if the movie would have more frames than the header can declare.
+    pub fn add_samples(&mut self, samples: &[ControllerState]) -> Result<(), MovieError> {
+        let controller_count = self.controller_count();
+        if !samples.len().is_multiple_of(controller_count) {
+            return Err(FrameError::PartialFrame {
+                samples: samples.len(),
+                controller_count,
+            }
+            .into());
+        }
+
+        check_frame_count(
+            self.frame_count() as u64
+                + samples.len().checked_div(controller_count).unwrap_or(0) as u64,
+        )?;
+        self.inputs.extend_from_slice(samples);
+        Ok(())
+    }
+
+    /// Shortens the movie to `frames` frames, or extends it with neutral frames.
+    ///
+    /// Fails without changing the movie if the header cannot declare that many frames.
+    pub fn set_frame_count(&mut self, frames: usize) -> Result<(), MovieError> {
+        check_frame_count(frames as u64)?;
+        self.inputs
+            .resize(frames * self.controller_count(), ControllerState::default());
+        Ok(())
+    }
+}
+
+/// Checks that the header can declare a number of frames.
+pub(crate) fn check_frame_count(frames: u64) -> Result<(), CounterError> {
+    checked("controller_input_samples", frames).map(|_| ())
+}
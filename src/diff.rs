@@ -0,0 +1,470 @@
+//! Edit scripts between input tracks.
+//!
+//! [`EditScript::between`] finds a shortest sequence of frame insertions and deletions
+//! turning one [`InputTrack`] into another, using the O(ND) difference algorithm by
+//! Myers in linear space. Adjacent deletions and insertions are reported together as
+//! a [`Edit::Replace`].
+//!
+//! [`MovieDiff::between`] compares whole movies, header and every controller. A
+//! [`DryRun`] applies edits to a scratch copy of a movie and reports the would-be
+//! [`MovieDiff`], so a destructive edit can be confirmed before it is made.
+
+use std::{
+    fmt::{self, Display},
+    ops::{Deref, DerefMut, Range},
+};
+
+use crate::{
+    FrameError, Movie, MovieError,
+    layout::HEADER_FIELDS,
+    raw::ControllerState,
+    track::{FrameIndex, InputTrack},
+};
+
+/// A change to a run of frames. Frame indices refer to the original track.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Edit {
+    /// Frames inserted before frame `at`.
+    Insert {
+        /// The frame the states are inserted before.
+        at: FrameIndex,
+        /// The inserted states.
+        states: Vec<ControllerState>,
+    },
+    /// Frames removed.
+    Delete {
+        /// The removed frames.
+        frames: Range<FrameIndex>,
+    },
+    /// Frames replaced by a possibly different number of frames.
+    Replace {
+        /// The replaced frames.
+        frames: Range<FrameIndex>,
+        /// The states replacing them.
+        states: Vec<ControllerState>,
+    },
+}
+
+impl Edit {
+    /// Returns the frames of the original track affected by the edit. Empty for
+    /// insertions.
+    pub fn frames(&self) -> Range<FrameIndex> {
+        match self {
+            Edit::Insert { at, .. } => *at..*at,
+            Edit::Delete { frames } | Edit::Replace { frames, .. } => frames.clone(),
+        }
+    }
+
+    /// Returns the states the edit introduces. Empty for deletions.
+    pub fn states(&self) -> &[ControllerState] {
+        match self {
+            Edit::Insert { states, .. } | Edit::Replace { states, .. } => states,
+            Edit::Delete { .. } => &[],
+        }
+    }
+}
+
+/// The edits turning one input track into another, in frame order.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct EditScript {
+    /// The edits, in frame order, with no two touching the same frames.
+    edits: Vec<Edit>,
+}
+
+impl EditScript {
+    /// Computes a minimal edit script turning `old` into `new`.
+    pub fn between(old: &InputTrack, new: &InputTrack) -> Self {
+        let old = old.to_vec();
+        let new = new.to_vec();
+
+        let mut ops = Vec::new();
+        diff_into(&old, &new, &mut ops);
+
+        let mut edits = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        let (mut deleted, mut inserted) = (0, 0);
+
+        let mut flush = |i: usize, j: usize, deleted: &mut usize, inserted: &mut usize| {
+            let frames = i - *deleted..i;
+            let states = new[j - *inserted..j].to_vec();
+            match (*deleted, *inserted) {
+                (0, 0) => {}
+                (0, _) => edits.push(Edit::Insert {
+                    at: frames.start,
+                    states,
+                }),
+                (_, 0) => edits.push(Edit::Delete { frames }),
+                _ => edits.push(Edit::Replace { frames, states }),
+            }
+            *deleted = 0;
+            *inserted = 0;
+        };
+
+        for op in ops {
+            match op {
+                Op::Equal(len) => {
+                    flush(i, j, &mut deleted, &mut inserted);
+                    i += len;
+                    j += len;
+                }
+                Op::Delete(len) => {
+                    deleted += len;
+                    i += len;
+                }
+                Op::Insert(len) => {
+                    inserted += len;
+                    j += len;
+                }
+            }
+        }
+        flush(i, j, &mut deleted, &mut inserted);
+
+        EditScript { edits }
+    }
+
+    /// Returns the edits, in frame order.
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    /// Returns `true` if the tracks are identical.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Returns the number of frames deleted plus the number of frames inserted.
+    /// A replacement counts as both.
+    pub fn distance(&self) -> usize {
+        self.edits
+            .iter()
+            .map(|edit| edit.frames().len() + edit.states().len())
+            .sum()
+    }
+
+    /// Applies the edits to a track, returning the edited track.
+    ///
+    /// Returns an error if an edit refers to frames past the end of the track.
+    pub fn apply(&self, track: &InputTrack) -> Result<InputTrack, MovieError> {
+        let states = track.to_vec();
+        let mut result = Vec::with_capacity(states.len());
+        let mut position = 0;
+
+        for edit in &self.edits {
+            let frames = edit.frames();
+            if frames.end > states.len() || frames.start < position {
+                return Err(FrameError::OutOfBounds {
+                    frame: frames.end,
+                    frame_count: states.len(),
+                }
+                .into());
+            }
+
+            result.extend_from_slice(&states[position..frames.start]);
+            result.extend_from_slice(edit.states());
+            position = frames.end;
+        }
+        result.extend_from_slice(&states[position..]);
+
+        Ok(result.into())
+    }
+}
+
+/// The differences between two movies.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct MovieDiff {
+    /// The names of the header fields whose encoding differs, in file order.
+    pub header_fields: Vec<&'static str>,
+    /// The edits turning each controller's track into the new one, by port. A
+    /// controller present in only one of the movies is compared against an empty track.
+    pub tracks: Vec<EditScript>,
+    /// The number of frames of the old movie.
+    pub old_frame_count: usize,
+    /// The number of frames of the new movie.
+    pub new_frame_count: usize,
+}
+
+impl MovieDiff {
+    /// Compares two movies.
+    pub fn between(old: &Movie, new: &Movie) -> Result<Self, MovieError> {
+        let (old_header, new_header) = (old.header_bytes()?, new.header_bytes()?);
+        let header_fields = HEADER_FIELDS
+            .iter()
+            .filter(|field| old_header[field.range()] != new_header[field.range()])
+            .map(|field| field.name)
+            .collect();
+
+        let ports = old.controller_count().max(new.controller_count());
+        let tracks = (0..ports)
+            .map(|port| {
+                let old = old.track(port).unwrap_or_default();
+                let new = new.track(port).unwrap_or_default();
+                EditScript::between(&old, &new)
+            })
+            .collect();
+
+        Ok(MovieDiff {
+            header_fields,
+            tracks,
+            old_frame_count: old.frame_count(),
+            new_frame_count: new.frame_count(),
+        })
+    }
+
+    /// Returns `true` if the movies are identical.
+    pub fn is_empty(&self) -> bool {
+        self.header_fields.is_empty() && self.tracks.iter().all(EditScript::is_empty)
+    }
+}
+
+impl Display for MovieDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+
+        if self.old_frame_count != self.new_frame_count {
+            writeln!(
+                f,
+                "frames: {} -> {}",
+                self.old_frame_count, self.new_frame_count
+            )?;
+        }
+
+        if !self.header_fields.is_empty() {
+            writeln!(f, "header: {}", self.header_fields.join(", "))?;
+        }
+
+        for (port, script) in self.tracks.iter().enumerate() {
+            for edit in script.edits() {
+                let frames = edit.frames();
+                match edit {
+                    Edit::Insert { at, states } => {
+                        writeln!(f, "port {port}: insert {} frames at {at}", states.len())?
+                    }
+                    Edit::Delete { .. } => writeln!(
+                        f,
+                        "port {port}: delete frames {}..{}",
+                        frames.start, frames.end
+                    )?,
+                    Edit::Replace { states, .. } => writeln!(
+                        f,
+                        "port {port}: replace frames {}..{} with {} frames",
+                        frames.start,
+                        frames.end,
+                        states.len()
+                    )?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A scratch copy of a movie for previewing edits without modifying it.
+///
+/// A dry run dereferences to the copy, so any editing method of [`Movie`], or a
+/// function from [`transform`](crate::transform), can be applied to it.
+/// [`DryRun::diff`] then reports what the edits would change in the original:
+///
+/// ```
+/// use m64_movie::{BinReadExt, Movie, diff::DryRun};
+///
+/// # let movie = Movie::from_bytes(include_bytes!("../movies/1key.m64")).unwrap();
+/// let mut dry_run = DryRun::new(&movie);
+/// dry_run.remove_frames(0..30)?;
+/// let diff = dry_run.diff()?;
+/// assert_eq!(diff.new_frame_count, movie.frame_count() - 30);
+/// # Ok::<(), m64_movie::MovieError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct DryRun<'a> {
+    /// The movie the edits are previewed for.
+    original: &'a Movie,
+    /// The copy the edits are applied to.
+    scratch: Movie,
+}
+
+impl<'a> DryRun<'a> {
+    /// Starts a dry run on a copy of a movie.
+    pub fn new(movie: &'a Movie) -> Self {
+        DryRun {
+            original: movie,
+            scratch: movie.clone(),
+        }
+    }
+
+    /// Returns the changes the edits made so far would make to the original movie.
+    pub fn diff(&self) -> Result<MovieDiff, MovieError> {
+        MovieDiff::between(self.original, &self.scratch)
+    }
+
+    /// Returns the edited copy, to commit the edits.
+    pub fn into_movie(self) -> Movie {
+        self.scratch
+    }
+}
+
+impl Deref for DryRun<'_> {
+    type Target = Movie;
+
+    fn deref(&self) -> &Self::Target {
+        &self.scratch
+    }
+}
+
+impl DerefMut for DryRun<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.scratch
+    }
+}
+
+impl Movie {
+    /// Returns the changes an edit would make to the movie, without making them.
+    /// See [`DryRun`].
+    pub fn preview<F, T>(&self, edit: F) -> Result<MovieDiff, MovieError>
+    where
+        F: FnOnce(&mut Movie) -> Result<T, MovieError>,
+    {
+        let mut dry_run = DryRun::new(self);
+        edit(&mut dry_run)?;
+        dry_run.diff()
+    }
+}
+
+/// A run of frames in a difference, before grouping into [`Edit`]s.
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    /// Frames present in both tracks.
+    Equal(usize),
+    /// Frames only in the old track.
+    Delete(usize),
+    /// Frames only in the new track.
+    Insert(usize),
+}
+
+/// Appends the difference between `a` and `b` to `ops`.
+fn diff_into(a: &[ControllerState], b: &[ControllerState], ops: &mut Vec<Op>) {
+    let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    push(ops, Op::Equal(prefix));
+    if a.is_empty() || b.is_empty() {
+        push(ops, Op::Delete(a.len()));
+        push(ops, Op::Insert(b.len()));
+    } else if let Some((x, y)) = middle_snake(a, b) {
+        diff_into(&a[..x], &b[..y], ops);
+        diff_into(&a[x..], &b[y..], ops);
+    } else {
+        push(ops, Op::Delete(a.len()));
+        push(ops, Op::Insert(b.len()));
+    }
+    push(ops, Op::Equal(suffix));
+}
+
+/// Appends a run to `ops`, merging it into the previous run of the same kind.
+fn push(ops: &mut Vec<Op>, op: Op) {
+    match (ops.last_mut(), op) {
+        (_, Op::Equal(0) | Op::Delete(0) | Op::Insert(0)) => {}
+        (Some(Op::Equal(last)), Op::Equal(len))
+        | (Some(Op::Delete(last)), Op::Delete(len))
+        | (Some(Op::Insert(last)), Op::Insert(len)) => *last += len,
+        _ => ops.push(op),
+    }
+}
+
+/// Finds the point where a shortest edit path crosses its middle, searching forwards
+/// from the start and backwards from the end at the same time.
+///
+/// Returns `None` if the tracks have nothing in common.
+fn middle_snake(a: &[ControllerState], b: &[ControllerState]) -> Option<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d;
+    let len = 2 * max_d + 2;
+    let delta = n - m;
+    let front = delta % 2 != 0;
+
+    let mut forward = vec![-1isize; len as usize];
+    let mut backward = vec![-1isize; len as usize];
+    forward[(offset + 1) as usize] = 0;
+    backward[(offset + 1) as usize] = 0;
+
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+
+    for d in 0..max_d {
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let k1_offset = (offset + k1) as usize;
+            let mut x1 = if k1 == -d || (k1 != d && forward[k1_offset - 1] < forward[k1_offset + 1])
+            {
+                forward[k1_offset + 1]
+            } else {
+                forward[k1_offset - 1] + 1
+            };
+            let mut y1 = x1 - k1;
+            while x1 < n && y1 < m && a[x1 as usize] == b[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            forward[k1_offset] = x1;
+
+            if x1 > n {
+                k1_end += 2;
+            } else if y1 > m {
+                k1_start += 2;
+            } else if front {
+                let k2_offset = offset + delta - k1;
+                if (0..len).contains(&k2_offset) && backward[k2_offset as usize] != -1 {
+                    let x2 = n - backward[k2_offset as usize];
+                    if x1 >= x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k1 += 2;
+        }
+
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let k2_offset = (offset + k2) as usize;
+            let mut x2 =
+                if k2 == -d || (k2 != d && backward[k2_offset - 1] < backward[k2_offset + 1]) {
+                    backward[k2_offset + 1]
+                } else {
+                    backward[k2_offset - 1] + 1
+                };
+            let mut y2 = x2 - k2;
+            while x2 < n && y2 < m && a[(n - x2 - 1) as usize] == b[(m - y2 - 1) as usize] {
+                x2 += 1;
+                y2 += 1;
+            }
+            backward[k2_offset] = x2;
+
+            if x2 > n {
+                k2_end += 2;
+            } else if y2 > m {
+                k2_start += 2;
+            } else if !front {
+                let k1_offset = offset + delta - k2;
+                if (0..len).contains(&k1_offset) && forward[k1_offset as usize] != -1 {
+                    let x1 = forward[k1_offset as usize];
+                    let y1 = offset + x1 - k1_offset;
+                    if x1 >= n - x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k2 += 2;
+        }
+    }
+
+    None
+}
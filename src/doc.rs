@@ -1,4 +1,215 @@
 //! Additional implementation details documentation.
+//!
+//! [`explain`] returns the documentation of a single header field, for editors that
+//! show it next to the value, e.g. as a tooltip.
 
 #[doc = include_str!("../doc/m64.md")]
 pub mod m64_header {}
+
+/// The documentation of a header field, as listed in [`m64_header`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct FieldDoc {
+    /// The name of the field, matching [`HeaderField::name`](crate::layout::HeaderField::name).
+    pub name: &'static str,
+    /// What the field stores.
+    pub description: &'static str,
+    /// The values the field may hold.
+    pub valid_values: &'static str,
+    /// The format versions the field applies to, and how it changed between them.
+    pub version_notes: &'static str,
+}
+
+/// Creates a [`FieldDoc`].
+const fn doc(
+    name: &'static str,
+    description: &'static str,
+    valid_values: &'static str,
+    version_notes: &'static str,
+) -> FieldDoc {
+    FieldDoc {
+        name,
+        description,
+        valid_values,
+        version_notes,
+    }
+}
+
+/// The documentation of every field in [`HEADER_FIELDS`](crate::layout::HEADER_FIELDS),
+/// in the same order.
+pub const FIELD_DOCS: &[FieldDoc] = &[
+    doc(
+        "magic",
+        "File signature identifying an M64 movie.",
+        "Always 4D 36 34 1A (\"M64\\x1A\").",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "version",
+        "Version of the movie format.",
+        "3. Versions 1 and 2 store inputs at a different offset and are unsupported.",
+        "Version 3 is written by every Mupen64 release since rerecording was added.",
+    ),
+    doc(
+        "uid",
+        "Identifier of the recording, which savestates are matched against.",
+        "The recording time as a Unix timestamp, but any value is accepted.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "vertical_interrupts",
+        "Number of vertical interrupts (VIs) emulated during the movie.",
+        "Any value. At least the number of frames for movies with inputs.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "rerecord_count",
+        "Number of times a savestate was loaded while recording.",
+        "Any value.",
+        "The high word is stored in the extended data since extended version 1.",
+    ),
+    doc(
+        "vis_per_second",
+        "Number of vertical interrupts per second.",
+        "60 for NTSC and 50 for PAL ROMs. 0 is read as the rate of the ROM's region.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "controller_count",
+        "Number of controllers present.",
+        "0 to 4, matching the presence bits of the controller flags.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "extended_version",
+        "Version of the extended header fields.",
+        "0, or 1 for movies recorded with Mupen64 1.1.9 or later.",
+        "0 is reserved for movies recorded before Mupen64 1.1.9.",
+    ),
+    doc(
+        "extended_flags",
+        "Flags describing the emulation settings of the recording.",
+        "Bit 0: recorded in WiiVC emulation mode. The other bits are reserved and 0.",
+        "Only valid if the extended version is 1 or more.",
+    ),
+    doc(
+        "controller_input_samples",
+        "Number of input samples stored, counting every controller.",
+        "The number of frames times the number of controllers.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "start_type",
+        "What the movie starts from.",
+        "1: a snapshot (.st file), 2: power-on, 4: EEPROM. Other values are invalid.",
+        "EEPROM starts were added by later Mupen64 releases.",
+    ),
+    doc(
+        "reserved01",
+        "Reserved space.",
+        "0.",
+        "Unused in every version.",
+    ),
+    doc(
+        "controller_flags",
+        "Which controllers are present, and their accessories.",
+        "Bits 0-3: controllers present, bits 4-7: memory paks, bits 8-11: rumble paks. \
+         The other bits are 0.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "extended_data.authorship_info",
+        "Special authorship information, such as the program that created the movie.",
+        "Any value.",
+        "Only valid if the extended version is 1 or more.",
+    ),
+    doc(
+        "extended_data.bruteforce_data",
+        "Additional data regarding bruteforcing.",
+        "Any value.",
+        "Only valid if the extended version is 1 or more.",
+    ),
+    doc(
+        "extended_data.rerecord_count_high",
+        "High word of the rerecord count.",
+        "Any value.",
+        "Only valid if the extended version is 1 or more.",
+    ),
+    doc(
+        "extended_data.reserved",
+        "Reserved extended data.",
+        "0.",
+        "Only valid if the extended version is 1 or more.",
+    ),
+    doc(
+        "reserved02",
+        "Reserved space.",
+        "0.",
+        "Unused in every version.",
+    ),
+    doc(
+        "rom_name",
+        "Internal name of the ROM used when recording, copied from the ROM header.",
+        "Up to 32 ASCII characters, padded with NUL bytes.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "rom_crc32",
+        "First checksum (CRC1) of the ROM used when recording, copied from the ROM header.",
+        "Any value.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "rom_country",
+        "Country code of the ROM used when recording, copied from the ROM header.",
+        "A known country code, e.g. 0x45 for North America or 0x4A for Japan.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "reserved03",
+        "Reserved space.",
+        "0.",
+        "Unused in every version.",
+    ),
+    doc(
+        "video_plugin",
+        "Name of the video plugin used when recording.",
+        "Up to 64 ASCII characters, padded with NUL bytes.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "sound_plugin",
+        "Name of the sound plugin used when recording.",
+        "Up to 64 ASCII characters, padded with NUL bytes.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "input_plugin",
+        "Name of the input plugin used when recording.",
+        "Up to 64 ASCII characters, padded with NUL bytes.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "rsp_plugin",
+        "Name of the RSP plugin used when recording.",
+        "Up to 64 ASCII characters, padded with NUL bytes.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "author_name",
+        "Names of the authors of the movie.",
+        "Up to 222 bytes of UTF-8, padded with NUL bytes.",
+        "Unchanged in every version.",
+    ),
+    doc(
+        "description",
+        "Description of the movie.",
+        "Up to 256 bytes of UTF-8, padded with NUL bytes.",
+        "Unchanged in every version.",
+    ),
+];
+
+/// Returns the documentation of a header field, by the name used in
+/// [`HEADER_FIELDS`](crate::layout::HEADER_FIELDS), or `None` if there is no such field.
+pub fn explain(field: &str) -> Option<&'static FieldDoc> {
+    FIELD_DOCS.iter().find(|doc| doc.name == field)
+}
@@ -0,0 +1,338 @@
+//! In-place editing of movie files.
+//!
+//! A [`MovieEditor`] keeps track of what was changed since a movie file was opened,
+//! and [`MovieEditor::save`] rewrites only the changed header fields and frames,
+//! instead of the whole file. A metadata tweak on a long movie only writes a few
+//! hundred bytes.
+//!
+//! With [`MovieEditor::journal_to`], every frame edit is also appended to a
+//! [journal](crate::journal), so the session can be audited or replayed after a crash.
+//! With [`MovieEditor::track_blame`], every changed frame is attributed to the current
+//! author in a [`Blame`].
+
+use std::{
+    fs::OpenOptions,
+    io::{Seek, SeekFrom, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    BinReadExt, Movie, MovieError,
+    blame::Blame,
+    journal::{JournalChange, JournalEntry, append_journal},
+    layout::HEADER_FIELDS,
+    metadata::{MetadataPatch, MovieHeader},
+    raw::{
+        ControllerState,
+        m64::{HEADER_LEN, SAMPLE_LEN},
+    },
+};
+
+/// What [`MovieEditor::save`] wrote.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SaveReport {
+    /// The names of the header fields that were rewritten, in file order.
+    pub header_fields: Vec<&'static str>,
+    /// The ranges of frames that were rewritten, in order.
+    pub frames: Vec<Range<usize>>,
+    /// The number of bytes written.
+    pub bytes_written: usize,
+}
+
+/// A movie file opened for editing. See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct MovieEditor {
+    /// The path of the movie file.
+    path: PathBuf,
+    /// The movie being edited.
+    movie: Movie,
+    /// The header as encoded when the file was last opened or saved.
+    saved_header: Vec<u8>,
+    /// The ranges of frames changed since the file was last opened or saved.
+    dirty: Vec<Range<usize>>,
+    /// The journal file frame edits are appended to, if any.
+    journal: Option<PathBuf>,
+    /// The states of the frames borrowed mutably since the journal was last synced,
+    /// before they were borrowed.
+    pending: Vec<(usize, Vec<ControllerState>)>,
+    /// Who last changed each frame, if tracked, and the author of new changes.
+    blame: Option<(Blame, String)>,
+}
+
+impl MovieEditor {
+    /// Opens a movie file for editing.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MovieError> {
+        let movie = Movie::from_file(&path)?;
+        let saved_header = movie.header_bytes()?;
+
+        Ok(MovieEditor {
+            path: path.as_ref().to_path_buf(),
+            movie,
+            saved_header,
+            dirty: Vec::new(),
+            journal: None,
+            pending: Vec::new(),
+            blame: None,
+        })
+    }
+
+    /// Returns the editor with frame edits appended to a journal file, which is
+    /// created if needed. Header edits are not journaled.
+    ///
+    /// Edits made through [`MovieEditor::frame_mut`] and
+    /// [`MovieEditor::port_state_mut`] are only known once the borrow ends, so they are
+    /// journaled on the next insertion, removal, save or [`MovieEditor::sync_journal`].
+    pub fn journal_to<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.journal = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Returns the path of the journal file, if any.
+    pub fn journal_path(&self) -> Option<&Path> {
+        self.journal.as_deref()
+    }
+
+    /// Returns the editor with every changed frame attributed to `author` in `blame`,
+    /// e.g. the blame stored in the project's [`Bundle`](crate::bundle::Bundle), or
+    /// [`Blame::new`] to start tracking.
+    ///
+    /// The blame is resized to the length of the movie.
+    pub fn track_blame<S: Into<String>>(mut self, mut blame: Blame, author: S) -> Self {
+        blame.resize(self.movie.frame_count());
+        self.blame = Some((blame, author.into()));
+        self
+    }
+
+    /// Sets the author new changes are attributed to. Does nothing without blame
+    /// tracking.
+    pub fn set_author<S: Into<String>>(&mut self, author: S) {
+        if let Some((_, current)) = &mut self.blame {
+            *current = author.into();
+        }
+    }
+
+    /// Returns who last changed each frame, if tracked.
+    pub fn blame(&self) -> Option<&Blame> {
+        self.blame.as_ref().map(|(blame, _)| blame)
+    }
+
+    /// Appends the frame edits not journaled yet to the journal. Does nothing
+    /// without a journal.
+    pub fn sync_journal(&mut self) -> Result<(), MovieError> {
+        let mut entries = Vec::new();
+        for (frame, old) in self.pending.drain(..) {
+            let Some(new) = self.movie.frame(frame) else {
+                continue;
+            };
+
+            for (port, (&old, &new)) in old.iter().zip(new).enumerate() {
+                if old != new {
+                    entries.push(JournalEntry::now(JournalChange::Set {
+                        frame,
+                        port,
+                        old,
+                        new,
+                    }));
+                }
+            }
+        }
+
+        self.append_journal(&entries)
+    }
+
+    /// Appends entries to the journal, if any.
+    fn append_journal(&self, entries: &[JournalEntry]) -> Result<(), MovieError> {
+        match &self.journal {
+            Some(path) => append_journal(path, entries),
+            None => Ok(()),
+        }
+    }
+
+    /// Records the states of a frame before it is borrowed mutably, for the journal.
+    fn snapshot(&mut self, frame: usize) {
+        if self.journal.is_none() || self.pending.iter().any(|(pending, _)| *pending == frame) {
+            return;
+        }
+
+        if let Some(states) = self.movie.frame(frame) {
+            self.pending.push((frame, states.to_vec()));
+        }
+    }
+
+    /// Returns the path of the movie file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the movie being edited.
+    pub fn movie(&self) -> &Movie {
+        &self.movie
+    }
+
+    /// Returns the edited movie, discarding unsaved tracking.
+    pub fn into_movie(self) -> Movie {
+        self.movie
+    }
+
+    /// Returns `true` if anything changed since the file was last opened or saved.
+    pub fn is_dirty(&self) -> Result<bool, MovieError> {
+        Ok(!self.dirty.is_empty() || self.movie.header_bytes()? != self.saved_header)
+    }
+
+    /// Edits the header fields of the movie. See [`Movie::set_header`].
+    pub fn edit_header<F>(&mut self, f: F) -> Result<(), MovieError>
+    where
+        F: FnOnce(&mut MovieHeader),
+    {
+        let mut header = self.movie.header();
+        f(&mut header);
+        self.movie.set_header(header)
+    }
+
+    /// Applies a metadata patch. See [`Movie::apply_metadata`].
+    pub fn apply_metadata(&mut self, patch: &MetadataPatch) -> Result<(), MovieError> {
+        self.movie.apply_metadata(patch)
+    }
+
+    /// Returns a mutable reference to the controller states of a frame, marking it changed.
+    pub fn frame_mut(&mut self, frame: usize) -> Option<&mut [ControllerState]> {
+        self.movie.frame(frame)?;
+        self.touch(frame);
+        self.movie.frame_mut(frame)
+    }
+
+    /// Returns a mutable reference to the state of a single controller in a frame,
+    /// marking the frame changed.
+    pub fn port_state_mut(&mut self, frame: usize, port: usize) -> Option<&mut ControllerState> {
+        self.movie.port_state(frame, port)?;
+        self.touch(frame);
+        self.movie.port_state_mut(frame, port)
+    }
+
+    /// Marks an existing frame changed before it is edited in place.
+    fn touch(&mut self, frame: usize) {
+        self.snapshot(frame);
+        self.mark(frame..frame + 1);
+        self.attribute(frame..frame + 1);
+    }
+
+    /// Inserts frames before frame `at`. Every frame from `at` on is rewritten on save.
+    /// See [`Movie::insert_frames`].
+    pub fn insert_frames(
+        &mut self,
+        at: usize,
+        samples: &[ControllerState],
+    ) -> Result<(), MovieError> {
+        self.sync_journal()?;
+        self.movie.insert_frames(at, samples)?;
+        self.mark(at..self.movie.frame_count());
+        if let Some((blame, author)) = &mut self.blame {
+            let count = self.movie.controller_count().max(1);
+            blame.insert(at, samples.len() / count, author);
+        }
+        self.append_journal(&[JournalEntry::now(JournalChange::Insert {
+            at,
+            states: samples.to_vec(),
+        })])
+    }
+
+    /// Removes a range of frames. Every frame from the start of the range on is
+    /// rewritten on save. See [`Movie::remove_frames`].
+    pub fn remove_frames(
+        &mut self,
+        range: Range<usize>,
+    ) -> Result<Vec<ControllerState>, MovieError> {
+        self.sync_journal()?;
+        let start = range.start;
+        let removed = self.movie.remove_frames(range.clone())?;
+        self.mark(start..self.movie.frame_count());
+        if let Some((blame, _)) = &mut self.blame {
+            blame.remove(range);
+        }
+        self.append_journal(&[JournalEntry::now(JournalChange::Remove {
+            start,
+            states: removed.clone(),
+        })])?;
+        Ok(removed)
+    }
+
+    /// Attributes a range of frames to the current author, if blame is tracked.
+    fn attribute(&mut self, frames: Range<usize>) {
+        if let Some((blame, author)) = &mut self.blame {
+            blame.attribute(frames, author);
+        }
+    }
+
+    /// Records a range of frames as changed.
+    fn mark(&mut self, frames: Range<usize>) {
+        if !frames.is_empty() {
+            self.dirty.push(frames);
+        }
+    }
+
+    /// Returns the changed frame ranges, sorted, merged and clipped to the movie.
+    fn dirty_ranges(&self) -> Vec<Range<usize>> {
+        let frame_count = self.movie.frame_count();
+        let mut dirty: Vec<Range<usize>> = self
+            .dirty
+            .iter()
+            .map(|range| range.start.min(frame_count)..range.end.min(frame_count))
+            .filter(|range| !range.is_empty())
+            .collect();
+        dirty.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<usize>> = Vec::new();
+        for range in dirty {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+
+    /// Writes the changes since the file was last opened or saved.
+    ///
+    /// Only header fields whose encoding changed and frames marked as changed are
+    /// written. The file is truncated if frames were removed. The journal, if any, is
+    /// synced first.
+    pub fn save(&mut self) -> Result<SaveReport, MovieError> {
+        self.sync_journal()?;
+        let header = self.movie.header_bytes()?;
+        let frames = self.dirty_ranges();
+        let mut report = SaveReport::default();
+        let mut file = OpenOptions::new().write(true).open(&self.path)?;
+
+        for field in HEADER_FIELDS {
+            let range = field.range();
+            if header[range.clone()] != self.saved_header[range.clone()] {
+                file.seek(SeekFrom::Start(field.offset as u64))?;
+                file.write_all(&header[range])?;
+                report.header_fields.push(field.name);
+                report.bytes_written += field.len;
+            }
+        }
+
+        let count = self.movie.controller_count();
+        for range in &frames {
+            let bytes: Vec<u8> = self.movie.samples()[range.start * count..range.end * count]
+                .iter()
+                .flat_map(|&state| u32::from(state).to_le_bytes())
+                .collect();
+
+            let offset = HEADER_LEN + range.start * count * SAMPLE_LEN;
+            file.seek(SeekFrom::Start(offset as u64))?;
+            file.write_all(&bytes)?;
+            report.bytes_written += bytes.len();
+        }
+
+        file.set_len(self.movie.serialized_len() as u64)?;
+        file.flush()?;
+
+        self.saved_header = header;
+        self.dirty.clear();
+        report.frames = frames;
+        Ok(report)
+    }
+}
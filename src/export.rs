@@ -0,0 +1,221 @@
+//! Exporters for data derived from a movie.
+//!
+//! [`Subtitles`] turns frame-indexed notes, such as a movie's annotations or
+//! markers, into SubRip (`.srt`) or Advanced SubStation Alpha (`.ass`) subtitles for
+//! encodes. Frames are converted to timestamps with the movie's [`Timing`].
+
+use std::{fs, path::Path, time::Duration};
+
+use crate::{Movie, MovieError, selection::FrameSelection, timing::Timing};
+
+/// A subtitle file format.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SubtitleFormat {
+    /// SubRip (`.srt`).
+    Srt,
+    /// Advanced SubStation Alpha (`.ass`).
+    Ass,
+}
+
+impl SubtitleFormat {
+    /// Returns the file extension of the format, without the leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Ass => "ass",
+        }
+    }
+}
+
+/// A subtitle shown over a range of frames.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Cue {
+    /// The first frame on which the subtitle is shown.
+    pub start: usize,
+    /// The frame on which the subtitle is hidden again.
+    pub end: usize,
+    /// The text of the subtitle. May contain line breaks.
+    pub text: String,
+}
+
+/// A set of subtitle cues, timed against a movie.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subtitles {
+    /// The timing used to convert frames to timestamps.
+    timing: Timing,
+    /// The cues, in the order they were added.
+    cues: Vec<Cue>,
+}
+
+/// The header of every `.ass` file, up to and including the events format line.
+const ASS_HEADER: &str = "\
+[Script Info]
+ScriptType: v4.00+
+WrapStyle: 0
+ScaledBorderAndShadow: yes
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,0,2,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+";
+
+impl Subtitles {
+    /// Creates an empty set of subtitles.
+    pub fn new(timing: Timing) -> Self {
+        Subtitles {
+            timing,
+            cues: Vec::new(),
+        }
+    }
+
+    /// Creates subtitles from frame-indexed notes. Each note is shown until the
+    /// next one starts, and the last until `end`.
+    pub fn from_notes<'a, I>(timing: Timing, notes: I, end: usize) -> Self
+    where
+        I: IntoIterator<Item = (usize, &'a str)>,
+    {
+        let mut notes = notes.into_iter().collect::<Vec<_>>();
+        notes.sort_by_key(|&(frame, _)| frame);
+
+        let mut subtitles = Subtitles::new(timing);
+        for (i, &(start, text)) in notes.iter().enumerate() {
+            let next = notes.get(i + 1).map_or(end, |&(frame, _)| frame);
+            subtitles.push(Cue {
+                start,
+                end: next.max(start),
+                text: text.to_owned(),
+            });
+        }
+
+        subtitles
+    }
+
+    /// Creates subtitles from the annotations of a movie.
+    pub fn from_annotations(movie: &Movie) -> Self {
+        Subtitles::from_notes(
+            movie.timing(),
+            movie.annotations().iter(),
+            movie.frame_count(),
+        )
+    }
+
+    /// Creates subtitles from the markers of a movie, showing each label until the next
+    /// marker.
+    pub fn from_markers(movie: &Movie) -> Self {
+        let markers = movie.markers().iter().collect::<Vec<_>>();
+        Subtitles::from_notes(
+            movie.timing(),
+            markers
+                .iter()
+                .map(|marker| (marker.frame, marker.label.as_str())),
+            movie.frame_count(),
+        )
+    }
+
+    /// Creates subtitles from the annotations of a movie on the selected frames.
+    ///
+    /// Each cue is cut at the end of the selected range it starts in, so no subtitle
+    /// is shown over frames outside the selection.
+    pub fn from_annotations_in(movie: &Movie, selection: &FrameSelection) -> Self {
+        let mut subtitles = Subtitles::from_annotations(movie);
+        subtitles.cues.retain_mut(|cue| {
+            match selection
+                .ranges()
+                .iter()
+                .find(|range| range.contains(&cue.start))
+            {
+                Some(range) => {
+                    cue.end = cue.end.min(range.end);
+                    true
+                }
+                None => false,
+            }
+        });
+
+        subtitles
+    }
+
+    /// Adds a cue.
+    pub fn push(&mut self, cue: Cue) {
+        self.cues.push(cue);
+    }
+
+    /// Returns the cues, in the order they were added.
+    pub fn cues(&self) -> &[Cue] {
+        &self.cues
+    }
+
+    /// Renders the subtitles in the given format.
+    pub fn render(&self, format: SubtitleFormat) -> String {
+        let mut out = String::new();
+        if format == SubtitleFormat::Ass {
+            out.push_str(ASS_HEADER);
+        }
+
+        for (i, cue) in self.cues.iter().enumerate() {
+            let start = self.timing.frame_to_duration(cue.start);
+            let end = self.timing.frame_to_duration(cue.end);
+
+            let line = match format {
+                SubtitleFormat::Srt => format!(
+                    "{}\n{} --> {}\n{}\n\n",
+                    i + 1,
+                    srt_timestamp(start),
+                    srt_timestamp(end),
+                    cue.text.trim_end()
+                ),
+                SubtitleFormat::Ass => format!(
+                    "Dialogue: 0,{},{},Default,,0,0,0,,{}\n",
+                    ass_timestamp(start),
+                    ass_timestamp(end),
+                    ass_text(&cue.text)
+                ),
+            };
+            out.push_str(&line);
+        }
+
+        out
+    }
+
+    /// Writes the subtitles to a file in the given format.
+    pub fn to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: SubtitleFormat,
+    ) -> Result<(), MovieError> {
+        fs::write(path, self.render(format))?;
+        Ok(())
+    }
+}
+
+/// Formats a timestamp as `HH:MM:SS,mmm`.
+fn srt_timestamp(time: Duration) -> String {
+    let millis = time.as_millis();
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Formats a timestamp as `H:MM:SS.cc`.
+fn ass_timestamp(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centis / 360_000,
+        centis / 6000 % 60,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+/// Escapes the text of an `.ass` event, which must fit on a single line.
+fn ass_text(text: &str) -> String {
+    text.trim_end().replace('\n', "\\N")
+}
@@ -0,0 +1,65 @@
+//! Hashing of movie bytes.
+
+use sha2::{Digest, Sha256};
+
+/// The 64-bit FNV offset basis.
+const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+
+/// The 64-bit FNV prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+/// An incremental 64-bit FNV-1a hasher.
+///
+/// FNV-1a is not cryptographic. It is used to cheaply detect changes to a movie,
+/// not to guard against tampering.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Fnv1a {
+    /// The hash of the bytes written so far.
+    state: u64,
+}
+
+impl Fnv1a {
+    /// Creates a hasher that has not seen any bytes.
+    pub fn new() -> Self {
+        Fnv1a {
+            state: FNV_OFFSET_BASIS,
+        }
+    }
+
+    /// Adds bytes to the hash.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= byte as u64;
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Returns the hash of the bytes written so far.
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+}
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the 64-bit FNV-1a hash of the bytes.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.update(bytes);
+    hasher.finish()
+}
+
+/// Returns the SHA-256 digest of the bytes, as 64 lowercase hexadecimal digits.
+///
+/// Unlike FNV-1a, SHA-256 guards against tampering, so it identifies files that are
+/// archived and shared, e.g. in [bundle manifests](crate::bundle::Manifest).
+pub fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
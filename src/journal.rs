@@ -0,0 +1,259 @@
+//! Append-only journals of frame edits.
+//!
+//! A journal records every change made to the frames of a movie, one
+//! [`JournalEntry`] per line, so long editing sessions can be audited and replayed
+//! after a crash. [`MovieEditor::journal_to`](crate::edit::MovieEditor::journal_to)
+//! writes one alongside the movie file. Each line holds the time of the change in
+//! milliseconds since the Unix epoch, the kind of change, and the states involved
+//! in the hexadecimal format of [`ControllerState::to_hex`]:
+//!
+//! ```text
+//! 1700000000000 set 100 0 0000_0000 0080_0000
+//! 1700000000250 insert 200 0000_0000 0000_0000
+//! 1700000000500 remove 300 0080_7F00
+//! ```
+//!
+//! Inserted and removed states span whole frames, one state per controller.
+
+use std::{
+    fmt::{self, Display},
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use crate::{ConvertError, FrameError, Movie, MovieError, raw::ControllerState};
+
+/// A change to the frames of a movie.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum JournalChange {
+    /// The state of a controller on a frame was replaced.
+    Set {
+        /// The frame changed.
+        frame: usize,
+        /// The controller port changed.
+        port: usize,
+        /// The state before the change.
+        old: ControllerState,
+        /// The state after the change.
+        new: ControllerState,
+    },
+    /// Frames were inserted.
+    Insert {
+        /// The frame the states were inserted before.
+        at: usize,
+        /// The inserted states.
+        states: Vec<ControllerState>,
+    },
+    /// Frames were removed.
+    Remove {
+        /// The first frame removed.
+        start: usize,
+        /// The removed states.
+        states: Vec<ControllerState>,
+    },
+}
+
+/// A change recorded in a journal, with the time it was made.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JournalEntry {
+    /// The time of the change, since the Unix epoch, in whole milliseconds.
+    pub timestamp: Duration,
+    /// The change.
+    pub change: JournalChange,
+}
+
+impl JournalEntry {
+    /// Creates an entry for a change made now.
+    pub fn now(change: JournalChange) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        JournalEntry {
+            timestamp: Duration::from_millis(timestamp.as_millis() as u64),
+            change,
+        }
+    }
+
+    /// Applies the change to a movie.
+    ///
+    /// Fails without changing the movie if the movie does not hold the states the
+    /// change replaced or removed, as when it has diverged from the journal.
+    pub fn apply(&self, movie: &mut Movie) -> Result<(), MovieError> {
+        match &self.change {
+            JournalChange::Set {
+                frame,
+                port,
+                old,
+                new,
+            } => {
+                movie.check_port(*port)?;
+                let frame_count = movie.frame_count();
+                let state = movie
+                    .port_state_mut(*frame, *port)
+                    .ok_or(FrameError::OutOfBounds {
+                        frame: *frame,
+                        frame_count,
+                    })?;
+                if state != old {
+                    return Err(FrameError::StateMismatch {
+                        frame: *frame,
+                        port: *port,
+                    }
+                    .into());
+                }
+                *state = *new;
+            }
+            JournalChange::Insert { at, states } => movie.insert_frames(*at, states)?,
+            JournalChange::Remove { start, states } => {
+                let count = movie.controller_count().max(1);
+                if !states.len().is_multiple_of(count) {
+                    return Err(FrameError::PartialFrame {
+                        samples: states.len(),
+                        controller_count: count,
+                    }
+                    .into());
+                }
+
+                let frames = states.len() / count;
+                let end = start.checked_add(frames).ok_or(FrameError::OutOfBounds {
+                    frame: usize::MAX,
+                    frame_count: movie.frame_count(),
+                })?;
+
+                let samples = movie.check_frame_range(&(*start..end))?;
+                if let Some(offset) = movie.inputs[samples.clone()]
+                    .iter()
+                    .zip(states)
+                    .position(|(current, removed)| current != removed)
+                {
+                    return Err(FrameError::StateMismatch {
+                        frame: start + offset / count,
+                        port: offset % count,
+                    }
+                    .into());
+                }
+
+                movie.remove_frames(*start..end)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for JournalEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.timestamp.as_millis())?;
+
+        let (kind, frame, states) = match &self.change {
+            JournalChange::Set {
+                frame,
+                port,
+                old,
+                new,
+            } => {
+                return write!(f, " set {frame} {port} {} {}", old.to_hex(), new.to_hex());
+            }
+            JournalChange::Insert { at, states } => ("insert", at, states),
+            JournalChange::Remove { start, states } => ("remove", start, states),
+        };
+
+        write!(f, " {kind} {frame}")?;
+        for state in states {
+            write!(f, " {}", state.to_hex())?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a journal. Blank lines and lines starting with `#` are skipped.
+pub fn parse_journal(text: &str) -> Result<Vec<JournalEntry>, MovieError> {
+    let mut entries = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |reason: String| ConvertError::InvalidLine {
+            line: index + 1,
+            reason,
+        };
+        let number = |value: Option<&str>, what: &str| {
+            value
+                .and_then(|value| value.parse::<u64>().ok())
+                .ok_or_else(|| invalid(format!("expected {what}")))
+        };
+
+        let mut words = line.split_whitespace();
+        let timestamp = Duration::from_millis(number(words.next(), "a timestamp")?);
+        let kind = words.next().unwrap_or_default();
+        let frame = number(words.next(), "a frame index")? as usize;
+
+        let change = match kind {
+            "set" => {
+                let port = number(words.next(), "a controller port")? as usize;
+                let mut state = || {
+                    let word = words
+                        .next()
+                        .ok_or_else(|| invalid("expected a controller state".to_string()))?;
+                    ControllerState::from_hex(word)
+                };
+                let (old, new) = (state()?, state()?);
+                JournalChange::Set {
+                    frame,
+                    port,
+                    old,
+                    new,
+                }
+            }
+            "insert" | "remove" => {
+                let states = words
+                    .by_ref()
+                    .map(ControllerState::from_hex)
+                    .collect::<Result<Vec<_>, _>>()?;
+                if kind == "insert" {
+                    JournalChange::Insert { at: frame, states }
+                } else {
+                    JournalChange::Remove {
+                        start: frame,
+                        states,
+                    }
+                }
+            }
+            _ => return Err(invalid(format!("unknown change {kind:?}")).into()),
+        };
+
+        if let Some(word) = words.next() {
+            return Err(invalid(format!("unexpected {word:?}")).into());
+        }
+
+        entries.push(JournalEntry { timestamp, change });
+    }
+
+    Ok(entries)
+}
+
+/// Applies the entries of a journal to a movie, in order.
+///
+/// Stops at the first entry that does not apply, leaving the earlier entries applied.
+pub fn replay_journal(movie: &mut Movie, entries: &[JournalEntry]) -> Result<(), MovieError> {
+    entries.iter().try_for_each(|entry| entry.apply(movie))
+}
+
+/// Appends entries to a journal file, creating it if needed.
+pub fn append_journal<P: AsRef<Path>>(path: P, entries: &[JournalEntry]) -> Result<(), MovieError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let text: String = entries.iter().map(|entry| format!("{entry}\n")).collect();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+    Ok(())
+}
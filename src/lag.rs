@@ -0,0 +1,195 @@
+//! Lag frames, on which the game did not act on its input.
+//!
+//! The M64 format has no notion of lag, which is why a movie's VI count and frame
+//! count drift apart. A [`LagMask`] records which frames lagged, either from a
+//! [`Sidecar`](crate::sidecar::Sidecar) or from an emulator log, so that lag can be
+//! excluded from frame iteration and timing.
+//!
+//! Lag frames are written as a comma-separated list of frames and inclusive ranges,
+//! e.g. `12,40-45,90`. An emulator log holds one such list per line, with lines
+//! starting with `#` ignored.
+
+use std::{
+    fmt::{self, Display},
+    ops::RangeInclusive,
+    time::Duration,
+};
+
+use crate::{Movie, MovieError, SidecarError, bookmarks::Bookmarks, counters::MAX_FRAME_COUNT};
+
+/// The set of lag frames of a movie.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct LagMask {
+    /// The indices of the lag frames.
+    frames: Bookmarks<()>,
+}
+
+impl LagMask {
+    /// Creates a mask without lag frames.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a mask from an emulator log listing lag frames and ranges.
+    ///
+    /// Frames are capped below [`MAX_FRAME_COUNT`], the most frames a movie can have.
+    /// Use [`LagMask::from_log_bounded`] when the log belongs to a known movie.
+    pub fn from_log(text: &str) -> Result<Self, MovieError> {
+        LagMask::from_log_bounded(text, MAX_FRAME_COUNT)
+    }
+
+    /// Parses a mask from an emulator log, rejecting frames at or past `max_frames`,
+    /// e.g. the frame count of the movie it belongs to.
+    pub fn from_log_bounded(text: &str, max_frames: usize) -> Result<Self, MovieError> {
+        let mut mask = LagMask::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            mask.parse_list(index + 1, line, max_frames)?;
+        }
+
+        Ok(mask)
+    }
+
+    /// Adds the frames and ranges of a comma-separated list to the mask, rejecting
+    /// frames at or past `max_frames`.
+    pub(crate) fn parse_list(
+        &mut self,
+        line: usize,
+        list: &str,
+        max_frames: usize,
+    ) -> Result<(), SidecarError> {
+        for item in list
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let invalid = || SidecarError::InvalidFrame {
+                line,
+                value: item.to_string(),
+            };
+
+            let (start, end) = item.split_once('-').unwrap_or((item, item));
+            let start = start.trim().parse::<usize>().map_err(|_| invalid())?;
+            let end = end.trim().parse::<usize>().map_err(|_| invalid())?;
+            if start > end || end >= max_frames.min(MAX_FRAME_COUNT) {
+                return Err(invalid());
+            }
+
+            self.frames.extend((start..=end).map(|frame| (frame, ())));
+        }
+
+        Ok(())
+    }
+
+    /// Marks a frame as lagged or not.
+    pub fn set(&mut self, frame: usize, lag: bool) {
+        if lag {
+            self.frames.insert(frame, ());
+        } else {
+            self.frames.remove(frame);
+        }
+    }
+
+    /// Returns `true` if the frame lagged.
+    pub fn is_lag(&self, frame: usize) -> bool {
+        self.frames.contains(frame)
+    }
+
+    /// Returns the number of lag frames.
+    pub fn lag_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if there are no lag frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns an iterator over the lag frames, in order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.frames.frames()
+    }
+
+    /// Returns the last lag frame at or before a frame.
+    pub fn last_lag_before(&self, frame: usize) -> Option<usize> {
+        self.frames.nearest_before(frame).map(|(frame, _)| frame)
+    }
+
+    /// Returns the lag frames as inclusive ranges of consecutive frames.
+    pub fn ranges(&self) -> Vec<RangeInclusive<usize>> {
+        let mut ranges: Vec<RangeInclusive<usize>> = Vec::new();
+
+        for frame in self.frames.frames() {
+            match ranges.last_mut() {
+                Some(range) if *range.end() + 1 == frame => *range = *range.start()..=frame,
+                _ => ranges.push(frame..=frame),
+            }
+        }
+
+        ranges
+    }
+}
+
+impl FromIterator<usize> for LagMask {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        LagMask {
+            frames: iter.into_iter().map(|frame| (frame, ())).collect(),
+        }
+    }
+}
+
+impl Display for LagMask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, range) in self.ranges().into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+
+            if range.start() == range.end() {
+                write!(f, "{}", range.start())?;
+            } else {
+                write!(f, "{}-{}", range.start(), range.end())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Movie {
+    /// Returns an iterator over every frame index, including lag frames, as the
+    /// console advances through them one or more VIs at a time.
+    pub fn vi_frames(&self) -> impl Iterator<Item = usize> + use<> {
+        0..self.frame_count()
+    }
+
+    /// Returns an iterator over the indices of the frames on which the game acted on
+    /// its input. Without a lag mask, this is every frame.
+    pub fn real_frames(&self) -> impl Iterator<Item = usize> + '_ {
+        self.vi_frames()
+            .filter(|&frame| !self.lag_mask().is_some_and(|mask| mask.is_lag(frame)))
+    }
+
+    /// Returns the time spent on lag frames.
+    pub fn lag_duration(&self) -> Duration {
+        let Some(mask) = self.lag_mask() else {
+            return Duration::ZERO;
+        };
+
+        let timing = self.timing();
+        mask.iter()
+            .filter(|&frame| frame < self.frame_count())
+            .map(|frame| timing.frame_to_duration(frame + 1) - timing.frame_to_duration(frame))
+            .sum()
+    }
+
+    /// Returns the length of the movie without the time spent on lag frames.
+    pub fn duration_without_lag(&self) -> Duration {
+        self.duration().saturating_sub(self.lag_duration())
+    }
+}
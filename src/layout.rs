@@ -0,0 +1,273 @@
+//! The byte layout of M64 files.
+//!
+//! [`describe_offset`] names the header field or input sample at a byte offset, and
+//! [`describe_byte_diff`] turns two encoded movies into the fields, frames and
+//! buttons that differ between them.
+
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+};
+
+use crate::{
+    ControllerButton,
+    raw::{
+        ControllerState,
+        m64::{HEADER_LEN, SAMPLE_LEN},
+    },
+};
+
+/// The offset of the controller count in the header.
+const CONTROLLER_COUNT_OFFSET: usize = 0x15;
+
+/// A field of the M64 header.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct HeaderField {
+    /// The name of the field, matching the field of [`RawMovie`](crate::RawMovie).
+    pub name: &'static str,
+    /// The offset of the field in the file.
+    pub offset: usize,
+    /// The length of the field in bytes.
+    pub len: usize,
+}
+
+impl HeaderField {
+    /// Returns the byte range of the field in the file.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+}
+
+/// Creates a [`HeaderField`].
+const fn field(name: &'static str, offset: usize, len: usize) -> HeaderField {
+    HeaderField { name, offset, len }
+}
+
+/// Every field of the M64 header, in file order. The fields cover the whole header.
+pub const HEADER_FIELDS: &[HeaderField] = &[
+    field("magic", 0x000, 4),
+    field("version", 0x004, 4),
+    field("uid", 0x008, 4),
+    field("vertical_interrupts", 0x00C, 4),
+    field("rerecord_count", 0x010, 4),
+    field("vis_per_second", 0x014, 1),
+    field("controller_count", 0x015, 1),
+    field("extended_version", 0x016, 1),
+    field("extended_flags", 0x017, 1),
+    field("controller_input_samples", 0x018, 4),
+    field("start_type", 0x01C, 2),
+    field("reserved01", 0x01E, 2),
+    field("controller_flags", 0x020, 4),
+    field("extended_data.authorship_info", 0x024, 4),
+    field("extended_data.bruteforce_data", 0x028, 4),
+    field("extended_data.rerecord_count_high", 0x02C, 4),
+    field("extended_data.reserved", 0x030, 20),
+    field("reserved02", 0x044, 128),
+    field("rom_name", 0x0C4, 32),
+    field("rom_crc32", 0x0E4, 4),
+    field("rom_country", 0x0E8, 2),
+    field("reserved03", 0x0EA, 56),
+    field("video_plugin", 0x122, 64),
+    field("sound_plugin", 0x162, 64),
+    field("input_plugin", 0x1A2, 64),
+    field("rsp_plugin", 0x1E2, 64),
+    field("author_name", 0x222, 222),
+    field("description", 0x300, 256),
+];
+
+/// A part of an encoded [`ControllerState`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum InputPart {
+    /// The button bits, in the first two bytes.
+    Buttons,
+    /// The analog x-axis, in the third byte.
+    XAxis,
+    /// The analog y-axis, in the fourth byte.
+    YAxis,
+}
+
+impl Display for InputPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputPart::Buttons => write!(f, "buttons"),
+            InputPart::XAxis => write!(f, "x axis"),
+            InputPart::YAxis => write!(f, "y axis"),
+        }
+    }
+}
+
+/// What a byte of an M64 file encodes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FieldRef {
+    /// A byte of a header field.
+    Header {
+        /// The field containing the byte.
+        field: &'static HeaderField,
+        /// The index of the byte within the field.
+        index: usize,
+    },
+    /// A byte of an input sample.
+    Input {
+        /// The index of the sample, counting the samples of every controller.
+        sample: usize,
+        /// The part of the sample containing the byte.
+        part: InputPart,
+    },
+}
+
+impl FieldRef {
+    /// Returns the frame and controller port of an input sample, given the number of
+    /// controllers in the movie. Returns `None` for header fields or zero controllers.
+    pub fn frame_and_port(&self, controller_count: usize) -> Option<(usize, usize)> {
+        match self {
+            FieldRef::Input { sample, .. } if controller_count != 0 => {
+                Some((sample / controller_count, sample % controller_count))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for FieldRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldRef::Header { field, index } => write!(f, "{}[{index}]", field.name),
+            FieldRef::Input { sample, part } => write!(f, "sample {sample} {part}"),
+        }
+    }
+}
+
+/// Returns what the byte at an offset of an M64 file encodes. Every offset past the
+/// header is read as part of an input sample.
+pub fn describe_offset(offset: usize) -> Option<FieldRef> {
+    if offset >= HEADER_LEN {
+        let offset = offset - HEADER_LEN;
+        let part = match offset % SAMPLE_LEN {
+            0 | 1 => InputPart::Buttons,
+            2 => InputPart::XAxis,
+            _ => InputPart::YAxis,
+        };
+        return Some(FieldRef::Input {
+            sample: offset / SAMPLE_LEN,
+            part,
+        });
+    }
+
+    HEADER_FIELDS
+        .iter()
+        .find(|field| field.range().contains(&offset))
+        .map(|field| FieldRef::Header {
+            field,
+            index: offset - field.offset,
+        })
+}
+
+/// A difference between two encoded movies, see [`describe_byte_diff`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ByteDiff {
+    /// A header field differs.
+    Header(&'static HeaderField),
+    /// The input of a controller differs on a frame.
+    Input {
+        /// The frame of the input.
+        frame: usize,
+        /// The controller port of the input.
+        port: usize,
+        /// The buttons pressed in only one of the movies.
+        buttons: Vec<ControllerButton>,
+        /// Whether the x-axis differs.
+        x_axis: bool,
+        /// Whether the y-axis differs.
+        y_axis: bool,
+    },
+    /// The movies have a different length. Bytes past the shorter one are not compared.
+    Length {
+        /// The length of the first movie.
+        left: usize,
+        /// The length of the second movie.
+        right: usize,
+    },
+}
+
+impl Display for ByteDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ByteDiff::Header(field) => write!(f, "{} (0x{:03X})", field.name, field.offset),
+            ByteDiff::Input {
+                frame,
+                port,
+                buttons,
+                x_axis,
+                y_axis,
+            } => {
+                let mut parts: Vec<String> = buttons.iter().map(|b| format!("{b:?}")).collect();
+                if *x_axis {
+                    parts.push(InputPart::XAxis.to_string());
+                }
+                if *y_axis {
+                    parts.push(InputPart::YAxis.to_string());
+                }
+                write!(f, "frame {frame}, port {port}: {}", parts.join(", "))
+            }
+            ByteDiff::Length { left, right } => {
+                write!(f, "length differs: {left} vs {right} bytes")
+            }
+        }
+    }
+}
+
+/// Reads the sample at an offset, treating missing bytes as zero.
+fn read_sample(bytes: &[u8], offset: usize) -> u32 {
+    let mut sample = [0; SAMPLE_LEN];
+    let end = bytes.len().min(offset + SAMPLE_LEN);
+    sample[..end - offset].copy_from_slice(&bytes[offset..end]);
+    u32::from_le_bytes(sample)
+}
+
+/// Describes every difference between two encoded movies, in file order.
+///
+/// Header differences are reported per field. Input differences are reported per
+/// frame and controller port, using the controller count of the first movie.
+pub fn describe_byte_diff(left: &[u8], right: &[u8]) -> Vec<ByteDiff> {
+    let len = left.len().min(right.len());
+    let mut diffs: Vec<ByteDiff> = HEADER_FIELDS
+        .iter()
+        .filter(|field| {
+            let range = field.offset.min(len)..(field.offset + field.len).min(len);
+            left[range.clone()] != right[range]
+        })
+        .map(ByteDiff::Header)
+        .collect();
+
+    let controller_count = match left.get(CONTROLLER_COUNT_OFFSET) {
+        Some(&count) if count != 0 => count as usize,
+        _ => 1,
+    };
+
+    for offset in (HEADER_LEN..len).step_by(SAMPLE_LEN) {
+        let a = read_sample(&left[..len], offset);
+        let b = read_sample(&right[..len], offset);
+        if a == b {
+            continue;
+        }
+
+        let changed = a ^ b;
+        let sample = (offset - HEADER_LEN) / SAMPLE_LEN;
+        diffs.push(ByteDiff::Input {
+            frame: sample / controller_count,
+            port: sample % controller_count,
+            buttons: ControllerState::from(changed & 0xFFFF).get_pressed(),
+            x_axis: changed & 0x00FF_0000 != 0,
+            y_axis: changed & 0xFF00_0000 != 0,
+        });
+    }
+
+    if left.len() != right.len() {
+        diffs.push(ByteDiff::Length {
+            left: left.len(),
+            right: right.len(),
+        });
+    }
+
+    diffs
+}
@@ -2,16 +2,67 @@
 #![warn(missing_docs)]
 #![warn(clippy::missing_docs_in_private_items)]
 
+pub mod analysis;
+pub mod annotations;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod blame;
+pub mod bookmarks;
+pub mod bundle;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod catalog;
+pub mod chord;
+pub mod companion;
+pub mod convert;
+#[cfg(feature = "corpus")]
+pub mod corpus;
+pub mod counters;
+pub mod diff;
 pub mod doc;
+pub mod edit;
+pub mod export;
+pub mod generate;
+pub mod hash;
+pub mod journal;
+pub mod lag;
+pub mod layout;
+pub mod lint;
+#[cfg(feature = "m64p")]
+pub mod m64p;
+pub mod macros;
+pub mod markers;
+pub mod metadata;
+pub mod names;
 pub mod parsed;
+pub mod paste;
+pub mod patch;
+pub mod playback;
 pub mod raw;
+pub mod recording;
+pub mod region;
+pub mod segment;
+pub mod selection;
+#[cfg(feature = "service")]
+pub mod service;
 pub mod shared;
+pub mod sidecar;
+pub mod strings;
+pub mod summary;
+pub mod timing;
+pub mod track;
+pub mod transform;
+pub mod verify;
+pub mod version;
 
 #[doc(inline)]
 pub use parsed::Movie;
 
 #[doc(inline)]
-pub use raw::RawMovie;
+pub use raw::{M64_MAGIC, RawMovie, is_m64};
+
+#[doc(inline)]
+pub use verify::verify_roundtrip;
 
 /// Error type for [`RawMovie`] operations.
 #[derive(Debug, thiserror::Error)]
@@ -28,6 +79,59 @@ pub enum MovieError {
     /// Error when parsing a [`Movie`].
     #[error("Failed to parse movie: {0}")]
     MovieParseError(#[from] MovieParseError),
+    /// Error when reading a ROM image.
+    #[error("Failed to read ROM: {0}")]
+    RomError(#[from] RomError),
+    /// Error when converting inputs from another format.
+    #[error("Failed to convert inputs: {0}")]
+    ConvertError(#[from] ConvertError),
+    /// Error when accessing or editing frames of a [`Movie`].
+    #[error("Invalid frame access: {0}")]
+    FrameError(#[from] FrameError),
+    /// Error when parsing a [`Sidecar`](`sidecar::Sidecar`).
+    #[error("Failed to parse sidecar: {0}")]
+    SidecarError(#[from] SidecarError),
+    /// Error when applying a byte patch with [`patch::apply`].
+    #[error("Failed to apply patch: {0}")]
+    PatchError(#[from] PatchError),
+    /// Error when using a [`Segment`](`segment::Segment`).
+    #[error("Invalid segment: {0}")]
+    SegmentError(#[from] SegmentError),
+    /// Error when reading a [`Bundle`](`bundle::Bundle`).
+    #[error("Invalid bundle: {0}")]
+    BundleError(#[from] BundleError),
+    /// Error when parsing a [`Blame`](`blame::Blame`).
+    #[error("Failed to parse blame: {0}")]
+    BlameError(#[from] BlameError),
+    /// Error when a movie is rejected by its [`WriteOptions`](parsed::WriteOptions).
+    #[error("Failed to write movie: {0}")]
+    WriteError(#[from] WriteError),
+    /// Error when parsing the name of a header value.
+    #[error("Failed to parse name: {0}")]
+    NameError(#[from] NameError),
+    /// Error when a header counter would not fit in its field.
+    #[error("Invalid header counter: {0}")]
+    CounterError(#[from] CounterError),
+    /// Error when building Arrow record batches.
+    #[cfg(feature = "arrow")]
+    #[error("Failed to build record batch: {0}")]
+    ArrowError(#[from] arrow_schema::ArrowError),
+    /// Error when writing Parquet files.
+    #[cfg(feature = "arrow")]
+    #[error("Failed to write Parquet: {0}")]
+    ParquetError(#[from] parquet::errors::ParquetError),
+    /// Error when serializing or deserializing JSON.
+    #[cfg(feature = "json")]
+    #[error("Failed to process JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    /// Error when deserializing TOML.
+    #[cfg(feature = "toml")]
+    #[error("Failed to parse TOML: {0}")]
+    TomlDeError(#[from] toml::de::Error),
+    /// Error when serializing TOML.
+    #[cfg(feature = "toml")]
+    #[error("Failed to write TOML: {0}")]
+    TomlSerError(#[from] toml::ser::Error),
 }
 
 /// Error type for [`EncodedFixedStr`](`shared::EncodedFixedStr`) encoding and decoding.
@@ -39,7 +143,7 @@ pub enum EncodedFixedStrError {
     /// Error when the string is not valid ASCII.
     #[error("Invalid ASCII string: {0}")]
     InvalidAscii(String),
-    /// Errors related to [`fixedstr::zstr`].
+    /// Error when a string does not fit in the fixed size.
     #[error("Fixed string error: {0}")]
     FixedStrError(String),
 }
@@ -53,6 +157,242 @@ pub enum MovieParseError {
     /// Error when the movie file has an invalid or unsupported extended version.
     #[error("Invalid movie extended version: {0}")]
     UnsupportedExtendedVersion(u8),
+    /// Error when the controller count does not match the controllers flagged as present.
+    #[error("Movie declares {declared} controllers, but {present} are flagged as present")]
+    ControllerCountMismatch {
+        /// The number of controllers declared in the header.
+        declared: u8,
+        /// The number of controllers flagged as present.
+        present: u8,
+    },
+    /// Error when the number of input samples does not match the header. Such files
+    /// can still be read with [`RawMovie::from_bytes_forced`], which repairs the
+    /// counter.
+    #[error(
+        "Header declares {expected} input samples, but the {input_bytes}-byte input section holds {found}"
+    )]
+    InputSampleMismatch {
+        /// The number of samples declared by the header: the input sample counter
+        /// times the controller count.
+        expected: usize,
+        /// The number of samples found.
+        found: usize,
+        /// The length of the input section in bytes.
+        input_bytes: usize,
+    },
+}
+
+/// Error type for frame-based access to a [`Movie`].
+#[derive(Debug, thiserror::Error)]
+pub enum FrameError {
+    /// Error when a frame index is past the end of the movie.
+    #[error("Frame {frame} is out of bounds for a movie with {frame_count} frames")]
+    OutOfBounds {
+        /// The requested frame index.
+        frame: usize,
+        /// The number of frames in the movie.
+        frame_count: usize,
+    },
+    /// Error when a controller port does not exist in the movie.
+    #[error("Port {port} is out of bounds for a movie with {controller_count} controllers")]
+    PortOutOfBounds {
+        /// The requested controller port.
+        port: usize,
+        /// The number of controllers in the movie.
+        controller_count: usize,
+    },
+    /// Error when a frame does not hold the state a change expects, e.g. when replaying
+    /// a journal onto a movie that has diverged from it.
+    #[error("Frame {frame} does not hold the expected state of port {port}")]
+    StateMismatch {
+        /// The frame index.
+        frame: usize,
+        /// The controller port.
+        port: usize,
+    },
+    /// Error when a range of frames starts after it ends.
+    #[error("Frame range {start}..{end} starts after it ends")]
+    InvalidRange {
+        /// The first frame of the range.
+        start: usize,
+        /// The end of the range, exclusive.
+        end: usize,
+    },
+    /// Error when a number of samples does not make up a whole number of frames.
+    #[error("{samples} samples do not make up whole frames of {controller_count} controllers")]
+    PartialFrame {
+        /// The number of samples given.
+        samples: usize,
+        /// The number of controllers in the movie.
+        controller_count: usize,
+    },
+}
+
+/// Error type for reading N64 ROM images.
+#[derive(Debug, thiserror::Error)]
+pub enum RomError {
+    /// Error when the ROM image is too short to contain a header.
+    #[error("ROM is too short to contain a header: {0} bytes")]
+    TooShort(usize),
+    /// Error when the ROM image does not start with a known PI configuration word.
+    #[error("Unknown ROM format: {0:#010X}")]
+    UnknownFormat(u32),
+}
+
+/// Error type for [`convert`] errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ConvertError {
+    /// Error when a line of a text input format cannot be parsed.
+    #[error("Invalid input on line {line}: {reason}")]
+    InvalidLine {
+        /// The line number, starting at 1.
+        line: usize,
+        /// Why the line is invalid.
+        reason: String,
+    },
+    /// Error when the header of a movie in another format is invalid or unsupported.
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    /// Error when a controller state is not valid hexadecimal.
+    #[error("Invalid hexadecimal controller state {0:?}")]
+    InvalidHex(String),
+    /// Error when a button mask does not have exactly one bit set.
+    #[error("Button mask {0:#06X} does not select a single button")]
+    InvalidButtonMask(u16),
+}
+
+/// Error type for [`Sidecar`](`sidecar::Sidecar`) parsing errors.
+#[derive(Debug, thiserror::Error)]
+pub enum SidecarError {
+    /// Error when a line starts with an unknown entry kind.
+    #[error("Unknown entry {kind:?} on line {line}")]
+    UnknownEntry {
+        /// The line number, starting at 1.
+        line: usize,
+        /// The unknown entry kind.
+        kind: String,
+    },
+    /// Error when a frame index cannot be parsed.
+    #[error("Invalid frame index {value:?} on line {line}")]
+    InvalidFrame {
+        /// The line number, starting at 1.
+        line: usize,
+        /// The value that failed to parse.
+        value: String,
+    },
+}
+
+/// Error type for [`Segment`](`segment::Segment`) errors.
+#[derive(Debug, thiserror::Error)]
+pub enum SegmentError {
+    /// Error when a segment requires different controllers than the movie has present.
+    #[error("Segment requires controllers {required:04b}, but the movie has {present:04b}")]
+    ControllerMismatch {
+        /// The presence flags of the controllers required by the segment, one bit per port.
+        required: u8,
+        /// The presence flags of the controllers in the movie, one bit per port.
+        present: u8,
+    },
+    /// Error when a segment was recorded on a different ROM than the movie.
+    #[error("Segment {name:?} was recorded on ROM {found:08X}, but the movie uses {expected:08X}")]
+    RomMismatch {
+        /// The name of the segment.
+        name: String,
+        /// The CRC32 checksum of the movie's ROM.
+        expected: u32,
+        /// The CRC32 checksum of the segment's ROM.
+        found: u32,
+    },
+}
+
+/// Error type for [`Bundle`](`bundle::Bundle`) errors.
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    /// Error when a bundle does not contain a movie.
+    #[error("Bundle does not contain a movie")]
+    MissingMovie,
+    /// Error when a bundle contains a file it does not know.
+    #[error("Unknown bundle entry {0:?}")]
+    UnknownEntry(String),
+    /// Error when a bundle contains the same file twice.
+    #[error("Duplicate bundle entry {0:?}")]
+    DuplicateEntry(String),
+    /// Error when a file of a bundle cannot be decoded.
+    #[error("Invalid bundle entry {0:?}")]
+    InvalidEntry(String),
+}
+
+/// Error type for [`Blame`](`blame::Blame`) parsing errors.
+#[derive(Debug, thiserror::Error)]
+pub enum BlameError {
+    /// Error when a line is not a frame count or an attributed frame range.
+    #[error("Invalid line {line}: {text:?}")]
+    InvalidLine {
+        /// The line number, starting at 1.
+        line: usize,
+        /// The offending line.
+        text: String,
+    },
+}
+
+/// Error type for [`WriteOptions`](parsed::WriteOptions) checks.
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+    /// Error when a movie of a version other than 3 is written with
+    /// [`WriteOptions::require_version_3`](parsed::WriteOptions::require_version_3).
+    #[error("Refusing to write movie version {0}, only version 3 is supported")]
+    UnsupportedVersion(u32),
+    /// Error when a header field does not decode back to its value as little-endian.
+    #[error("Header field {field} encodes {found:#010X}, expected {expected:#010X}")]
+    EncodingMismatch {
+        /// The name of the field, as in [`HEADER_FIELDS`](layout::HEADER_FIELDS).
+        field: &'static str,
+        /// The value of the field.
+        expected: u32,
+        /// The value decoded from the encoded header.
+        found: u32,
+    },
+}
+
+/// Error type for parsing the [names](names) of header values.
+#[derive(Debug, thiserror::Error)]
+pub enum NameError {
+    /// Error when a name does not match any value.
+    #[error("Unknown {kind} {name:?}")]
+    Unknown {
+        /// What kind of value was parsed, e.g. `start type`.
+        kind: &'static str,
+        /// The name that was parsed.
+        name: String,
+    },
+}
+
+/// Error type for the checked updates of the header counters in [`counters`].
+#[derive(Debug, thiserror::Error)]
+pub enum CounterError {
+    /// Error when a counter would exceed the range of its 32-bit header field.
+    #[error("Header counter {counter} would be {value}, which does not fit in 32 bits")]
+    Overflow {
+        /// The name of the counter, as in [`HEADER_FIELDS`](layout::HEADER_FIELDS).
+        counter: &'static str,
+        /// The value the counter would have.
+        value: u64,
+    },
+}
+
+/// Error type for [`patch::apply`] errors.
+#[derive(Debug, thiserror::Error)]
+pub enum PatchError {
+    /// Error when a patch extends past the end of the bytes.
+    #[error("Patch of {len} bytes at offset {offset:#X} exceeds {size} bytes")]
+    OutOfBounds {
+        /// The offset of the patch.
+        offset: usize,
+        /// The length of the patch.
+        len: usize,
+        /// The length of the bytes being patched.
+        size: usize,
+    },
 }
 
 /// Extensions for reading binary data.
@@ -79,7 +419,8 @@ pub trait BinWriteExt {
 }
 
 /// An enum representing the buttons on a Mupen64 controller.
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ControllerButton {
     /// The right directional pad button.
     DPadRight,
@@ -114,3 +455,55 @@ pub enum ControllerButton {
     /// Reserved button 02.
     Reserved02,
 }
+
+impl ControllerButton {
+    /// Every button, in the order of their bits in a [`ControllerState`](raw::ControllerState).
+    pub const ALL: [ControllerButton; 16] = [
+        ControllerButton::DPadRight,
+        ControllerButton::DPadLeft,
+        ControllerButton::DPadDown,
+        ControllerButton::DPadUp,
+        ControllerButton::Start,
+        ControllerButton::Z,
+        ControllerButton::B,
+        ControllerButton::A,
+        ControllerButton::CRight,
+        ControllerButton::CLeft,
+        ControllerButton::CDown,
+        ControllerButton::CUp,
+        ControllerButton::TriggerRight,
+        ControllerButton::TriggerLeft,
+        ControllerButton::Reserved01,
+        ControllerButton::Reserved02,
+    ];
+
+    /// Returns the bit of the button in the 16 button bits of a
+    /// [`ControllerState`](raw::ControllerState).
+    pub const fn mask(self) -> u16 {
+        1 << self as u16
+    }
+
+    /// Returns an iterator over every button, in the order of [`ControllerButton::ALL`].
+    pub fn iter() -> impl Iterator<Item = ControllerButton> {
+        Self::ALL.into_iter()
+    }
+}
+
+impl From<ControllerButton> for u16 {
+    fn from(button: ControllerButton) -> u16 {
+        button.mask()
+    }
+}
+
+/// Converts a mask with exactly one bit set to its button.
+impl TryFrom<u16> for ControllerButton {
+    type Error = ConvertError;
+
+    fn try_from(mask: u16) -> Result<Self, Self::Error> {
+        if !mask.is_power_of_two() {
+            return Err(ConvertError::InvalidButtonMask(mask));
+        }
+
+        Ok(Self::ALL[mask.trailing_zeros() as usize])
+    }
+}
@@ -0,0 +1,230 @@
+//! Detection of axis values that games mishandle.
+//!
+//! Some games crash or misbehave on particular analog stick values, most commonly
+//! -128, which has no positive counterpart. What is unsafe depends on the game, so
+//! the rules are given by a [`Constraints`] implementation; [`AxisConstraints`]
+//! covers the common cases.
+//!
+//! [`Movie::lint_axes`] reports the samples breaking the rules, and
+//! [`Movie::fix_axes`] nudges them to the nearest safe values. Both can be scoped to
+//! a [`FrameSelection`] with [`Movie::lint_axes_in`] and [`Movie::fix_axes_in`].
+
+use std::fmt::{self, Display};
+
+use crate::{Movie, selection::FrameSelection};
+
+/// The analog stick values a game handles correctly.
+pub trait Constraints {
+    /// Returns `true` if the game handles the stick at `(x, y)`.
+    fn is_safe(&self, x: i8, y: i8) -> bool;
+
+    /// Returns the safe position closest to `(x, y)`, or `None` if there is none.
+    ///
+    /// Returns `(x, y)` itself if it is safe. Ties are broken in favour of the
+    /// position found first, scanning rows from the lowest `y`.
+    fn nearest_safe(&self, x: i8, y: i8) -> Option<(i8, i8)> {
+        if self.is_safe(x, y) {
+            return Some((x, y));
+        }
+
+        let mut best: Option<((i8, i8), i32)> = None;
+        for ring in 1..=i8::MAX as i32 * 2 + 1 {
+            if best.is_some_and(|(_, distance)| distance < ring * ring) {
+                break;
+            }
+
+            for dy in -ring..=ring {
+                for dx in -ring..=ring {
+                    if dx.abs() != ring && dy.abs() != ring {
+                        continue;
+                    }
+
+                    let (Ok(nx), Ok(ny)) =
+                        (i8::try_from(x as i32 + dx), i8::try_from(y as i32 + dy))
+                    else {
+                        continue;
+                    };
+
+                    let distance = dx * dx + dy * dy;
+                    if best.is_none_or(|(_, best)| distance < best) && self.is_safe(nx, ny) {
+                        best = Some(((nx, ny), distance));
+                    }
+                }
+            }
+        }
+
+        best.map(|(position, _)| position)
+    }
+}
+
+/// Configurable stick constraints covering the common cases.
+///
+/// The default forbids -128 on either axis.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AxisConstraints {
+    /// Whether -128 is forbidden on either axis.
+    forbid_minimum: bool,
+    /// The largest distance from the centre allowed on each axis, if limited.
+    max_magnitude: Option<u8>,
+    /// Specific positions the game mishandles.
+    forbidden: Vec<(i8, i8)>,
+}
+
+impl Default for AxisConstraints {
+    fn default() -> Self {
+        AxisConstraints {
+            forbid_minimum: true,
+            max_magnitude: None,
+            forbidden: Vec::new(),
+        }
+    }
+}
+
+impl AxisConstraints {
+    /// Creates the default constraints, forbidding -128 on either axis.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates constraints allowing every position.
+    pub fn none() -> Self {
+        AxisConstraints {
+            forbid_minimum: false,
+            ..Self::default()
+        }
+    }
+
+    /// Sets whether -128 is forbidden on either axis.
+    pub fn forbid_minimum(mut self, forbid: bool) -> Self {
+        self.forbid_minimum = forbid;
+        self
+    }
+
+    /// Limits the distance from the centre on each axis.
+    pub fn max_magnitude(mut self, max: u8) -> Self {
+        self.max_magnitude = Some(max);
+        self
+    }
+
+    /// Forbids a specific position.
+    pub fn forbid(mut self, x: i8, y: i8) -> Self {
+        self.forbidden.push((x, y));
+        self
+    }
+}
+
+impl Constraints for AxisConstraints {
+    fn is_safe(&self, x: i8, y: i8) -> bool {
+        if self.forbid_minimum && (x == i8::MIN || y == i8::MIN) {
+            return false;
+        }
+
+        if let Some(max) = self.max_magnitude
+            && (x.unsigned_abs() > max || y.unsigned_abs() > max)
+        {
+            return false;
+        }
+
+        !self.forbidden.contains(&(x, y))
+    }
+}
+
+/// A sample whose stick position breaks the constraints.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisIssue {
+    /// The frame of the sample.
+    pub frame: usize,
+    /// The controller port of the sample.
+    pub port: usize,
+    /// The x-axis value.
+    pub x: i8,
+    /// The y-axis value.
+    pub y: i8,
+}
+
+impl Display for AxisIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame {}, port {}: unsafe stick position ({}, {})",
+            self.frame, self.port, self.x, self.y
+        )
+    }
+}
+
+impl Movie {
+    /// Returns the samples whose stick position breaks the constraints, in order.
+    pub fn lint_axes<C: Constraints + ?Sized>(&self, constraints: &C) -> Vec<AxisIssue> {
+        let count = self.controller_count().max(1);
+
+        self.samples()
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| !constraints.is_safe(state.x_axis(), state.y_axis()))
+            .map(|(index, state)| AxisIssue {
+                frame: index / count,
+                port: index % count,
+                x: state.x_axis(),
+                y: state.y_axis(),
+            })
+            .collect()
+    }
+
+    /// Moves every unsafe stick position to the nearest safe one, returning the
+    /// number of samples changed. Samples with no safe position are left unchanged.
+    pub fn fix_axes<C: Constraints + ?Sized>(&mut self, constraints: &C) -> usize {
+        let mut fixed = 0;
+        for state in &mut self.inputs {
+            let (x, y) = (state.x_axis(), state.y_axis());
+            if let Some((nx, ny)) = constraints.nearest_safe(x, y)
+                && (nx, ny) != (x, y)
+            {
+                state.set_x_axis(nx);
+                state.set_y_axis(ny);
+                fixed += 1;
+            }
+        }
+        fixed
+    }
+
+    /// Returns the samples of the selected frames whose stick position breaks the
+    /// constraints, in order.
+    pub fn lint_axes_in<C: Constraints + ?Sized>(
+        &self,
+        constraints: &C,
+        selection: &FrameSelection,
+    ) -> Vec<AxisIssue> {
+        self.lint_axes(constraints)
+            .into_iter()
+            .filter(|issue| selection.contains(issue.frame))
+            .collect()
+    }
+
+    /// Moves every unsafe stick position of the selected frames to the nearest safe
+    /// one, returning the number of samples changed. See [`Movie::fix_axes`].
+    pub fn fix_axes_in<C: Constraints + ?Sized>(
+        &mut self,
+        constraints: &C,
+        selection: &FrameSelection,
+    ) -> usize {
+        let count = self.controller_count().max(1);
+
+        let mut fixed = 0;
+        for (index, state) in self.inputs.iter_mut().enumerate() {
+            if !selection.contains(index / count) {
+                continue;
+            }
+
+            let (x, y) = (state.x_axis(), state.y_axis());
+            if let Some((nx, ny)) = constraints.nearest_safe(x, y)
+                && (nx, ny) != (x, y)
+            {
+                state.set_x_axis(nx);
+                state.set_y_axis(ny);
+                fixed += 1;
+            }
+        }
+        fixed
+    }
+}
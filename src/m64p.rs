@@ -0,0 +1,373 @@
+//! A bridge to the mupen64plus input plugin API.
+//!
+//! mupen64plus loads input plugins as shared libraries and asks them for the state of
+//! each controller through `GetKeys`. An [`InputPlugin`] answers those calls from an
+//! [`InputProvider`], and [`m64p_input_plugin!`](crate::m64p_input_plugin) exports
+//! the plugin functions for a movie, so a `cdylib` crate of a few lines is a movie
+//! playback plugin:
+//!
+//! ```ignore
+//! m64_movie::m64p_input_plugin!(c"Movie playback", || {
+//!     m64_movie::Movie::from_file(std::env::var("M64_MOVIE").unwrap())
+//! });
+//! ```
+//!
+//! Mupen64 movies hold one sample per controller each time the game polls its
+//! inputs, so the plugin moves to the next frame whenever a controller is polled a
+//! second time.
+
+use std::ffi::c_int;
+
+use crate::{playback::InputProvider, raw::ControllerFlags};
+
+/// The plugin type of input plugins, `M64PLUGIN_INPUT`.
+pub const PLUGIN_TYPE_INPUT: c_int = 4;
+
+/// The input plugin API version implemented.
+pub const INPUT_API_VERSION: c_int = 0x020100;
+
+/// The `m64p_error` returned on success.
+pub const M64ERR_SUCCESS: c_int = 0;
+
+/// The `m64p_error` returned when shutting down a plugin that was not started.
+pub const M64ERR_NOT_INIT: c_int = 1;
+
+/// The `m64p_error` returned when starting a plugin twice.
+pub const M64ERR_ALREADY_INIT: c_int = 2;
+
+/// The `m64p_error` returned when the plugin fails to start.
+pub const M64ERR_PLUGIN_FAIL: c_int = 11;
+
+/// No accessory is plugged into the controller.
+pub const PLUGIN_NONE: c_int = 1;
+
+/// A memory pack is plugged into the controller.
+pub const PLUGIN_MEMPAK: c_int = 2;
+
+/// A rumble pack is plugged into the controller.
+pub const PLUGIN_RUMBLE_PAK: c_int = 3;
+
+/// The number of controller ports of the console.
+const PORTS: usize = 4;
+
+/// A controller port, as described to the emulator. Mirrors `CONTROL`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Control {
+    /// Whether a controller is plugged in.
+    pub present: c_int,
+    /// Whether the plugin handles the raw controller protocol. Always `0`.
+    pub raw_data: c_int,
+    /// The accessory plugged into the controller, e.g. [`PLUGIN_MEMPAK`].
+    pub plugin: c_int,
+    /// The kind of controller. Always `0`, a standard controller.
+    pub kind: c_int,
+}
+
+/// The controller ports passed to `InitiateControllers`. Mirrors `CONTROL_INFO`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ControlInfo {
+    /// The four controller ports, to be filled in by the plugin.
+    pub controls: *mut Control,
+}
+
+/// An input plugin answering the emulator from an [`InputProvider`].
+#[derive(Debug, Clone)]
+pub struct InputPlugin<P> {
+    /// The source of the inputs.
+    provider: P,
+    /// The controllers plugged in.
+    flags: ControllerFlags,
+    /// The ports polled since the last frame advance.
+    polled: [bool; PORTS],
+}
+
+impl<P: InputProvider> InputPlugin<P> {
+    /// Creates a plugin for the controllers described by `flags`.
+    pub fn new(provider: P, flags: ControllerFlags) -> Self {
+        InputPlugin {
+            provider,
+            flags,
+            polled: [false; PORTS],
+        }
+    }
+
+    /// Returns the source of the inputs.
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Returns the controller ports to report to the emulator.
+    pub fn controls(&self) -> [Control; PORTS] {
+        std::array::from_fn(|port| {
+            let (present, mempak, rumblepak) = port_flags(self.flags, port);
+            Control {
+                present: present.into(),
+                raw_data: 0,
+                plugin: if mempak {
+                    PLUGIN_MEMPAK
+                } else if rumblepak {
+                    PLUGIN_RUMBLE_PAK
+                } else {
+                    PLUGIN_NONE
+                },
+                kind: 0,
+            }
+        })
+    }
+
+    /// Fills in the controller ports, as `InitiateControllers` does.
+    ///
+    /// # Safety
+    ///
+    /// `info.controls` must be null or point to four writable [`Control`]s.
+    pub unsafe fn initiate_controllers(&self, info: ControlInfo) {
+        if info.controls.is_null() {
+            return;
+        }
+
+        for (port, control) in self.controls().into_iter().enumerate() {
+            // SAFETY: the caller guarantees room for four controls.
+            unsafe { info.controls.add(port).write(control) };
+        }
+    }
+
+    /// Returns the `BUTTONS` value of a controller port, as `GetKeys` does, moving to
+    /// the next frame if the port was already polled.
+    ///
+    /// Ports without a controller read as neutral.
+    pub fn get_keys(&mut self, control: c_int) -> u32 {
+        let Some(port) = self.movie_port(control) else {
+            return 0;
+        };
+
+        let control = control as usize;
+        if self.polled[control] {
+            self.provider.frame_advanced();
+            self.polled = [false; PORTS];
+        }
+        self.polled[control] = true;
+
+        self.provider.poll(port).into()
+    }
+
+    /// Returns the movie port of an emulator controller port. Movies only store the
+    /// controllers that are plugged in, so the ports are numbered without gaps.
+    pub fn movie_port(&self, control: c_int) -> Option<usize> {
+        let control = usize::try_from(control).ok().filter(|&port| port < PORTS)?;
+        port_flags(self.flags, control).0.then(|| {
+            (0..control)
+                .filter(|&port| port_flags(self.flags, port).0)
+                .count()
+        })
+    }
+}
+
+/// Returns whether a port has a controller, a memory pack and a rumble pack.
+fn port_flags(flags: ControllerFlags, port: usize) -> (bool, bool, bool) {
+    match port {
+        0 => (
+            flags.controller_01_present(),
+            flags.controller_01_has_mempak(),
+            flags.controller_01_has_rumblepak(),
+        ),
+        1 => (
+            flags.controller_02_present(),
+            flags.controller_02_has_mempak(),
+            flags.controller_02_has_rumblepak(),
+        ),
+        2 => (
+            flags.controller_03_present(),
+            flags.controller_03_has_mempak(),
+            flags.controller_03_has_rumblepak(),
+        ),
+        3 => (
+            flags.controller_04_present(),
+            flags.controller_04_has_mempak(),
+            flags.controller_04_has_rumblepak(),
+        ),
+        _ => (false, false, false),
+    }
+}
+
+/// Exports the functions of a mupen64plus input plugin playing back a movie.
+///
+/// Takes the plugin name as a `&'static CStr` and a function loading the movie,
+/// returning `Result<Movie, MovieError>`. The movie is loaded once, when the plugin
+/// is first started. See the [module documentation](crate::m64p).
+#[macro_export]
+macro_rules! m64p_input_plugin {
+    ($name:expr, $load:expr) => {
+        #[doc(hidden)]
+        mod __m64p_input_plugin {
+            use ::std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+            use $crate::{Movie, m64p::InputPlugin, playback::MovieCursor};
+
+            /// The movie played back.
+            pub static MOVIE: OnceLock<Movie> = OnceLock::new();
+
+            /// The running plugin, between `PluginStartup` and `PluginShutdown`.
+            pub static PLUGIN: Mutex<Option<InputPlugin<MovieCursor<'static>>>> = Mutex::new(None);
+
+            /// Locks the running plugin. A poisoned lock is recovered, as panicking
+            /// in a callback would abort the emulator.
+            pub fn plugin() -> MutexGuard<'static, Option<InputPlugin<MovieCursor<'static>>>> {
+                PLUGIN.lock().unwrap_or_else(PoisonError::into_inner)
+            }
+        }
+
+        /// Starts the plugin, loading the movie.
+        ///
+        /// # Safety
+        ///
+        /// Called by mupen64plus. The arguments are not used.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn PluginStartup(
+            _core: *mut ::std::ffi::c_void,
+            _context: *mut ::std::ffi::c_void,
+            _debug: *mut ::std::ffi::c_void,
+        ) -> ::std::ffi::c_int {
+            if __m64p_input_plugin::plugin().is_some() {
+                return $crate::m64p::M64ERR_ALREADY_INIT;
+            }
+
+            // The movie is loaded without holding the lock, and a panic while loading
+            // it fails the startup instead of unwinding into the emulator.
+            let movie = match __m64p_input_plugin::MOVIE.get() {
+                Some(movie) => movie,
+                None => match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe($load)) {
+                    Ok(Ok(movie)) => __m64p_input_plugin::MOVIE.get_or_init(|| movie),
+                    Ok(Err(_)) | Err(_) => return $crate::m64p::M64ERR_PLUGIN_FAIL,
+                },
+            };
+
+            let mut plugin = __m64p_input_plugin::plugin();
+            if plugin.is_some() {
+                return $crate::m64p::M64ERR_ALREADY_INIT;
+            }
+            *plugin = Some($crate::m64p::InputPlugin::new(
+                movie.cursor(),
+                movie.controller_flags(),
+            ));
+            $crate::m64p::M64ERR_SUCCESS
+        }
+
+        /// Stops the plugin.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub extern "C" fn PluginShutdown() -> ::std::ffi::c_int {
+            match __m64p_input_plugin::plugin().take() {
+                Some(_) => $crate::m64p::M64ERR_SUCCESS,
+                None => $crate::m64p::M64ERR_NOT_INIT,
+            }
+        }
+
+        /// Describes the plugin.
+        ///
+        /// # Safety
+        ///
+        /// Each pointer must be null or valid for writes.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn PluginGetVersion(
+            plugin_type: *mut ::std::ffi::c_int,
+            plugin_version: *mut ::std::ffi::c_int,
+            api_version: *mut ::std::ffi::c_int,
+            name: *mut *const ::std::ffi::c_char,
+            capabilities: *mut ::std::ffi::c_int,
+        ) -> ::std::ffi::c_int {
+            let name_value: &'static ::std::ffi::CStr = $name;
+            // SAFETY: the caller guarantees each non-null pointer is writable.
+            unsafe {
+                if !plugin_type.is_null() {
+                    *plugin_type = $crate::m64p::PLUGIN_TYPE_INPUT;
+                }
+                if !plugin_version.is_null() {
+                    *plugin_version = 0x010000;
+                }
+                if !api_version.is_null() {
+                    *api_version = $crate::m64p::INPUT_API_VERSION;
+                }
+                if !name.is_null() {
+                    *name = name_value.as_ptr();
+                }
+                if !capabilities.is_null() {
+                    *capabilities = 0;
+                }
+            }
+            $crate::m64p::M64ERR_SUCCESS
+        }
+
+        /// Reports the controllers of the movie.
+        ///
+        /// # Safety
+        ///
+        /// `info.controls` must be null or point to four writable controls.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn InitiateControllers(info: $crate::m64p::ControlInfo) {
+            if let Some(plugin) = __m64p_input_plugin::plugin().as_ref() {
+                // SAFETY: forwarded from the caller.
+                unsafe { plugin.initiate_controllers(info) };
+            }
+        }
+
+        /// Reads the inputs of a controller from the movie.
+        ///
+        /// # Safety
+        ///
+        /// `keys` must be null or valid for writes.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn GetKeys(control: ::std::ffi::c_int, keys: *mut u32) {
+            let value = __m64p_input_plugin::plugin()
+                .as_mut()
+                .map_or(0, |plugin| plugin.get_keys(control));
+            if !keys.is_null() {
+                // SAFETY: the caller guarantees `keys` is writable.
+                unsafe { *keys = value };
+            }
+        }
+
+        /// Unused, as the plugin does not handle the raw controller protocol.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub extern "C" fn ReadController(_control: ::std::ffi::c_int, _command: *mut u8) {}
+
+        /// Unused, as the plugin does not handle the raw controller protocol.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub extern "C" fn ControllerCommand(_control: ::std::ffi::c_int, _command: *mut u8) {}
+
+        /// Rewinds the movie when a ROM is opened.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub extern "C" fn RomOpen() -> ::std::ffi::c_int {
+            if let Some(movie) = __m64p_input_plugin::MOVIE.get() {
+                if let Some(plugin) = __m64p_input_plugin::plugin().as_mut() {
+                    *plugin =
+                        $crate::m64p::InputPlugin::new(movie.cursor(), movie.controller_flags());
+                }
+            }
+            1
+        }
+
+        /// Called when the ROM is closed.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub extern "C" fn RomClosed() {}
+
+        /// Ignores keyboard input.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub extern "C" fn SDL_KeyDown(_keymod: ::std::ffi::c_int, _keysym: ::std::ffi::c_int) {}
+
+        /// Ignores keyboard input.
+        #[allow(non_snake_case)]
+        #[unsafe(no_mangle)]
+        pub extern "C" fn SDL_KeyUp(_keymod: ::std::ffi::c_int, _keysym: ::std::ffi::c_int) {}
+    };
+}
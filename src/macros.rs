@@ -0,0 +1,173 @@
+//! Reusable input sequences.
+//!
+//! A [`Macro`] is a named sequence of controller states for a single controller,
+//! such as mashing through a dialogue box or a pause buffer. Macros are collected
+//! in a [`MacroLibrary`] and stamped onto a movie with [`apply_at`].
+
+use std::{collections::BTreeMap, ops::Range};
+
+use crate::{FrameError, Movie, MovieError, raw::ControllerState};
+
+/// A named sequence of controller states for a single controller.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Macro {
+    /// The name of the macro.
+    name: String,
+    /// The controller state for each frame of the macro.
+    states: Vec<ControllerState>,
+}
+
+impl Macro {
+    /// Creates a new macro from a sequence of controller states.
+    pub fn new<S: Into<String>>(name: S, states: Vec<ControllerState>) -> Self {
+        Macro {
+            name: name.into(),
+            states,
+        }
+    }
+
+    /// Records the states of a controller over a range of frames of a movie.
+    pub fn record<S: Into<String>>(
+        name: S,
+        movie: &Movie,
+        port: usize,
+        frames: Range<usize>,
+    ) -> Result<Self, MovieError> {
+        movie.check_port(port)?;
+
+        let frame_count = movie.frame_count();
+        if frames.end > frame_count {
+            return Err(FrameError::OutOfBounds {
+                frame: frames.end,
+                frame_count,
+            }
+            .into());
+        }
+
+        let states = frames
+            .filter_map(|frame| movie.port_state(frame, port).copied())
+            .collect();
+
+        Ok(Macro::new(name, states))
+    }
+
+    /// Returns the name of the macro.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the controller state for each frame of the macro.
+    pub fn states(&self) -> &[ControllerState] {
+        &self.states
+    }
+
+    /// Returns the number of frames in the macro.
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns `true` if the macro has no frames.
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+/// A collection of macros, keyed by name.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct MacroLibrary {
+    /// The macros, keyed by name.
+    macros: BTreeMap<String, Macro>,
+}
+
+impl MacroLibrary {
+    /// Creates an empty library.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a macro to the library, returning the macro it replaced with the same name, if any.
+    pub fn insert(&mut self, m: Macro) -> Option<Macro> {
+        self.macros.insert(m.name.clone(), m)
+    }
+
+    /// Returns the macro with the given name.
+    pub fn get(&self, name: &str) -> Option<&Macro> {
+        self.macros.get(name)
+    }
+
+    /// Removes the macro with the given name, returning it.
+    pub fn remove(&mut self, name: &str) -> Option<Macro> {
+        self.macros.remove(name)
+    }
+
+    /// Returns an iterator over all macros, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = &Macro> {
+        self.macros.values()
+    }
+
+    /// Returns the number of macros in the library.
+    pub fn len(&self) -> usize {
+        self.macros.len()
+    }
+
+    /// Returns `true` if the library holds no macros.
+    pub fn is_empty(&self) -> bool {
+        self.macros.is_empty()
+    }
+}
+
+impl FromIterator<Macro> for MacroLibrary {
+    fn from_iter<I: IntoIterator<Item = Macro>>(iter: I) -> Self {
+        let mut library = MacroLibrary::new();
+        for m in iter {
+            library.insert(m);
+        }
+
+        library
+    }
+}
+
+/// How a macro is applied to a movie.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ApplyMode {
+    /// Overwrite the controller's existing states. The movie is extended with neutral
+    /// frames if the macro runs past its end.
+    Overlay,
+    /// Insert new frames for the macro, shifting later frames back. Other
+    /// controllers are neutral during the inserted frames.
+    Insert,
+}
+
+/// Applies a macro to a controller of a movie, starting at the given frame.
+pub fn apply_at(
+    movie: &mut Movie,
+    m: &Macro,
+    frame: usize,
+    port: usize,
+    mode: ApplyMode,
+) -> Result<(), MovieError> {
+    movie.check_port(port)?;
+
+    match mode {
+        ApplyMode::Overlay => {
+            let end = frame + m.len();
+            let frame_count = movie.frame_count();
+            if frame > frame_count {
+                return Err(FrameError::OutOfBounds { frame, frame_count }.into());
+            }
+
+            if end > frame_count {
+                movie.insert_neutral_frame_run(frame_count, end - frame_count)?;
+            }
+        }
+        ApplyMode::Insert => movie.insert_neutral_frame_run(frame, m.len())?,
+    }
+
+    for (offset, state) in m.states.iter().enumerate() {
+        if let Some(target) = movie.port_state_mut(frame + offset, port) {
+            *target = *state;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Named frames marking points of progress in a movie.
+//!
+//! Markers label the frames at which a run reaches a milestone, such as entering a
+//! level or collecting a star. Two movies of the same game that share marker labels
+//! can be aligned against each other, see [`compare`](crate::analysis::compare).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::bookmarks::Bookmarks;
+
+/// A named frame.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Marker {
+    /// The name of the marker, unique within a movie.
+    pub label: String,
+    /// The frame index the marker points at.
+    pub frame: usize,
+}
+
+/// A set of markers with unique labels.
+///
+/// Markers are not part of the M64 format. They are persisted alongside the movie
+/// in a [`Sidecar`](crate::sidecar::Sidecar) file.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Markers {
+    /// The frame of each marker, keyed by label.
+    frames: BTreeMap<String, usize>,
+    /// The labels of the markers on each frame, for lookups by frame.
+    labels: Bookmarks<BTreeSet<String>>,
+}
+
+impl Markers {
+    /// Creates an empty set of markers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Places a marker at a frame, returning the frame the label previously pointed at, if any.
+    pub fn insert<S: Into<String>>(&mut self, label: S, frame: usize) -> Option<usize> {
+        let label = label.into();
+        let previous = self.remove(&label);
+        match self.labels.get_mut(frame) {
+            Some(labels) => {
+                labels.insert(label.clone());
+            }
+            None => {
+                self.labels.insert(frame, BTreeSet::from([label.clone()]));
+            }
+        }
+        self.frames.insert(label, frame);
+        previous
+    }
+
+    /// Returns the frame of the marker with the given label.
+    pub fn get(&self, label: &str) -> Option<usize> {
+        self.frames.get(label).copied()
+    }
+
+    /// Removes the marker with the given label, returning its frame.
+    pub fn remove(&mut self, label: &str) -> Option<usize> {
+        let frame = self.frames.remove(label)?;
+        if let Some(labels) = self.labels.get_mut(frame) {
+            labels.remove(label);
+            if labels.is_empty() {
+                self.labels.remove(frame);
+            }
+        }
+        Some(frame)
+    }
+
+    /// Returns the markers in frame order. Markers on the same frame are ordered by label.
+    pub fn iter(&self) -> impl Iterator<Item = Marker> + '_ {
+        self.labels.iter().flat_map(|(frame, labels)| {
+            labels.iter().map(move |label| Marker {
+                label: label.clone(),
+                frame,
+            })
+        })
+    }
+
+    /// Returns the last marker at or before a frame. Of several markers on that frame,
+    /// the last by label is returned.
+    pub fn nearest_before(&self, frame: usize) -> Option<Marker> {
+        let (frame, labels) = self.labels.nearest_before(frame)?;
+        labels.last().map(|label| Marker {
+            label: label.clone(),
+            frame,
+        })
+    }
+
+    /// Returns the number of markers.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if there are no markers.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Removes all markers.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.labels.clear();
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, usize)> for Markers {
+    fn from_iter<I: IntoIterator<Item = (S, usize)>>(iter: I) -> Self {
+        let mut markers = Markers::new();
+        for (label, frame) in iter {
+            markers.insert(label, frame);
+        }
+        markers
+    }
+}
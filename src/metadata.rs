@@ -0,0 +1,258 @@
+//! Editing of movie metadata outside of the binary format.
+//!
+//! A [`MetadataPatch`] describes the metadata a team wants every export of a
+//! movie to carry. With the `toml` feature enabled, patches can be kept in a
+//! TOML file:
+//!
+//! ```toml
+//! author = "MKDasher, Nahoc"
+//! description = "120 star TAS"
+//! input_plugin = "TAS Input Plugin 0.6"
+//! start_type = "power-on"
+//! ```
+//!
+//! For edits beyond what a patch covers, [`Movie::header_to_toml`] exports every
+//! header field as a [`MovieHeader`] document, which can be edited by hand and
+//! imported again with [`Movie::import_header_toml`].
+
+use crate::{
+    Movie, MovieError,
+    parsed::{ExtendedData, GameInfo, MupenMetadata, PluginInfo, RecordingInfo},
+    raw::{ControllerFlags, MovieStartType},
+    shared::{EncodedFixedStr, FixedString},
+};
+
+/// A set of metadata fields to overwrite on a [`Movie`]. Fields set to `None` are left unchanged.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct MetadataPatch {
+    /// The author name to set.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub author: Option<String>,
+    /// The movie description to set.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub description: Option<String>,
+    /// The video plugin name to set.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub video_plugin: Option<String>,
+    /// The sound plugin name to set.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub sound_plugin: Option<String>,
+    /// The input plugin name to set.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub input_plugin: Option<String>,
+    /// The RSP plugin name to set.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub rsp_plugin: Option<String>,
+    /// The start type to set.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub start_type: Option<MovieStartType>,
+}
+
+impl MetadataPatch {
+    /// Returns `true` if the patch does not change anything.
+    pub fn is_empty(&self) -> bool {
+        self == &MetadataPatch::default()
+    }
+
+    /// Parses a patch from TOML.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, MovieError> {
+        Ok(toml::from_str(s)?)
+    }
+
+    /// Serializes the patch to TOML.
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> Result<String, MovieError> {
+        Ok(toml::to_string(self)?)
+    }
+}
+
+/// Options controlling what [`Movie::anonymize_with`] clears.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct AnonymizeOptions {
+    /// Also reset the rerecord count to zero.
+    pub clear_rerecords: bool,
+}
+
+impl AnonymizeOptions {
+    /// Returns the options with [`AnonymizeOptions::clear_rerecords`] set.
+    pub fn clear_rerecords(mut self, clear_rerecords: bool) -> Self {
+        self.clear_rerecords = clear_rerecords;
+        self
+    }
+}
+
+/// Every header field of a [`Movie`], without its inputs.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MovieHeader {
+    /// Metadata about the Mupen64 movie format.
+    pub metadata: MupenMetadata,
+    /// Information about the game used in the movie.
+    pub game_info: GameInfo,
+    /// Information about the plugins used in the movie.
+    pub plugin_info: PluginInfo,
+    /// Information about the recording, including author and movie details.
+    pub recording_info: RecordingInfo,
+    /// Flags indicating the presence and capabilities of controllers.
+    pub controller_flags: ControllerFlags,
+}
+
+impl MovieHeader {
+    /// Parses a header from TOML.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, MovieError> {
+        Ok(toml::from_str(s)?)
+    }
+
+    /// Serializes the header to TOML.
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> Result<String, MovieError> {
+        Ok(toml::to_string(self)?)
+    }
+}
+
+/// Pads `target` as `original`.
+#[cfg(feature = "toml")]
+fn keep_padding<const N: usize, E>(
+    target: &mut EncodedFixedStr<N, E>,
+    original: &EncodedFixedStr<N, E>,
+) {
+    target.set_padding(original.padding());
+}
+
+/// Replaces `target` with `value` if set, validating it for the field's size and encoding
+/// and keeping its padding.
+fn patch_str<const N: usize, E>(
+    target: &mut EncodedFixedStr<N, E>,
+    value: &Option<String>,
+) -> Result<(), MovieError>
+where
+    EncodedFixedStr<N, E>: FixedString<Error = MovieError>,
+{
+    if let Some(value) = value {
+        *target = EncodedFixedStr::from_str(value)?.with_padding(target.padding());
+    }
+
+    Ok(())
+}
+
+impl Movie {
+    /// Overwrites the metadata fields set in the patch.
+    ///
+    /// All values are validated before anything is written, so the movie is left
+    /// unchanged if any value is invalid.
+    pub fn apply_metadata(&mut self, patch: &MetadataPatch) -> Result<(), MovieError> {
+        let mut recording_info = self.recording_info.clone();
+        let mut plugin_info = self.plugin_info.clone();
+
+        patch_str(&mut recording_info.author_name, &patch.author)?;
+        patch_str(&mut recording_info.description, &patch.description)?;
+        patch_str(&mut plugin_info.video_plugin, &patch.video_plugin)?;
+        patch_str(&mut plugin_info.sound_plugin, &patch.sound_plugin)?;
+        patch_str(&mut plugin_info.input_plugin, &patch.input_plugin)?;
+        patch_str(&mut plugin_info.rsp_plugin, &patch.rsp_plugin)?;
+
+        if let Some(start_type) = patch.start_type {
+            recording_info.start_type = start_type;
+        }
+
+        self.recording_info = recording_info;
+        self.plugin_info = plugin_info;
+        Ok(())
+    }
+
+    /// Removes everything identifying the author of the movie: the author name,
+    /// description, UID and extended authorship info. Everything else, including the
+    /// inputs and rerecord count, is kept.
+    ///
+    /// Retained [source bytes](Movie::source) are dropped, as they hold the original header.
+    pub fn anonymize(&mut self) {
+        self.anonymize_with(AnonymizeOptions::default());
+    }
+
+    /// Anonymizes the movie with the given options. See [`Movie::anonymize`].
+    pub fn anonymize_with(&mut self, options: AnonymizeOptions) {
+        self.recording_info.author_name = EncodedFixedStr::from_utf8_const("");
+        self.recording_info.description = EncodedFixedStr::from_utf8_const("");
+        self.recording_info.uid = 0;
+
+        if let ExtendedData::ExtendedDataV1 {
+            authorship_info, ..
+        } = &mut self.metadata.extended_data
+        {
+            *authorship_info = 0;
+        }
+
+        if options.clear_rerecords {
+            self.recording_info.rerecord_count = 0;
+            if let ExtendedData::ExtendedDataV1 {
+                rerecord_count_high,
+                ..
+            } = &mut self.metadata.extended_data
+            {
+                *rerecord_count_high = 0;
+            }
+        }
+
+        self.source = None;
+    }
+
+    /// Returns a copy of every header field of the movie.
+    pub fn header(&self) -> MovieHeader {
+        MovieHeader {
+            metadata: self.metadata.clone(),
+            game_info: self.game_info.clone(),
+            plugin_info: self.plugin_info.clone(),
+            recording_info: self.recording_info.clone(),
+            controller_flags: self.controller_flags(),
+        }
+    }
+
+    /// Replaces every header field of the movie, keeping its inputs.
+    ///
+    /// Fails without changing the movie if the header flags a different number of
+    /// controllers than the movie has, see [`Movie::set_controller_flags`].
+    pub fn set_header(&mut self, header: MovieHeader) -> Result<(), MovieError> {
+        self.set_controller_flags(header.controller_flags)?;
+        self.metadata = header.metadata;
+        self.game_info = header.game_info;
+        self.plugin_info = header.plugin_info;
+        self.recording_info = header.recording_info;
+        Ok(())
+    }
+
+    /// Exports every header field of the movie as a human-editable TOML document.
+    #[cfg(feature = "toml")]
+    pub fn header_to_toml(&self) -> Result<String, MovieError> {
+        self.header().to_toml()
+    }
+
+    /// Replaces every header field of the movie with those of a TOML document
+    /// produced by [`Movie::header_to_toml`], keeping its inputs.
+    ///
+    /// TOML holds the text of the string fields but not their padding, so each string
+    /// field keeps the [padding](EncodedFixedStr::padding) it had in the movie.
+    #[cfg(feature = "toml")]
+    pub fn import_header_toml(&mut self, s: &str) -> Result<(), MovieError> {
+        let mut header = MovieHeader::from_toml(s)?;
+        let (game, plugins, recording) = (
+            &mut header.game_info,
+            &mut header.plugin_info,
+            &mut header.recording_info,
+        );
+        keep_padding(&mut game.rom_name, &self.game_info.rom_name);
+        keep_padding(&mut plugins.video_plugin, &self.plugin_info.video_plugin);
+        keep_padding(&mut plugins.sound_plugin, &self.plugin_info.sound_plugin);
+        keep_padding(&mut plugins.input_plugin, &self.plugin_info.input_plugin);
+        keep_padding(&mut plugins.rsp_plugin, &self.plugin_info.rsp_plugin);
+        keep_padding(&mut recording.author_name, &self.recording_info.author_name);
+        keep_padding(&mut recording.description, &self.recording_info.description);
+        self.set_header(header)
+    }
+}
@@ -0,0 +1,197 @@
+//! Names of header values, for CLIs and config files.
+//!
+//! [`MovieStartType`], [`CountryCode`] and [`PakType`] implement [`Display`] and
+//! [`FromStr`] with the names below, so they can be referenced by name instead of by
+//! their numeric codes. With the `cli` feature, they also implement `clap::ValueEnum`.
+//!
+//! | Value              | Names                                         |
+//! |--------------------|-----------------------------------------------|
+//! | [`MovieStartType`] | `snapshot`, `power-on`, `eeprom`              |
+//! | [`PakType`]        | `none`, `mempak`, `rumblepak`                 |
+//! | [`CountryCode`]    | `north-america`, `japan`, `europe`, and so on |
+//!
+//! Names are parsed regardless of case. A country code may also be given as the
+//! character stored in the ROM, e.g. `E`, or as a hexadecimal code, e.g. `0x45`.
+//!
+//! ```
+//! use m64_movie::{raw::MovieStartType, region::CountryCode};
+//!
+//! assert_eq!("power-on".parse::<MovieStartType>().unwrap(), MovieStartType::PowerOn);
+//! assert_eq!("E".parse::<CountryCode>().unwrap(), CountryCode::NorthAmerica);
+//! assert_eq!(CountryCode::NorthAmerica.to_string(), "north-america");
+//! ```
+
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::{
+    NameError,
+    raw::{MovieStartType, PakType},
+    region::CountryCode,
+};
+
+/// Implements [`Display`], [`FromStr`] and, with the `cli` feature, `clap::ValueEnum`
+/// for an enum without fields, given the name of each variant.
+macro_rules! named_enum {
+    ($type:ty, $kind:literal, [$($variant:path => $name:literal),* $(,)?]) => {
+        impl $type {
+            /// Returns the name of the value, as parsed by [`FromStr`].
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($variant => $name,)*
+                }
+            }
+        }
+
+        impl Display for $type {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        impl FromStr for $type {
+            type Err = NameError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                [$($variant),*]
+                    .into_iter()
+                    .find(|value| value.name().eq_ignore_ascii_case(s.trim()))
+                    .ok_or_else(|| NameError::Unknown {
+                        kind: $kind,
+                        name: s.to_string(),
+                    })
+            }
+        }
+
+        #[cfg(feature = "cli")]
+        impl clap::ValueEnum for $type {
+            fn value_variants<'a>() -> &'a [Self] {
+                &[$($variant),*]
+            }
+
+            fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+                Some(clap::builder::PossibleValue::new(self.name()))
+            }
+        }
+    };
+}
+
+named_enum!(MovieStartType, "start type", [
+    MovieStartType::Snapshot => "snapshot",
+    MovieStartType::PowerOn => "power-on",
+    MovieStartType::EEPROM => "eeprom",
+]);
+
+named_enum!(PakType, "pak type", [
+    PakType::None => "none",
+    PakType::Mempak => "mempak",
+    PakType::Rumblepak => "rumblepak",
+]);
+
+/// The country codes that have a name, in the order of their codes.
+const NAMED_COUNTRIES: [CountryCode; 18] = [
+    CountryCode::Beta,
+    CountryCode::Asia,
+    CountryCode::Brazil,
+    CountryCode::China,
+    CountryCode::Germany,
+    CountryCode::NorthAmerica,
+    CountryCode::France,
+    CountryCode::GatewayNtsc,
+    CountryCode::Netherlands,
+    CountryCode::Italy,
+    CountryCode::Japan,
+    CountryCode::Korea,
+    CountryCode::GatewayPal,
+    CountryCode::Canada,
+    CountryCode::Europe,
+    CountryCode::Spain,
+    CountryCode::Australia,
+    CountryCode::Scandinavia,
+];
+
+impl CountryCode {
+    /// Returns the name of the country, as parsed by [`FromStr`], or `None` for the
+    /// codes without a name.
+    pub fn name(self) -> Option<&'static str> {
+        Some(match self {
+            CountryCode::Beta => "beta",
+            CountryCode::Asia => "asia",
+            CountryCode::Brazil => "brazil",
+            CountryCode::China => "china",
+            CountryCode::Germany => "germany",
+            CountryCode::NorthAmerica => "north-america",
+            CountryCode::France => "france",
+            CountryCode::GatewayNtsc => "gateway-ntsc",
+            CountryCode::Netherlands => "netherlands",
+            CountryCode::Italy => "italy",
+            CountryCode::Japan => "japan",
+            CountryCode::Korea => "korea",
+            CountryCode::GatewayPal => "gateway-pal",
+            CountryCode::Canada => "canada",
+            CountryCode::Europe => "europe",
+            CountryCode::Spain => "spain",
+            CountryCode::Australia => "australia",
+            CountryCode::Scandinavia => "scandinavia",
+            CountryCode::OtherEurope(_) | CountryCode::Other(_) => return None,
+        })
+    }
+}
+
+/// Formats the name of the country, the character of other European codes, e.g. `X`,
+/// or the hexadecimal code of unknown countries, e.g. `0x0000`.
+impl Display for CountryCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.name(), self) {
+            (Some(name), _) => f.write_str(name),
+            (None, CountryCode::OtherEurope(code)) => write!(f, "{}", *code as char),
+            (None, _) => write!(f, "{:#06X}", u16::from(*self)),
+        }
+    }
+}
+
+impl FromStr for CountryCode {
+    type Err = NameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let unknown = || NameError::Unknown {
+            kind: "country code",
+            name: s.to_string(),
+        };
+
+        if let Some(country) = NAMED_COUNTRIES.into_iter().find(|country| {
+            country
+                .name()
+                .is_some_and(|name| name.eq_ignore_ascii_case(s))
+        }) {
+            return Ok(country);
+        }
+
+        if let [code] = s.as_bytes()
+            && code.is_ascii_graphic()
+        {
+            return Ok(CountryCode::from(*code as u16));
+        }
+
+        s.strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+            .map(CountryCode::from)
+            .ok_or_else(unknown)
+    }
+}
+
+/// Only the countries with a name are listed. Other codes cannot be parsed by clap.
+#[cfg(feature = "cli")]
+impl clap::ValueEnum for CountryCode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &NAMED_COUNTRIES
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        self.name().map(clap::builder::PossibleValue::new)
+    }
+}
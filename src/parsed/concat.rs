@@ -0,0 +1,84 @@
+//! Reading movies followed by other data.
+//!
+//! [`BinReadExt::from_bytes`] reads every byte after the header as inputs, so a file
+//! with several movies appended to each other, or with data after the inputs, is
+//! rejected for having more samples than declared. [`Movie::from_bytes_prefix`]
+//! instead reads only the samples the header declares and returns the bytes after
+//! them, and [`Movie::from_bytes_concatenated`] reads every movie of such a file.
+
+use std::io::Cursor;
+
+use binrw::BinRead;
+
+use crate::{
+    MovieError, MovieParseError,
+    parsed::Movie,
+    raw::{
+        ControllerState,
+        m64::{HEADER_LEN, RawMovie, SAMPLE_LEN, is_m64},
+    },
+};
+
+/// The movies read from a file holding several movies. See
+/// [`Movie::from_bytes_concatenated`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Concatenated {
+    /// The movies, in the order they appear in the file.
+    pub movies: Vec<Movie>,
+    /// The bytes after the last movie that do not start another movie.
+    pub trailing: Vec<u8>,
+}
+
+impl Movie {
+    /// Reads a movie from the start of some bytes, returning it along with the bytes
+    /// following its inputs.
+    ///
+    /// Only as many samples as the header declares are read. Fails if there are
+    /// fewer, or if the header is invalid.
+    pub fn from_bytes_prefix(bytes: &[u8]) -> Result<(Movie, &[u8]), MovieError> {
+        let header = bytes.get(..HEADER_LEN).unwrap_or(bytes);
+        let mut raw = RawMovie::read_le(&mut Cursor::new(header))?;
+
+        let present = raw.controller_flags.num_controllers_present() as usize;
+        let declared = raw.controller_input_samples as usize * present;
+        let available = (bytes.len() - HEADER_LEN) / SAMPLE_LEN;
+        if available < declared {
+            return Err(MovieParseError::InputSampleMismatch {
+                expected: declared,
+                found: available,
+                input_bytes: bytes.len() - HEADER_LEN,
+            }
+            .into());
+        }
+
+        let end = HEADER_LEN + declared * SAMPLE_LEN;
+        raw.inputs = bytes[HEADER_LEN..end]
+            .chunks_exact(SAMPLE_LEN)
+            .map(|sample| ControllerState::from(u32::from_le_bytes(sample.try_into().unwrap())))
+            .collect();
+
+        Ok((Movie::try_from(raw)?, &bytes[end..]))
+    }
+
+    /// Reads every movie of a file holding movies appended to each other.
+    ///
+    /// Movies are read with [`Movie::from_bytes_prefix`] for as long as the remaining
+    /// bytes start with an M64 header. Anything left after the last movie is kept in
+    /// [`Concatenated::trailing`]. Fails if the first movie, or any later one that
+    /// starts with an M64 header, cannot be read.
+    pub fn from_bytes_concatenated(bytes: &[u8]) -> Result<Concatenated, MovieError> {
+        let (first, mut rest) = Movie::from_bytes_prefix(bytes)?;
+
+        let mut movies = vec![first];
+        while is_m64(rest) {
+            let (movie, next) = Movie::from_bytes_prefix(rest)?;
+            movies.push(movie);
+            rest = next;
+        }
+
+        Ok(Concatenated {
+            movies,
+            trailing: rest.to_vec(),
+        })
+    }
+}
@@ -1,11 +1,24 @@
+use std::{fs, ops::Range, path::Path};
+
 use crate::{
-    MovieError, MovieParseError,
-    raw::{self, ControllerFlags, ControllerState, MovieStartType, RawMovie},
-    shared::{Ascii, EncodedFixedStr, Reserved, Utf8},
+    FrameError, MovieError, MovieParseError,
+    annotations::Annotations,
+    lag::LagMask,
+    markers::Markers,
+    metadata::MovieHeader,
+    parsed::Source,
+    raw::{
+        self, ControllerFlags, ControllerState, MovieStartType, RawHeader, RawMovie,
+        m64::SAMPLE_LEN,
+    },
+    shared::{Ascii, EncodedFixedStr, RawString, Reserved, Utf8},
+    sidecar::Sidecar,
+    track::FrameIndex,
 };
 
 /// Extended flags for Mupen64 movies.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExtendedFlags {
     ExtendedFlagsV0,
     ExtendedFlagsV1 { wiivc_emulation_mode: bool },
@@ -28,7 +41,8 @@ impl From<ExtendedFlags> for raw::ExtendedFlags {
 }
 
 /// Extended data for Mupen64 movies.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExtendedData {
     ExtendedDataV0,
     ExtendedDataV1 {
@@ -61,6 +75,7 @@ impl From<ExtendedData> for raw::ExtendedData {
 
 /// Metadata for a Mupen64 movie file.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MupenMetadata {
     /// The version of the Mupen64 movie format.
     pub version: u32,
@@ -71,10 +86,110 @@ pub struct MupenMetadata {
     pub extended_flags: ExtendedFlags,
     /// Extended data for the movie, which is only valid if the extended version is non-zero.
     pub extended_data: ExtendedData,
+    /// Data found in the regions the format reserves, or `None` if they are all zero.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub legacy: Option<LegacyData>,
+}
+
+/// Non-zero data found in the reserved regions of a version 3 header.
+///
+/// Older Mupen64 builds and third-party tools stored their own data in these regions.
+/// The raw bytes of each region are kept, and `None` if the region is all zero. The
+/// layouts known from Mupen64 builds before 1.1.9, which wrote extended version 0,
+/// are also decoded, see [`OldMovieInfo`].
+///
+/// These bytes are written as zero by default. Write with
+/// [`ReservedRegions::Preserved`](super::ReservedRegions::Preserved), or use
+/// [`Movie::to_bytes_preserving_source`], to keep them when re-encoding a file.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LegacyData {
+    /// The 2 bytes after the start type, at offset 0x01E.
+    pub reserved01: Option<Vec<u8>>,
+    /// The 128 bytes after the extended data, at offset 0x044.
+    pub reserved02: Option<Vec<u8>>,
+    /// The 56 bytes after the ROM country code, at offset 0x0EA.
+    pub reserved03: Option<Vec<u8>>,
+    /// The author and description stored at offset 0x044 by builds before 1.1.9, or
+    /// `None` if the movie has a later extended version or the region is all zero.
+    /// Only [`reserved02`](Self::reserved02) is written back.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub old_info: Option<OldMovieInfo>,
+}
+
+impl LegacyData {
+    /// Collects the non-zero reserved regions of a raw header, or returns `None` if
+    /// they are all zero.
+    pub fn from_raw(raw: &RawMovie) -> Option<Self> {
+        /// Returns the bytes of a region, or `None` if it is all zero.
+        fn region<const N: usize>(reserved: &Reserved<N>) -> Option<Vec<u8>> {
+            (!reserved.is_zeroed()).then(|| reserved.as_bytes().to_vec())
+        }
+
+        let reserved02 = region(&raw.reserved02);
+        let old_info = match raw.extended_version {
+            0 => reserved02.as_deref().map(OldMovieInfo::from_region),
+            _ => None,
+        };
+
+        let legacy = LegacyData {
+            reserved01: region(&raw.reserved01),
+            reserved02,
+            reserved03: region(&raw.reserved03),
+            old_info,
+        };
+
+        (legacy != LegacyData::default()).then_some(legacy)
+    }
+}
+
+/// The author and description fields of the headers written by Mupen64 builds before
+/// 1.1.9.
+///
+/// These builds stored a 48-byte author and an 80-byte description at offset 0x044,
+/// in the region that later builds reserve, before the longer fields at 0x222 and
+/// 0x300 were added. Text is read up to its terminator, and bytes that are not UTF-8,
+/// e.g. from a legacy code page, are replaced by U+FFFD.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OldMovieInfo {
+    /// The author, from the 48 bytes at offset 0x044.
+    pub author: String,
+    /// The description, from the 80 bytes at offset 0x074.
+    pub description: String,
+}
+
+impl OldMovieInfo {
+    /// The length of the old author field.
+    const AUTHOR_LEN: usize = 48;
+
+    /// Decodes the 128-byte reserved region at offset 0x044.
+    fn from_region(region: &[u8]) -> Self {
+        /// Decodes a field up to its terminator.
+        fn text(bytes: &[u8]) -> String {
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end])
+                .trim_end()
+                .to_string()
+        }
+
+        let (author, description) = region.split_at(Self::AUTHOR_LEN);
+        OldMovieInfo {
+            author: text(author),
+            description: text(description),
+        }
+    }
 }
 
 /// Information about the game used in the movie.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameInfo {
     /// The internal name of the ROM used in the movie. This value is taken
     /// directly from the ROM. Should be a 32-byte ASCII string.
@@ -89,6 +204,7 @@ pub struct GameInfo {
 
 /// Information about the plugins used in the movie.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PluginInfo {
     /// The name of the video plugin used in the movie. This value is
     /// taken directly from the plugin. Should be a 64-byte ASCII string.
@@ -106,6 +222,7 @@ pub struct PluginInfo {
 
 /// Information about the recording, including author and movie details.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecordingInfo {
     /// Author name info for the movie. Should be 222-byte UTF-8 string.
     pub author_name: EncodedFixedStr<222, Utf8>,
@@ -113,18 +230,14 @@ pub struct RecordingInfo {
     pub description: EncodedFixedStr<256, Utf8>,
     /// The unique identifier for the movie.
     pub uid: u32,
-    /// The number of vertical interrupts in the movie.
-    pub vertical_interrupts: u32,
-    /// The number of rerecords in the movie.
-    pub rerecord_count: u32,
+    /// The number of vertical interrupts in the movie, updated through the checked
+    /// methods of [`counters`](crate::counters).
+    pub(crate) vertical_interrupts: u32,
+    /// The number of rerecords in the movie, updated through the checked methods of
+    /// [`counters`](crate::counters).
+    pub(crate) rerecord_count: u32,
     /// The number of vertical interrupts per second.
     pub vis_per_second: u8,
-    /// The number of controllers used in the movie.
-    pub controller_count: u8,
-    /// The number of input samples for any controller in the movie.
-    pub controller_input_samples: u32,
-    /// Flags indicating the presence and capabilities of controllers.
-    pub controller_flags: ControllerFlags,
     /// The start type of the movie, indicating how the movie begins.
     pub start_type: MovieStartType,
 }
@@ -133,8 +246,11 @@ pub struct RecordingInfo {
 ///
 /// Only version 3 is supported. Please refer to the
 /// [file format documentation](https://tasvideos.org/EmulatorResources/Mupen/M64) for more details.
-
-#[derive(Debug, Clone, Eq, PartialEq)]
+///
+/// Unlike [`RawMovie`], a [`Movie`] is always internally consistent: the number of
+/// controllers is derived from the [`ControllerFlags`], inputs are only accessible
+/// as whole frames, and the header counters are computed when the movie is written.
+#[derive(Debug, Clone)]
 pub struct Movie {
     /// Metadata about the Mupen64 movie format.
     pub metadata: MupenMetadata,
@@ -144,10 +260,41 @@ pub struct Movie {
     pub plugin_info: PluginInfo,
     /// Information about the recording, including author and movie details.
     pub recording_info: RecordingInfo,
-    /// Controller inputs for the movie.
-    pub inputs: Vec<ControllerState>,
+    /// Flags indicating the presence and capabilities of controllers.
+    controller_flags: ControllerFlags,
+    /// Controller inputs for the movie, interleaved by controller.
+    pub(crate) inputs: Vec<ControllerState>,
+    /// Notes attached to frames of the movie. These are not part of the M64 format.
+    annotations: Annotations,
+    /// Named frames marking points of progress in the movie. These are not part of the M64 format.
+    markers: Markers,
+    /// The lag frames of the movie, if known. These are not part of the M64 format.
+    lag_mask: Option<LagMask>,
+    /// The bytes the movie was parsed from, if retained. See [`ParseOptions`](super::ParseOptions).
+    pub(crate) source: Option<Source>,
+    /// The bytes found after the inputs, if retained. See [`ParseOptions`](super::ParseOptions).
+    pub(crate) trailing_data: Vec<u8>,
+}
+
+/// Movies are equal if they hold the same data, whether or not their source was
+/// retained.
+impl PartialEq for Movie {
+    fn eq(&self, other: &Self) -> bool {
+        self.metadata == other.metadata
+            && self.game_info == other.game_info
+            && self.plugin_info == other.plugin_info
+            && self.recording_info == other.recording_info
+            && self.controller_flags == other.controller_flags
+            && self.inputs == other.inputs
+            && self.annotations == other.annotations
+            && self.markers == other.markers
+            && self.lag_mask == other.lag_mask
+            && self.trailing_data == other.trailing_data
+    }
 }
 
+impl Eq for Movie {}
+
 pub trait MovieDetails {
     /// Creates a new instance from the raw type.
     fn from_raw(raw: &RawMovie) -> Result<Self, MovieError>
@@ -199,14 +346,29 @@ impl MovieDetails for MupenMetadata {
             extended_version: raw.extended_version,
             extended_flags,
             extended_data,
+            legacy: LegacyData::from_raw(raw),
         })
     }
 }
 
+/// Parses an ASCII string field, keeping the padding observed in the file.
+fn padded_ascii<const N: usize>(
+    raw: &RawString<N>,
+) -> Result<EncodedFixedStr<N, Ascii>, MovieError> {
+    let (bytes, padding) = raw.split_padding();
+    Ok(EncodedFixedStr::from_ascii(bytes)?.with_padding(padding))
+}
+
+/// Parses a UTF-8 string field, keeping the padding observed in the file.
+fn padded_utf8<const N: usize>(raw: &RawString<N>) -> Result<EncodedFixedStr<N, Utf8>, MovieError> {
+    let (bytes, padding) = raw.split_padding();
+    Ok(EncodedFixedStr::from_utf8(bytes)?.with_padding(padding))
+}
+
 impl MovieDetails for GameInfo {
     fn from_raw(raw: &RawMovie) -> Result<Self, MovieError> {
         Ok(GameInfo {
-            rom_name: EncodedFixedStr::from_ascii_str(raw.rom_name.to_string())?,
+            rom_name: padded_ascii(&raw.rom_name)?,
             rom_crc32: raw.rom_crc32,
             rom_country: raw.rom_country,
         })
@@ -216,10 +378,10 @@ impl MovieDetails for GameInfo {
 impl MovieDetails for PluginInfo {
     fn from_raw(raw: &RawMovie) -> Result<Self, MovieError> {
         Ok(PluginInfo {
-            video_plugin: EncodedFixedStr::from_ascii_str(raw.video_plugin.to_string())?,
-            sound_plugin: EncodedFixedStr::from_ascii_str(raw.sound_plugin.to_string())?,
-            input_plugin: EncodedFixedStr::from_ascii_str(raw.input_plugin.to_string())?,
-            rsp_plugin: EncodedFixedStr::from_ascii_str(raw.rsp_plugin.to_string())?,
+            video_plugin: padded_ascii(&raw.video_plugin)?,
+            sound_plugin: padded_ascii(&raw.sound_plugin)?,
+            input_plugin: padded_ascii(&raw.input_plugin)?,
+            rsp_plugin: padded_ascii(&raw.rsp_plugin)?,
         })
     }
 }
@@ -227,15 +389,12 @@ impl MovieDetails for PluginInfo {
 impl MovieDetails for RecordingInfo {
     fn from_raw(raw: &RawMovie) -> Result<Self, MovieError> {
         Ok(RecordingInfo {
-            author_name: EncodedFixedStr::from_utf8_str(raw.author_name.to_string())?,
-            description: EncodedFixedStr::from_utf8_str(raw.description.to_string())?,
+            author_name: padded_utf8(&raw.author_name)?,
+            description: padded_utf8(&raw.description)?,
             uid: raw.uid,
             vertical_interrupts: raw.vertical_interrupts,
             rerecord_count: raw.rerecord_count,
             vis_per_second: raw.vis_per_second,
-            controller_count: raw.controller_count,
-            controller_input_samples: raw.controller_input_samples,
-            controller_flags: raw.controller_flags,
             start_type: raw.start_type,
         })
     }
@@ -245,48 +404,112 @@ impl TryFrom<RawMovie> for Movie {
     type Error = MovieError;
 
     fn try_from(raw: RawMovie) -> Result<Self, Self::Error> {
+        let present = check_controller_count(&raw)?;
+        if present == 0 && !raw.inputs.is_empty()
+            || present != 0 && !raw.inputs.len().is_multiple_of(present)
+        {
+            return Err(FrameError::PartialFrame {
+                samples: raw.inputs.len(),
+                controller_count: present,
+            }
+            .into());
+        }
+
+        let expected = raw.controller_input_samples as usize * present;
+        if raw.inputs.len() != expected {
+            return Err(MovieParseError::InputSampleMismatch {
+                expected,
+                found: raw.inputs.len(),
+                input_bytes: raw.inputs.len() * SAMPLE_LEN,
+            }
+            .into());
+        }
+
+        Movie::from_checked_raw(raw)
+    }
+}
+
+/// Creates a movie without inputs from a header read without its inputs.
+///
+/// The input sample counter is not checked, as the inputs were not read.
+impl TryFrom<RawHeader> for Movie {
+    type Error = MovieError;
+
+    fn try_from(header: RawHeader) -> Result<Self, Self::Error> {
+        let raw = header.with_inputs(Vec::new());
+        check_controller_count(&raw)?;
+        Movie::from_checked_raw(raw)
+    }
+}
+
+/// Returns the number of controllers of a raw movie, or an error if the controller
+/// count does not match the controllers flagged as present.
+fn check_controller_count(raw: &RawMovie) -> Result<usize, MovieParseError> {
+    let present = raw.controller_flags.num_controllers_present();
+    if raw.controller_count != present {
+        return Err(MovieParseError::ControllerCountMismatch {
+            declared: raw.controller_count,
+            present,
+        });
+    }
+
+    Ok(present as usize)
+}
+
+impl Movie {
+    /// Creates a movie from a raw movie whose inputs were checked against its header.
+    fn from_checked_raw(raw: RawMovie) -> Result<Self, MovieError> {
         Ok(Movie {
             metadata: MupenMetadata::from_raw(&raw)?,
             game_info: GameInfo::from_raw(&raw)?,
             plugin_info: PluginInfo::from_raw(&raw)?,
             recording_info: RecordingInfo::from_raw(&raw)?,
+            controller_flags: raw.controller_flags,
             inputs: raw.inputs,
+            annotations: Annotations::default(),
+            markers: Markers::default(),
+            lag_mask: None,
+            source: None,
+            trailing_data: Vec::new(),
         })
     }
 }
 
+/// Creates a movie without inputs from its header fields.
+impl From<MovieHeader> for Movie {
+    fn from(header: MovieHeader) -> Self {
+        Movie {
+            metadata: header.metadata,
+            game_info: header.game_info,
+            plugin_info: header.plugin_info,
+            recording_info: header.recording_info,
+            controller_flags: header.controller_flags,
+            inputs: Vec::new(),
+            annotations: Annotations::default(),
+            markers: Markers::default(),
+            lag_mask: None,
+            source: None,
+            trailing_data: Vec::new(),
+        }
+    }
+}
+
 impl From<Movie> for RawMovie {
     fn from(movie: Movie) -> Self {
+        let header = movie.modeled_header();
         RawMovie {
-            version: movie.metadata.version,
-            extended_version: movie.metadata.extended_version,
-            extended_flags: movie.metadata.extended_flags.into(),
-            extended_data: movie.metadata.extended_data.into(),
-            rom_name: movie.game_info.rom_name.to_string().into(),
-            rom_crc32: movie.game_info.rom_crc32,
-            rom_country: movie.game_info.rom_country,
-            video_plugin: movie.plugin_info.video_plugin.to_string().into(),
-            sound_plugin: movie.plugin_info.sound_plugin.to_string().into(),
-            input_plugin: movie.plugin_info.input_plugin.to_string().into(),
-            rsp_plugin: movie.plugin_info.rsp_plugin.to_string().into(),
-            author_name: movie.recording_info.author_name.to_string().into(),
-            description: movie.recording_info.description.to_string().into(),
-            uid: movie.recording_info.uid,
-            vertical_interrupts: movie.recording_info.vertical_interrupts,
-            rerecord_count: movie.recording_info.rerecord_count,
-            vis_per_second: movie.recording_info.vis_per_second,
-            controller_count: movie.recording_info.controller_count,
-            controller_input_samples: movie.recording_info.controller_input_samples,
-            controller_flags: movie.recording_info.controller_flags,
-            start_type: movie.recording_info.start_type,
             inputs: movie.inputs,
-            reserved01: Reserved::default(),
-            reserved02: Reserved::default(),
-            reserved03: Reserved::default(),
+            ..header
         }
     }
 }
 
+impl From<&Movie> for RawMovie {
+    fn from(movie: &Movie) -> Self {
+        movie.to_raw()
+    }
+}
+
 impl Movie {
     /// Creates a new [`Movie`] from a [`RawMovie`].
     pub fn from_raw(raw: RawMovie) -> Result<Self, MovieError> {
@@ -298,6 +521,48 @@ impl Movie {
         RawMovie::from(self)
     }
 
+    /// Creates a [`RawMovie`] from the [`Movie`], copying only the header fields and inputs.
+    pub fn to_raw(&self) -> RawMovie {
+        RawMovie {
+            inputs: self.inputs.clone(),
+            ..self.modeled_header()
+        }
+    }
+
+    /// Creates a [`RawMovie`] holding the header of the [`Movie`] and no inputs.
+    ///
+    /// The controller counters are derived from the movie's inputs, and the reserved
+    /// regions are zeroed.
+    pub(crate) fn modeled_header(&self) -> RawMovie {
+        RawMovie {
+            version: self.metadata.version,
+            extended_version: self.metadata.extended_version,
+            extended_flags: self.metadata.extended_flags.into(),
+            extended_data: self.metadata.extended_data.into(),
+            rom_name: self.game_info.rom_name.into(),
+            rom_crc32: self.game_info.rom_crc32,
+            rom_country: self.game_info.rom_country,
+            video_plugin: self.plugin_info.video_plugin.into(),
+            sound_plugin: self.plugin_info.sound_plugin.into(),
+            input_plugin: self.plugin_info.input_plugin.into(),
+            rsp_plugin: self.plugin_info.rsp_plugin.into(),
+            author_name: self.recording_info.author_name.into(),
+            description: self.recording_info.description.into(),
+            uid: self.recording_info.uid,
+            vertical_interrupts: self.recording_info.vertical_interrupts,
+            rerecord_count: self.recording_info.rerecord_count,
+            vis_per_second: self.recording_info.vis_per_second,
+            controller_count: self.controller_count() as u8,
+            controller_input_samples: self.frame_count() as u32,
+            controller_flags: self.controller_flags,
+            start_type: self.recording_info.start_type,
+            inputs: Vec::new(),
+            reserved01: Reserved::default(),
+            reserved02: Reserved::default(),
+            reserved03: Reserved::default(),
+        }
+    }
+
     /// Returns an iterator over the controller states. Each iteration yields an iterator
     /// containing the states of all controllers for that frame.
     ///
@@ -306,8 +571,306 @@ impl Movie {
     pub fn controller_inputs_stream(
         &self,
     ) -> impl Iterator<Item = impl Iterator<Item = &ControllerState>> {
-        self.inputs
-            .chunks(self.recording_info.controller_count as usize)
-            .map(move |chunk| chunk.iter())
+        self.frames().map(|frame| frame.iter())
+    }
+
+    /// Returns an iterator over the frames of the movie. Each frame holds one
+    /// controller state per controller.
+    pub fn frames(&self) -> impl Iterator<Item = &[ControllerState]> {
+        self.inputs.chunks(self.controller_count().max(1))
+    }
+
+    /// Returns all controller states of the movie, interleaved by controller.
+    pub fn samples(&self) -> &[ControllerState] {
+        &self.inputs
+    }
+
+    /// Returns the flags indicating the presence and capabilities of controllers.
+    pub fn controller_flags(&self) -> ControllerFlags {
+        self.controller_flags
+    }
+
+    /// Replaces the controller flags.
+    ///
+    /// Changing which controllers are present changes the shape of every frame,
+    /// so it is only allowed while the movie has no frames. Memory and rumble
+    /// pack flags can always be changed.
+    pub fn set_controller_flags(&mut self, flags: ControllerFlags) -> Result<(), MovieError> {
+        let present = flags.num_controllers_present() as usize;
+        if present != self.controller_count() && !self.inputs.is_empty() {
+            return Err(MovieParseError::ControllerCountMismatch {
+                declared: self.controller_count() as u8,
+                present: present as u8,
+            }
+            .into());
+        }
+
+        self.controller_flags = flags;
+        Ok(())
+    }
+
+    /// Returns the number of controllers in each frame.
+    pub fn controller_count(&self) -> usize {
+        self.controller_flags.num_controllers_present() as usize
+    }
+
+    /// Returns the number of frames in the movie. Each frame holds one sample per controller.
+    pub fn frame_count(&self) -> usize {
+        match self.controller_count() {
+            0 => 0,
+            count => self.inputs.len() / count,
+        }
+    }
+
+    /// Returns the controller states of a frame, one per controller.
+    pub fn frame(&self, frame: usize) -> Option<&[ControllerState]> {
+        let samples = self.frame_samples(frame)?;
+        self.inputs.get(samples)
+    }
+
+    /// Returns a mutable reference to the controller states of a frame.
+    pub fn frame_mut(&mut self, frame: usize) -> Option<&mut [ControllerState]> {
+        let samples = self.frame_samples(frame)?;
+        self.inputs.get_mut(samples)
+    }
+
+    /// Returns the range of samples holding a frame, or `None` if the movie has no
+    /// controllers or the range does not fit in a `usize`.
+    fn frame_samples(&self, frame: usize) -> Option<Range<usize>> {
+        let count = self.controller_count();
+        if count == 0 {
+            return None;
+        }
+
+        let start = frame.checked_mul(count)?;
+        Some(start..start.checked_add(count)?)
+    }
+
+    /// Returns the state of a single controller in a frame.
+    pub fn port_state(&self, frame: usize, port: usize) -> Option<&ControllerState> {
+        self.frame(frame)?.get(port)
+    }
+
+    /// Returns a mutable reference to the state of a single controller in a frame.
+    pub fn port_state_mut(&mut self, frame: usize, port: usize) -> Option<&mut ControllerState> {
+        self.frame_mut(frame)?.get_mut(port)
+    }
+
+    /// Inserts frames before frame `at`. The samples are interleaved by controller,
+    /// so their number must be a multiple of the controller count.
+    ///
+    /// Annotations, markers and lag frames at or after `at` move with their frames.
+    pub fn insert_frames(
+        &mut self,
+        at: usize,
+        samples: &[ControllerState],
+    ) -> Result<(), MovieError> {
+        let count = self.controller_count();
+        self.check_frame_index(at)?;
+        self.check_whole_frames(samples.len())?;
+
+        let index = at * count;
+        self.inputs.splice(index..index, samples.iter().copied());
+
+        let frames = samples.len() / count;
+        self.remap_frame_data(|frame| match frame {
+            frame if frame >= at => frame.checked_add(frames),
+            frame => Some(frame),
+        });
+        Ok(())
+    }
+
+    /// Inserts `frames` frames with all controllers in their neutral state before frame `at`.
+    pub fn insert_neutral_frame_run(&mut self, at: usize, frames: usize) -> Result<(), MovieError> {
+        let samples = vec![ControllerState::default(); frames * self.controller_count()];
+        self.insert_frames(at, &samples)
+    }
+
+    /// Inserts a neutral frame before each of the given frames, in a single pass.
+    ///
+    /// Frame indices refer to the movie before any insertion, and may be given in any
+    /// order. A frame listed several times gets as many neutral frames before it.
+    /// Fails without changing the movie if a frame is past the end of the movie, or if
+    /// the movie has no controllers.
+    ///
+    /// Annotations, markers and lag frames move with their frames.
+    pub fn insert_neutral_frames(&mut self, at_frames: &[FrameIndex]) -> Result<(), MovieError> {
+        let count = self.controller_count();
+        self.check_whole_frames(at_frames.len() * count)?;
+        for &at in at_frames {
+            self.check_frame_index(at)?;
+        }
+
+        let mut at_frames = at_frames.to_vec();
+        at_frames.sort_unstable();
+
+        let old = std::mem::take(&mut self.inputs);
+        self.inputs = Vec::with_capacity(old.len() + at_frames.len() * count);
+
+        let mut copied = 0;
+        for &at in &at_frames {
+            self.inputs.extend_from_slice(&old[copied..at * count]);
+            self.inputs
+                .extend(std::iter::repeat_n(ControllerState::default(), count));
+            copied = at * count;
+        }
+        self.inputs.extend_from_slice(&old[copied..]);
+
+        self.remap_frame_data(|frame| {
+            frame.checked_add(at_frames.partition_point(|&at| at <= frame))
+        });
+        Ok(())
+    }
+
+    /// Removes a range of frames, returning the removed samples interleaved by controller.
+    ///
+    /// Annotations, markers and lag frames after the range move with their frames, and
+    /// those within the range are dropped.
+    pub fn remove_frames(
+        &mut self,
+        range: Range<usize>,
+    ) -> Result<Vec<ControllerState>, MovieError> {
+        let samples = self.check_frame_range(&range)?;
+        let removed = self.inputs.drain(samples).collect();
+
+        self.remap_frame_data(|frame| match frame {
+            frame if frame < range.start => Some(frame),
+            frame if frame >= range.end => Some(frame - range.len()),
+            _ => None,
+        });
+        Ok(removed)
+    }
+
+    /// Returns an error if `frame` is past the end of the movie. The end itself is valid.
+    pub(crate) fn check_frame_index(&self, frame: usize) -> Result<(), FrameError> {
+        let frame_count = self.frame_count();
+        if frame > frame_count {
+            return Err(FrameError::OutOfBounds { frame, frame_count });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the range of samples holding a range of frames, or an error if the range
+    /// starts after it ends or extends past the end of the movie.
+    pub(crate) fn check_frame_range(
+        &self,
+        range: &Range<usize>,
+    ) -> Result<Range<usize>, FrameError> {
+        if range.start > range.end {
+            return Err(FrameError::InvalidRange {
+                start: range.start,
+                end: range.end,
+            });
+        }
+        self.check_frame_index(range.end)?;
+
+        let count = self.controller_count();
+        let overflow = || FrameError::OutOfBounds {
+            frame: range.end,
+            frame_count: self.frame_count(),
+        };
+        let start = range.start.checked_mul(count).ok_or_else(overflow)?;
+        let end = range.end.checked_mul(count).ok_or_else(overflow)?;
+        Ok(start..end)
+    }
+
+    /// Returns an error if `port` is not a controller of the movie.
+    pub(crate) fn check_port(&self, port: usize) -> Result<(), FrameError> {
+        let controller_count = self.controller_count();
+        if port >= controller_count {
+            return Err(FrameError::PortOutOfBounds {
+                port,
+                controller_count,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if `samples` does not make up a whole number of frames.
+    fn check_whole_frames(&self, samples: usize) -> Result<(), FrameError> {
+        let controller_count = self.controller_count();
+        if controller_count == 0 || !samples.is_multiple_of(controller_count) {
+            return Err(FrameError::PartialFrame {
+                samples,
+                controller_count,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the notes attached to frames of the movie.
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// Returns a mutable reference to the notes attached to frames of the movie.
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        &mut self.annotations
+    }
+
+    /// Returns the named frames marking points of progress in the movie.
+    pub fn markers(&self) -> &Markers {
+        &self.markers
+    }
+
+    /// Returns a mutable reference to the named frames marking points of progress in the movie.
+    pub fn markers_mut(&mut self) -> &mut Markers {
+        &mut self.markers
+    }
+
+    /// Returns the lag frames of the movie, if known.
+    pub fn lag_mask(&self) -> Option<&LagMask> {
+        self.lag_mask.as_ref()
+    }
+
+    /// Sets or clears the lag frames of the movie.
+    pub fn set_lag_mask(&mut self, lag_mask: Option<LagMask>) {
+        self.lag_mask = lag_mask;
+    }
+
+    /// Moves the annotations, markers and lag frames of the movie to the frames given
+    /// by `map`, dropping those for which it returns `None`.
+    pub(crate) fn remap_frame_data(&mut self, map: impl Fn(FrameIndex) -> Option<FrameIndex>) {
+        self.annotations = self
+            .annotations
+            .iter()
+            .filter_map(|(frame, note)| Some((map(frame)?, note)))
+            .collect();
+        self.markers = self
+            .markers
+            .iter()
+            .filter_map(|marker| Some((marker.label, map(marker.frame)?)))
+            .collect();
+        self.lag_mask = self
+            .lag_mask
+            .as_ref()
+            .map(|lag_mask| lag_mask.iter().filter_map(&map).collect());
+    }
+
+    /// Loads the data persisted in a [`Sidecar`] file into the movie, rejecting lag
+    /// frames past the end of the movie.
+    pub fn load_sidecar<P: AsRef<Path>>(&mut self, path: P) -> Result<(), MovieError> {
+        let sidecar = Sidecar::parse_bounded(&fs::read_to_string(path)?, self.frame_count())?;
+        self.annotations = sidecar.annotations;
+        self.markers = sidecar.markers;
+        self.lag_mask = sidecar.lag_mask;
+        Ok(())
+    }
+
+    /// Saves the data that cannot be stored in the M64 format to a [`Sidecar`] file.
+    pub fn save_sidecar<P: AsRef<Path>>(&self, path: P) -> Result<(), MovieError> {
+        self.sidecar().to_file(path)
+    }
+
+    /// Returns the data that cannot be stored in the M64 format as a [`Sidecar`].
+    pub fn sidecar(&self) -> Sidecar {
+        Sidecar {
+            annotations: self.annotations.clone(),
+            markers: self.markers.clone(),
+            lag_mask: self.lag_mask.clone(),
+        }
     }
 }
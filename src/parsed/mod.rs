@@ -1,28 +1,259 @@
 //! Parsed movie data structures guaranteed to be valid Mupen64 movie files.
 
-use std::path::Path;
+use std::{
+    fs::File,
+    io::{BufRead, BufWriter, Cursor, ErrorKind, Read, Seek, Write},
+    path::Path,
+};
 
+use binrw::{BinRead, BinResult, BinWrite, Endian, VecArgs};
+
+#[doc(hidden)]
+pub mod concat;
 #[doc(hidden)]
 pub mod m64;
+#[doc(hidden)]
+pub mod source;
+#[doc(hidden)]
+pub mod write;
 
 pub use crate::raw::{ControllerFlags, ControllerState, MovieStartType};
 
+#[doc(inline)]
+pub use concat::*;
 #[doc(inline)]
 pub use m64::*;
+#[doc(inline)]
+pub use source::*;
+#[doc(inline)]
+pub use write::*;
+
+use crate::{
+    BinReadExt, BinWriteExt, MovieError, MovieParseError,
+    raw::m64::{HEADER_LEN, RawMovie, SAMPLE_LEN},
+};
 
-use crate::{BinReadExt, BinWriteExt, MovieError, raw::m64::RawMovie};
+/// The number of samples encoded per write by [`Movie::write_stream`].
+const STREAM_CHUNK_SAMPLES: usize = 1024;
 
 impl BinReadExt for Movie {
     type Error = MovieError;
 
     fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
         let raw_movie = RawMovie::from_bytes(bytes)?;
-        Movie::try_from(raw_movie)
+        Movie::try_from(raw_movie).map_err(|err| match err {
+            // Report the actual length of the input section, including any
+            // trailing partial sample.
+            MovieError::MovieParseError(MovieParseError::InputSampleMismatch {
+                expected,
+                found,
+                ..
+            }) => MovieParseError::InputSampleMismatch {
+                expected,
+                found,
+                input_bytes: bytes.len().saturating_sub(HEADER_LEN),
+            }
+            .into(),
+            err => err,
+        })
     }
 
     fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Self::Error> {
-        let raw_movie = RawMovie::from_file(path)?;
-        Movie::try_from(raw_movie)
+        Movie::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+impl Movie {
+    /// Writes the movie in the M64 format.
+    ///
+    /// The header is written first, followed by the inputs straight from the movie,
+    /// so no copy of the inputs is made.
+    pub fn write_to<W: Write + Seek>(&self, writer: &mut W) -> Result<(), MovieError> {
+        self.write_le(writer)?;
+        Ok(())
+    }
+
+    /// Writes the movie in the M64 format to a writer that need not be seekable, such
+    /// as a socket or standard output.
+    ///
+    /// Every header counter is computed from the movie before anything is written,
+    /// so the output is produced in a single forward pass.
+    pub fn write_stream<W: Write>(&self, writer: W) -> Result<(), MovieError> {
+        self.write_stream_with(writer, WriteOptions::default())
+    }
+
+    /// Reads a movie in the M64 format from a reader that need not be seekable, such
+    /// as a pipe or a network stream.
+    ///
+    /// The header is consumed first, followed by the inputs until the end of the
+    /// stream, so this accepts exactly what [`BinReadExt::from_bytes`] does without
+    /// buffering the whole file.
+    pub fn from_reader<R: BufRead>(mut reader: R) -> Result<Movie, MovieError> {
+        let mut header = vec![0; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        let mut raw = RawMovie::read_le(&mut Cursor::new(header))?;
+
+        let mut sample = [0; SAMPLE_LEN];
+        let mut filled = 0;
+        let mut input_bytes = 0;
+        loop {
+            let buf = match reader.fill_buf() {
+                Ok([]) => break,
+                Ok(buf) => buf,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            let len = buf.len();
+            for &byte in buf {
+                sample[filled] = byte;
+                filled += 1;
+                if filled == SAMPLE_LEN {
+                    raw.inputs
+                        .push(ControllerState::from(u32::from_le_bytes(sample)));
+                    filled = 0;
+                }
+            }
+
+            input_bytes += len;
+            reader.consume(len);
+        }
+
+        Movie::try_from(raw).map_err(|err| match err {
+            MovieError::MovieParseError(MovieParseError::InputSampleMismatch {
+                expected,
+                found,
+                ..
+            }) => MovieParseError::InputSampleMismatch {
+                expected,
+                found,
+                input_bytes,
+            }
+            .into(),
+            err => err,
+        })
+    }
+
+    /// Returns the number of bytes the movie is encoded to: the 1024-byte header
+    /// followed by 4 bytes per controller input sample.
+    pub fn serialized_len(&self) -> usize {
+        HEADER_LEN + SAMPLE_LEN * self.inputs.len()
+    }
+
+    /// Returns a reader producing the movie in the M64 format.
+    ///
+    /// Only the header is encoded up front. The inputs are encoded as they are read,
+    /// so the movie can be streamed without allocating its full serialized form.
+    pub fn byte_reader(&self) -> impl Read + '_ {
+        let (header, error) = match self.header_bytes() {
+            Ok(header) => (header, None),
+            Err(err) => (Vec::new(), Some(err)),
+        };
+
+        ByteReader {
+            header,
+            error,
+            inputs: &self.inputs,
+            position: 0,
+        }
+    }
+
+    /// Encodes the M64 header of the movie.
+    pub(crate) fn header_bytes(&self) -> BinResult<Vec<u8>> {
+        let mut header = Cursor::new(Vec::with_capacity(HEADER_LEN));
+        self.modeled_header().write_le(&mut header)?;
+        Ok(header.into_inner())
+    }
+}
+
+/// A reader encoding a movie as it is read. See [`Movie::byte_reader`].
+struct ByteReader<'a> {
+    /// The encoded header.
+    header: Vec<u8>,
+    /// The error raised while encoding the header, reported on the first read.
+    error: Option<binrw::Error>,
+    /// The inputs, encoded as they are read.
+    inputs: &'a [ControllerState],
+    /// The number of bytes read so far.
+    position: usize,
+}
+
+impl Read for ByteReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(err) = self.error.take() {
+            return Err(std::io::Error::other(err));
+        }
+
+        let mut written = 0;
+        while written < buf.len() {
+            let chunk = if self.position < self.header.len() {
+                &self.header[self.position..]
+            } else {
+                let offset = self.position - self.header.len();
+                let Some(state) = self.inputs.get(offset / SAMPLE_LEN) else {
+                    break;
+                };
+
+                &u32::from(*state).to_le_bytes()[offset % SAMPLE_LEN..]
+            };
+
+            let len = chunk.len().min(buf.len() - written);
+            buf[written..written + len].copy_from_slice(&chunk[..len]);
+            written += len;
+            self.position += len;
+        }
+
+        Ok(written)
+    }
+}
+
+/// Reads a movie, possibly embedded in a larger stream.
+///
+/// Unlike [`BinReadExt`], which reads inputs until the end of the data, this reads
+/// exactly the number of samples declared in the header, leaving the reader
+/// positioned after the movie. M64 files are always little-endian, so the endianness
+/// argument is ignored.
+impl BinRead for Movie {
+    type Args<'a> = ();
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        _endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let pos = reader.stream_position()?;
+
+        let mut header = vec![0; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        let mut raw = RawMovie::read_le(&mut Cursor::new(header))?;
+
+        let count = raw.controller_count as usize * raw.controller_input_samples as usize;
+        raw.inputs = Vec::read_le_args(reader, VecArgs::builder().count(count).finalize())?;
+
+        Movie::try_from(raw).map_err(|err| binrw::Error::Custom {
+            pos,
+            err: Box::new(err),
+        })
+    }
+}
+
+/// Writes the header, followed by the inputs straight from the movie.
+///
+/// M64 files are always little-endian, so the endianness argument is ignored.
+impl BinWrite for Movie {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        _endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        // The header pads itself to an absolute offset, so it is encoded on its own
+        // for the movie to be embeddable at any position.
+        writer.write_all(&self.header_bytes()?)?;
+
+        self.inputs.write_le(writer)
     }
 }
 
@@ -30,12 +261,15 @@ impl BinWriteExt for Movie {
     type Error = MovieError;
 
     fn to_bytes(&self) -> Result<Vec<u8>, Self::Error> {
-        let raw_movie: RawMovie = self.clone().into_raw();
-        raw_movie.to_bytes()
+        let mut cursor = Cursor::new(Vec::with_capacity(self.serialized_len()));
+        self.write_to(&mut cursor)?;
+        Ok(cursor.into_inner())
     }
 
     fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Self::Error> {
-        let raw_movie: RawMovie = self.clone().into_raw();
-        raw_movie.to_file(path)
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
     }
 }
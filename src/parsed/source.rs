@@ -0,0 +1,197 @@
+//! Retaining the bytes a [`Movie`] was parsed from.
+
+use std::{fs, io::Cursor, path::Path};
+
+use binrw::BinRead;
+
+use crate::{
+    BinReadExt, BinWriteExt, MovieError,
+    hash::fnv1a,
+    layout::HEADER_FIELDS,
+    parsed::Movie,
+    raw::{RawHeader, m64::HEADER_LEN},
+};
+
+/// Options controlling how a [`Movie`] is parsed.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct ParseOptions {
+    /// Keep the original header bytes and a hash of the whole file, see [`Source`].
+    pub retain_source: bool,
+    /// Read only the samples the header declares, and keep the bytes after them as
+    /// [`Movie::trailing_data`] instead of rejecting the file.
+    pub retain_trailing_data: bool,
+}
+
+impl ParseOptions {
+    /// Returns the options with [`ParseOptions::retain_source`] set.
+    pub fn retain_source(mut self, retain_source: bool) -> Self {
+        self.retain_source = retain_source;
+        self
+    }
+
+    /// Returns the options with [`ParseOptions::retain_trailing_data`] set.
+    pub fn retain_trailing_data(mut self, retain_trailing_data: bool) -> Self {
+        self.retain_trailing_data = retain_trailing_data;
+        self
+    }
+}
+
+/// The original bytes of a parsed movie.
+///
+/// Only the 1024-byte header is kept. The inputs are covered by a 64-bit FNV-1a
+/// hash of the whole file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Source {
+    /// The original header bytes.
+    header: Vec<u8>,
+    /// The header as this crate encoded it right after parsing. Bytes that still
+    /// encode the same way have not been edited.
+    encoded_header: Vec<u8>,
+    /// The length of the original file.
+    len: usize,
+    /// The 64-bit FNV-1a hash of the original file.
+    hash: u64,
+}
+
+impl Source {
+    /// Returns the original header bytes.
+    pub fn header(&self) -> &[u8] {
+        &self.header
+    }
+
+    /// Returns the length of the original file in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the original file was empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the 64-bit FNV-1a hash of the original file.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl Movie {
+    /// Parses a movie from bytes with the given options.
+    pub fn from_bytes_with(bytes: &[u8], options: ParseOptions) -> Result<Self, MovieError> {
+        let mut movie = if options.retain_trailing_data {
+            let (mut movie, trailing_data) = Movie::from_bytes_prefix(bytes)?;
+            movie.trailing_data = trailing_data.to_vec();
+            movie
+        } else {
+            Movie::from_bytes(bytes)?
+        };
+
+        if options.retain_source {
+            movie.source = Some(Source {
+                header: bytes[..HEADER_LEN.min(bytes.len())].to_vec(),
+                encoded_header: movie.header_bytes()?,
+                len: bytes.len(),
+                hash: fnv1a(bytes),
+            });
+        }
+
+        Ok(movie)
+    }
+
+    /// Reads a movie from a file with the given options.
+    pub fn from_file_with<P: AsRef<Path>>(
+        path: P,
+        options: ParseOptions,
+    ) -> Result<Self, MovieError> {
+        Movie::from_bytes_with(&fs::read(path)?, options)
+    }
+
+    /// Returns the original bytes of the movie, if it was parsed with
+    /// [`ParseOptions::retain_source`].
+    pub fn source(&self) -> Option<&Source> {
+        self.source.as_ref()
+    }
+
+    /// Returns whether [`Movie::to_bytes_preserving_source`] would produce different
+    /// bytes than the file the movie was parsed from, or `None` if the source was not
+    /// retained.
+    pub fn is_modified(&self) -> Option<bool> {
+        let source = self.source.as_ref()?;
+        Some(match self.to_bytes_preserving_source() {
+            Ok(bytes) => bytes.len() != source.len || fnv1a(&bytes) != source.hash,
+            Err(_) => true,
+        })
+    }
+
+    /// Encodes the movie, restoring the original bytes of every header byte that
+    /// was not edited since parsing.
+    ///
+    /// This keeps data this crate does not model, such as non-zero reserved regions
+    /// or bytes after a string's terminator, intact, and appends the
+    /// [trailing data](Movie::trailing_data). Without a retained source or trailing
+    /// data, this is the same as [`BinWriteExt::to_bytes`].
+    pub fn to_bytes_preserving_source(&self) -> Result<Vec<u8>, MovieError> {
+        let mut bytes = self.to_bytes()?;
+        self.restore_source(&mut bytes);
+        bytes.extend_from_slice(&self.trailing_data);
+        Ok(bytes)
+    }
+
+    /// Returns the bytes found after the inputs, if the movie was parsed with
+    /// [`ParseOptions::retain_trailing_data`]. Empty otherwise.
+    ///
+    /// They are only written with [`WriteOptions::trailing_data`](super::WriteOptions::trailing_data)
+    /// or by [`Movie::to_bytes_preserving_source`].
+    pub fn trailing_data(&self) -> &[u8] {
+        &self.trailing_data
+    }
+
+    /// Replaces the bytes written after the inputs.
+    pub fn set_trailing_data(&mut self, trailing_data: Vec<u8>) {
+        self.trailing_data = trailing_data;
+    }
+
+    /// Returns the header of the movie as a [`RawMovie`] without inputs, with the
+    /// exact raw values of every field.
+    ///
+    /// If the source was retained, header bytes that were not edited since parsing
+    /// keep their original values, including reserved regions and string padding, as
+    /// written by [`Movie::to_bytes_preserving_source`]. Otherwise, the reserved
+    /// regions are zeroed.
+    pub fn raw_header(&self) -> RawHeader {
+        let header = self.modeled_header().header();
+        if self.source.is_none() {
+            return header;
+        }
+
+        let Ok(mut bytes) = self.header_bytes() else {
+            return header;
+        };
+        self.restore_source(&mut bytes);
+
+        RawHeader::read_le(&mut Cursor::new(bytes)).unwrap_or(header)
+    }
+
+    /// Restores the original bytes of every header field that was not edited since
+    /// parsing, if the source was retained.
+    ///
+    /// A field is restored whole, and only if its whole encoding is unchanged, so the
+    /// original bytes of an edited field never leak into its new value.
+    pub(crate) fn restore_source(&self, bytes: &mut [u8]) {
+        let Some(source) = &self.source else {
+            return;
+        };
+
+        for field in HEADER_FIELDS {
+            let range = field.range();
+            if let (Some(current), Some(encoded), Some(original)) = (
+                bytes.get(range.clone()),
+                source.encoded_header.get(range.clone()),
+                source.header.get(range.clone()),
+            ) && current == encoded
+            {
+                bytes[range].copy_from_slice(original);
+            }
+        }
+    }
+}
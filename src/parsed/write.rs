@@ -0,0 +1,238 @@
+//! Options controlling how a [`Movie`] is written.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Cursor, Write},
+    ops::Range,
+    path::Path,
+};
+
+use binrw::BinWrite;
+
+use crate::{
+    MovieError, WriteError,
+    counters::check_frame_count,
+    layout::HEADER_FIELDS,
+    parsed::Movie,
+    raw::m64::{M64_MAGIC, RawMovie, SAMPLE_LEN, SUPPORTED_VERSION},
+    shared::{Padding, Reserved},
+};
+
+/// The range of the version in the header.
+const VERSION_RANGE: Range<usize> = 0x004..0x008;
+
+/// How the reserved regions of the header are written.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum ReservedRegions {
+    /// Every reserved byte is written as zero, as the format requires.
+    #[default]
+    Zeroed,
+    /// The reserved regions hold the [`LegacyData`](super::LegacyData) of the movie,
+    /// if any. Regions of the wrong length are written as zero.
+    Preserved,
+}
+
+/// Options controlling how a [`Movie`] is written.
+///
+/// The default options write what [`BinWriteExt::to_bytes`](crate::BinWriteExt::to_bytes)
+/// does: only version 3 is written, the reserved regions are zeroed, the encoded
+/// header is not checked, no trailing data is written, and string fields keep the
+/// padding they were parsed with.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WriteOptions {
+    /// Decode the counters of the encoded header as little-endian and check that they
+    /// match the movie, along with the magic bytes.
+    pub check_encoding: bool,
+    /// Refuse to write a movie whose version is not 3, the only version this crate
+    /// supports. Otherwise, the version is written as is, and the rest of the header
+    /// is laid out as in version 3.
+    pub require_version_3: bool,
+    /// How the reserved regions of the header are written.
+    pub reserved: ReservedRegions,
+    /// Write the [trailing data](Movie::trailing_data) of the movie after the inputs.
+    pub trailing_data: bool,
+    /// Pad every string field with the given padding. Otherwise, each field keeps the
+    /// [padding](crate::shared::EncodedFixedStr::padding) it was parsed with.
+    pub padding: Option<Padding>,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            check_encoding: false,
+            require_version_3: true,
+            reserved: ReservedRegions::default(),
+            trailing_data: false,
+            padding: None,
+        }
+    }
+}
+
+impl WriteOptions {
+    /// Returns the strictest options: the encoding is checked and only version 3 is
+    /// written. The reserved regions are zeroed.
+    pub fn strict() -> Self {
+        WriteOptions::default().check_encoding(true)
+    }
+
+    /// Returns the options with [`WriteOptions::check_encoding`] set.
+    pub fn check_encoding(mut self, check_encoding: bool) -> Self {
+        self.check_encoding = check_encoding;
+        self
+    }
+
+    /// Returns the options with [`WriteOptions::require_version_3`] set.
+    pub fn require_version_3(mut self, require_version_3: bool) -> Self {
+        self.require_version_3 = require_version_3;
+        self
+    }
+
+    /// Returns the options with [`WriteOptions::reserved`] set.
+    pub fn reserved(mut self, reserved: ReservedRegions) -> Self {
+        self.reserved = reserved;
+        self
+    }
+
+    /// Returns the options with [`WriteOptions::trailing_data`] set.
+    pub fn trailing_data(mut self, trailing_data: bool) -> Self {
+        self.trailing_data = trailing_data;
+        self
+    }
+
+    /// Returns the options with [`WriteOptions::padding`] set.
+    pub fn padding(mut self, padding: Option<Padding>) -> Self {
+        self.padding = padding;
+        self
+    }
+}
+
+impl Movie {
+    /// Encodes the movie with the given options.
+    pub fn to_bytes_with(&self, options: WriteOptions) -> Result<Vec<u8>, MovieError> {
+        let mut bytes = Vec::with_capacity(self.serialized_len());
+        self.write_stream_with(&mut bytes, options)?;
+        Ok(bytes)
+    }
+
+    /// Writes the movie to a file with the given options.
+    pub fn to_file_with<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: WriteOptions,
+    ) -> Result<(), MovieError> {
+        self.write_stream_with(BufWriter::new(File::create(path)?), options)
+    }
+
+    /// Writes the movie to a writer that need not be seekable with the given options.
+    /// See [`Movie::write_stream`].
+    ///
+    /// Nothing is written if the options reject the movie.
+    pub fn write_stream_with<W: Write>(
+        &self,
+        mut writer: W,
+        options: WriteOptions,
+    ) -> Result<(), MovieError> {
+        writer.write_all(&self.header_bytes_with(options)?)?;
+
+        let mut chunk = Vec::with_capacity(SAMPLE_LEN * super::STREAM_CHUNK_SAMPLES);
+        for states in self.inputs.chunks(super::STREAM_CHUNK_SAMPLES) {
+            chunk.clear();
+            for &state in states {
+                chunk.extend_from_slice(&u32::from(state).to_le_bytes());
+            }
+            writer.write_all(&chunk)?;
+        }
+
+        if options.trailing_data {
+            writer.write_all(&self.trailing_data)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Encodes the M64 header of the movie with the given options.
+    pub(crate) fn header_bytes_with(&self, options: WriteOptions) -> Result<Vec<u8>, MovieError> {
+        if options.require_version_3 && self.metadata.version != 3 {
+            return Err(WriteError::UnsupportedVersion(self.metadata.version).into());
+        }
+        check_frame_count(self.frame_count() as u64)?;
+
+        let mut header = self.modeled_header();
+        // The raw header only encodes version 3, so other versions are patched in
+        // after encoding.
+        header.version = SUPPORTED_VERSION;
+        if options.reserved == ReservedRegions::Preserved
+            && let Some(legacy) = &self.metadata.legacy
+        {
+            header.reserved01 = region(legacy.reserved01.as_deref());
+            header.reserved02 = region(legacy.reserved02.as_deref());
+            header.reserved03 = region(legacy.reserved03.as_deref());
+        }
+        if let Some(padding) = options.padding {
+            header.rom_name = self.game_info.rom_name.to_raw(padding);
+            header.video_plugin = self.plugin_info.video_plugin.to_raw(padding);
+            header.sound_plugin = self.plugin_info.sound_plugin.to_raw(padding);
+            header.input_plugin = self.plugin_info.input_plugin.to_raw(padding);
+            header.rsp_plugin = self.plugin_info.rsp_plugin.to_raw(padding);
+            header.author_name = self.recording_info.author_name.to_raw(padding);
+            header.description = self.recording_info.description.to_raw(padding);
+        }
+
+        let mut bytes = Cursor::new(Vec::new());
+        header.write_le(&mut bytes)?;
+        let mut bytes = bytes.into_inner();
+        header.version = self.metadata.version;
+        bytes[VERSION_RANGE].copy_from_slice(&header.version.to_le_bytes());
+
+        if options.check_encoding {
+            check_encoding(&header, &bytes)?;
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// Creates a reserved region from legacy bytes, zeroed if they are missing or of the
+/// wrong length.
+fn region<const N: usize>(bytes: Option<&[u8]>) -> Reserved<N> {
+    bytes
+        .and_then(|bytes| <[u8; N]>::try_from(bytes).ok())
+        .map(Reserved::new)
+        .unwrap_or_default()
+}
+
+/// Checks that the magic bytes and the little-endian counters of an encoded header
+/// match the header it was encoded from.
+fn check_encoding(header: &RawMovie, bytes: &[u8]) -> Result<(), WriteError> {
+    let counters = [
+        ("magic", u32::from_le_bytes(M64_MAGIC)),
+        ("version", header.version),
+        ("uid", header.uid),
+        ("vertical_interrupts", header.vertical_interrupts),
+        ("rerecord_count", header.rerecord_count),
+        ("controller_input_samples", header.controller_input_samples),
+        ("rom_crc32", header.rom_crc32),
+    ];
+
+    for (name, expected) in counters {
+        let field = HEADER_FIELDS
+            .iter()
+            .find(|field| field.name == name)
+            .expect("every counter is a header field");
+        let found = bytes
+            .get(field.range())
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u32::from_le_bytes);
+
+        if found != Some(expected) {
+            return Err(WriteError::EncodingMismatch {
+                field: name,
+                expected,
+                found: found.unwrap_or_default(),
+            });
+        }
+    }
+
+    Ok(())
+}
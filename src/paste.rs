@@ -0,0 +1,97 @@
+//! Pasting blocks of frames copied as text.
+//!
+//! [`Movie::paste_frames`] accepts the text formats produced by the exporters of this
+//! crate, so frames can be copied from one movie and pasted into another through the
+//! clipboard:
+//!
+//! - BizHawk input log lines, see [`bizhawk`](crate::convert::bizhawk);
+//! - hexadecimal blocks, see [`hex`](crate::convert::hex).
+//!
+//! The format is detected from the first line holding inputs.
+
+use crate::{
+    FrameError, Movie, MovieError,
+    convert::{bizhawk::InputLogLayout, hex::parse_hex_block},
+    raw::ControllerState,
+    track::FrameIndex,
+};
+
+/// How pasted frames are placed in a movie.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum PasteMode {
+    /// Replace the frames from the paste position on, extending the movie if needed.
+    #[default]
+    Overwrite,
+    /// Insert the frames before the paste position.
+    Insert,
+}
+
+/// Parses a block of frames copied as text, returning the samples interleaved by
+/// controller.
+///
+/// The frame indices of hexadecimal blocks are ignored: frames are returned in the
+/// order they are listed.
+pub fn parse_paste_block(
+    text: &str,
+    controller_count: usize,
+) -> Result<Vec<ControllerState>, MovieError> {
+    let is_input_log = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| line.starts_with('|') || line.starts_with('['));
+
+    if is_input_log {
+        return InputLogLayout::n64(controller_count).parse(text);
+    }
+
+    let mut samples = Vec::new();
+    for (_, frame) in parse_hex_block(text)? {
+        if frame.len() != controller_count {
+            return Err(FrameError::PartialFrame {
+                samples: frame.len(),
+                controller_count,
+            }
+            .into());
+        }
+        samples.extend(frame);
+    }
+
+    Ok(samples)
+}
+
+impl Movie {
+    /// Pastes a block of frames copied as text at frame `at`, overwriting the frames
+    /// from there on and extending the movie if needed. Returns the number of frames
+    /// pasted.
+    pub fn paste_frames(&mut self, at: FrameIndex, text: &str) -> Result<usize, MovieError> {
+        self.paste_frames_with(at, text, PasteMode::Overwrite)
+    }
+
+    /// Pastes a block of frames copied as text at frame `at`, placing them as given
+    /// by `mode`. Returns the number of frames pasted.
+    ///
+    /// Nothing is changed if the text cannot be parsed or `at` is past the end of
+    /// the movie.
+    pub fn paste_frames_with(
+        &mut self,
+        at: FrameIndex,
+        text: &str,
+        mode: PasteMode,
+    ) -> Result<usize, MovieError> {
+        let count = self.controller_count();
+        let samples = parse_paste_block(text, count)?;
+        self.check_frame_index(at)?;
+
+        match mode {
+            PasteMode::Overwrite => {
+                let start = at * count;
+                let end = (start + samples.len()).min(self.inputs.len());
+                self.inputs.splice(start..end, samples.iter().copied());
+            }
+            PasteMode::Insert => self.insert_frames(at, &samples)?,
+        }
+
+        Ok(samples.len() / count.max(1))
+    }
+}
@@ -0,0 +1,114 @@
+//! Patching of encoded movies at raw byte offsets.
+//!
+//! [`apply`] writes a set of byte patches into an encoded movie, checking that every
+//! patch is in bounds before anything is written, and warns about patches that spill
+//! over from one header field into another. With [`PatchOptions::validate`] set, the
+//! patched bytes must also still parse as a [`Movie`].
+
+use std::fmt::{self, Display};
+
+use crate::{
+    BinReadExt, Movie, MovieError, PatchError,
+    layout::{HEADER_FIELDS, HeaderField},
+};
+
+/// Options controlling how patches are applied.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct PatchOptions {
+    /// Check that the patched bytes still parse as a [`Movie`], and undo the patches
+    /// if they do not.
+    pub validate: bool,
+}
+
+impl PatchOptions {
+    /// Returns the options with [`PatchOptions::validate`] set.
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+}
+
+/// A suspicious patch found by [`apply`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PatchWarning {
+    /// The offset of the patch.
+    pub offset: usize,
+    /// The length of the patch.
+    pub len: usize,
+    /// The header fields written by the patch, in file order.
+    pub fields: Vec<&'static HeaderField>,
+}
+
+impl Display for PatchWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.fields.iter().map(|field| field.name).collect();
+        write!(
+            f,
+            "patch of {} bytes at 0x{:03X} spans fields {}",
+            self.len,
+            self.offset,
+            names.join(", ")
+        )
+    }
+}
+
+/// Applies patches of `(offset, bytes)` to an encoded movie, returning a warning for
+/// every patch that writes to more than one header field.
+///
+/// Patches are applied in order, so later patches overwrite earlier ones. No patch is
+/// applied if any of them is out of bounds.
+pub fn apply(
+    bytes: &mut [u8],
+    patches: &[(usize, &[u8])],
+) -> Result<Vec<PatchWarning>, MovieError> {
+    apply_with(bytes, patches, PatchOptions::default())
+}
+
+/// Applies patches to an encoded movie with the given options. See [`apply`].
+pub fn apply_with(
+    bytes: &mut [u8],
+    patches: &[(usize, &[u8])],
+    options: PatchOptions,
+) -> Result<Vec<PatchWarning>, MovieError> {
+    for &(offset, patch) in patches {
+        if offset
+            .checked_add(patch.len())
+            .is_none_or(|end| end > bytes.len())
+        {
+            return Err(PatchError::OutOfBounds {
+                offset,
+                len: patch.len(),
+                size: bytes.len(),
+            }
+            .into());
+        }
+    }
+
+    let original = options.validate.then(|| bytes.to_vec());
+    let mut warnings = Vec::new();
+    for &(offset, patch) in patches {
+        bytes[offset..offset + patch.len()].copy_from_slice(patch);
+
+        let end = offset + patch.len();
+        let fields: Vec<&'static HeaderField> = HEADER_FIELDS
+            .iter()
+            .filter(|field| field.offset < end && offset < field.offset + field.len)
+            .collect();
+        if fields.len() > 1 {
+            warnings.push(PatchWarning {
+                offset,
+                len: patch.len(),
+                fields,
+            });
+        }
+    }
+
+    if let Some(original) = original
+        && let Err(err) = Movie::from_bytes(bytes)
+    {
+        bytes.copy_from_slice(&original);
+        return Err(err);
+    }
+
+    Ok(warnings)
+}
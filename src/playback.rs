@@ -0,0 +1,119 @@
+//! Stateful playback of movie inputs.
+//!
+//! A [`MovieCursor`] hands out the inputs of a movie one frame at a time, as an
+//! emulator polls them, and can jump to any frame.
+//!
+//! Frontends that take inputs either from a movie or from live controllers can be
+//! generic over [`InputProvider`], which [`MovieCursor`] implements.
+
+use crate::{FrameError, Movie, MovieError, raw::ControllerState, track::FrameIndex};
+
+/// A position in the inputs of a movie. See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct MovieCursor<'a> {
+    /// The movie being played back.
+    movie: &'a Movie,
+    /// The frame returned by the next call to [`MovieCursor::advance`].
+    frame: FrameIndex,
+}
+
+impl<'a> MovieCursor<'a> {
+    /// Creates a cursor at the first frame of a movie.
+    pub fn new(movie: &'a Movie) -> Self {
+        MovieCursor { movie, frame: 0 }
+    }
+
+    /// Returns the movie being played back.
+    pub fn movie(&self) -> &'a Movie {
+        self.movie
+    }
+
+    /// Returns the frame the cursor is at, which the next call to
+    /// [`MovieCursor::advance`] returns.
+    pub fn current_frame(&self) -> FrameIndex {
+        self.frame
+    }
+
+    /// Returns the number of frames left to play, including the current one.
+    pub fn remaining(&self) -> usize {
+        self.movie.frame_count().saturating_sub(self.frame)
+    }
+
+    /// Returns `true` if every frame has been played.
+    pub fn is_finished(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Returns the controller states of the current frame, without moving the cursor.
+    pub fn current(&self) -> Option<&'a [ControllerState]> {
+        self.movie.frame(self.frame)
+    }
+
+    /// Returns the state of a single controller in the current frame, without moving
+    /// the cursor.
+    pub fn port(&self, port: usize) -> Option<ControllerState> {
+        self.movie.port_state(self.frame, port).copied()
+    }
+
+    /// Returns the controller states of the current frame and moves to the next one.
+    /// Returns `None` once every frame has been played.
+    pub fn advance(&mut self) -> Option<&'a [ControllerState]> {
+        let states = self.current()?;
+        self.frame += 1;
+        Some(states)
+    }
+
+    /// Moves the cursor to a frame. Seeking to the frame count puts the cursor at
+    /// the end of the movie.
+    pub fn seek(&mut self, frame: FrameIndex) -> Result<(), MovieError> {
+        let frame_count = self.movie.frame_count();
+        if frame > frame_count {
+            return Err(FrameError::OutOfBounds { frame, frame_count }.into());
+        }
+
+        self.frame = frame;
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for MovieCursor<'a> {
+    type Item = &'a [ControllerState];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.advance()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+impl ExactSizeIterator for MovieCursor<'_> {}
+
+/// A source of controller inputs for an emulator.
+pub trait InputProvider {
+    /// Returns the state of a controller for the current frame.
+    fn poll(&mut self, port: usize) -> ControllerState;
+
+    /// Notifies the provider that the emulator moved on to the next frame.
+    fn frame_advanced(&mut self);
+}
+
+/// Plays back the movie. Once the movie ends, or for a port the movie does not
+/// record, the neutral state is returned.
+impl InputProvider for MovieCursor<'_> {
+    fn poll(&mut self, port: usize) -> ControllerState {
+        self.port(port).unwrap_or_default()
+    }
+
+    fn frame_advanced(&mut self) {
+        self.advance();
+    }
+}
+
+impl Movie {
+    /// Returns a cursor at the first frame of the movie.
+    pub fn cursor(&self) -> MovieCursor<'_> {
+        MovieCursor::new(self)
+    }
+}
@@ -0,0 +1,156 @@
+//! The header of a raw Mupen64 movie file, without its inputs.
+
+use std::io::{Cursor, Read, Seek, Write};
+
+use binrw::{
+    BinRead, BinResult, BinWrite, Endian,
+    meta::{EndianKind, ReadEndian, WriteEndian},
+};
+
+use crate::{
+    MovieError,
+    raw::{
+        ControllerFlags, ControllerState, ExtendedData, ExtendedFlags, MovieStartType, RawMovie,
+        m64::{HEADER_LEN, SAMPLE_LEN},
+    },
+    shared::{RawString, Reserved},
+};
+
+/// Defines [`RawHeader`] with the given header fields of [`RawMovie`], documented by
+/// the fields of [`RawMovie`], along with the conversions between the two.
+macro_rules! raw_header {
+    ($($field:ident: $type:ty,)*) => {
+        /// The 1024-byte header of a raw Mupen64 movie file, without the inputs.
+        ///
+        /// Reading a header reads exactly 1024 bytes, so metadata can be scanned from many
+        /// movies without loading their inputs. The header is validated as for a
+        /// [`RawMovie`]. See [`RawMovie::read_header_only`].
+        #[derive(Debug, Clone, Eq, PartialEq)]
+        pub struct RawHeader {
+            $(
+                #[doc = concat!("See [`RawMovie::", stringify!($field), "`].")]
+                pub $field: $type,
+            )*
+        }
+
+        impl RawHeader {
+            /// Returns a movie with this header and the given inputs.
+            pub fn with_inputs(self, inputs: Vec<ControllerState>) -> RawMovie {
+                RawMovie {
+                    $($field: self.$field,)*
+                    inputs,
+                }
+            }
+        }
+
+        impl From<&RawMovie> for RawHeader {
+            fn from(movie: &RawMovie) -> Self {
+                RawHeader {
+                    $($field: movie.$field,)*
+                }
+            }
+        }
+    };
+}
+
+raw_header! {
+    version: u32,
+    uid: u32,
+    vertical_interrupts: u32,
+    rerecord_count: u32,
+    vis_per_second: u8,
+    controller_count: u8,
+    extended_version: u8,
+    extended_flags: ExtendedFlags,
+    controller_input_samples: u32,
+    start_type: MovieStartType,
+    reserved01: Reserved<2>,
+    controller_flags: ControllerFlags,
+    extended_data: ExtendedData,
+    reserved02: Reserved<128>,
+    rom_name: RawString<32>,
+    rom_crc32: u32,
+    rom_country: u16,
+    reserved03: Reserved<56>,
+    video_plugin: RawString<64>,
+    sound_plugin: RawString<64>,
+    input_plugin: RawString<64>,
+    rsp_plugin: RawString<64>,
+    author_name: RawString<222>,
+    description: RawString<256>,
+}
+
+impl RawHeader {
+    /// Returns the number of bytes the movie is encoded to according to the header:
+    /// the 1024-byte header followed by the declared samples of every controller.
+    pub fn declared_len(&self) -> u64 {
+        let samples = self.controller_input_samples as u64
+            * self.controller_flags.num_controllers_present() as u64;
+        HEADER_LEN as u64 + SAMPLE_LEN as u64 * samples
+    }
+}
+
+impl RawMovie {
+    /// Reads the header of a movie from a reader, consuming only its first 1024 bytes.
+    ///
+    /// The reader need not be seekable.
+    pub fn read_header_only<R: Read>(mut reader: R) -> Result<RawHeader, MovieError> {
+        let mut bytes = [0; HEADER_LEN];
+        reader.read_exact(&mut bytes)?;
+        Ok(RawHeader::read_le(&mut Cursor::new(bytes))?)
+    }
+
+    /// Returns the header of the movie.
+    pub fn header(&self) -> RawHeader {
+        RawHeader::from(self)
+    }
+}
+
+impl From<RawMovie> for RawHeader {
+    fn from(movie: RawMovie) -> Self {
+        RawHeader::from(&movie)
+    }
+}
+
+/// Headers are little-endian, as the rest of the movie.
+impl ReadEndian for RawHeader {
+    const ENDIAN: EndianKind = EndianKind::Endian(Endian::Little);
+}
+
+/// Headers are little-endian, as the rest of the movie.
+impl WriteEndian for RawHeader {
+    const ENDIAN: EndianKind = EndianKind::Endian(Endian::Little);
+}
+
+impl BinRead for RawHeader {
+    type Args<'a> = ();
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let mut bytes = [0; HEADER_LEN];
+        reader.read_exact(&mut bytes)?;
+        RawMovie::read_options(&mut Cursor::new(bytes), endian, args).map(RawHeader::from)
+    }
+}
+
+impl BinWrite for RawHeader {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        // Padding is aligned relative to the start of the header, not of the stream.
+        let mut bytes = Cursor::new(Vec::with_capacity(HEADER_LEN));
+        self.clone()
+            .with_inputs(Vec::new())
+            .write_options(&mut bytes, endian, args)?;
+        writer.write_all(bytes.get_ref())?;
+        Ok(())
+    }
+}
@@ -8,9 +8,12 @@ use bilge::{
     Bitsized,
     prelude::{DebugBits, DefaultBits, FromBits, Number, bitsize, u7, u20},
 };
-use binrw::{BinRead, BinWrite, NullString, helpers::until_eof};
+use binrw::{BinRead, BinWrite, helpers::until_eof};
 
-use crate::{ControllerButton, shared::Reserved};
+use crate::{
+    ControllerButton,
+    shared::{RawString, Reserved},
+};
 
 /// Validate a non-zero value that is only considered valid if
 /// the extended version is a specific value. The result is true if the
@@ -19,6 +22,33 @@ fn valid_only_if_ext_version_eq(extended_version: u8, valid_versions: &[u8], val
     valid_versions.contains(&extended_version) || value.iter().all(&|&b| b == 0)
 }
 
+/// The magic bytes at the start of every M64 file.
+pub const M64_MAGIC: [u8; 4] = *b"M64\x1A";
+
+/// The only version of the M64 format supported by this crate.
+pub(crate) const SUPPORTED_VERSION: u32 = 3;
+
+/// Returns `true` if the bytes start with the M64 magic and a supported version.
+///
+/// Only the first 8 bytes are inspected, so this is a cheap way to classify files
+/// without parsing them. A file passing this check may still fail to parse.
+pub fn is_m64(bytes: &[u8]) -> bool {
+    match bytes.get(..8) {
+        Some(header) => {
+            header[..4] == M64_MAGIC
+                && u32::from_le_bytes([header[4], header[5], header[6], header[7]])
+                    == SUPPORTED_VERSION
+        }
+        None => false,
+    }
+}
+
+/// The size of the M64 header, after which the inputs start.
+pub(crate) const HEADER_LEN: usize = 0x400;
+
+/// The size of a single encoded controller state.
+pub(crate) const SAMPLE_LEN: usize = 4;
+
 /// A raw Mupen64 movie file.
 ///
 /// Only version 3 is supported. Please refer to the
@@ -26,7 +56,11 @@ fn valid_only_if_ext_version_eq(extended_version: u8, valid_versions: &[u8], val
 #[derive(Debug, Clone, Eq, PartialEq, BinRead, BinWrite)]
 #[brw(little, magic = b"M64\x1A")]
 #[brw(
-    assert(self.version == 3, "Only version 3 is supported. Got: {}.", version),
+    assert(
+        self.version == SUPPORTED_VERSION,
+        "Only version 3 is supported. Got: {}.",
+        version
+    ),
     assert(
         valid_only_if_ext_version_eq(
             self.extended_version,
@@ -106,11 +140,8 @@ pub struct RawMovie {
 
     /// The internal name of the ROM used in the movie. This value is taken
     /// directly from the ROM. Should be a 32-byte ASCII string.
-    #[brw(
-        pad_size_to = 32,
-        assert(rom_name.is_ascii(), "ROM name must be ASCII")
-    )]
-    pub rom_name: NullString, // 0x0C4
+    #[brw(assert(rom_name.is_ascii(), "ROM name must be ASCII"))]
+    pub rom_name: RawString<32>, // 0x0C4
 
     /// The CRC32 checksum of the ROM used in the movie. This value is taken
     /// directly from the ROM.
@@ -125,43 +156,29 @@ pub struct RawMovie {
 
     /// The name of the video plugin used in the movie. This value is
     /// taken directly from the plugin. Should be a 64-byte ASCII string.
-    #[brw(
-        pad_size_to = 64,
-        assert(video_plugin.is_ascii(), "Video plugin name must be ASCII")
-    )]
-    pub video_plugin: NullString, // 0x122
+    #[brw(assert(video_plugin.is_ascii(), "Video plugin name must be ASCII"))]
+    pub video_plugin: RawString<64>, // 0x122
 
     /// The name of the sound plugin used in the movie. This value is
     /// taken directly from the plugin. Should be 64-byte ASCII string.
-    #[brw(
-        pad_size_to = 64,
-        assert(sound_plugin.is_ascii(), "Sound plugin name must be ASCII")
-    )]
-    pub sound_plugin: NullString, // 0x162
+    #[brw(assert(sound_plugin.is_ascii(), "Sound plugin name must be ASCII"))]
+    pub sound_plugin: RawString<64>, // 0x162
 
     /// The name of the input plugin used in the movie. This value is
     /// taken directly from the plugin. Should be 64-byte ASCII string.
-    #[brw(
-        pad_size_to = 64,
-        assert(input_plugin.is_ascii(), "Input plugin name must be ASCII")
-    )]
-    pub input_plugin: NullString, // 0x1A2
+    #[brw(assert(input_plugin.is_ascii(), "Input plugin name must be ASCII"))]
+    pub input_plugin: RawString<64>, // 0x1A2
 
     /// The name of the RSP plugin used in the movie. This value is
     /// taken directly from the plugin. Should be 64-byte ASCII string.
-    #[brw(
-        pad_size_to = 64,
-        assert(rsp_plugin.is_ascii(), "RSP plugin name must be ASCII")
-    )]
-    pub rsp_plugin: NullString, // 0x1E2
+    #[brw(assert(rsp_plugin.is_ascii(), "RSP plugin name must be ASCII"))]
+    pub rsp_plugin: RawString<64>, // 0x1E2
 
     /// Author name info for the movie. Should be 222-byte UTF-8 string.
-    #[brw(pad_size_to = 222)]
-    pub author_name: NullString, // 0x222
+    pub author_name: RawString<222>, // 0x222
 
     /// Author description info for the movie. Should be 256-byte UTF-8 string.
-    #[brw(pad_size_to = 256)]
-    pub description: NullString, // 0x300
+    pub description: RawString<256>, // 0x300
 
     /// Controller inputs for the movie.
     #[brw(align_before = 0x400)]
@@ -170,6 +187,12 @@ pub struct RawMovie {
 }
 
 impl RawMovie {
+    /// Returns the number of bytes the movie is encoded to: the 1024-byte header
+    /// followed by 4 bytes per controller input sample.
+    pub fn serialized_len(&self) -> usize {
+        HEADER_LEN + SAMPLE_LEN * self.inputs.len()
+    }
+
     /// Returns an iterator over the controller states. Each iteration yields an iterator
     /// containing the states of all controllers for that frame.
     ///
@@ -236,6 +259,155 @@ impl ControllerFlags {
             + self.controller_03_present() as u8
             + self.controller_04_present() as u8
     }
+
+    /// Returns the pak plugged into a controller, or `None` if the port does not exist.
+    ///
+    /// A controller flagged as having both a memory pak and a rumble pak is reported as
+    /// having a memory pak, as Mupen64 does.
+    pub fn pak_type(&self, port: usize) -> Option<PakType> {
+        let (mempak, rumblepak) = match port {
+            0 => (
+                self.controller_01_has_mempak(),
+                self.controller_01_has_rumblepak(),
+            ),
+            1 => (
+                self.controller_02_has_mempak(),
+                self.controller_02_has_rumblepak(),
+            ),
+            2 => (
+                self.controller_03_has_mempak(),
+                self.controller_03_has_rumblepak(),
+            ),
+            3 => (
+                self.controller_04_has_mempak(),
+                self.controller_04_has_rumblepak(),
+            ),
+            _ => return None,
+        };
+
+        Some(match (mempak, rumblepak) {
+            (true, _) => PakType::Mempak,
+            (false, true) => PakType::Rumblepak,
+            (false, false) => PakType::None,
+        })
+    }
+
+    /// Sets the pak plugged into a controller. Ports past the fourth are ignored.
+    pub fn set_pak_type(&mut self, port: usize, pak: PakType) {
+        let mempak = pak == PakType::Mempak;
+        let rumblepak = pak == PakType::Rumblepak;
+        match port {
+            0 => {
+                self.set_controller_01_has_mempak(mempak);
+                self.set_controller_01_has_rumblepak(rumblepak);
+            }
+            1 => {
+                self.set_controller_02_has_mempak(mempak);
+                self.set_controller_02_has_rumblepak(rumblepak);
+            }
+            2 => {
+                self.set_controller_03_has_mempak(mempak);
+                self.set_controller_03_has_rumblepak(rumblepak);
+            }
+            3 => {
+                self.set_controller_04_has_mempak(mempak);
+                self.set_controller_04_has_rumblepak(rumblepak);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The accessory plugged into a controller, as flagged in the [`ControllerFlags`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PakType {
+    /// No pak is plugged in.
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "none"))]
+    None,
+    /// A memory pak, whose contents are not stored in the movie.
+    #[cfg_attr(feature = "serde", serde(rename = "mempak"))]
+    Mempak,
+    /// A rumble pak.
+    #[cfg_attr(feature = "serde", serde(rename = "rumblepak"))]
+    Rumblepak,
+}
+
+/// A serde representation of [`ControllerFlags`] with one named field per flag.
+#[cfg(feature = "serde")]
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ControllerFlagsRepr {
+    /// Presence flags for controllers 1 to 4.
+    present: [bool; 4],
+    /// Memory pack flags for controllers 1 to 4.
+    mempak: [bool; 4],
+    /// Rumble pack flags for controllers 1 to 4.
+    rumblepak: [bool; 4],
+    /// The value of the remaining reserved bits.
+    #[serde(skip_serializing_if = "is_zero")]
+    reserved: u32,
+}
+
+/// Returns `true` if the value is zero.
+#[cfg(feature = "serde")]
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ControllerFlags {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ControllerFlagsRepr {
+            present: [
+                self.controller_01_present(),
+                self.controller_02_present(),
+                self.controller_03_present(),
+                self.controller_04_present(),
+            ],
+            mempak: [
+                self.controller_01_has_mempak(),
+                self.controller_02_has_mempak(),
+                self.controller_03_has_mempak(),
+                self.controller_04_has_mempak(),
+            ],
+            rumblepak: [
+                self.controller_01_has_rumblepak(),
+                self.controller_02_has_rumblepak(),
+                self.controller_03_has_rumblepak(),
+                self.controller_04_has_rumblepak(),
+            ],
+            reserved: self.value >> 12,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ControllerFlags {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ControllerFlagsRepr::deserialize(deserializer)?;
+        if repr.reserved > <u20 as Number>::MAX.value() {
+            return Err(serde::de::Error::custom("reserved bits exceed 20 bits"));
+        }
+
+        let mut flags = ControllerFlags::from(repr.reserved << 12);
+        flags.set_controller_01_present(repr.present[0]);
+        flags.set_controller_02_present(repr.present[1]);
+        flags.set_controller_03_present(repr.present[2]);
+        flags.set_controller_04_present(repr.present[3]);
+        flags.set_controller_01_has_mempak(repr.mempak[0]);
+        flags.set_controller_02_has_mempak(repr.mempak[1]);
+        flags.set_controller_03_has_mempak(repr.mempak[2]);
+        flags.set_controller_04_has_mempak(repr.mempak[3]);
+        flags.set_controller_01_has_rumblepak(repr.rumblepak[0]);
+        flags.set_controller_02_has_rumblepak(repr.rumblepak[1]);
+        flags.set_controller_03_has_rumblepak(repr.rumblepak[2]);
+        flags.set_controller_04_has_rumblepak(repr.rumblepak[3]);
+
+        Ok(flags)
+    }
 }
 
 /// A 32-byte structure for extended data found at offset 0x024 in the Mupen64 movie header.
@@ -264,16 +436,20 @@ impl TryFrom<&[u8]> for ExtendedData {
 /// An enum representing the start type of a Mupen64 movie.
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq, BinRead, BinWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[brw(little)]
 pub enum MovieStartType {
     /// The movie starts from a snapshot.
     #[brw(magic = 1u16)]
+    #[cfg_attr(feature = "serde", serde(rename = "snapshot"))]
     Snapshot,
     /// The movie starts from a power-on state.
     #[brw(magic = 2u16)]
+    #[cfg_attr(feature = "serde", serde(rename = "power-on"))]
     PowerOn,
     /// The movie starts from EEPROM.
     #[brw(magic = 4u16)]
+    #[cfg_attr(feature = "serde", serde(rename = "eeprom"))]
     EEPROM,
 }
 
@@ -437,30 +613,274 @@ impl ControllerState {
         }
     }
 
+    /// Merges another controller state into this one according to a policy.
+    pub fn merge(&self, other: &ControllerState, policy: MergePolicy) -> ControllerState {
+        let buttons = policy.buttons.apply(self.value as u16, other.value as u16);
+        let x_axis = policy.x_axis.apply(self.x_axis(), other.x_axis());
+        let y_axis = policy.y_axis.apply(self.y_axis(), other.y_axis());
+
+        let mut merged = ControllerState::from(buttons as u32);
+        merged.set_axis(x_axis, y_axis);
+        merged
+    }
+
+    /// Returns `true` if both states press the same buttons, and each axis differs by
+    /// at most `axis_tolerance`.
+    ///
+    /// This ignores the small differences between runs recorded with slightly
+    /// different stick calibration.
+    pub fn approx_eq(&self, other: &ControllerState, axis_tolerance: u8) -> bool {
+        self.value as u16 == other.value as u16
+            && self.x_axis().abs_diff(other.x_axis()) <= axis_tolerance
+            && self.y_axis().abs_diff(other.y_axis()) <= axis_tolerance
+    }
+
     /// Get a vector of all buttons that are currently pressed.
     pub fn get_pressed(&self) -> Vec<ControllerButton> {
-        let buttons = [
-            ControllerButton::DPadRight,
-            ControllerButton::DPadLeft,
-            ControllerButton::DPadDown,
-            ControllerButton::DPadUp,
-            ControllerButton::Start,
-            ControllerButton::Z,
-            ControllerButton::B,
-            ControllerButton::A,
-            ControllerButton::CRight,
-            ControllerButton::CLeft,
-            ControllerButton::CDown,
-            ControllerButton::CUp,
-            ControllerButton::TriggerRight,
-            ControllerButton::TriggerLeft,
-            ControllerButton::Reserved01,
-            ControllerButton::Reserved02,
-        ];
-
-        buttons
-            .into_iter()
+        ControllerButton::iter()
             .filter(|&button| self.is_set(button))
             .collect()
     }
+
+    /// Starts building a controller state, from the neutral state.
+    ///
+    /// ```
+    /// use m64_movie::{ControllerButton, raw::ControllerState};
+    ///
+    /// let state = ControllerState::build().a().z().stick(127, 0).done();
+    /// assert!(state.is_set(ControllerButton::A) && state.is_set(ControllerButton::Z));
+    /// assert_eq!(state.axis(), (127, 0));
+    /// ```
+    pub fn build() -> ControllerStateBuilder {
+        ControllerStateBuilder::default()
+    }
+}
+
+/// Defines a [`ControllerStateBuilder`] method pressing a button.
+macro_rules! builder_buttons {
+    ($($name:ident => $button:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Presses [`ControllerButton::", stringify!($button), "`].")]
+            pub fn $name(self) -> Self {
+                self.button(ControllerButton::$button)
+            }
+        )*
+    };
+}
+
+/// A fluent builder of [`ControllerState`]s. See [`ControllerState::build`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct ControllerStateBuilder {
+    /// The state built so far.
+    state: ControllerState,
+}
+
+impl ControllerStateBuilder {
+    builder_buttons! {
+        a => A,
+        b => B,
+        z => Z,
+        start => Start,
+        l => TriggerLeft,
+        r => TriggerRight,
+        d_up => DPadUp,
+        d_down => DPadDown,
+        d_left => DPadLeft,
+        d_right => DPadRight,
+        c_up => CUp,
+        c_down => CDown,
+        c_left => CLeft,
+        c_right => CRight,
+    }
+
+    /// Presses a button.
+    pub fn button(mut self, button: ControllerButton) -> Self {
+        self.state.set(button);
+        self
+    }
+
+    /// Presses several buttons.
+    pub fn buttons<I: IntoIterator<Item = ControllerButton>>(mut self, buttons: I) -> Self {
+        for button in buttons {
+            self.state.set(button);
+        }
+        self
+    }
+
+    /// Sets the analog x and y axis values.
+    pub fn stick(mut self, x: i8, y: i8) -> Self {
+        self.state.set_axis(x, y);
+        self
+    }
+
+    /// Sets the analog x-axis value.
+    pub fn x(mut self, x: i8) -> Self {
+        self.state.set_x_axis(x);
+        self
+    }
+
+    /// Sets the analog y-axis value.
+    pub fn y(mut self, y: i8) -> Self {
+        self.state.set_y_axis(y);
+        self
+    }
+
+    /// Returns the state built.
+    pub fn done(self) -> ControllerState {
+        self.state
+    }
+}
+
+impl From<ControllerStateBuilder> for ControllerState {
+    fn from(builder: ControllerStateBuilder) -> Self {
+        builder.done()
+    }
+}
+
+/// A serde representation of [`ControllerState`] with the pressed buttons and axes
+/// as named fields.
+#[cfg(feature = "serde")]
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ControllerStateRepr {
+    /// The pressed buttons.
+    buttons: Vec<ControllerButton>,
+    /// The analog x-axis value.
+    x: i8,
+    /// The analog y-axis value.
+    y: i8,
+}
+
+#[cfg(feature = "serde")]
+impl From<&ControllerState> for ControllerStateRepr {
+    fn from(state: &ControllerState) -> Self {
+        ControllerStateRepr {
+            buttons: state.get_pressed(),
+            x: state.x_axis(),
+            y: state.y_axis(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ControllerStateRepr> for ControllerState {
+    fn from(repr: ControllerStateRepr) -> Self {
+        let mut state = ControllerState::default();
+        for button in repr.buttons {
+            state.set(button);
+        }
+        state.set_axis(repr.x, repr.y);
+        state
+    }
+}
+
+/// Serializes as named fields in human-readable formats, and as the encoded `u32`
+/// otherwise. See [`serde_with`](crate::raw::serde_with) to force either representation.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ControllerState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            crate::raw::serde_with::readable::serialize(self, serializer)
+        } else {
+            crate::raw::serde_with::compact::serialize(self, serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ControllerState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            crate::raw::serde_with::readable::deserialize(deserializer)
+        } else {
+            crate::raw::serde_with::compact::deserialize(deserializer)
+        }
+    }
+}
+
+/// How the buttons of two controller states are combined by [`ControllerState::merge`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ButtonMerge {
+    /// A button is pressed if it is pressed in either state.
+    Or,
+    /// A button is pressed if it is pressed in both states.
+    And,
+    /// A button is pressed if it is pressed in exactly one state.
+    Xor,
+    /// Keep the buttons of the original state.
+    Keep,
+    /// Take the buttons of the other state.
+    Replace,
+}
+
+impl ButtonMerge {
+    /// Combines two button bitmasks.
+    fn apply(self, current: u16, other: u16) -> u16 {
+        match self {
+            ButtonMerge::Or => current | other,
+            ButtonMerge::And => current & other,
+            ButtonMerge::Xor => current ^ other,
+            ButtonMerge::Keep => current,
+            ButtonMerge::Replace => other,
+        }
+    }
+}
+
+/// How an analog axis of two controller states is combined by [`ControllerState::merge`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AxisMerge {
+    /// Keep the axis value of the original state.
+    Keep,
+    /// Take the axis value of the other state.
+    Replace,
+    /// Take the axis value of the other state, unless it is zero.
+    PreferNonZero,
+    /// Add both axis values, saturating at the axis limits.
+    Add,
+}
+
+impl AxisMerge {
+    /// Combines two axis values.
+    fn apply(self, current: i8, other: i8) -> i8 {
+        match self {
+            AxisMerge::Keep => current,
+            AxisMerge::Replace => other,
+            AxisMerge::PreferNonZero if other != 0 => other,
+            AxisMerge::PreferNonZero => current,
+            AxisMerge::Add => current.saturating_add(other),
+        }
+    }
+}
+
+/// A policy describing how [`ControllerState::merge`] combines each field of two states.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MergePolicy {
+    /// How the buttons are combined.
+    pub buttons: ButtonMerge,
+    /// How the x-axis is combined.
+    pub x_axis: AxisMerge,
+    /// How the y-axis is combined.
+    pub y_axis: AxisMerge,
+}
+
+impl MergePolicy {
+    /// Layers the other state on top: buttons are combined, and non-zero axes take precedence.
+    pub const OVERLAY: MergePolicy = MergePolicy {
+        buttons: ButtonMerge::Or,
+        x_axis: AxisMerge::PreferNonZero,
+        y_axis: AxisMerge::PreferNonZero,
+    };
+
+    /// Replaces the original state entirely with the other state.
+    pub const REPLACE: MergePolicy = MergePolicy {
+        buttons: ButtonMerge::Replace,
+        x_axis: AxisMerge::Replace,
+        y_axis: AxisMerge::Replace,
+    };
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        MergePolicy::OVERLAY
+    }
 }
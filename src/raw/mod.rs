@@ -6,11 +6,29 @@ use binrw::{BinRead, BinWrite};
 
 use crate::{BinReadExt, BinWriteExt, MovieError};
 
+#[doc(hidden)]
+pub mod header;
 #[doc(hidden)]
 pub mod m64;
+#[doc(hidden)]
+pub mod salvage;
+#[cfg(feature = "serde")]
+pub mod serde_with;
+#[doc(hidden)]
+pub mod stream;
+#[doc(hidden)]
+pub mod view;
 
+#[doc(inline)]
+pub use header::*;
 #[doc(inline)]
 pub use m64::*;
+#[doc(inline)]
+pub use salvage::*;
+#[doc(inline)]
+pub use stream::*;
+#[doc(inline)]
+pub use view::*;
 
 /// Helper macro to implement `BinReadExt` and `BinWriteExt` traits for a type implementing [`BinRead`].
 macro_rules! impl_bin_read_ext {
@@ -32,13 +50,19 @@ macro_rules! impl_bin_read_ext {
 }
 
 /// Helper macro to implement `BinWriteExt` traits for a type implementing [`BinWrite`].
+///
+/// An optional function returning the encoded size of a value is used to preallocate
+/// the buffer of `to_bytes`.
 macro_rules! impl_bin_write_ext {
     ($type:ty) => {
+        impl_bin_write_ext!($type, |_: &$type| 0);
+    };
+    ($type:ty, $len:expr) => {
         impl BinWriteExt for $type {
             type Error = MovieError;
 
             fn to_bytes(&self) -> Result<Vec<u8>, Self::Error> {
-                let mut cursor = Cursor::new(Vec::new());
+                let mut cursor = Cursor::new(Vec::with_capacity(($len)(self)));
                 self.write(&mut cursor)?;
                 Ok(cursor.into_inner())
             }
@@ -67,7 +91,11 @@ macro_rules! impl_try_from {
 
 impl_try_from!(RawMovie);
 impl_bin_read_ext!(RawMovie);
-impl_bin_write_ext!(RawMovie);
+impl_bin_write_ext!(RawMovie, RawMovie::serialized_len);
+
+impl_try_from!(RawHeader);
+impl_bin_read_ext!(RawHeader);
+impl_bin_write_ext!(RawHeader, |_: &RawHeader| HEADER_LEN);
 
 impl_try_from!(ExtendedFlags);
 impl_bin_read_ext!(ExtendedFlags);
@@ -0,0 +1,237 @@
+//! Salvaging of damaged M64 files.
+//!
+//! [`RawMovie::from_bytes_forced`] reads the header fields from their fixed offsets
+//! even when the file would normally be rejected, repairing just enough for the
+//! movie to be decoded and reporting every repair as a [`SalvageIssue`].
+
+use std::fmt::{self, Display};
+
+use crate::{
+    BinReadExt, MovieError,
+    raw::m64::{HEADER_LEN, M64_MAGIC, RawMovie, SAMPLE_LEN, SUPPORTED_VERSION},
+};
+
+/// The offset of the extended version byte.
+const EXTENDED_VERSION_OFFSET: usize = 0x16;
+
+/// The offset of the extended flags byte.
+const EXTENDED_FLAGS_OFFSET: usize = 0x17;
+
+/// The offset of the start type.
+const START_TYPE_OFFSET: usize = 0x1C;
+
+/// The offset of the extended data fields that are only valid for extended version 1.
+const EXTENDED_DATA_OFFSET: usize = 0x24;
+
+/// The length of the extended data fields that are only valid for extended version 1.
+const EXTENDED_DATA_LEN: usize = 12;
+
+/// The start type written in place of an unknown one.
+const FALLBACK_START_TYPE: u16 = 2;
+
+/// The ASCII string fields of the header, with their offsets and lengths.
+const ASCII_FIELDS: [(&str, usize, usize); 5] = [
+    ("ROM name", 0x0C4, 32),
+    ("video plugin", 0x122, 64),
+    ("sound plugin", 0x162, 64),
+    ("input plugin", 0x1A2, 64),
+    ("RSP plugin", 0x1E2, 64),
+];
+
+/// Something that was wrong with a salvaged movie.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SalvageIssue {
+    /// The file was shorter than the header. The missing bytes were read as zeros.
+    TruncatedHeader(usize),
+    /// The magic bytes were wrong and have been replaced.
+    BadMagic([u8; 4]),
+    /// The version was not 3 and has been replaced.
+    UnsupportedVersion(u32),
+    /// The extended version was unknown and has been replaced with 0.
+    UnsupportedExtendedVersion(u8),
+    /// Extended flags or data were set without extended version 1 and have been cleared.
+    UnexpectedExtendedData(u8),
+    /// The start type was unknown and has been replaced with power-on.
+    UnknownStartType(u16),
+    /// A string field contained non-ASCII bytes, which have been replaced with `?`.
+    NonAsciiString(&'static str),
+    /// The inputs ended with an incomplete sample of this many bytes, which was dropped.
+    PartialSample(usize),
+    /// The controller count does not match the controller flags. Not repaired.
+    ControllerCountMismatch {
+        /// The controller count in the header.
+        count: u8,
+        /// The number of controllers flagged as present.
+        present: u8,
+    },
+    /// The sample count and controller count do not match the inputs. The sample count
+    /// has been set to the number of frames in the file, dropping the samples of an
+    /// incomplete last frame, unless no controller is flagged as present.
+    SampleCountMismatch {
+        /// The number of samples implied by the header, for all controllers.
+        expected: usize,
+        /// The number of samples in the file.
+        actual: usize,
+    },
+}
+
+impl Display for SalvageIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SalvageIssue::TruncatedHeader(len) => {
+                write!(f, "header truncated to {len} bytes, padded with zeros")
+            }
+            SalvageIssue::BadMagic(magic) => write!(f, "bad magic {magic:02X?}, replaced"),
+            SalvageIssue::UnsupportedVersion(version) => {
+                write!(f, "unsupported version {version}, replaced with 3")
+            }
+            SalvageIssue::UnsupportedExtendedVersion(version) => {
+                write!(f, "unsupported extended version {version}, replaced with 0")
+            }
+            SalvageIssue::UnexpectedExtendedData(version) => write!(
+                f,
+                "extended data set with extended version {version}, cleared"
+            ),
+            SalvageIssue::UnknownStartType(start_type) => {
+                write!(f, "unknown start type {start_type}, replaced with power-on")
+            }
+            SalvageIssue::NonAsciiString(field) => {
+                write!(f, "{field} is not ASCII, invalid bytes replaced with '?'")
+            }
+            SalvageIssue::PartialSample(len) => {
+                write!(f, "dropped a trailing partial sample of {len} bytes")
+            }
+            SalvageIssue::ControllerCountMismatch { count, present } => write!(
+                f,
+                "header counts {count} controllers, but {present} are flagged as present"
+            ),
+            SalvageIssue::SampleCountMismatch { expected, actual } => write!(
+                f,
+                "header implies {expected} samples, but the file has {actual}, sample count \
+                 repaired"
+            ),
+        }
+    }
+}
+
+/// A movie read by [`RawMovie::from_bytes_forced`], with everything that looked wrong.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Salvaged {
+    /// The repaired movie.
+    pub movie: RawMovie,
+    /// The problems found while reading the movie.
+    pub issues: Vec<SalvageIssue>,
+}
+
+impl Salvaged {
+    /// Returns `true` if nothing looked wrong.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Reads a little-endian `u32` at the offset.
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Reads a little-endian `u16` at the offset.
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+}
+
+impl RawMovie {
+    /// Reads a movie, repairing any header field that would otherwise make it unreadable.
+    ///
+    /// Unlike [`RawMovie::from_bytes`](crate::BinReadExt::from_bytes), the magic,
+    /// version and field validity are not enforced. Every repair is reported in
+    /// [`Salvaged::issues`], along with inconsistencies that would prevent converting
+    /// the movie to a [`Movie`](crate::Movie).
+    pub fn from_bytes_forced(bytes: &[u8]) -> Result<Salvaged, MovieError> {
+        let mut issues = Vec::new();
+        let mut bytes = bytes.to_vec();
+
+        if bytes.len() < HEADER_LEN {
+            issues.push(SalvageIssue::TruncatedHeader(bytes.len()));
+            bytes.resize(HEADER_LEN, 0);
+        }
+
+        let partial = (bytes.len() - HEADER_LEN) % SAMPLE_LEN;
+        if partial != 0 {
+            issues.push(SalvageIssue::PartialSample(partial));
+            bytes.truncate(bytes.len() - partial);
+        }
+
+        if bytes[..4] != M64_MAGIC {
+            issues.push(SalvageIssue::BadMagic(bytes[..4].try_into().unwrap()));
+            bytes[..4].copy_from_slice(&M64_MAGIC);
+        }
+
+        let version = read_u32(&bytes, 4);
+        if version != SUPPORTED_VERSION {
+            issues.push(SalvageIssue::UnsupportedVersion(version));
+            bytes[4..8].copy_from_slice(&SUPPORTED_VERSION.to_le_bytes());
+        }
+
+        let mut extended_version = bytes[EXTENDED_VERSION_OFFSET];
+        if extended_version > 1 {
+            issues.push(SalvageIssue::UnsupportedExtendedVersion(extended_version));
+            extended_version = 0;
+            bytes[EXTENDED_VERSION_OFFSET] = 0;
+        }
+
+        let extended_data = EXTENDED_DATA_OFFSET..EXTENDED_DATA_OFFSET + EXTENDED_DATA_LEN;
+        if extended_version != 1
+            && (bytes[EXTENDED_FLAGS_OFFSET] & 1 != 0
+                || bytes[extended_data.clone()].iter().any(|&b| b != 0))
+        {
+            issues.push(SalvageIssue::UnexpectedExtendedData(extended_version));
+            bytes[EXTENDED_FLAGS_OFFSET] &= !1;
+            bytes[extended_data].fill(0);
+        }
+
+        let start_type = read_u16(&bytes, START_TYPE_OFFSET);
+        if ![1, 2, 4].contains(&start_type) {
+            issues.push(SalvageIssue::UnknownStartType(start_type));
+            bytes[START_TYPE_OFFSET..START_TYPE_OFFSET + 2]
+                .copy_from_slice(&FALLBACK_START_TYPE.to_le_bytes());
+        }
+
+        for (field, offset, len) in ASCII_FIELDS {
+            let field_bytes = &mut bytes[offset..offset + len];
+            if !field_bytes.is_ascii() {
+                issues.push(SalvageIssue::NonAsciiString(field));
+                field_bytes
+                    .iter_mut()
+                    .filter(|b| !b.is_ascii())
+                    .for_each(|b| *b = b'?');
+            }
+        }
+
+        let mut movie = RawMovie::from_bytes(&bytes)?;
+
+        let present = movie.controller_flags.num_controllers_present();
+        if movie.controller_count != present {
+            issues.push(SalvageIssue::ControllerCountMismatch {
+                count: movie.controller_count,
+                present,
+            });
+        }
+
+        let expected = movie.controller_input_samples as usize * movie.controller_count as usize;
+        if movie.inputs.len() != expected {
+            issues.push(SalvageIssue::SampleCountMismatch {
+                expected,
+                actual: movie.inputs.len(),
+            });
+
+            if present != 0 {
+                let frames = movie.inputs.len() / present as usize;
+                movie.inputs.truncate(frames * present as usize);
+                movie.controller_input_samples = frames as u32;
+            }
+        }
+
+        Ok(Salvaged { movie, issues })
+    }
+}
@@ -0,0 +1,60 @@
+//! Serde helpers to choose the representation of a [`ControllerState`].
+//!
+//! By default, a [`ControllerState`] serializes as named fields in human-readable
+//! formats such as TOML, and as its encoded `u32` in binary formats. Use these
+//! modules with `#[serde(with = "...")]` to force one representation:
+//!
+//! ```
+//! use m64_movie::raw::ControllerState;
+//!
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Frame {
+//!     #[serde(with = "m64_movie::raw::serde_with::compact")]
+//!     input: ControllerState,
+//! }
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::raw::{ControllerState, m64::ControllerStateRepr};
+
+/// Represents a [`ControllerState`] as its pressed buttons and axes, such as
+/// `{ buttons = ["A", "Z"], x = 0, y = 127 }`.
+pub mod readable {
+    use super::*;
+
+    /// Serializes a controller state as named fields.
+    pub fn serialize<S: Serializer>(
+        state: &ControllerState,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        ControllerStateRepr::from(state).serialize(serializer)
+    }
+
+    /// Deserializes a controller state from named fields.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ControllerState, D::Error> {
+        Ok(ControllerStateRepr::deserialize(deserializer)?.into())
+    }
+}
+
+/// Represents a [`ControllerState`] as the `u32` it is encoded as in a movie.
+pub mod compact {
+    use super::*;
+
+    /// Serializes a controller state as its encoded `u32`.
+    pub fn serialize<S: Serializer>(
+        state: &ControllerState,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        u32::from(*state).serialize(serializer)
+    }
+
+    /// Deserializes a controller state from its encoded `u32`.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ControllerState, D::Error> {
+        Ok(ControllerState::from(u32::deserialize(deserializer)?))
+    }
+}
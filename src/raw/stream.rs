@@ -0,0 +1,123 @@
+//! Lazy reading of the inputs of a raw Mupen64 movie file.
+
+use std::io::{BufReader, ErrorKind, Read};
+
+use crate::{
+    MovieError, MovieParseError,
+    raw::{ControllerState, RawHeader, RawMovie, m64::SAMPLE_LEN},
+};
+
+/// An iterator over the input samples of a movie, read lazily from a reader. See
+/// [`RawMovie::frames_from_reader`].
+///
+/// The samples are read through a buffer, so the reader need not be buffered. After
+/// an error, the iterator yields nothing more.
+#[derive(Debug)]
+pub struct InputStream<R> {
+    /// The header of the movie.
+    header: RawHeader,
+    /// The reader, positioned at the next sample.
+    reader: BufReader<R>,
+    /// The number of samples declared by the header.
+    samples_declared: usize,
+    /// The number of samples read so far.
+    samples_read: usize,
+    /// Whether the end of the inputs or an error was reached.
+    done: bool,
+}
+
+impl<R: Read> InputStream<R> {
+    /// Returns the header of the movie.
+    pub fn header(&self) -> &RawHeader {
+        &self.header
+    }
+
+    /// Returns the number of samples read so far.
+    pub fn samples_read(&self) -> usize {
+        self.samples_read
+    }
+
+    /// Returns the number of samples declared by the header: the input sample counter
+    /// times the controller count.
+    pub fn samples_declared(&self) -> usize {
+        self.samples_declared
+    }
+
+    /// Reads the next sample, or `None` after the declared samples.
+    fn read_sample(&mut self) -> Result<Option<ControllerState>, MovieError> {
+        if self.samples_read == self.samples_declared {
+            return Ok(None);
+        }
+
+        let mut sample = [0; SAMPLE_LEN];
+        let mut filled = 0;
+        while filled < SAMPLE_LEN {
+            match self.reader.read(&mut sample[filled..]) {
+                Ok(0) if filled == 0 => {
+                    return Err(MovieParseError::InputSampleMismatch {
+                        expected: self.samples_declared,
+                        found: self.samples_read,
+                        input_bytes: self.samples_read * SAMPLE_LEN,
+                    }
+                    .into());
+                }
+                Ok(0) => return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into()),
+                Ok(read) => filled += read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Some(ControllerState::from(u32::from_le_bytes(sample))))
+    }
+}
+
+impl<R: Read> Iterator for InputStream<R> {
+    type Item = Result<ControllerState, MovieError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.read_sample() {
+            Ok(Some(state)) => {
+                self.samples_read += 1;
+                Some(Ok(state))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl RawMovie {
+    /// Reads the header of a movie from a reader, returning an iterator reading its
+    /// input samples lazily, so the inputs of long movies need not be held in memory.
+    ///
+    /// The samples of every controller are yielded in order, as they are stored, until
+    /// the number of samples declared by the header. Anything after them, e.g. trailing
+    /// data, is not read. Fails if the header cannot be read. A reader ending before
+    /// the declared samples is yielded as an error.
+    ///
+    /// The reader need not be seekable, since the samples are read in order, so
+    /// movies can be streamed from pipes and sockets as well as files.
+    pub fn frames_from_reader<R: Read>(mut reader: R) -> Result<InputStream<R>, MovieError> {
+        let header = RawMovie::read_header_only(&mut reader)?;
+        let samples_declared = header.controller_input_samples as usize
+            * header.controller_flags.num_controllers_present() as usize;
+        Ok(InputStream {
+            header,
+            reader: BufReader::new(reader),
+            samples_declared,
+            samples_read: 0,
+            done: false,
+        })
+    }
+}
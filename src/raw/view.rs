@@ -0,0 +1,84 @@
+//! A borrowed view of a raw Mupen64 movie file.
+
+use crate::{
+    BinReadExt, MovieError,
+    raw::{
+        ControllerState, RawHeader, RawMovie,
+        m64::{HEADER_LEN, SAMPLE_LEN},
+    },
+};
+
+/// A raw Mupen64 movie borrowing its inputs from the bytes it was parsed from.
+///
+/// Only the header is decoded. The inputs are kept as the encoded bytes and decoded
+/// on access, so inspecting a movie does not copy its input section. As for a
+/// [`RawMovie`], a trailing partial sample is ignored.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RawMovieRef<'a> {
+    /// The decoded header.
+    header: RawHeader,
+    /// The encoded input samples, 4 bytes each.
+    inputs: &'a [u8],
+}
+
+impl<'a> RawMovieRef<'a> {
+    /// Parses the header of a movie and borrows the inputs following it.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, MovieError> {
+        let header = RawHeader::from_bytes(bytes)?;
+        let inputs = &bytes[HEADER_LEN..];
+        let whole = inputs.len() - inputs.len() % SAMPLE_LEN;
+
+        Ok(RawMovieRef {
+            header,
+            inputs: &inputs[..whole],
+        })
+    }
+
+    /// Returns the header of the movie.
+    pub fn header(&self) -> &RawHeader {
+        &self.header
+    }
+
+    /// Returns the encoded input samples, 4 little-endian bytes each.
+    pub fn input_bytes(&self) -> &'a [u8] {
+        self.inputs
+    }
+
+    /// Returns the number of input samples.
+    pub fn sample_count(&self) -> usize {
+        self.inputs.len() / SAMPLE_LEN
+    }
+
+    /// Returns an input sample.
+    pub fn sample(&self, index: usize) -> Option<ControllerState> {
+        let start = index.checked_mul(SAMPLE_LEN)?;
+        self.inputs
+            .get(start..start.checked_add(SAMPLE_LEN)?)
+            .map(decode)
+    }
+
+    /// Returns an iterator over the input samples, decoded as they are read.
+    pub fn samples(&self) -> impl ExactSizeIterator<Item = ControllerState> + 'a {
+        self.inputs.chunks_exact(SAMPLE_LEN).map(decode)
+    }
+
+    /// Copies the movie into an owned [`RawMovie`].
+    pub fn to_owned_movie(&self) -> RawMovie {
+        self.header.clone().with_inputs(self.samples().collect())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for RawMovieRef<'a> {
+    type Error = MovieError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        RawMovieRef::parse(bytes)
+    }
+}
+
+/// Decodes an encoded input sample.
+fn decode(sample: &[u8]) -> ControllerState {
+    ControllerState::from(u32::from_le_bytes(
+        sample.try_into().expect("samples are 4 bytes long"),
+    ))
+}
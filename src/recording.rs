@@ -0,0 +1,91 @@
+//! Live recording of movies.
+//!
+//! A [`RecordingSession`] is the write-side counterpart of a
+//! [`MovieCursor`](crate::playback::MovieCursor): a frontend pushes the controller
+//! states of each frame as they are polled, and the header counters are kept up to
+//! date as it goes.
+
+use crate::{
+    CounterError, FrameError, Movie, MovieError, metadata::MovieHeader, raw::ControllerState,
+};
+
+/// A movie being recorded. See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct RecordingSession {
+    /// The movie recorded so far.
+    movie: Movie,
+}
+
+impl RecordingSession {
+    /// Starts recording a movie with the given header.
+    ///
+    /// The VI and rerecord counts of the template are reset to zero, including the
+    /// high word of the rerecord count of extended version 1 movies.
+    pub fn new(header_template: MovieHeader) -> Self {
+        let mut movie = Movie::from(header_template);
+        movie.recording_info.vertical_interrupts = 0;
+        movie
+            .set_rerecord_count(0)
+            .expect("every movie can count zero rerecords");
+
+        RecordingSession { movie }
+    }
+
+    /// Returns the number of controllers recorded each frame.
+    pub fn controller_count(&self) -> usize {
+        self.movie.controller_count()
+    }
+
+    /// Returns the number of frames recorded so far.
+    pub fn frame_count(&self) -> usize {
+        self.movie.frame_count()
+    }
+
+    /// Returns the number of vertical interrupts recorded so far.
+    pub fn vertical_interrupts(&self) -> u32 {
+        self.movie.recording_info.vertical_interrupts
+    }
+
+    /// Returns the rerecord count. See [`Movie::rerecord_count`].
+    pub fn rerecords(&self) -> u64 {
+        self.movie.rerecord_count()
+    }
+
+    /// Returns the movie recorded so far.
+    pub fn movie(&self) -> &Movie {
+        &self.movie
+    }
+
+    /// Appends a frame, with one state per controller.
+    pub fn push_frame(&mut self, states: &[ControllerState]) -> Result<(), MovieError> {
+        let controller_count = self.controller_count();
+        if states.len() != controller_count {
+            return Err(FrameError::PartialFrame {
+                samples: states.len(),
+                controller_count,
+            }
+            .into());
+        }
+
+        self.movie.inputs.extend_from_slice(states);
+        Ok(())
+    }
+
+    /// Records a vertical interrupt. Frontends call this once per VI, which happens
+    /// more often than input polls in most games.
+    pub fn push_vertical_interrupt(&mut self) {
+        let info = &mut self.movie.recording_info;
+        info.vertical_interrupts = info.vertical_interrupts.saturating_add(1);
+    }
+
+    /// Sets the rerecord count, e.g. after the frontend loaded a savestate. See
+    /// [`Movie::set_rerecord_count`].
+    pub fn set_rerecords(&mut self, rerecords: u64) -> Result<(), CounterError> {
+        self.movie.set_rerecord_count(rerecords)
+    }
+
+    /// Ends the recording, returning the movie.
+    pub fn finalize(self) -> Movie {
+        self.movie
+    }
+}
@@ -0,0 +1,141 @@
+//! ROM country codes and the video standard they imply.
+
+/// The video standard of a console region, which determines its refresh rate.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Region {
+    /// NTSC consoles run at 60 vertical interrupts per second.
+    Ntsc,
+    /// PAL consoles run at 50 vertical interrupts per second.
+    Pal,
+}
+
+impl Region {
+    /// Returns the number of vertical interrupts per second of the region.
+    pub fn vis_per_second(self) -> u8 {
+        match self {
+            Region::Ntsc => 60,
+            Region::Pal => 50,
+        }
+    }
+}
+
+/// The country code of a ROM, as stored in the movie header.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CountryCode {
+    /// Beta (`7`).
+    Beta,
+    /// Asia (`A`).
+    Asia,
+    /// Brazil (`B`).
+    Brazil,
+    /// China (`C`).
+    China,
+    /// Germany (`D`).
+    Germany,
+    /// North America (`E`).
+    NorthAmerica,
+    /// France (`F`).
+    France,
+    /// Gateway 64, NTSC (`G`).
+    GatewayNtsc,
+    /// The Netherlands (`H`).
+    Netherlands,
+    /// Italy (`I`).
+    Italy,
+    /// Japan (`J`).
+    Japan,
+    /// Korea (`K`).
+    Korea,
+    /// Gateway 64, PAL (`L`).
+    GatewayPal,
+    /// Canada (`N`).
+    Canada,
+    /// Europe (`P`).
+    Europe,
+    /// Spain (`S`).
+    Spain,
+    /// Australia (`U`).
+    Australia,
+    /// Scandinavia (`W`).
+    Scandinavia,
+    /// Other European releases (`X`, `Y`, `Z`).
+    OtherEurope(u8),
+    /// Any other country code.
+    Other(u16),
+}
+
+impl CountryCode {
+    /// Returns the video standard used by consoles of the country.
+    ///
+    /// Unknown country codes are assumed to be NTSC, as Mupen64 does.
+    pub fn region(self) -> Region {
+        match self {
+            CountryCode::China
+            | CountryCode::Germany
+            | CountryCode::France
+            | CountryCode::Netherlands
+            | CountryCode::Italy
+            | CountryCode::GatewayPal
+            | CountryCode::Europe
+            | CountryCode::Spain
+            | CountryCode::Australia
+            | CountryCode::Scandinavia
+            | CountryCode::OtherEurope(_) => Region::Pal,
+            _ => Region::Ntsc,
+        }
+    }
+}
+
+impl From<u16> for CountryCode {
+    fn from(code: u16) -> Self {
+        match code {
+            0x37 => CountryCode::Beta,
+            0x41 => CountryCode::Asia,
+            0x42 => CountryCode::Brazil,
+            0x43 => CountryCode::China,
+            0x44 => CountryCode::Germany,
+            0x45 => CountryCode::NorthAmerica,
+            0x46 => CountryCode::France,
+            0x47 => CountryCode::GatewayNtsc,
+            0x48 => CountryCode::Netherlands,
+            0x49 => CountryCode::Italy,
+            0x4A => CountryCode::Japan,
+            0x4B => CountryCode::Korea,
+            0x4C => CountryCode::GatewayPal,
+            0x4E => CountryCode::Canada,
+            0x50 => CountryCode::Europe,
+            0x53 => CountryCode::Spain,
+            0x55 => CountryCode::Australia,
+            0x57 => CountryCode::Scandinavia,
+            0x58..=0x5A => CountryCode::OtherEurope(code as u8),
+            _ => CountryCode::Other(code),
+        }
+    }
+}
+
+impl From<CountryCode> for u16 {
+    fn from(code: CountryCode) -> Self {
+        match code {
+            CountryCode::Beta => 0x37,
+            CountryCode::Asia => 0x41,
+            CountryCode::Brazil => 0x42,
+            CountryCode::China => 0x43,
+            CountryCode::Germany => 0x44,
+            CountryCode::NorthAmerica => 0x45,
+            CountryCode::France => 0x46,
+            CountryCode::GatewayNtsc => 0x47,
+            CountryCode::Netherlands => 0x48,
+            CountryCode::Italy => 0x49,
+            CountryCode::Japan => 0x4A,
+            CountryCode::Korea => 0x4B,
+            CountryCode::GatewayPal => 0x4C,
+            CountryCode::Canada => 0x4E,
+            CountryCode::Europe => 0x50,
+            CountryCode::Spain => 0x53,
+            CountryCode::Australia => 0x55,
+            CountryCode::Scandinavia => 0x57,
+            CountryCode::OtherEurope(code) => code as u16,
+            CountryCode::Other(code) => code,
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Reusable chunks of input, saved to their own files.
+//!
+//! A [`Segment`] holds a run of frames, such as a door entry or a star dance skip,
+//! together with the controller configuration it was recorded with and where it
+//! came from, so it can be stored in a library and inserted into other movies.
+//!
+//! Segments are stored in a small little-endian binary format:
+//!
+//! | Field             | Type                              |
+//! |-------------------|-----------------------------------|
+//! | Magic             | `M64S\x1A`                        |
+//! | Version           | `u16`, currently 1                |
+//! | Controller flags  | `u32`, as in the M64 header       |
+//! | Name              | null-terminated UTF-8             |
+//! | Author            | null-terminated UTF-8             |
+//! | Source            | null-terminated UTF-8             |
+//! | ROM name          | null-terminated UTF-8             |
+//! | ROM CRC32         | `u32`                             |
+//! | Source frame      | `u32`                             |
+//! | Frame count       | `u32`                             |
+//! | Inputs            | `u32` per controller per frame    |
+
+use std::{fs::File, io::Cursor, ops::Range, path::Path};
+
+use binrw::{BinRead, BinWrite, NullString, binrw};
+
+use crate::{
+    BinReadExt, BinWriteExt, FrameError, Movie, MovieError, SegmentError,
+    annotations::Annotations,
+    markers::Markers,
+    raw::{ControllerFlags, ControllerState},
+};
+
+/// The current version of the segment file format.
+const SEGMENT_VERSION: u16 = 1;
+
+/// The bits of [`ControllerFlags`] marking which controllers are present.
+const PRESENT_MASK: u32 = 0xF;
+
+/// Where a segment was recorded.
+#[derive(Debug, Clone, Default, Eq, PartialEq, BinRead, BinWrite)]
+#[brw(little)]
+pub struct Provenance {
+    /// The author of the movie the segment was recorded from.
+    #[br(try_map = |s: NullString| String::try_from(s))]
+    #[bw(map = |s: &String| NullString::from(s.as_str()))]
+    pub author: String,
+    /// A free-text description of the source, such as the movie's file name.
+    #[br(try_map = |s: NullString| String::try_from(s))]
+    #[bw(map = |s: &String| NullString::from(s.as_str()))]
+    pub source: String,
+    /// The internal name of the ROM the segment was recorded on.
+    #[br(try_map = |s: NullString| String::try_from(s))]
+    #[bw(map = |s: &String| NullString::from(s.as_str()))]
+    pub rom_name: String,
+    /// The CRC32 checksum of the ROM the segment was recorded on.
+    pub rom_crc32: u32,
+    /// The frame of the source movie at which the segment starts.
+    #[br(map = |frame: u32| frame as usize)]
+    #[bw(map = |frame: &usize| *frame as u32)]
+    pub source_frame: usize,
+}
+
+/// A named run of frames that can be inserted into movies.
+#[binrw]
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[brw(little, magic = b"M64S\x1A")]
+pub struct Segment {
+    /// The version of the segment file format.
+    #[br(assert(version == SEGMENT_VERSION, "Unsupported segment version: {}", version))]
+    #[bw(calc = SEGMENT_VERSION)]
+    version: u16,
+    /// The controllers the segment was recorded with.
+    #[br(assert(
+        controller_flags.num_controllers_present() > 0,
+        "Segment has no controllers."
+    ))]
+    controller_flags: ControllerFlags,
+    /// The name of the segment.
+    #[br(try_map = |s: NullString| String::try_from(s))]
+    #[bw(map = |s: &String| NullString::from(s.as_str()))]
+    name: String,
+    /// Where the segment was recorded.
+    provenance: Provenance,
+    /// The number of frames in the segment.
+    #[br(temp)]
+    #[bw(calc = (inputs.len() / controller_flags.num_controllers_present() as usize) as u32)]
+    frame_count: u32,
+    /// The controller inputs, interleaved by controller.
+    #[br(count = frame_count as usize * controller_flags.num_controllers_present() as usize)]
+    inputs: Vec<ControllerState>,
+}
+
+impl Segment {
+    /// Creates a segment from samples interleaved by controller. The number of samples
+    /// must be a multiple of the number of controllers present in `controller_flags`.
+    pub fn new<S: Into<String>>(
+        name: S,
+        controller_flags: ControllerFlags,
+        inputs: Vec<ControllerState>,
+    ) -> Result<Self, MovieError> {
+        let controller_count = controller_flags.num_controllers_present() as usize;
+        if controller_count == 0 || !inputs.len().is_multiple_of(controller_count) {
+            return Err(FrameError::PartialFrame {
+                samples: inputs.len(),
+                controller_count,
+            }
+            .into());
+        }
+
+        Ok(Segment {
+            controller_flags,
+            name: name.into(),
+            provenance: Provenance::default(),
+            inputs,
+        })
+    }
+
+    /// Records a range of frames of a movie, taking the provenance from its header.
+    pub fn record<S: Into<String>>(
+        name: S,
+        movie: &Movie,
+        frames: Range<usize>,
+    ) -> Result<Self, MovieError> {
+        let samples = movie.check_frame_range(&frames)?;
+        let inputs = movie.samples()[samples].to_vec();
+
+        let mut segment = Segment::new(name, movie.controller_flags(), inputs)?;
+        segment.provenance = Provenance {
+            author: movie.recording_info.author_name.to_string(),
+            source: String::new(),
+            rom_name: movie.game_info.rom_name.to_string(),
+            rom_crc32: movie.game_info.rom_crc32,
+            source_frame: frames.start,
+        };
+
+        Ok(segment)
+    }
+
+    /// Returns the name of the segment.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns where the segment was recorded.
+    pub fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+
+    /// Returns a mutable reference to where the segment was recorded.
+    pub fn provenance_mut(&mut self) -> &mut Provenance {
+        &mut self.provenance
+    }
+
+    /// Returns the controllers the segment was recorded with.
+    pub fn controller_flags(&self) -> ControllerFlags {
+        self.controller_flags
+    }
+
+    /// Returns the number of controllers in the segment.
+    pub fn controller_count(&self) -> usize {
+        self.controller_flags.num_controllers_present() as usize
+    }
+
+    /// Returns the number of frames in the segment.
+    pub fn frame_count(&self) -> usize {
+        self.inputs.len() / self.controller_count()
+    }
+
+    /// Returns the controller inputs, interleaved by controller.
+    pub fn samples(&self) -> &[ControllerState] {
+        &self.inputs
+    }
+}
+
+impl BinReadExt for Segment {
+    type Error = MovieError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let mut cursor = Cursor::new(bytes);
+        Ok(Self::read(&mut cursor)?)
+    }
+
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Self::Error> {
+        let mut file = File::open(path)?;
+        Ok(Self::read(&mut file)?)
+    }
+}
+
+impl BinWriteExt for Segment {
+    type Error = MovieError;
+
+    fn to_bytes(&self) -> Result<Vec<u8>, Self::Error> {
+        let mut cursor = Cursor::new(Vec::new());
+        self.write(&mut cursor)?;
+        Ok(cursor.into_inner())
+    }
+
+    fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Self::Error> {
+        let mut file = File::create(path)?;
+        self.write(&mut file)?;
+        Ok(())
+    }
+}
+
+impl Movie {
+    /// Inserts the frames of a segment before frame `at`.
+    ///
+    /// The segment must have been recorded with the same controllers present as the movie.
+    pub fn insert_segment(&mut self, at: usize, segment: &Segment) -> Result<(), MovieError> {
+        let required = u32::from(segment.controller_flags) & PRESENT_MASK;
+        let present = u32::from(self.controller_flags()) & PRESENT_MASK;
+        if required != present {
+            return Err(SegmentError::ControllerMismatch {
+                required: required as u8,
+                present: present as u8,
+            }
+            .into());
+        }
+
+        self.insert_frames(at, &segment.inputs)
+    }
+
+    /// Cuts the movie into segments at the frames of the markers, in frame order.
+    ///
+    /// Each segment is named after the marker it starts at, and runs until the next
+    /// marker or the end of the movie. Frames before the first marker make up a leading
+    /// segment with an empty name, which is left out if the first marker is on frame 0.
+    /// Markers sharing a frame produce empty segments.
+    pub fn split_at_markers(&self, markers: &Markers) -> Result<Vec<Segment>, MovieError> {
+        let markers: Vec<_> = markers.iter().collect();
+        let frame_count = self.frame_count();
+
+        let mut cuts = Vec::with_capacity(markers.len() + 1);
+        match markers.first() {
+            Some(first) if first.frame == 0 => {}
+            _ => cuts.push((String::new(), 0)),
+        }
+        cuts.extend(
+            markers
+                .into_iter()
+                .map(|marker| (marker.label, marker.frame)),
+        );
+
+        let ends = cuts
+            .iter()
+            .skip(1)
+            .map(|&(_, frame)| frame)
+            .chain([frame_count]);
+        cuts.iter()
+            .zip(ends)
+            .map(|((label, start), end)| Segment::record(label.as_str(), self, *start..end))
+            .collect()
+    }
+
+    /// Assembles a movie from segments, taking the header from `template`.
+    ///
+    /// Every segment must have been recorded with the controllers present in the
+    /// template, and on its ROM if the segment records one. The frames of the template
+    /// are discarded along with their annotations, markers and lag frames, and the VI
+    /// count is recomputed from its VI/frame ratio.
+    pub fn from_segments(template: &Movie, segments: &[Segment]) -> Result<Movie, MovieError> {
+        let rom_crc32 = template.game_info.rom_crc32;
+        for segment in segments {
+            let found = segment.provenance.rom_crc32;
+            if found != 0 && found != rom_crc32 {
+                return Err(SegmentError::RomMismatch {
+                    name: segment.name.clone(),
+                    expected: rom_crc32,
+                    found,
+                }
+                .into());
+            }
+        }
+
+        let timing = template.timing();
+        let mut movie = template.clone();
+        movie.inputs.clear();
+        *movie.annotations_mut() = Annotations::default();
+        *movie.markers_mut() = Markers::default();
+        movie.set_lag_mask(None);
+        for segment in segments {
+            movie.insert_segment(movie.frame_count(), segment)?;
+        }
+
+        movie.recording_info.vertical_interrupts =
+            timing.frame_to_vi(movie.frame_count()).min(u32::MAX as u64) as u32;
+        Ok(movie)
+    }
+}
@@ -0,0 +1,240 @@
+//! Sets of frames made of several ranges.
+//!
+//! A [`FrameSelection`] scopes an operation to any set of frames, built with set
+//! algebra on ranges. For example, frames 100 to 200 and 5000 to 5100, except 5050:
+//!
+//! ```
+//! use m64_movie::selection::FrameSelection;
+//!
+//! let selection = FrameSelection::from(100..201)
+//!     .union(&(5000..5101).into())
+//!     .difference(&(5050..5051).into());
+//!
+//! assert!(selection.contains(5049));
+//! assert!(!selection.contains(5050));
+//! assert_eq!(selection.to_string(), "100-200,5000-5049,5051-5100");
+//! ```
+//!
+//! Selections are accepted by [`remap_buttons_in`](crate::transform::remap_buttons_in),
+//! [`Movie::to_hex_block_in`], [`Subtitles::from_annotations_in`](crate::export::Subtitles::from_annotations_in)
+//! and [`Movie::lint_axes_in`], and [`Movie::extract`] copies the selected frames to a
+//! new movie.
+
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+};
+
+use crate::{Movie, track::FrameIndex};
+
+/// A set of frames, stored as sorted, disjoint ranges.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct FrameSelection {
+    /// The selected ranges, sorted, non-empty, and neither overlapping nor touching.
+    ranges: Vec<Range<FrameIndex>>,
+}
+
+impl FrameSelection {
+    /// Creates an empty selection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a selection of every frame of a movie.
+    pub fn all(movie: &Movie) -> Self {
+        FrameSelection::from(0..movie.frame_count())
+    }
+
+    /// Adds a range of frames to the selection.
+    pub fn insert(&mut self, range: Range<FrameIndex>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // The ranges touching the new one are merged into it.
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = match self.ranges[first..last] {
+            [] => range,
+            ref touching => {
+                touching[0].start.min(range.start)..touching[touching.len() - 1].end.max(range.end)
+            }
+        };
+
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    /// Returns the frames in either selection.
+    pub fn union(&self, other: &FrameSelection) -> FrameSelection {
+        let mut union = self.clone();
+        for range in &other.ranges {
+            union.insert(range.clone());
+        }
+
+        union
+    }
+
+    /// Returns the frames in both selections.
+    pub fn intersect(&self, other: &FrameSelection) -> FrameSelection {
+        let mut ranges = Vec::new();
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let overlap = x.start.max(y.start)..x.end.min(y.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+
+            if x.end < y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+
+        FrameSelection { ranges }
+    }
+
+    /// Returns the frames of the first `frame_count` frames that are not selected.
+    pub fn invert(&self, frame_count: usize) -> FrameSelection {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for range in &self.ranges {
+            if range.start >= frame_count {
+                break;
+            }
+            if start < range.start {
+                ranges.push(start..range.start);
+            }
+            start = range.end;
+        }
+
+        if start < frame_count {
+            ranges.push(start..frame_count);
+        }
+
+        FrameSelection { ranges }
+    }
+
+    /// Returns the selected frames that are not in `other`.
+    pub fn difference(&self, other: &FrameSelection) -> FrameSelection {
+        let end = self.ranges.last().map_or(0, |range| range.end);
+        self.intersect(&other.invert(end))
+    }
+
+    /// Returns `true` if a frame is selected.
+    pub fn contains(&self, frame: FrameIndex) -> bool {
+        let index = self.ranges.partition_point(|range| range.end <= frame);
+        self.ranges
+            .get(index)
+            .is_some_and(|range| range.contains(&frame))
+    }
+
+    /// Returns the index of a frame among the selected frames, or `None` if it is not
+    /// selected.
+    pub fn position(&self, frame: FrameIndex) -> Option<usize> {
+        let index = self.ranges.partition_point(|range| range.end <= frame);
+        let range = self
+            .ranges
+            .get(index)
+            .filter(|range| range.contains(&frame))?;
+        let before: usize = self.ranges[..index]
+            .iter()
+            .map(ExactSizeIterator::len)
+            .sum();
+        Some(before + frame - range.start)
+    }
+
+    /// Returns the number of selected frames.
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(ExactSizeIterator::len).sum()
+    }
+
+    /// Returns `true` if no frames are selected.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the selected ranges, sorted and disjoint.
+    pub fn ranges(&self) -> &[Range<FrameIndex>] {
+        &self.ranges
+    }
+
+    /// Returns an iterator over the selected frames, in order.
+    pub fn frames(&self) -> impl Iterator<Item = FrameIndex> + '_ {
+        self.ranges.iter().flat_map(Clone::clone)
+    }
+}
+
+impl From<Range<FrameIndex>> for FrameSelection {
+    fn from(range: Range<FrameIndex>) -> Self {
+        let mut selection = FrameSelection::new();
+        selection.insert(range);
+        selection
+    }
+}
+
+impl FromIterator<Range<FrameIndex>> for FrameSelection {
+    fn from_iter<I: IntoIterator<Item = Range<FrameIndex>>>(iter: I) -> Self {
+        let mut selection = FrameSelection::new();
+        for range in iter {
+            selection.insert(range);
+        }
+
+        selection
+    }
+}
+
+/// Formats the selection as a comma-separated list of frames and inclusive ranges,
+/// as for lag frames, e.g. `12,40-45,90`.
+impl Display for FrameSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+
+            match range.len() {
+                1 => write!(f, "{}", range.start)?,
+                _ => write!(f, "{}-{}", range.start, range.end - 1)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Movie {
+    /// Returns a copy of the movie holding only the selected frames, in order.
+    ///
+    /// Frames past the end of the movie are ignored. The VI count is reduced to the VIs
+    /// spanned by the frames kept, as for [`Movie::slice_by_time`]. Annotations,
+    /// markers and lag frames follow their frames, and are dropped if their frame is
+    /// not selected.
+    pub fn extract(&self, selection: &FrameSelection) -> Movie {
+        let timing = self.timing();
+        let count = self.controller_count();
+        let selection = selection.intersect(&FrameSelection::all(self));
+
+        let mut extract = self.clone();
+        extract.inputs = selection
+            .ranges()
+            .iter()
+            .flat_map(|range| &self.inputs[range.start * count..range.end * count])
+            .copied()
+            .collect();
+
+        let vis: u64 = selection
+            .ranges()
+            .iter()
+            .map(|range| timing.frame_to_vi(range.end) - timing.frame_to_vi(range.start))
+            .sum();
+        extract.recording_info.vertical_interrupts = vis.min(u32::MAX as u64) as u32;
+        extract.remap_frame_data(|frame| selection.position(frame));
+
+        extract
+    }
+}
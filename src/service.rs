@@ -0,0 +1,375 @@
+//! A JSON-RPC service for editor frontends.
+//!
+//! [`serve`] reads JSON-RPC 2.0 requests, one per line, and writes one response per
+//! line, so editors written in other languages can drive this crate over stdio or a
+//! socket. The methods are:
+//!
+//! | Method     | Params                                  | Result                          |
+//! |------------|-----------------------------------------|---------------------------------|
+//! | `open`     | `path`                                  | `frameCount`, `controllerCount` |
+//! | `getFrame` | `frame`                                 | the states of the frame         |
+//! | `setFrame` | `frame`, `states`                       | `null`                          |
+//! | `splice`   | `start`, `deleteCount`, `states`        | the removed states              |
+//! | `save`     | `path`, defaulting to the opened file   | `null`                          |
+//!
+//! States are written as by the `serde` implementation of [`ControllerState`], e.g.
+//! `{"buttons": ["A"], "x": 0, "y": 127}`, one per controller and frame.
+//!
+//! Paths are opened and saved as given, so the service must only be reachable by
+//! trusted clients. See [`serve_tcp`].
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, ToSocketAddrs},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+
+use crate::{BinReadExt, BinWriteExt, Movie, MovieError, raw::ControllerState};
+
+/// The JSON-RPC error code of a request that is not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+
+/// The JSON-RPC error code of a request that is not a valid request object.
+const INVALID_REQUEST: i64 = -32600;
+
+/// The JSON-RPC error code of an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The JSON-RPC error code of invalid method parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+/// The JSON-RPC error code of a method that failed, e.g. on a [`MovieError`].
+const SERVER_ERROR: i64 = -32000;
+
+/// The longest request line read, in bytes, excluding the line break.
+pub const MAX_REQUEST_LEN: usize = 16 * 1024 * 1024;
+
+/// A JSON-RPC error returned to the client.
+#[derive(Debug)]
+struct RpcError {
+    /// The JSON-RPC error code.
+    code: i64,
+    /// A description of the error.
+    message: String,
+}
+
+impl RpcError {
+    /// Creates an error with the given code.
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<MovieError> for RpcError {
+    fn from(err: MovieError) -> Self {
+        RpcError::new(SERVER_ERROR, err.to_string())
+    }
+}
+
+/// A JSON-RPC request.
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// The protocol version, which must be `2.0`.
+    jsonrpc: String,
+    /// The request identifier, or `None` for notifications.
+    #[serde(default)]
+    id: Option<Value>,
+    /// The method called.
+    method: String,
+    /// The parameters of the method.
+    #[serde(default)]
+    params: Value,
+}
+
+/// The parameters of `open`.
+#[derive(Debug, Deserialize)]
+struct OpenParams {
+    /// The movie file to open.
+    path: PathBuf,
+}
+
+/// The parameters of `getFrame`.
+#[derive(Debug, Deserialize)]
+struct GetFrameParams {
+    /// The frame to read.
+    frame: usize,
+}
+
+/// The parameters of `setFrame`.
+#[derive(Debug, Deserialize)]
+struct SetFrameParams {
+    /// The frame to replace.
+    frame: usize,
+    /// The new states of the frame, one per controller.
+    states: Vec<ControllerState>,
+}
+
+/// The parameters of `splice`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpliceParams {
+    /// The first frame removed, and where the new frames are inserted.
+    start: usize,
+    /// The number of frames removed.
+    #[serde(default)]
+    delete_count: usize,
+    /// The states of the inserted frames, one per controller and frame.
+    #[serde(default)]
+    states: Vec<ControllerState>,
+}
+
+/// The parameters of `save`.
+#[derive(Debug, Default, Deserialize)]
+struct SaveParams {
+    /// Where to save the movie, defaulting to the opened file.
+    #[serde(default)]
+    path: Option<PathBuf>,
+}
+
+/// The state of a service: the movie opened by the client, if any.
+#[derive(Debug, Default)]
+pub struct Service {
+    /// The opened movie and the file it was read from.
+    opened: Option<(PathBuf, Movie)>,
+}
+
+impl Service {
+    /// Creates a service with no movie opened.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the opened movie, if any.
+    pub fn movie(&self) -> Option<&Movie> {
+        self.opened.as_ref().map(|(_, movie)| movie)
+    }
+
+    /// Handles a single JSON-RPC request, returning the response to send, or `None`
+    /// for notifications.
+    pub fn handle(&mut self, request: &str) -> Option<String> {
+        let (id, result) = match serde_json::from_str::<Value>(request) {
+            Err(err) => (
+                Value::Null,
+                Err(RpcError::new(PARSE_ERROR, err.to_string())),
+            ),
+            Ok(value) => {
+                let id = value.get("id").cloned().unwrap_or(Value::Null);
+                match serde_json::from_value::<Request>(value) {
+                    Ok(request) if request.jsonrpc == "2.0" => {
+                        let result = self.call(&request.method, request.params);
+                        request.id?;
+                        (id, result)
+                    }
+                    Ok(_) => (
+                        id,
+                        Err(RpcError::new(INVALID_REQUEST, "expected jsonrpc 2.0")),
+                    ),
+                    Err(err) => (id, Err(RpcError::new(INVALID_REQUEST, err.to_string()))),
+                }
+            }
+        };
+
+        Some(response(id, result))
+    }
+
+    /// Calls a method.
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "open" => {
+                let OpenParams { path } = parse_params(params)?;
+                let movie = Movie::from_file(&path)?;
+                let result = json!({
+                    "frameCount": movie.frame_count(),
+                    "controllerCount": movie.controller_count(),
+                });
+                self.opened = Some((path, movie));
+                Ok(result)
+            }
+            "getFrame" => {
+                let GetFrameParams { frame } = parse_params(params)?;
+                let states = self.opened()?.frame(frame).ok_or_else(|| {
+                    RpcError::new(INVALID_PARAMS, format!("frame {frame} does not exist"))
+                })?;
+                to_value(states)
+            }
+            "setFrame" => {
+                let SetFrameParams { frame, states } = parse_params(params)?;
+                let target = self.opened_mut()?.frame_mut(frame).ok_or_else(|| {
+                    RpcError::new(INVALID_PARAMS, format!("frame {frame} does not exist"))
+                })?;
+                if target.len() != states.len() {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!("expected {} states, found {}", target.len(), states.len()),
+                    ));
+                }
+
+                target.copy_from_slice(&states);
+                Ok(Value::Null)
+            }
+            "splice" => {
+                let SpliceParams {
+                    start,
+                    delete_count,
+                    states,
+                } = parse_params(params)?;
+                let movie = self.opened_mut()?;
+                // Removing frames drops the annotations, markers and lag frames within
+                // them, so a failed splice restores the whole movie.
+                let original = movie.clone();
+                let removed = movie.remove_frames(start..start.saturating_add(delete_count))?;
+                if let Err(err) = movie.insert_frames(start, &states) {
+                    *movie = original;
+                    return Err(err.into());
+                }
+                to_value(&removed)
+            }
+            "save" => {
+                let SaveParams { path } = if params.is_null() {
+                    SaveParams::default()
+                } else {
+                    parse_params(params)?
+                };
+                let (opened_path, movie) = self.opened.as_ref().ok_or_else(not_opened)?;
+                movie.to_file(path.as_ref().unwrap_or(opened_path))?;
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method {method:?}"),
+            )),
+        }
+    }
+
+    /// Returns the opened movie.
+    fn opened(&self) -> Result<&Movie, RpcError> {
+        self.movie().ok_or_else(not_opened)
+    }
+
+    /// Returns the opened movie, mutably.
+    fn opened_mut(&mut self) -> Result<&mut Movie, RpcError> {
+        self.opened
+            .as_mut()
+            .map(|(_, movie)| movie)
+            .ok_or_else(not_opened)
+    }
+}
+
+/// Formats the response to a request.
+fn response(id: Value, result: Result<Value, RpcError>) -> String {
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": err.code, "message": err.message },
+        }),
+    };
+    response.to_string()
+}
+
+/// The error returned by methods called before `open`.
+fn not_opened() -> RpcError {
+    RpcError::new(SERVER_ERROR, "no movie is open")
+}
+
+/// Deserializes the parameters of a method.
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+}
+
+/// Serializes the result of a method.
+fn to_value<T: serde::Serialize + ?Sized>(value: &T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|err| RpcError::new(SERVER_ERROR, err.to_string()))
+}
+
+/// Serves requests read line by line from `reader`, writing responses to `writer`,
+/// until the end of the input.
+///
+/// A line that is not valid UTF-8, or longer than [`MAX_REQUEST_LEN`] bytes, is
+/// answered with a parse error.
+pub fn serve<R: BufRead, W: Write>(mut reader: R, mut writer: W) -> io::Result<()> {
+    let mut service = Service::new();
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        let read = (&mut reader)
+            .take(MAX_REQUEST_LEN as u64 + 1)
+            .read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        } else if line.len() > MAX_REQUEST_LEN {
+            reader.skip_until(b'\n')?;
+            let err = RpcError::new(
+                PARSE_ERROR,
+                format!("request exceeds {MAX_REQUEST_LEN} bytes"),
+            );
+            writeln!(writer, "{}", response(Value::Null, Err(err)))?;
+            writer.flush()?;
+            continue;
+        }
+
+        let response = match str::from_utf8(&line) {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => service.handle(line),
+            Err(err) => Some(response(
+                Value::Null,
+                Err(RpcError::new(PARSE_ERROR, err.to_string())),
+            )),
+        };
+
+        if let Some(response) = response {
+            writeln!(writer, "{response}")?;
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Serves requests on the standard input and output.
+pub fn serve_stdio() -> io::Result<()> {
+    serve(io::stdin().lock(), io::stdout().lock())
+}
+
+/// Serves requests on a TCP socket, one connection at a time. Each connection has
+/// its own opened movie.
+///
+/// Clients are not authenticated, and `open` and `save` read and write any path the
+/// client names with the permissions of this process. The address must therefore be
+/// a loopback address, e.g. `127.0.0.1:4064`: an address reachable from other
+/// machines is rejected with [`io::ErrorKind::PermissionDenied`].
+///
+/// Errors of a connection, e.g. a reset by the client, are printed to the standard
+/// error and the next connection is served. Only failing to bind returns an error.
+pub fn serve_tcp<A: ToSocketAddrs>(addr: A) -> io::Result<()> {
+    let addrs: Vec<_> = addr.to_socket_addrs()?.collect();
+    if let Some(addr) = addrs.iter().find(|addr| !addr.ip().is_loopback()) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("refusing to serve on {addr}, which is not a loopback address"),
+        ));
+    }
+
+    let listener = TcpListener::bind(&addrs[..])?;
+
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| serve(BufReader::new(stream.try_clone()?), stream));
+        if let Err(err) = result {
+            eprintln!("m64 service: connection failed: {err}");
+        }
+    }
+
+    Ok(())
+}
@@ -1,9 +1,11 @@
 //! Shared types and traits for binary reading and writing.
 
-use std::fmt::{self, Debug, Display};
+use std::{
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+};
 
 use binrw::{BinRead, BinWrite, NullString};
-use fixedstr::zstr;
 
 use crate::{EncodedFixedStrError, MovieError};
 
@@ -20,12 +22,215 @@ impl<const T: usize> Default for Reserved<T> {
     }
 }
 
+impl<const T: usize> Reserved<T> {
+    /// Creates a reserved region holding the given bytes.
+    pub fn new(bytes: [u8; T]) -> Self {
+        Reserved { reserved: bytes }
+    }
+
+    /// Returns the bytes of the region.
+    pub fn as_bytes(&self) -> &[u8; T] {
+        &self.reserved
+    }
+
+    /// Returns `true` if every byte of the region is zero, as the format requires.
+    ///
+    /// Non-zero bytes usually mean an emulator or tool stashed its own data in the
+    /// region.
+    pub fn is_zeroed(&self) -> bool {
+        self.reserved.iter().all(|&b| b == 0)
+    }
+
+    /// Replaces the bytes of the region.
+    pub fn set_bytes(&mut self, bytes: [u8; T]) {
+        self.reserved = bytes;
+    }
+
+    /// Sets every byte of the region to zero.
+    pub fn clear(&mut self) {
+        self.reserved = [0; T];
+    }
+}
+
 impl<const T: usize> Debug for Reserved<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Reserved({} bytes)", T)
     }
 }
 
+/// The byte filling a string field after the string.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum Padding {
+    /// Null bytes, as the format specifies.
+    #[default]
+    Zero,
+    /// Spaces, as written by some emulators. A string padded with spaces cannot end
+    /// with a space, since trailing spaces are read back as padding.
+    Space,
+}
+
+impl Padding {
+    /// Returns the byte the field is padded with.
+    pub const fn byte(self) -> u8 {
+        match self {
+            Padding::Zero => 0,
+            Padding::Space => b' ',
+        }
+    }
+}
+
+/// A fixed-size string field as stored in a file.
+///
+/// The string ends at the first null byte, or spans the whole field if it has none,
+/// so full-length strings without a terminator round-trip unchanged.
+#[derive(Copy, Clone, Eq, PartialEq, BinRead, BinWrite)]
+pub struct RawString<const N: usize> {
+    /// The bytes of the field, including any padding.
+    bytes: [u8; N],
+}
+
+impl<const N: usize> RawString<N> {
+    /// Creates a field from its raw bytes, including any padding.
+    pub fn new(bytes: [u8; N]) -> Self {
+        RawString { bytes }
+    }
+
+    /// Returns the bytes of the string, up to the first null byte.
+    pub fn as_bytes(&self) -> &[u8] {
+        let len = self.bytes.iter().position(|&b| b == 0).unwrap_or(N);
+        &self.bytes[..len]
+    }
+
+    /// Creates a field holding the bytes of a string followed by `padding`.
+    /// The string is truncated to `N` bytes if it is longer.
+    pub fn padded(s: &[u8], padding: Padding) -> Self {
+        let mut bytes = [padding.byte(); N];
+        let len = s.len().min(N);
+        bytes[..len].copy_from_slice(&s[..len]);
+        RawString { bytes }
+    }
+
+    /// Returns the bytes of the field, including any padding.
+    pub fn raw_bytes(&self) -> &[u8; N] {
+        &self.bytes
+    }
+
+    /// Splits the field into the string and what it is padded with.
+    ///
+    /// A field is zero-padded if it holds a null byte, in which case the string ends
+    /// at the first null byte. Otherwise, trailing spaces are taken as space padding.
+    pub fn split_padding(&self) -> (&[u8], Padding) {
+        match self.bytes.iter().position(|&b| b == 0) {
+            Some(len) => (&self.bytes[..len], Padding::Zero),
+            None => {
+                let len = self
+                    .bytes
+                    .iter()
+                    .rposition(|&b| b != b' ')
+                    .map_or(0, |i| i + 1);
+                if len < N {
+                    (&self.bytes[..len], Padding::Space)
+                } else {
+                    (&self.bytes[..], Padding::Zero)
+                }
+            }
+        }
+    }
+
+    /// Returns the length of the string in bytes.
+    pub fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    /// Returns `true` if the string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.as_bytes().is_empty()
+    }
+
+    /// Returns `true` if the string is ASCII.
+    pub fn is_ascii(&self) -> bool {
+        self.as_bytes().is_ascii()
+    }
+}
+
+impl<const N: usize> Default for RawString<N> {
+    fn default() -> Self {
+        RawString { bytes: [0; N] }
+    }
+}
+
+/// Creates a zero-padded field from a string, truncated to `N` bytes if it is longer.
+impl<const N: usize> From<&str> for RawString<N> {
+    fn from(s: &str) -> Self {
+        RawString::padded(s.as_bytes(), Padding::Zero)
+    }
+}
+
+impl<const N: usize> From<String> for RawString<N> {
+    fn from(s: String) -> Self {
+        RawString::from(s.as_str())
+    }
+}
+
+impl<const N: usize> Display for RawString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(self.as_bytes()))
+    }
+}
+
+impl<const N: usize> Debug for RawString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RawString({:?})",
+            String::from_utf8_lossy(self.as_bytes())
+        )
+    }
+}
+
+/// Creates an ASCII [`EncodedFixedStr`] from a string literal at compile time.
+///
+/// The size is inferred from context, or given as the first argument. Strings that
+/// are not ASCII or do not fit fail to compile.
+///
+/// ```
+/// use m64_movie::{ascii_fixed, shared::{Ascii, EncodedFixedStr}};
+///
+/// const VIDEO: EncodedFixedStr<64, Ascii> = ascii_fixed!("Jabo's Direct3D8 1.6");
+/// let rom_name = ascii_fixed!(32, "SUPER MARIO 64");
+/// assert_eq!(rom_name, "SUPER MARIO 64");
+/// ```
+#[macro_export]
+macro_rules! ascii_fixed {
+    ($s:expr) => {
+        const { $crate::shared::EncodedFixedStr::<_, $crate::shared::Ascii>::from_ascii_const($s) }
+    };
+    ($n:expr, $s:expr) => {
+        const { $crate::shared::EncodedFixedStr::<$n, $crate::shared::Ascii>::from_ascii_const($s) }
+    };
+}
+
+/// Creates a UTF-8 [`EncodedFixedStr`] from a string literal at compile time.
+///
+/// The size is inferred from context, or given as the first argument. Strings that
+/// do not fit fail to compile.
+///
+/// ```
+/// use m64_movie::utf8_fixed;
+///
+/// let author = utf8_fixed!(222, "こんにちは");
+/// assert_eq!(author, "こんにちは");
+/// ```
+#[macro_export]
+macro_rules! utf8_fixed {
+    ($s:expr) => {
+        const { $crate::shared::EncodedFixedStr::<_, $crate::shared::Utf8>::from_utf8_const($s) }
+    };
+    ($n:expr, $s:expr) => {
+        const { $crate::shared::EncodedFixedStr::<$n, $crate::shared::Utf8>::from_utf8_const($s) }
+    };
+}
+
 /// A marker type for ASCII encoded strings.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Ascii;
@@ -34,18 +239,127 @@ pub struct Ascii;
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Utf8;
 
-/// An enum representing a fixed-size string that can be either ASCII or UTF-8 encoded.
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// A fixed-size string that can be either ASCII or UTF-8 encoded.
+///
+/// The string may use all `N` bytes, in which case it is stored without a null
+/// terminator.
+#[derive(Copy, Clone)]
 pub struct EncodedFixedStr<const N: usize, E> {
-    /// The underlying fixed-size string value.
-    value: zstr<N>,
+    /// The bytes of the string, zero-padded to `N` bytes.
+    bytes: [u8; N],
+    /// The length of the string in bytes.
+    len: usize,
+    /// What fills the field after the string when it is written.
+    padding: Padding,
     /// A marker to indicate the encoding type.
-    _marker: std::marker::PhantomData<E>,
+    _marker: PhantomData<E>,
 }
 
+impl<const N: usize, E> EncodedFixedStr<N, E> {
+    /// Creates a new `EncodedFixedStr` from a string already validated for the encoding.
+    fn from_validated_str(s: &str) -> Result<Self, MovieError> {
+        if s.len() > N {
+            return Err(EncodedFixedStrError::FixedStrError(format!(
+                "string of {} bytes does not fit in {} bytes",
+                s.len(),
+                N
+            ))
+            .into());
+        }
+
+        let mut bytes = [0; N];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+
+        Ok(EncodedFixedStr {
+            bytes,
+            len: s.len(),
+            padding: Padding::Zero,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Creates a new `EncodedFixedStr` from a string that is known to fit, in a const context.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than `N` bytes. In a const context, this is a
+    /// compile-time error.
+    const fn from_str_const(s: &str) -> Self {
+        let src = s.as_bytes();
+        assert!(src.len() <= N, "string does not fit in the fixed size");
+
+        let mut bytes = [0; N];
+        let mut i = 0;
+        while i < src.len() {
+            bytes[i] = src[i];
+            i += 1;
+        }
+
+        EncodedFixedStr {
+            bytes,
+            len: src.len(),
+            padding: Padding::Zero,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the string as a string slice.
+    pub fn as_str(&self) -> &str {
+        str::from_utf8(&self.bytes[..self.len]).expect("string is validated on construction")
+    }
+
+    /// Returns the length of the string in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns what fills the field after the string when it is written: the padding
+    /// observed when the field was parsed, or zeros for a new string. It can be
+    /// overridden with [`WriteOptions::padding`](crate::parsed::WriteOptions::padding).
+    pub fn padding(&self) -> Padding {
+        self.padding
+    }
+
+    /// Sets what fills the field after the string when it is written.
+    pub(crate) fn set_padding(&mut self, padding: Padding) {
+        self.padding = padding;
+    }
+
+    /// Returns the string with a different padding.
+    pub(crate) fn with_padding(mut self, padding: Padding) -> Self {
+        self.set_padding(padding);
+        self
+    }
+
+    /// Returns the field holding the string followed by `padding`.
+    pub(crate) fn to_raw(&self, padding: Padding) -> RawString<N> {
+        RawString::padded(self.as_str().as_bytes(), padding)
+    }
+}
+
+/// Strings are equal if they hold the same text, regardless of their padding.
+impl<const N: usize, E> PartialEq for EncodedFixedStr<N, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize, E> Eq for EncodedFixedStr<N, E> {}
+
 impl<const N: usize, E> Display for EncodedFixedStr<N, E> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.value)
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl<const N: usize, E> Debug for EncodedFixedStr<N, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncodedFixedStr({:?})", self.as_str())
     }
 }
 
@@ -71,13 +385,19 @@ impl<const N: usize> EncodedFixedStr<N, Utf8> {
         Self::from_utf8_str(s)
     }
 
+    /// Creates a new `EncodedFixedStr` from a UTF-8 string slice in a const context.
+    /// See also [`utf8_fixed!`](crate::utf8_fixed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than `N` bytes.
+    pub const fn from_utf8_const(s: &str) -> Self {
+        Self::from_str_const(s)
+    }
+
     /// Creates a new `EncodedFixedStr` from a UTF-8 string slice.
     pub fn from_utf8_str<S: AsRef<str>>(s: S) -> Result<Self, MovieError> {
-        Ok(EncodedFixedStr {
-            value: zstr::try_make(s.as_ref())
-                .map_err(|err: &str| EncodedFixedStrError::FixedStrError(err.to_string()))?,
-            _marker: std::marker::PhantomData,
-        })
+        Self::from_validated_str(s.as_ref())
     }
 }
 
@@ -101,6 +421,17 @@ impl<const N: usize> EncodedFixedStr<N, Ascii> {
         )
     }
 
+    /// Creates a new `EncodedFixedStr` from an ASCII string slice in a const context.
+    /// See also [`ascii_fixed!`](crate::ascii_fixed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is not ASCII or is longer than `N` bytes.
+    pub const fn from_ascii_const(s: &str) -> Self {
+        assert!(s.is_ascii(), "string is not ASCII");
+        Self::from_str_const(s)
+    }
+
     /// Creates a new `EncodedFixedStr` from an ASCII string slice.
     pub fn from_ascii_str<S: AsRef<str>>(s: S) -> Result<Self, MovieError> {
         let s = s.as_ref();
@@ -109,11 +440,7 @@ impl<const N: usize> EncodedFixedStr<N, Ascii> {
             return Err(EncodedFixedStrError::InvalidAscii(s.to_string()).into());
         }
 
-        Ok(EncodedFixedStr {
-            value: zstr::try_make(s)
-                .map_err(|arg0: &str| EncodedFixedStrError::FixedStrError(arg0.to_string()))?,
-            _marker: std::marker::PhantomData,
-        })
+        Self::from_validated_str(s)
     }
 }
 
@@ -151,8 +478,79 @@ where
     }
 }
 
+impl<const N: usize, E, Err> std::str::FromStr for EncodedFixedStr<N, E>
+where
+    EncodedFixedStr<N, E>: FixedString<Error = Err>,
+{
+    type Err = Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as FixedString>::from_str(s)
+    }
+}
+
+impl<const N: usize, E> PartialEq<str> for EncodedFixedStr<N, E> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize, E> PartialEq<&str> for EncodedFixedStr<N, E> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize, E> PartialEq<String> for EncodedFixedStr<N, E> {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize, E> PartialEq<EncodedFixedStr<N, E>> for str {
+    fn eq(&self, other: &EncodedFixedStr<N, E>) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl<const N: usize, E> PartialEq<EncodedFixedStr<N, E>> for &str {
+    fn eq(&self, other: &EncodedFixedStr<N, E>) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl<const N: usize, E> PartialEq<EncodedFixedStr<N, E>> for String {
+    fn eq(&self, other: &EncodedFixedStr<N, E>) -> bool {
+        self == other.as_str()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize, E> serde::Serialize for EncodedFixedStr<N, E> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize, E> serde::Deserialize<'de> for EncodedFixedStr<N, E>
+where
+    EncodedFixedStr<N, E>: FixedString<Error = MovieError>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Self::from_str(s).map_err(serde::de::Error::custom)
+    }
+}
+
 impl<const N: usize, E> From<EncodedFixedStr<N, E>> for NullString {
     fn from(encoded: EncodedFixedStr<N, E>) -> Self {
-        encoded.value.as_str().into()
+        encoded.as_str().into()
+    }
+}
+
+impl<const N: usize, E> From<EncodedFixedStr<N, E>> for RawString<N> {
+    fn from(encoded: EncodedFixedStr<N, E>) -> Self {
+        encoded.to_raw(encoded.padding)
     }
 }
@@ -0,0 +1,171 @@
+//! A plain-text file stored next to a movie, holding data the M64 format has no room for.
+//!
+//! Each non-empty line holds one entry, starting with the entry kind:
+//!
+//! ```text
+//! # m64-movie sidecar
+//! annotation 1200 Lag spike after the door
+//! annotation 1350 BLJ setup
+//! marker 2400 Bob-omb Battlefield
+//! lag 12,40-45,90
+//! ```
+//!
+//! Lines starting with `#` are comments. Backslashes and line breaks within
+//! free text are escaped as `\\` and `\n`.
+
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    MovieError, SidecarError, annotations::Annotations, counters::MAX_FRAME_COUNT, lag::LagMask,
+    markers::Markers,
+};
+
+/// The comment written at the top of every sidecar file.
+const SIDECAR_HEADER: &str = "# m64-movie sidecar";
+
+/// The extension appended to a movie's file name to locate its sidecar.
+pub const SIDECAR_EXTENSION: &str = "sidecar";
+
+/// Data persisted alongside a movie.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Sidecar {
+    /// Notes attached to frames of the movie.
+    pub annotations: Annotations,
+    /// Named frames marking points of progress.
+    pub markers: Markers,
+    /// The lag frames of the movie, if known.
+    pub lag_mask: Option<LagMask>,
+}
+
+impl Sidecar {
+    /// Returns the sidecar path for a movie file, e.g. `run.m64` becomes `run.m64.sidecar`.
+    pub fn path_for<P: AsRef<Path>>(movie_path: P) -> PathBuf {
+        let mut path = movie_path.as_ref().as_os_str().to_owned();
+        path.push(".");
+        path.push(SIDECAR_EXTENSION);
+        PathBuf::from(path)
+    }
+
+    /// Parses a sidecar from its text representation.
+    ///
+    /// Lag frames are capped below [`MAX_FRAME_COUNT`], the most frames a movie can
+    /// have. Use [`Sidecar::parse_bounded`] when the sidecar belongs to a known movie.
+    pub fn parse(text: &str) -> Result<Self, MovieError> {
+        Sidecar::parse_bounded(text, MAX_FRAME_COUNT)
+    }
+
+    /// Parses a sidecar from its text representation, rejecting lag frames at or past
+    /// `max_frames`, e.g. the frame count of the movie it belongs to.
+    pub fn parse_bounded(text: &str, max_frames: usize) -> Result<Self, MovieError> {
+        let mut sidecar = Sidecar::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim_start();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match kind {
+                "annotation" => {
+                    let (frame, note) = rest.split_once(' ').unwrap_or((rest, ""));
+                    let frame = parse_frame(line_number, frame)?;
+                    sidecar.annotations.insert(frame, unescape(note));
+                }
+                "marker" => {
+                    let (frame, label) = rest.split_once(' ').unwrap_or((rest, ""));
+                    let frame = parse_frame(line_number, frame)?;
+                    sidecar.markers.insert(unescape(label), frame);
+                }
+                "lag" => {
+                    sidecar.lag_mask.get_or_insert_default().parse_list(
+                        line_number,
+                        rest,
+                        max_frames,
+                    )?;
+                }
+                _ => {
+                    return Err(SidecarError::UnknownEntry {
+                        line: line_number,
+                        kind: kind.to_string(),
+                    }
+                    .into());
+                }
+            }
+        }
+
+        Ok(sidecar)
+    }
+
+    /// Reads a sidecar from a file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, MovieError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Writes the sidecar to a file.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), MovieError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+impl Display for Sidecar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", SIDECAR_HEADER)?;
+
+        for (frame, note) in self.annotations.iter() {
+            writeln!(f, "annotation {} {}", frame, escape(note))?;
+        }
+
+        for marker in self.markers.iter() {
+            writeln!(f, "marker {} {}", marker.frame, escape(&marker.label))?;
+        }
+
+        if let Some(lag_mask) = &self.lag_mask {
+            writeln!(f, "lag {}", lag_mask)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a frame index, reporting the offending line on failure.
+fn parse_frame(line: usize, value: &str) -> Result<usize, SidecarError> {
+    value.parse().map_err(|_| SidecarError::InvalidFrame {
+        line,
+        value: value.to_string(),
+    })
+}
+
+/// Escapes free text so that it fits on a single line.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Reverses [`escape`].
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                result.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
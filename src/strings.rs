@@ -0,0 +1,194 @@
+//! Auditing how the string fields of a header round-trip.
+//!
+//! Historical movies were written by many tools, some of which padded strings with
+//! spaces, left garbage after the terminator, or wrote text in a legacy code page.
+//! [`string_fields_report`] inspects the raw bytes of each string field without
+//! failing on any of these, and tells which fields would change if the movie was
+//! re-encoded, before its metadata is normalized.
+
+use std::fmt::{self, Display};
+
+use crate::{Movie, layout::HEADER_FIELDS, parsed::WriteOptions, raw::m64::HEADER_LEN};
+
+/// The string fields of the header, with the encoding the format declares for them.
+const STRING_FIELDS: [(&str, StringEncoding); 7] = [
+    ("rom_name", StringEncoding::Ascii),
+    ("video_plugin", StringEncoding::Ascii),
+    ("sound_plugin", StringEncoding::Ascii),
+    ("input_plugin", StringEncoding::Ascii),
+    ("rsp_plugin", StringEncoding::Ascii),
+    ("author_name", StringEncoding::Utf8),
+    ("description", StringEncoding::Utf8),
+];
+
+/// The encoding of the bytes of a string.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StringEncoding {
+    /// Only ASCII bytes.
+    Ascii,
+    /// Valid UTF-8 with at least one non-ASCII character.
+    Utf8,
+    /// Neither, such as text in a legacy code page.
+    Unknown,
+}
+
+impl StringEncoding {
+    /// Detects the encoding of some bytes.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.is_ascii() {
+            StringEncoding::Ascii
+        } else if str::from_utf8(bytes).is_ok() {
+            StringEncoding::Utf8
+        } else {
+            StringEncoding::Unknown
+        }
+    }
+
+    /// Returns `true` if bytes of this encoding are valid in a field declared with
+    /// the `declared` encoding.
+    pub fn fits(self, declared: StringEncoding) -> bool {
+        match declared {
+            StringEncoding::Ascii => self == StringEncoding::Ascii,
+            StringEncoding::Utf8 => self != StringEncoding::Unknown,
+            StringEncoding::Unknown => true,
+        }
+    }
+}
+
+impl Display for StringEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringEncoding::Ascii => write!(f, "ASCII"),
+            StringEncoding::Utf8 => write!(f, "UTF-8"),
+            StringEncoding::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// What fills a string field after the string.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FieldPadding {
+    /// A null terminator, followed by the rest of the field.
+    Zero,
+    /// Trailing spaces, without a null terminator.
+    Space,
+    /// Nothing: the string spans the whole field.
+    Full,
+}
+
+/// How a string field of a header is stored, and whether it survives re-encoding.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StringFieldReport {
+    /// The name of the field, as in [`HEADER_FIELDS`].
+    pub field: &'static str,
+    /// The encoding the format declares for the field.
+    pub declared: StringEncoding,
+    /// The text of the field, with invalid UTF-8 replaced.
+    pub text: String,
+    /// The encoding detected from the bytes of the text.
+    pub encoding: StringEncoding,
+    /// What fills the field after the text.
+    pub padding: FieldPadding,
+    /// `true` if the field is not zero-filled after the text, i.e. it is padded with
+    /// spaces, or holds non-zero bytes after its null terminator.
+    pub nonstandard_padding: bool,
+    /// `true` if parsing and re-encoding the movie would change the bytes of the
+    /// field, or if the field would be rejected by the parser.
+    pub changes_on_reencode: bool,
+}
+
+impl StringFieldReport {
+    /// Inspects the bytes of a field.
+    fn new(field: &'static str, declared: StringEncoding, bytes: &[u8]) -> Self {
+        let (len, padding) = match bytes.iter().position(|&b| b == 0) {
+            Some(len) => (len, FieldPadding::Zero),
+            None => match bytes.iter().rposition(|&b| b != b' ') {
+                Some(last) if last + 1 == bytes.len() => (bytes.len(), FieldPadding::Full),
+                last => (last.map_or(0, |last| last + 1), FieldPadding::Space),
+            },
+        };
+
+        let text = &bytes[..len];
+        let tail = &bytes[len..];
+        let nonstandard_padding = match padding {
+            FieldPadding::Zero => tail.iter().any(|&b| b != 0),
+            FieldPadding::Space => true,
+            FieldPadding::Full => false,
+        };
+
+        let encoding = StringEncoding::detect(text);
+        // A parsed movie keeps the text and the padding byte, but not what follows
+        // the null terminator.
+        let changes_on_reencode =
+            !encoding.fits(declared) || (padding == FieldPadding::Zero && nonstandard_padding);
+
+        StringFieldReport {
+            field,
+            declared,
+            text: String::from_utf8_lossy(text).into_owned(),
+            encoding,
+            padding,
+            nonstandard_padding,
+            changes_on_reencode,
+        }
+    }
+
+    /// Returns `true` if the field is stored as the format expects and round-trips
+    /// unchanged.
+    pub fn is_clean(&self) -> bool {
+        !self.nonstandard_padding && !self.changes_on_reencode
+    }
+}
+
+impl Display for StringFieldReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?} ({}", self.field, self.text, self.encoding)?;
+        if !self.encoding.fits(self.declared) {
+            write!(f, ", expected {}", self.declared)?;
+        }
+        if self.nonstandard_padding {
+            write!(f, ", nonstandard padding")?;
+        }
+        if self.changes_on_reencode {
+            write!(f, ", changes on re-encode")?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+/// Reports on every string field of an encoded header, in file order.
+///
+/// Only the bytes at the fixed offsets of the fields are read, so this works on files
+/// the parser rejects. Bytes missing from a truncated header are read as zeros.
+pub fn string_fields_report(header: &[u8]) -> Vec<StringFieldReport> {
+    let mut padded = header[..header.len().min(HEADER_LEN)].to_vec();
+    padded.resize(HEADER_LEN, 0);
+
+    HEADER_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let &(_, declared) = STRING_FIELDS.iter().find(|(name, _)| *name == field.name)?;
+            Some(StringFieldReport::new(
+                field.name,
+                declared,
+                &padded[field.range()],
+            ))
+        })
+        .collect()
+}
+
+impl Movie {
+    /// Reports on every string field of the movie, in file order.
+    ///
+    /// If the movie was parsed with [`ParseOptions::retain_source`](crate::parsed::ParseOptions::retain_source),
+    /// the fields that were not edited are inspected as they were in the original
+    /// file. Otherwise, they are inspected as the movie would encode them.
+    pub fn string_fields_report(&self) -> Vec<StringFieldReport> {
+        let mut header = self
+            .header_bytes_with(WriteOptions::default().require_version_3(false))
+            .unwrap_or_default();
+        self.restore_source(&mut header);
+        string_fields_report(&header)
+    }
+}
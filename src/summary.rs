@@ -0,0 +1,107 @@
+//! Lightweight summaries of movies for indexing.
+//!
+//! A [`MovieSummary`] holds what a catalog needs to list, sort and deduplicate
+//! movies, without their inputs. It can be built from a parsed [`Movie`], or from the
+//! header alone with [`MovieSummary::from_header_bytes`] when only the first 1024
+//! bytes of a file have been read.
+
+use std::time::Duration;
+
+use crate::{
+    Movie, MovieError, RawMovie,
+    hash::Fnv1a,
+    raw::{
+        ControllerFlags, MovieStartType,
+        m64::{HEADER_LEN, SAMPLE_LEN},
+    },
+};
+
+/// The header fields and derived values of a movie, without its inputs.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MovieSummary {
+    /// The author name.
+    pub author: String,
+    /// The movie description.
+    pub description: String,
+    /// The internal name of the ROM.
+    pub rom_name: String,
+    /// The CRC32 checksum of the ROM, as stored in the movie header.
+    pub rom_crc32: u32,
+    /// The country code of the ROM.
+    pub rom_country: u16,
+    /// The unique identifier of the movie.
+    pub uid: u32,
+    /// How the movie begins.
+    pub start_type: MovieStartType,
+    /// Flags indicating the presence and capabilities of controllers.
+    pub controller_flags: ControllerFlags,
+    /// The number of controllers present.
+    pub controller_count: usize,
+    /// The number of frames.
+    pub frame_count: usize,
+    /// The number of vertical interrupts.
+    pub vertical_interrupts: u32,
+    /// The number of vertical interrupts per second.
+    pub vis_per_second: u8,
+    /// The number of rerecords.
+    pub rerecord_count: u32,
+    /// The length of the movie, from its VI count. See [`Movie::duration`].
+    pub duration: Duration,
+    /// The 64-bit FNV-1a hash of the encoded inputs, or `None` if the summary was
+    /// built from the header alone.
+    pub input_hash: Option<u64>,
+}
+
+impl MovieSummary {
+    /// Builds a summary from the header of an encoded movie. Bytes past the header are
+    /// ignored, so only the first 1024 bytes of a file need to be read.
+    ///
+    /// The frame count is taken from the header, and the input hash is `None`.
+    pub fn from_header_bytes(bytes: &[u8]) -> Result<Self, MovieError> {
+        let header = RawMovie::read_header_only(&bytes[..HEADER_LEN.min(bytes.len())])?;
+        let frame_count = header.controller_input_samples as usize;
+
+        let mut summary = MovieSummary::from(&Movie::try_from(header)?);
+        summary.frame_count = frame_count;
+        summary.input_hash = None;
+        Ok(summary)
+    }
+}
+
+impl From<&Movie> for MovieSummary {
+    fn from(movie: &Movie) -> Self {
+        let mut input_hash = Fnv1a::new();
+        for &state in movie.samples() {
+            let bytes: [u8; SAMPLE_LEN] = u32::from(state).to_le_bytes();
+            input_hash.update(&bytes);
+        }
+
+        let game_info = &movie.game_info;
+        let recording_info = &movie.recording_info;
+        MovieSummary {
+            author: recording_info.author_name.to_string(),
+            description: recording_info.description.to_string(),
+            rom_name: game_info.rom_name.to_string(),
+            rom_crc32: game_info.rom_crc32,
+            rom_country: game_info.rom_country,
+            uid: recording_info.uid,
+            start_type: recording_info.start_type,
+            controller_flags: movie.controller_flags(),
+            controller_count: movie.controller_count(),
+            frame_count: movie.frame_count(),
+            vertical_interrupts: recording_info.vertical_interrupts,
+            vis_per_second: recording_info.vis_per_second,
+            rerecord_count: recording_info.rerecord_count,
+            duration: movie.duration(),
+            input_hash: Some(input_hash.finish()),
+        }
+    }
+}
+
+impl Movie {
+    /// Returns a summary of the movie for indexing.
+    pub fn summary(&self) -> MovieSummary {
+        MovieSummary::from(self)
+    }
+}
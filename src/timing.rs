@@ -0,0 +1,210 @@
+//! Conversions between frames, vertical interrupts and wall-clock time.
+//!
+//! A movie stores one input sample per frame that the game polls its controllers,
+//! while the console refreshes at a fixed number of vertical interrupts (VIs) per
+//! second. Games usually poll once every one or two VIs, so [`Timing`] maps frames
+//! to VIs using the ratio recorded in the movie header.
+
+use std::{ops::Range, time::Duration};
+
+use crate::{
+    Movie, MovieError,
+    region::{CountryCode, Region},
+};
+
+/// The number of nanoseconds in a second.
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// The timing of a movie, used to convert between frames, VIs and durations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Timing {
+    /// The video standard of the movie's ROM.
+    region: Region,
+    /// The number of VIs per second.
+    vis_per_second: u32,
+    /// The number of VIs spanned by [`Timing::frames`] frames.
+    vis: u64,
+    /// The number of frames spanning [`Timing::vis`] VIs.
+    frames: u64,
+}
+
+impl Timing {
+    /// Creates a timing for a region at its nominal refresh rate, with one VI per frame.
+    pub fn new(region: Region) -> Self {
+        Timing {
+            region,
+            vis_per_second: region.vis_per_second() as u32,
+            vis: 1,
+            frames: 1,
+        }
+    }
+
+    /// Creates the timing of a movie from its header.
+    ///
+    /// The refresh rate is taken from the VI/s field, falling back to the rate of the
+    /// ROM's region if it is zero. The VI/frame ratio is taken from the recorded VI
+    /// count and the number of frames, falling back to one VI per frame for movies
+    /// without inputs or VIs.
+    pub fn from_movie(movie: &Movie) -> Self {
+        let region = CountryCode::from(movie.game_info.rom_country).region();
+        let timing = Timing::new(region);
+
+        let timing = match movie.recording_info.vis_per_second {
+            0 => timing,
+            vis_per_second => timing.with_vis_per_second(vis_per_second as u32),
+        };
+
+        timing.with_vis_per_frame(
+            movie.recording_info.vertical_interrupts as u64,
+            movie.frame_count() as u64,
+        )
+    }
+
+    /// Returns the timing with a different refresh rate. A rate of zero is ignored.
+    pub fn with_vis_per_second(mut self, vis_per_second: u32) -> Self {
+        if vis_per_second != 0 {
+            self.vis_per_second = vis_per_second;
+        }
+
+        self
+    }
+
+    /// Returns the timing with `vis` VIs spanning `frames` frames. The ratio is
+    /// ignored if either value is zero.
+    pub fn with_vis_per_frame(mut self, vis: u64, frames: u64) -> Self {
+        if vis != 0 && frames != 0 {
+            self.vis = vis;
+            self.frames = frames;
+        }
+
+        self
+    }
+
+    /// Returns the video standard of the timing.
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Returns the number of VIs per second.
+    pub fn vis_per_second(&self) -> u32 {
+        self.vis_per_second
+    }
+
+    /// Returns `true` if the refresh rate differs from the nominal rate of the region,
+    /// e.g. a PAL ROM recorded at 60 VI/s.
+    pub fn is_off_region(&self) -> bool {
+        self.vis_per_second != self.region.vis_per_second() as u32
+    }
+
+    /// Returns the average number of VIs per frame.
+    pub fn vis_per_frame(&self) -> f64 {
+        self.vis as f64 / self.frames as f64
+    }
+
+    /// Returns the time at which a VI starts.
+    pub fn vi_to_duration(&self, vi: u64) -> Duration {
+        let nanos = vi as u128 * NANOS_PER_SECOND / self.vis_per_second as u128;
+        Duration::from_nanos(nanos as u64)
+    }
+
+    /// Returns the VI during which the given time falls.
+    pub fn duration_to_vi(&self, duration: Duration) -> u64 {
+        (duration.as_nanos() * self.vis_per_second as u128 / NANOS_PER_SECOND) as u64
+    }
+
+    /// Returns the VI at which a frame starts.
+    pub fn frame_to_vi(&self, frame: usize) -> u64 {
+        (frame as u128 * self.vis as u128 / self.frames as u128) as u64
+    }
+
+    /// Returns the frame during which a VI falls.
+    pub fn vi_to_frame(&self, vi: u64) -> usize {
+        (vi as u128 * self.frames as u128 / self.vis as u128) as usize
+    }
+
+    /// Returns the time at which a frame starts.
+    pub fn frame_to_duration(&self, frame: usize) -> Duration {
+        let nanos = frame as u128 * self.vis as u128 * NANOS_PER_SECOND
+            / (self.frames as u128 * self.vis_per_second as u128);
+        Duration::from_nanos(nanos as u64)
+    }
+
+    /// Returns the frame during which the given time falls.
+    pub fn duration_to_frame(&self, duration: Duration) -> usize {
+        let frame = duration.as_nanos() * self.frames as u128 * self.vis_per_second as u128
+            / (self.vis as u128 * NANOS_PER_SECOND);
+        frame as usize
+    }
+}
+
+impl Movie {
+    /// Returns the timing of the movie, derived from its header.
+    pub fn timing(&self) -> Timing {
+        Timing::from_movie(self)
+    }
+
+    /// Returns the length of the movie, from its recorded VI count.
+    pub fn duration(&self) -> Duration {
+        self.timing()
+            .vi_to_duration(self.recording_info.vertical_interrupts as u64)
+    }
+
+    /// Returns the frame being played at the given time, or `None` if the movie has
+    /// ended by then.
+    ///
+    /// The result is exact for the start times returned by [`Movie::frame_times`].
+    pub fn frame_at(&self, time: Duration) -> Option<usize> {
+        let frame = frame_containing(&self.timing(), time);
+        (frame < self.frame_count()).then_some(frame)
+    }
+
+    /// Returns a copy of the movie holding only the frames played during a range of
+    /// time, e.g. to cut a segment at video timestamps.
+    ///
+    /// A frame is kept if any part of it falls within the range, and the range is
+    /// clamped to the length of the movie. The VI count is reduced to the VIs spanned by
+    /// the frames kept. Annotations, markers and lag frames are moved with their frames,
+    /// and dropped if their frame is not kept.
+    pub fn slice_by_time(&self, range: Range<Duration>) -> Result<Movie, MovieError> {
+        let timing = self.timing();
+        let frame_count = self.frame_count();
+
+        let start = frame_containing(&timing, range.start).min(frame_count);
+        let end = if range.end <= range.start {
+            start
+        } else {
+            let last = frame_containing(&timing, range.end);
+            let end = if timing.frame_to_duration(last) < range.end {
+                last + 1
+            } else {
+                last
+            };
+            end.min(frame_count)
+        };
+
+        let mut slice = self.clone();
+        slice.remove_frames(end..frame_count)?;
+        slice.remove_frames(0..start)?;
+        slice.recording_info.vertical_interrupts =
+            (timing.frame_to_vi(end) - timing.frame_to_vi(start)) as u32;
+
+        Ok(slice)
+    }
+
+    /// Returns an iterator over the start time of each frame.
+    pub fn frame_times(&self) -> impl Iterator<Item = Duration> + '_ {
+        let timing = self.timing();
+        (0..self.frame_count()).map(move |frame| timing.frame_to_duration(frame))
+    }
+}
+
+/// Returns the frame during which the given time falls, correcting for the rounding
+/// of frame start times down to the nanosecond.
+fn frame_containing(timing: &Timing, time: Duration) -> usize {
+    let frame = timing.duration_to_frame(time);
+    if timing.frame_to_duration(frame + 1) <= time {
+        frame + 1
+    } else {
+        frame
+    }
+}
@@ -0,0 +1,409 @@
+//! Input tracks of a single controller.
+
+use crate::{
+    FrameError, Movie, MovieError,
+    hash::Fnv1a,
+    raw::{ControllerState, MergePolicy},
+    transform::{ResampleStrategy, resample_indices},
+};
+
+/// The index of a frame in an [`InputTrack`], starting at 0.
+pub type FrameIndex = usize;
+
+/// The sequence of states of a single controller, one per frame.
+///
+/// A track stores one state per frame by default. Since most frames repeat the state
+/// of the previous one, [`InputTrack::compress`] switches it to a run-length encoded
+/// representation that keeps random access, at the cost of a binary search per read.
+#[derive(Debug, Clone, Default)]
+pub struct InputTrack {
+    /// The controller states, in either representation.
+    storage: Storage,
+    /// The hasher after each frame of the longest prefix hashed since its last edit.
+    /// See [`InputTrack::hash_prefix`].
+    prefix_hashes: Vec<Fnv1a>,
+}
+
+/// The in-memory representation of an [`InputTrack`].
+#[derive(Debug, Clone)]
+enum Storage {
+    /// One state per frame.
+    Plain(Vec<ControllerState>),
+    /// One state per run of equal frames.
+    Runs(Runs),
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Storage::Plain(Vec::new())
+    }
+}
+
+/// Run-length encoded controller states. Adjacent runs always hold different states.
+#[derive(Debug, Clone, Default)]
+struct Runs {
+    /// The exclusive end frame of each run, in increasing order.
+    ends: Vec<usize>,
+    /// The state held for the whole of each run.
+    states: Vec<ControllerState>,
+}
+
+impl Runs {
+    /// Returns the number of frames covered by the runs.
+    fn len(&self) -> usize {
+        self.ends.last().copied().unwrap_or(0)
+    }
+
+    /// Returns the index of the run containing a frame.
+    fn run_of(&self, frame: usize) -> Option<usize> {
+        let run = self.ends.partition_point(|&end| end <= frame);
+        (run < self.ends.len()).then_some(run)
+    }
+
+    /// Returns the state of a frame.
+    fn get(&self, frame: usize) -> Option<ControllerState> {
+        self.run_of(frame).map(|run| self.states[run])
+    }
+
+    /// Appends a frame, extending the last run if it holds the same state.
+    fn push(&mut self, state: ControllerState) {
+        match (self.ends.last_mut(), self.states.last()) {
+            (Some(end), Some(&last)) if last == state => *end += 1,
+            _ => {
+                self.ends.push(self.len() + 1);
+                self.states.push(state);
+            }
+        }
+    }
+
+    /// Replaces the state of a frame, splitting its run and merging the result with
+    /// its neighbours as needed.
+    fn set(&mut self, frame: usize, state: ControllerState) -> Option<ControllerState> {
+        let run = self.run_of(frame)?;
+        let old = self.states[run];
+        if old == state {
+            return Some(old);
+        }
+
+        let start = if run == 0 { 0 } else { self.ends[run - 1] };
+        let end = self.ends[run];
+
+        let mut pieces = Vec::with_capacity(3);
+        if frame > start {
+            pieces.push((frame, old));
+        }
+        let changed = run + pieces.len();
+        pieces.push((frame + 1, state));
+        if frame + 1 < end {
+            pieces.push((end, old));
+        }
+
+        self.ends
+            .splice(run..=run, pieces.iter().map(|&(end, _)| end));
+        self.states
+            .splice(run..=run, pieces.iter().map(|&(_, state)| state));
+
+        if self.states.get(changed + 1) == Some(&state) {
+            self.ends[changed] = self.ends[changed + 1];
+            self.ends.remove(changed + 1);
+            self.states.remove(changed + 1);
+        }
+        if changed > 0 && self.states[changed - 1] == state {
+            self.ends[changed - 1] = self.ends[changed];
+            self.ends.remove(changed);
+            self.states.remove(changed);
+        }
+
+        Some(old)
+    }
+
+    /// Returns an iterator over the state of each frame.
+    fn iter(&self) -> impl Iterator<Item = ControllerState> + '_ {
+        let starts = std::iter::once(0).chain(self.ends.iter().copied());
+        self.states
+            .iter()
+            .zip(self.ends.iter().zip(starts))
+            .flat_map(|(&state, (&end, start))| std::iter::repeat_n(state, end - start))
+    }
+}
+
+impl FromIterator<ControllerState> for Runs {
+    fn from_iter<I: IntoIterator<Item = ControllerState>>(iter: I) -> Self {
+        let mut runs = Runs::default();
+        for state in iter {
+            runs.push(state);
+        }
+
+        runs
+    }
+}
+
+impl InputTrack {
+    /// Creates an empty track.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a track of `frames` frames, calling `f` with each frame index to produce its state.
+    pub fn generate<F>(frames: usize, f: F) -> Self
+    where
+        F: FnMut(usize) -> ControllerState,
+    {
+        (0..frames).map(f).collect()
+    }
+
+    /// Returns the number of frames in the track.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Plain(states) => states.len(),
+            Storage::Runs(runs) => runs.len(),
+        }
+    }
+
+    /// Returns `true` if the track has no frames.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the controller state of a frame.
+    pub fn get(&self, frame: usize) -> Option<ControllerState> {
+        match &self.storage {
+            Storage::Plain(states) => states.get(frame).copied(),
+            Storage::Runs(runs) => runs.get(frame),
+        }
+    }
+
+    /// Replaces the controller state of a frame, returning the previous state.
+    /// Returns `None` and leaves the track unchanged if the frame does not exist.
+    pub fn set(&mut self, frame: usize, state: ControllerState) -> Option<ControllerState> {
+        let old = match &mut self.storage {
+            Storage::Plain(states) => states
+                .get_mut(frame)
+                .map(|target| std::mem::replace(target, state)),
+            Storage::Runs(runs) => runs.set(frame, state),
+        };
+
+        if old.is_some_and(|old| old != state) {
+            self.prefix_hashes.truncate(frame);
+        }
+
+        old
+    }
+
+    /// Appends a frame to the end of the track.
+    pub fn push(&mut self, state: ControllerState) {
+        match &mut self.storage {
+            Storage::Plain(states) => states.push(state),
+            Storage::Runs(runs) => runs.push(state),
+        }
+    }
+
+    /// Returns an iterator over the controller state of each frame.
+    pub fn iter(&self) -> impl Iterator<Item = ControllerState> + '_ {
+        let (plain, runs) = match &self.storage {
+            Storage::Plain(states) => (Some(states.iter().copied()), None),
+            Storage::Runs(runs) => (None, Some(runs.iter())),
+        };
+
+        plain
+            .into_iter()
+            .flatten()
+            .chain(runs.into_iter().flatten())
+    }
+
+    /// Returns the controller states as a vector.
+    pub fn to_vec(&self) -> Vec<ControllerState> {
+        match &self.storage {
+            Storage::Plain(states) => states.clone(),
+            Storage::Runs(runs) => runs.iter().collect(),
+        }
+    }
+
+    /// Switches the track to run-length encoded storage, holding one state per run of
+    /// equal frames. Reads stay random access, and edits keep the track encoded.
+    ///
+    /// This saves memory when runs average more than three frames, as is typical of
+    /// recorded inputs.
+    pub fn compress(&mut self) {
+        if let Storage::Plain(states) = &mut self.storage {
+            self.storage = Storage::Runs(std::mem::take(states).into_iter().collect());
+        }
+    }
+
+    /// Switches the track back to storing one state per frame.
+    pub fn decompress(&mut self) {
+        if let Storage::Runs(runs) = &self.storage {
+            self.storage = Storage::Plain(runs.iter().collect());
+        }
+    }
+
+    /// Returns `true` if the track uses run-length encoded storage.
+    pub fn is_compressed(&self) -> bool {
+        matches!(self.storage, Storage::Runs(_))
+    }
+
+    /// Returns the number of runs of consecutive equal states in the track.
+    pub fn run_count(&self) -> usize {
+        match &self.storage {
+            Storage::Plain(states) => states.chunk_by(|a, b| a == b).count(),
+            Storage::Runs(runs) => runs.states.len(),
+        }
+    }
+
+    /// Returns the 64-bit FNV-1a hash of the frames before `frame`, as little-endian
+    /// samples, or `None` if `frame` is past the end of the track.
+    ///
+    /// An emulator state saved at the start of `frame` is only valid while the frames
+    /// before it are unchanged, so editors can key their greenzone on this hash: after
+    /// an edit at frame N, the states up to and including frame N keep their hash and
+    /// every later one changes.
+    ///
+    /// Hashes are cached, and an edit only discards the hashes of the frames after it,
+    /// so hashing again after an edit only rehashes from the edited frame onward.
+    pub fn hash_prefix(&mut self, frame: FrameIndex) -> Option<u64> {
+        if frame > self.len() {
+            return None;
+        }
+
+        let hashed = self.prefix_hashes.len();
+        if frame > hashed {
+            let mut hasher = self.prefix_hashes.last().copied().unwrap_or_default();
+            let states: Vec<_> = self.iter().skip(hashed).take(frame - hashed).collect();
+            for state in states {
+                hasher.update(&u32::from(state).to_le_bytes());
+                self.prefix_hashes.push(hasher);
+            }
+        }
+
+        Some(match frame {
+            0 => Fnv1a::new().finish(),
+            _ => self.prefix_hashes[frame - 1].finish(),
+        })
+    }
+
+    /// Resamples the track to `ratio` new frames per original frame, e.g. `0.5` for
+    /// inputs from an emulator polling twice per frame. The ratio is rounded to six
+    /// decimal places.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is not a positive, finite number.
+    pub fn resample(&self, ratio: f64, strategy: ResampleStrategy) -> InputTrack {
+        /// The denominator the ratio is rounded to.
+        const SCALE: u64 = 1_000_000;
+
+        assert!(
+            ratio.is_finite() && ratio > 0.0,
+            "resampling ratio must be positive and finite, got {ratio}"
+        );
+
+        let output = (ratio * SCALE as f64).round().max(1.0) as u64;
+        resample_indices(self.len(), output, SCALE, strategy)
+            .into_iter()
+            .map(|frame| self.get(frame).expect("resampled frames are in bounds"))
+            .collect()
+    }
+}
+
+/// Tracks are equal if they hold the same states, whatever their storage.
+impl PartialEq for InputTrack {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for InputTrack {}
+
+impl From<Vec<ControllerState>> for InputTrack {
+    fn from(states: Vec<ControllerState>) -> Self {
+        InputTrack {
+            storage: Storage::Plain(states),
+            prefix_hashes: Vec::new(),
+        }
+    }
+}
+
+impl From<InputTrack> for Vec<ControllerState> {
+    fn from(track: InputTrack) -> Self {
+        match track.storage {
+            Storage::Plain(states) => states,
+            Storage::Runs(runs) => runs.iter().collect(),
+        }
+    }
+}
+
+impl FromIterator<ControllerState> for InputTrack {
+    fn from_iter<I: IntoIterator<Item = ControllerState>>(iter: I) -> Self {
+        InputTrack::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl Extend<ControllerState> for InputTrack {
+    fn extend<I: IntoIterator<Item = ControllerState>>(&mut self, iter: I) {
+        match &mut self.storage {
+            Storage::Plain(states) => states.extend(iter),
+            Storage::Runs(runs) => iter.into_iter().for_each(|state| runs.push(state)),
+        }
+    }
+}
+
+impl Movie {
+    /// Returns the input track of a single controller.
+    pub fn track(&self, port: usize) -> Result<InputTrack, MovieError> {
+        self.check_port(port)?;
+
+        Ok(self
+            .inputs
+            .iter()
+            .skip(port)
+            .step_by(self.controller_count())
+            .copied()
+            .collect())
+    }
+
+    /// Appends `frames` frames to the movie, calling `f` with the frame index and
+    /// controller port to produce each controller state.
+    pub fn extend_with<F>(&mut self, frames: usize, mut f: F)
+    where
+        F: FnMut(usize, usize) -> ControllerState,
+    {
+        let start = self.frame_count();
+        let count = self.controller_count();
+
+        self.inputs.reserve(frames * count);
+        for frame in start..start + frames {
+            for port in 0..count {
+                self.inputs.push(f(frame, port));
+            }
+        }
+    }
+
+    /// Merges a sequence of states into a controller, starting at frame `at`,
+    /// e.g. to layer a correction on top of the existing inputs.
+    pub fn overlay(
+        &mut self,
+        at: usize,
+        port: usize,
+        states: &[ControllerState],
+        policy: MergePolicy,
+    ) -> Result<(), MovieError> {
+        self.check_port(port)?;
+
+        let frame_count = self.frame_count();
+        if at + states.len() > frame_count {
+            return Err(FrameError::OutOfBounds {
+                frame: at + states.len(),
+                frame_count,
+            }
+            .into());
+        }
+
+        for (offset, state) in states.iter().enumerate() {
+            if let Some(target) = self.port_state_mut(at + offset, port) {
+                *target = target.merge(state, policy);
+            }
+        }
+
+        Ok(())
+    }
+}
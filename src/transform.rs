@@ -0,0 +1,265 @@
+//! Whole-movie transformations of controller inputs.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    ControllerButton, Movie, MovieError,
+    raw::ControllerState,
+    region::{CountryCode, Region},
+    selection::FrameSelection,
+};
+
+/// A mapping from buttons to the buttons they are replaced with.
+///
+/// Buttons without a mapping are left unchanged. If several buttons map to the
+/// same target, the target is pressed if any of them is pressed.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct RemapProfile {
+    /// The target of each remapped button.
+    mapping: BTreeMap<ControllerButton, ControllerButton>,
+}
+
+impl RemapProfile {
+    /// Creates a profile that leaves every button unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the profile with `from` remapped to `to`.
+    pub fn with(mut self, from: ControllerButton, to: ControllerButton) -> Self {
+        self.mapping.insert(from, to);
+        self
+    }
+
+    /// Returns the profile with buttons `a` and `b` swapped.
+    pub fn swap(self, a: ControllerButton, b: ControllerButton) -> Self {
+        self.with(a, b).with(b, a)
+    }
+
+    /// Returns the button that `button` is remapped to.
+    pub fn get(&self, button: ControllerButton) -> ControllerButton {
+        self.mapping.get(&button).copied().unwrap_or(button)
+    }
+
+    /// Returns the controller state with its buttons remapped. Analog axes are unchanged.
+    pub fn apply(&self, state: &ControllerState) -> ControllerState {
+        let mut remapped = ControllerState::default();
+        remapped.set_axis(state.x_axis(), state.y_axis());
+
+        for button in state.get_pressed() {
+            remapped.set(self.get(button));
+        }
+
+        remapped
+    }
+}
+
+impl FromIterator<(ControllerButton, ControllerButton)> for RemapProfile {
+    fn from_iter<I: IntoIterator<Item = (ControllerButton, ControllerButton)>>(iter: I) -> Self {
+        RemapProfile {
+            mapping: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Remaps the buttons of a single controller for the whole movie.
+pub fn remap_buttons(
+    movie: &mut Movie,
+    port: usize,
+    profile: &RemapProfile,
+) -> Result<(), MovieError> {
+    remap_buttons_in(movie, port, profile, &FrameSelection::all(movie))
+}
+
+/// Remaps the buttons of a single controller on the selected frames. Frames past the
+/// end of the movie are ignored.
+pub fn remap_buttons_in(
+    movie: &mut Movie,
+    port: usize,
+    profile: &RemapProfile,
+    selection: &FrameSelection,
+) -> Result<(), MovieError> {
+    movie.check_port(port)?;
+
+    let frame_count = movie.frame_count();
+    for frame in selection.frames().take_while(|&frame| frame < frame_count) {
+        if let Some(state) = movie.port_state_mut(frame, port) {
+            *state = profile.apply(state);
+        }
+    }
+
+    Ok(())
+}
+
+/// How frames are picked when inputs are resampled to a different rate.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum ResampleStrategy {
+    /// Each new frame repeats the latest original frame started at or before it,
+    /// dropping or duplicating frames evenly.
+    #[default]
+    HoldLatest,
+    /// Each new frame takes the original frame closest in time.
+    Nearest,
+}
+
+/// Returns, for each frame of a sequence resampled from `input` to `output` frames
+/// per unit of time, the index of the original frame it takes its inputs from.
+pub(crate) fn resample_indices(
+    len: usize,
+    output: u64,
+    input: u64,
+    strategy: ResampleStrategy,
+) -> Vec<usize> {
+    if len == 0 || output == 0 || input == 0 {
+        return Vec::new();
+    }
+
+    let (len, output, input) = (len as u128, output as u128, input as u128);
+    let new_len = (2 * len * output + input) / (2 * input);
+
+    (0..new_len)
+        .map(|frame| {
+            let source = match strategy {
+                ResampleStrategy::HoldLatest => frame * input / output,
+                ResampleStrategy::Nearest => (2 * frame * input + output) / (2 * output),
+            };
+            source.min(len - 1) as usize
+        })
+        .collect()
+}
+
+/// Resamples the inputs of a movie recorded at the refresh rate of `from` to the
+/// refresh rate of `to`, as a starting point for porting a run between regions.
+///
+/// The VI rate and count are updated, and the ROM country is set to North America
+/// or Europe if it does not match `to`. The ROM name and CRC are left unchanged, as
+/// they cannot be derived.
+///
+/// Annotations, markers and lag frames move to the first resampled frame taken from
+/// their frame or a later one.
+pub fn retime(movie: &mut Movie, from: Region, to: Region, strategy: ResampleStrategy) {
+    let (input, output) = (from.vis_per_second() as u64, to.vis_per_second() as u64);
+    let count = movie.controller_count();
+    let frame_count = movie.frame_count();
+
+    let indices = resample_indices(frame_count, output, input, strategy);
+    let inputs: Vec<ControllerState> = indices
+        .iter()
+        .flat_map(|&frame| movie.frame(frame).unwrap_or_default().to_vec())
+        .collect();
+    debug_assert!(count == 0 || inputs.len().is_multiple_of(count));
+    movie.inputs = inputs;
+    movie.remap_frame_data(|frame| {
+        (frame <= frame_count).then(|| indices.partition_point(|&source| source < frame))
+    });
+
+    let info = &mut movie.recording_info;
+    info.vertical_interrupts =
+        (info.vertical_interrupts as u64 * output / input).min(u32::MAX as u64) as u32;
+    info.vis_per_second = to.vis_per_second();
+
+    let game_info = &mut movie.game_info;
+    if CountryCode::from(game_info.rom_country).region() != to {
+        game_info.rom_country = match to {
+            Region::Ntsc => CountryCode::NorthAmerica,
+            Region::Pal => CountryCode::Europe,
+        }
+        .into();
+    }
+}
+
+/// Reverses the order of the frames of a movie, keeping the controllers of each
+/// frame together.
+///
+/// The result is structurally valid but plays back differently, which makes it
+/// useful as a fixture when testing diff and merge logic. Annotations, markers and
+/// lag frames are mirrored with their frames.
+pub fn reverse(movie: &mut Movie) {
+    movie.inputs = reversed_frames(movie);
+
+    let frame_count = movie.frame_count();
+    movie.remap_frame_data(|frame| frame_count.checked_sub(frame)?.checked_sub(1));
+}
+
+/// Reverses the order of the frames of a single controller, leaving the other
+/// controllers unchanged.
+pub fn reverse_port(movie: &mut Movie, port: usize) -> Result<(), MovieError> {
+    let states = movie.track(port)?.to_vec();
+
+    for (frame, reversed) in states.into_iter().rev().enumerate() {
+        if let Some(state) = movie.port_state_mut(frame, port) {
+            *state = reversed;
+        }
+    }
+
+    Ok(())
+}
+
+/// Appends the frames of a movie in reverse order, so it plays forwards then
+/// backwards. The VI count is doubled.
+///
+/// Annotations, markers and lag frames stay on the forward half. They are not
+/// mirrored onto the backward half, since a marker labels a single frame.
+pub fn palindrome(movie: &mut Movie) {
+    let reversed = reversed_frames(movie);
+    movie.inputs.extend(reversed);
+
+    let info = &mut movie.recording_info;
+    info.vertical_interrupts = info.vertical_interrupts.saturating_mul(2);
+}
+
+/// Snaps every stick value of a movie to the nearest multiple of `step`, returning the
+/// number of samples changed.
+///
+/// This cleans up the jitter of inputs converted from analog captures. Ties are
+/// broken towards the center, and values are kept within the range of an axis, so
+/// with a step of 8, 126 snaps to 120. A step of 0 or 1 leaves the movie unchanged.
+pub fn quantize_axes(movie: &mut Movie, step: u8) -> usize {
+    if step <= 1 {
+        return 0;
+    }
+
+    let mut changed = 0;
+    for state in &mut movie.inputs {
+        let (x, y) = state.axis();
+        let snapped = (quantize_axis(x, step), quantize_axis(y, step));
+        if snapped != (x, y) {
+            state.set_axis(snapped.0, snapped.1);
+            changed += 1;
+        }
+    }
+
+    changed
+}
+
+/// Returns the multiple of `step` nearest to an axis value that fits in an axis.
+fn quantize_axis(value: i8, step: u8) -> i8 {
+    let (value, step) = (value as i32, step as i32);
+    let below = value.div_euclid(step) * step;
+    let above = below + step;
+
+    let nearest = match (value - below).cmp(&(above - value)) {
+        std::cmp::Ordering::Less => below,
+        std::cmp::Ordering::Greater => above,
+        std::cmp::Ordering::Equal if below.abs() <= above.abs() => below,
+        std::cmp::Ordering::Equal => above,
+    };
+
+    let fitting = match nearest {
+        n if n > i8::MAX as i32 => n - step,
+        n if n < i8::MIN as i32 => n + step,
+        n => n,
+    };
+    fitting as i8
+}
+
+/// Returns the samples of a movie with its frames in reverse order.
+fn reversed_frames(movie: &Movie) -> Vec<ControllerState> {
+    movie
+        .inputs
+        .chunks(movie.controller_count().max(1))
+        .rev()
+        .flatten()
+        .copied()
+        .collect()
+}
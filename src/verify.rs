@@ -0,0 +1,470 @@
+//! Verification of a movie against the ROM and companion files it is played back with.
+//!
+//! Most desyncs reported against a movie are not caused by the movie at all, but by
+//! a wrong ROM revision or a missing savestate. [`verify`] checks everything that can
+//! be checked before the emulator is even started, and returns a [`VerifyReport`]
+//! with a single pass/fail verdict.
+//!
+//! [`verify_roundtrip`] checks the crate itself against a file, reporting any byte
+//! that does not survive parsing and re-serializing.
+
+use std::fmt::{self, Display};
+
+use crate::{
+    BinReadExt, BinWriteExt, Movie, MovieError, RomError,
+    layout::{ByteDiff, describe_byte_diff},
+    parsed::ExtendedFlags,
+    raw::MovieStartType,
+};
+
+/// The big-endian (.z64) PI configuration word found at the start of every N64 ROM.
+const Z64_PI_CONFIG: u32 = 0x8037_1240;
+
+/// The PI configuration word of a byte-swapped (.v64) ROM, read as big-endian.
+const V64_PI_CONFIG: u32 = 0x3780_4012;
+
+/// The PI configuration word of a little-endian (.n64) ROM, read as big-endian.
+const N64_PI_CONFIG: u32 = 0x4012_3780;
+
+/// The minimum number of bytes required to read an N64 ROM header.
+const ROM_HEADER_LEN: usize = 0x40;
+
+/// The byte order of an N64 ROM dump.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RomFormat {
+    /// A big-endian dump (.z64), in the byte order of the cartridge.
+    BigEndian,
+    /// A dump with the bytes of every 16-bit word swapped (.v64).
+    ByteSwapped,
+    /// A dump with the bytes of every 32-bit word reversed (.n64).
+    LittleEndian,
+}
+
+impl RomFormat {
+    /// Detects the format of a ROM from its first 4 bytes, or returns `None` if they
+    /// are not a known PI configuration word.
+    pub fn detect(rom: &[u8]) -> Option<Self> {
+        let pi_config = u32::from_be_bytes(rom.get(..4)?.try_into().ok()?);
+        match pi_config {
+            Z64_PI_CONFIG => Some(RomFormat::BigEndian),
+            V64_PI_CONFIG => Some(RomFormat::ByteSwapped),
+            N64_PI_CONFIG => Some(RomFormat::LittleEndian),
+            _ => None,
+        }
+    }
+
+    /// Returns the file extension usually given to dumps in this format, without the
+    /// leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            RomFormat::BigEndian => "z64",
+            RomFormat::ByteSwapped => "v64",
+            RomFormat::LittleEndian => "n64",
+        }
+    }
+
+    /// Converts a ROM in this format to big-endian (.z64) byte order. A trailing
+    /// partial word is copied unchanged.
+    pub fn to_big_endian(self, rom: &[u8]) -> Vec<u8> {
+        let mut rom = rom.to_vec();
+        match self {
+            RomFormat::BigEndian => {}
+            RomFormat::ByteSwapped => rom.chunks_exact_mut(2).for_each(|word| word.swap(0, 1)),
+            RomFormat::LittleEndian => rom.chunks_exact_mut(4).for_each(|word| word.reverse()),
+        }
+        rom
+    }
+}
+
+impl Display for RomFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RomFormat::BigEndian => write!(f, "big-endian (.z64)"),
+            RomFormat::ByteSwapped => write!(f, "byte-swapped (.v64)"),
+            RomFormat::LittleEndian => write!(f, "little-endian (.n64)"),
+        }
+    }
+}
+
+/// Header information read from an N64 ROM image.
+///
+/// Values are stored in the same representation that Mupen64 writes to the
+/// movie header, so they can be compared directly against [`GameInfo`](crate::parsed::GameInfo).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RomHeader {
+    /// The byte order of the dump the header was read from.
+    pub format: RomFormat,
+    /// The first ROM checksum (CRC1), as stored in the movie header.
+    pub crc32: u32,
+    /// The internal name of the ROM, with trailing padding removed.
+    pub name: String,
+    /// The country code of the ROM, as stored in the movie header.
+    pub country: u16,
+}
+
+impl RomHeader {
+    /// Reads the header of a ROM image in any [`RomFormat`].
+    ///
+    /// The header is converted to big-endian first, so the values are the same for
+    /// every dump of a ROM.
+    pub fn from_bytes(rom: &[u8]) -> Result<Self, MovieError> {
+        if rom.len() < ROM_HEADER_LEN {
+            return Err(RomError::TooShort(rom.len()).into());
+        }
+
+        let Some(format) = RomFormat::detect(rom) else {
+            let pi_config = u32::from_be_bytes([rom[0], rom[1], rom[2], rom[3]]);
+            return Err(RomError::UnknownFormat(pi_config).into());
+        };
+        let rom = format.to_big_endian(&rom[..ROM_HEADER_LEN]);
+
+        // Mupen64 reads the header as little-endian words, which is why the
+        // checksum and country code appear byte-reversed in the movie.
+        let crc32 = u32::from_le_bytes([rom[0x10], rom[0x11], rom[0x12], rom[0x13]]);
+        let country = u16::from_le_bytes([rom[0x3E], rom[0x3F]]);
+        let name = String::from_utf8_lossy(&rom[0x20..0x34])
+            .trim_end_matches(['\0', ' '])
+            .to_string();
+
+        Ok(RomHeader {
+            format,
+            crc32,
+            name,
+            country,
+        })
+    }
+}
+
+/// Companion files that a movie may depend on, depending on its start type.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Companions<'a> {
+    /// The contents of the savestate (.st) the movie starts from.
+    pub savestate: Option<&'a [u8]>,
+    /// The contents of the save file (EEPROM, SRAM, FlashRAM) the movie starts from.
+    pub save: Option<&'a [u8]>,
+}
+
+/// The outcome of a single verification check.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CheckStatus {
+    /// The check passed.
+    Pass,
+    /// The check found something suspicious that does not necessarily cause a desync.
+    Warn,
+    /// The check failed. The movie is not expected to sync.
+    Fail,
+}
+
+impl Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "PASS"),
+            CheckStatus::Warn => write!(f, "WARN"),
+            CheckStatus::Fail => write!(f, "FAIL"),
+        }
+    }
+}
+
+/// A single verification check and its outcome.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Check {
+    /// A short name identifying the check.
+    pub name: &'static str,
+    /// The outcome of the check.
+    pub status: CheckStatus,
+    /// A human-readable explanation of the outcome.
+    pub message: String,
+}
+
+/// The result of verifying a movie.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct VerifyReport {
+    /// All checks that were performed, in order.
+    pub checks: Vec<Check>,
+}
+
+impl VerifyReport {
+    /// Returns `true` if no check failed. Warnings do not fail verification.
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Fail)
+    }
+
+    /// Returns an iterator over the checks with the given status.
+    pub fn with_status(&self, status: CheckStatus) -> impl Iterator<Item = &Check> {
+        self.checks
+            .iter()
+            .filter(move |check| check.status == status)
+    }
+
+    /// Records the outcome of a check.
+    fn push(&mut self, name: &'static str, status: CheckStatus, message: impl Into<String>) {
+        self.checks.push(Check {
+            name,
+            status,
+            message: message.into(),
+        });
+    }
+}
+
+impl Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "[{}] {}: {}", check.status, check.name, check.message)?;
+        }
+
+        if self.passed() {
+            write!(f, "Result: OK")
+        } else {
+            write!(f, "Result: FAILED")
+        }
+    }
+}
+
+/// Verifies a movie against a ROM image and the companion files it starts from.
+///
+/// The ROM may be in any [`RomFormat`]. Byte-swapped and little-endian dumps are
+/// compared by their big-endian header, and reported with a warning.
+pub fn verify(
+    movie: &Movie,
+    rom: &[u8],
+    companions: &Companions,
+) -> Result<VerifyReport, MovieError> {
+    let rom = RomHeader::from_bytes(rom)?;
+    let mut report = VerifyReport::default();
+
+    check_rom(movie, &rom, &mut report);
+    check_start_type(movie, companions, &mut report);
+    check_header(movie, &mut report);
+
+    Ok(report)
+}
+
+/// Checks that the ROM matches the one the movie was recorded with.
+fn check_rom(movie: &Movie, rom: &RomHeader, report: &mut VerifyReport) {
+    let game_info = &movie.game_info;
+
+    if rom.format == RomFormat::BigEndian {
+        report.push("ROM format", CheckStatus::Pass, rom.format.to_string());
+    } else {
+        report.push(
+            "ROM format",
+            CheckStatus::Warn,
+            format!(
+                "ROM is a {} dump, compared by its big-endian header",
+                rom.format
+            ),
+        );
+    }
+
+    if game_info.rom_crc32 == rom.crc32 {
+        report.push("ROM CRC", CheckStatus::Pass, format!("{:08X}", rom.crc32));
+    } else {
+        report.push(
+            "ROM CRC",
+            CheckStatus::Fail,
+            format!(
+                "movie expects {:08X}, ROM has {:08X}",
+                game_info.rom_crc32, rom.crc32
+            ),
+        );
+    }
+
+    let movie_name = game_info.rom_name.to_string();
+    if movie_name.trim_end() == rom.name {
+        report.push("ROM name", CheckStatus::Pass, rom.name.clone());
+    } else {
+        report.push(
+            "ROM name",
+            CheckStatus::Warn,
+            format!("movie expects {:?}, ROM has {:?}", movie_name, rom.name),
+        );
+    }
+
+    if game_info.rom_country == rom.country {
+        report.push(
+            "ROM country",
+            CheckStatus::Pass,
+            format!("{:#06X}", rom.country),
+        );
+    } else {
+        report.push(
+            "ROM country",
+            CheckStatus::Fail,
+            format!(
+                "movie expects {:#06X}, ROM has {:#06X}",
+                game_info.rom_country, rom.country
+            ),
+        );
+    }
+}
+
+/// Checks that the companion files required by the movie's start type are present.
+fn check_start_type(movie: &Movie, companions: &Companions, report: &mut VerifyReport) {
+    let start_type = movie.recording_info.start_type;
+
+    match (start_type, companions.savestate, companions.save) {
+        (MovieStartType::Snapshot, None, _) => report.push(
+            "Start type",
+            CheckStatus::Fail,
+            "movie starts from a snapshot, but no savestate was given",
+        ),
+        (MovieStartType::Snapshot, Some([]), _) => report.push(
+            "Start type",
+            CheckStatus::Fail,
+            "movie starts from a snapshot, but the savestate is empty",
+        ),
+        (MovieStartType::Snapshot, Some(_), _) => report.push(
+            "Start type",
+            CheckStatus::Pass,
+            "movie starts from the given savestate",
+        ),
+        (MovieStartType::EEPROM, _, None) => report.push(
+            "Start type",
+            CheckStatus::Fail,
+            "movie starts from EEPROM, but no save file was given",
+        ),
+        (MovieStartType::EEPROM, _, Some(_)) => report.push(
+            "Start type",
+            CheckStatus::Pass,
+            "movie starts from the given save file",
+        ),
+        (MovieStartType::PowerOn, None, None) => report.push(
+            "Start type",
+            CheckStatus::Pass,
+            "movie starts from power-on",
+        ),
+        (MovieStartType::PowerOn, _, _) => report.push(
+            "Start type",
+            CheckStatus::Warn,
+            "movie starts from power-on, companion files will be ignored",
+        ),
+    }
+
+    if start_type != MovieStartType::Snapshot && companions.savestate.is_some() {
+        report.push(
+            "Savestate",
+            CheckStatus::Warn,
+            "a savestate was given, but the movie does not start from a snapshot",
+        );
+    }
+}
+
+/// Checks that the header counters are plausible.
+///
+/// The controller count and input sample counter are always consistent in a
+/// parsed [`Movie`], so only the counters that are stored as-is are checked.
+fn check_header(movie: &Movie, report: &mut VerifyReport) {
+    let info = &movie.recording_info;
+
+    report.push(
+        "Controllers",
+        CheckStatus::Pass,
+        format!(
+            "{} controller(s), {} frame(s)",
+            movie.controller_count(),
+            movie.frame_count()
+        ),
+    );
+
+    // Every input poll happens during a vertical interrupt.
+    if info.vertical_interrupts as usize >= movie.frame_count() {
+        report.push(
+            "VI count",
+            CheckStatus::Pass,
+            format!("{} VI(s)", info.vertical_interrupts),
+        );
+    } else {
+        report.push(
+            "VI count",
+            CheckStatus::Warn,
+            format!(
+                "header declares {} VI(s), fewer than the {} input frame(s)",
+                info.vertical_interrupts,
+                movie.frame_count()
+            ),
+        );
+    }
+
+    let timing = movie.timing();
+    if info.vis_per_second == 0 {
+        report.push("VI/s", CheckStatus::Warn, "VI/s is not set");
+    } else if timing.is_off_region() {
+        report.push(
+            "VI/s",
+            CheckStatus::Warn,
+            format!(
+                "{} VI/s does not match the {:?} rate of {} VI/s",
+                info.vis_per_second,
+                timing.region(),
+                timing.region().vis_per_second()
+            ),
+        );
+    } else {
+        report.push(
+            "VI/s",
+            CheckStatus::Pass,
+            format!("{} VI/s", info.vis_per_second),
+        );
+    }
+
+    if let ExtendedFlags::ExtendedFlagsV1 {
+        wiivc_emulation_mode: true,
+    } = movie.metadata.extended_flags
+    {
+        report.push(
+            "WiiVC",
+            CheckStatus::Warn,
+            "movie was recorded in WiiVC emulation mode",
+        );
+    }
+}
+
+/// The result of [`verify_roundtrip`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RoundtripReport {
+    /// The length of the original file.
+    pub original_len: usize,
+    /// The length of the re-serialized movie.
+    pub written_len: usize,
+    /// Every difference between the original and re-serialized bytes, in file order.
+    pub diffs: Vec<ByteDiff>,
+}
+
+impl RoundtripReport {
+    /// Returns `true` if the movie was re-serialized to the exact original bytes.
+    pub fn is_lossless(&self) -> bool {
+        self.diffs.is_empty()
+    }
+}
+
+impl Display for RoundtripReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diff in &self.diffs {
+            writeln!(f, "{diff}")?;
+        }
+
+        if self.is_lossless() {
+            write!(f, "Result: lossless ({} bytes)", self.original_len)
+        } else {
+            write!(f, "Result: {} difference(s)", self.diffs.len())
+        }
+    }
+}
+
+/// Parses a movie, re-serializes it, and reports every byte that changed, mapped to
+/// header fields and input samples.
+///
+/// A lossy round-trip usually means the file holds data this crate does not model,
+/// such as non-zero reserved regions, or trailing bytes after the inputs. Fails if
+/// the bytes are not a valid movie.
+pub fn verify_roundtrip(bytes: &[u8]) -> Result<RoundtripReport, MovieError> {
+    let written = Movie::from_bytes(bytes)?.to_bytes()?;
+
+    Ok(RoundtripReport {
+        original_len: bytes.len(),
+        written_len: written.len(),
+        diffs: describe_byte_diff(bytes, &written),
+    })
+}
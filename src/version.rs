@@ -0,0 +1,156 @@
+//! Version-independent access to the versioned parts of a movie header.
+//!
+//! Every change to the M64 format so far has added fields guarded by a version
+//! number. [`MovieVersion`] exposes those fields as optional values, so code reading
+//! them keeps compiling when another version is supported. [`Movie::version_info`]
+//! returns the [`VersionInfo`] of a movie, which dispatches to the version it was
+//! recorded with.
+
+use crate::{
+    Movie,
+    parsed::{ExtendedData, ExtendedFlags},
+};
+
+/// The versioned fields of a movie header, abstracted over format versions.
+///
+/// Fields that do not exist in a version are `None`.
+pub trait MovieVersion {
+    /// Returns the value of the version field.
+    fn version(&self) -> u32;
+
+    /// Returns the value of the extended version field, or `None` if the version
+    /// has no such field.
+    fn extended_version(&self) -> Option<u8>;
+
+    /// Returns whether the movie was recorded in WiiVC emulation mode, or `None` if
+    /// the version does not record it.
+    fn wiivc_emulation_mode(&self) -> Option<bool>;
+
+    /// Returns the special authorship information, or `None` if the version does
+    /// not record it.
+    fn authorship_info(&self) -> Option<u32>;
+
+    /// Returns the bruteforcing data, or `None` if the version does not record it.
+    fn bruteforce_data(&self) -> Option<u32>;
+
+    /// Returns the high word of the rerecord count, or `None` if the version does
+    /// not record it.
+    fn rerecord_count_high(&self) -> Option<u32>;
+}
+
+/// The versioned fields of a version 3 header.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct V3 {
+    /// The value of the extended version field.
+    pub extended_version: u8,
+    /// The extended flags, valid from extended version 1.
+    pub extended_flags: ExtendedFlags,
+    /// The extended data, valid from extended version 1.
+    pub extended_data: ExtendedData,
+}
+
+impl MovieVersion for V3 {
+    fn version(&self) -> u32 {
+        3
+    }
+
+    fn extended_version(&self) -> Option<u8> {
+        Some(self.extended_version)
+    }
+
+    fn wiivc_emulation_mode(&self) -> Option<bool> {
+        match self.extended_flags {
+            ExtendedFlags::ExtendedFlagsV0 => None,
+            ExtendedFlags::ExtendedFlagsV1 {
+                wiivc_emulation_mode,
+            } => Some(wiivc_emulation_mode),
+        }
+    }
+
+    fn authorship_info(&self) -> Option<u32> {
+        match self.extended_data {
+            ExtendedData::ExtendedDataV0 => None,
+            ExtendedData::ExtendedDataV1 {
+                authorship_info, ..
+            } => Some(authorship_info),
+        }
+    }
+
+    fn bruteforce_data(&self) -> Option<u32> {
+        match self.extended_data {
+            ExtendedData::ExtendedDataV0 => None,
+            ExtendedData::ExtendedDataV1 {
+                bruteforce_data, ..
+            } => Some(bruteforce_data),
+        }
+    }
+
+    fn rerecord_count_high(&self) -> Option<u32> {
+        match self.extended_data {
+            ExtendedData::ExtendedDataV0 => None,
+            ExtendedData::ExtendedDataV1 {
+                rerecord_count_high,
+                ..
+            } => Some(rerecord_count_high),
+        }
+    }
+}
+
+/// The versioned fields of a movie, in the format version it uses.
+///
+/// New variants are added as format versions are supported, so matching on this
+/// enum requires a wildcard arm. Prefer the [`MovieVersion`] methods, which work
+/// with every version.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum VersionInfo {
+    /// A version 3 header, written by every supported Mupen64 release.
+    V3(V3),
+}
+
+impl VersionInfo {
+    /// Returns the version info as the [`MovieVersion`] of its version.
+    fn as_dyn(&self) -> &dyn MovieVersion {
+        match self {
+            VersionInfo::V3(v3) => v3,
+        }
+    }
+}
+
+impl MovieVersion for VersionInfo {
+    fn version(&self) -> u32 {
+        self.as_dyn().version()
+    }
+
+    fn extended_version(&self) -> Option<u8> {
+        self.as_dyn().extended_version()
+    }
+
+    fn wiivc_emulation_mode(&self) -> Option<bool> {
+        self.as_dyn().wiivc_emulation_mode()
+    }
+
+    fn authorship_info(&self) -> Option<u32> {
+        self.as_dyn().authorship_info()
+    }
+
+    fn bruteforce_data(&self) -> Option<u32> {
+        self.as_dyn().bruteforce_data()
+    }
+
+    fn rerecord_count_high(&self) -> Option<u32> {
+        self.as_dyn().rerecord_count_high()
+    }
+}
+
+impl Movie {
+    /// Returns the versioned fields of the movie header.
+    pub fn version_info(&self) -> VersionInfo {
+        let metadata = &self.metadata;
+        VersionInfo::V3(V3 {
+            extended_version: metadata.extended_version,
+            extended_flags: metadata.extended_flags,
+            extended_data: metadata.extended_data,
+        })
+    }
+}
@@ -0,0 +1,418 @@
+use m64_movie::{
+    BinReadExt, ControllerButton, Movie,
+    analysis::{
+        ButtonPress, REPORT_MIN_IDLE_FRAMES, RiskLevel, approx_differing_frames, button_timeline,
+        common_prefix_frames, compare, compare_many, desync_risk, diff_matrix, first_divergence,
+        neutral_ports, report,
+    },
+    markers::Markers,
+    raw::{ControllerFlags, ControllerState, MovieStartType},
+    sidecar::Sidecar,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_markers_order() {
+    let markers = [("castle", 900), ("bowser", 5000), ("key", 4000)]
+        .into_iter()
+        .collect::<Markers>();
+
+    assert_eq!(markers.get("key"), Some(4000));
+    assert_eq!(
+        markers.iter().map(|m| m.label).collect::<Vec<_>>(),
+        ["castle", "key", "bowser"]
+    );
+}
+
+#[test]
+fn test_markers_persist_in_sidecar() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.markers_mut().insert("Enter castle", 900);
+
+    let sidecar = Sidecar::parse(&movie.sidecar().to_string()).unwrap();
+    assert_eq!(sidecar.markers.get("Enter castle"), Some(900));
+}
+
+#[test]
+fn test_compare_movies() {
+    let mut baseline = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    baseline.markers_mut().insert("castle", 900);
+    baseline.markers_mut().insert("key", 4000);
+    baseline.markers_mut().insert("baseline only", 5000);
+
+    let mut candidate = baseline.clone();
+    candidate.remove_frames(1000..1030).unwrap();
+    candidate.insert_neutral_frame_run(4500, 6).unwrap();
+    candidate.markers_mut().remove("baseline only");
+    candidate.markers_mut().insert("key", 3970);
+
+    // Both movies lag on every other VI.
+    baseline
+        .recording_info
+        .set_vertical_interrupts(2 * baseline.frame_count() as u64)
+        .unwrap();
+    candidate
+        .recording_info
+        .set_vertical_interrupts(2 * candidate.frame_count() as u64)
+        .unwrap();
+
+    let comparison = compare(&baseline, &candidate);
+    assert_eq!(comparison.unmatched, ["baseline only"]);
+
+    let frames = comparison
+        .segments
+        .iter()
+        .map(|s| (s.end.as_deref(), s.baseline_frames, s.candidate_frames))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        frames,
+        [
+            (Some("castle"), 900, 900),
+            (Some("key"), 3100, 3070),
+            (None, 3416, 3422)
+        ]
+    );
+
+    assert_eq!(comparison.segments[0].seconds_saved, 0.0);
+    assert_eq!(comparison.segments[1].seconds_saved, 1.0);
+    assert!((comparison.segments[2].seconds_saved + 0.2).abs() < 1e-9);
+    assert!((comparison.seconds_saved() - 0.8).abs() < 1e-9);
+}
+
+#[test]
+fn test_compare_ignores_markers_past_the_end() {
+    let mut baseline = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    baseline.markers_mut().insert("castle", 900);
+    baseline.markers_mut().insert("key", 4000);
+
+    let mut candidate = baseline.clone();
+    candidate.markers_mut().insert("key", 100_000);
+
+    let comparison = compare(&baseline, &candidate);
+    assert_eq!(comparison.unmatched, ["key"]);
+    assert_eq!(
+        comparison
+            .segments
+            .iter()
+            .map(|s| (s.end.as_deref(), s.baseline_frames, s.candidate_frames))
+            .collect::<Vec<_>>(),
+        [(Some("castle"), 900, 900), (None, 6516, 6516)]
+    );
+}
+
+#[test]
+fn test_first_divergence() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let track = movie.track(0).unwrap();
+
+    let mut branch = track.clone();
+    let mut state = branch.get(3000).unwrap();
+    state.set_x_axis(state.x_axis().wrapping_add(1));
+    branch.set(3000, state);
+
+    assert_eq!(common_prefix_frames(&track, &branch), 3000);
+    assert_eq!(first_divergence(&track, &branch), Some(3000));
+    assert_eq!(first_divergence(&track, &track), None);
+
+    let prefix = track.iter().take(500).collect();
+    assert_eq!(common_prefix_frames(&track, &prefix), 500);
+    assert_eq!(first_divergence(&prefix, &track), Some(500));
+}
+
+#[test]
+fn test_desync_risk_of_standard_movie() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let risk = desync_risk(&movie);
+
+    assert_eq!(risk.highest(), RiskLevel::None, "{risk}");
+    assert_eq!(risk.score(), 0);
+    assert!(risk.items.iter().any(|item| item.name == "RSP plugin"));
+}
+
+#[test]
+fn test_desync_risk_scores_risky_fields() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.recording_info.start_type = MovieStartType::Snapshot;
+    movie.plugin_info.input_plugin = "Mupen64 Input".parse().unwrap();
+    movie.recording_info.vis_per_second = 50;
+
+    let mut flags = movie.controller_flags();
+    flags.set_controller_01_has_mempak(true);
+    movie.set_controller_flags(flags).unwrap();
+
+    let risk = desync_risk(&movie);
+    let risky = risk
+        .at_least(RiskLevel::Low)
+        .map(|item| (item.name, item.level))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        risky,
+        [
+            ("Input plugin", RiskLevel::Low),
+            ("Start type", RiskLevel::High),
+            ("Controller paks", RiskLevel::Medium),
+            ("VI/s", RiskLevel::High),
+        ]
+    );
+    assert_eq!(risk.score(), 1 + 5 + 3 + 5);
+    assert_eq!(risk.highest(), RiskLevel::High);
+}
+
+#[test]
+fn test_compare_many_revisions() {
+    let mut first = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    first.markers_mut().insert("castle", 900);
+    first.markers_mut().insert("key", 4000);
+
+    let mut second = first.clone();
+    second.remove_frames(1000..1030).unwrap();
+    second.markers_mut().insert("key", 3970);
+    second.markers_mut().insert("second only", 5000);
+
+    let mut third = second.clone();
+    third.remove_frames(100..110).unwrap();
+    third.markers_mut().insert("castle", 890);
+    third.markers_mut().insert("key", 3960);
+
+    let history = compare_many(&[&first, &second, &third]);
+    assert_eq!(history.unmatched, ["second only"]);
+    assert_eq!(history.total_frames, [7416, 7386, 7376]);
+
+    let rows = history
+        .segments
+        .iter()
+        .map(|s| (s.end.as_deref(), s.frames.clone(), s.deltas.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        [
+            (Some("castle"), vec![900, 900, 890], vec![0, 0, -10]),
+            (Some("key"), vec![3100, 3070, 3070], vec![0, -30, 0]),
+            (None, vec![3416, 3416, 3416], vec![0, 0, 0]),
+        ]
+    );
+
+    assert_eq!(compare_many(&[]), Default::default());
+}
+
+#[test]
+fn test_drop_unused_controllers() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert!(neutral_ports(&movie).is_empty());
+    assert!(movie.drop_unused_controllers().is_empty());
+
+    let samples = movie.remove_frames(0..movie.frame_count()).unwrap();
+    let mut flags = movie.controller_flags();
+    flags.set_controller_02_present(true);
+    flags.set_controller_03_present(true);
+    flags.set_controller_04_present(true);
+    flags.set_controller_02_has_rumblepak(true);
+    flags.set_controller_04_has_mempak(true);
+    flags.set_controller_03_has_mempak(true);
+    movie.set_controller_flags(flags).unwrap();
+    movie.extend_with(samples.len(), |frame, port| match port {
+        0 => samples[frame],
+        2 => samples[samples.len() - 1 - frame],
+        _ => ControllerState::default(),
+    });
+    let original = movie.clone();
+
+    assert_eq!(neutral_ports(&movie), [1, 3]);
+    assert_eq!(movie.drop_unused_controllers(), [1, 3]);
+    assert_eq!(movie.controller_count(), 2);
+    assert_eq!(u32::from(movie.controller_flags()), 0b0000_0100_0101);
+    assert_eq!(movie.track(0).unwrap(), original.track(0).unwrap());
+    assert_eq!(movie.track(1).unwrap(), original.track(2).unwrap());
+
+    // The first controller is kept when every controller is neutral.
+    let mut empty = original.clone();
+    empty.remove_frames(0..empty.frame_count()).unwrap();
+    assert_eq!(empty.drop_unused_controllers(), [1, 2, 3]);
+    assert_eq!(u32::from(empty.controller_flags()), 0b0001);
+}
+
+#[test]
+fn test_diff_matrix() {
+    let a = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut b = a.clone();
+
+    let identical = diff_matrix(&a, &b, 1000);
+    assert_eq!(identical.len(), 8);
+    assert!(identical.differing_buckets().is_empty());
+
+    for frame in 2000..2010 {
+        let state = b.port_state_mut(frame, 0).unwrap();
+        state.set_x_axis(state.x_axis().wrapping_add(1));
+    }
+    b.remove_frames(7400..7416).unwrap();
+
+    let matrix = diff_matrix(&a, &b, 1000);
+    assert_eq!(matrix.frame_count, 7416);
+    assert_eq!(matrix.ports.len(), 1);
+    assert_eq!(matrix.differing_buckets(), [2, 7]);
+    assert_eq!(matrix.combined[2], 10);
+    assert_eq!(matrix.combined[7], 16);
+    assert_eq!(matrix.bucket_frames(7), 7000..7416);
+
+    let strip = matrix.to_string();
+    assert_eq!(strip.lines().count(), 2);
+    assert_eq!(strip.lines().last(), Some("all    |  .    .|"));
+
+    assert_eq!(diff_matrix(&a, &b, 0).bucket, 1);
+}
+
+#[test]
+fn test_button_timeline() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let frame_count = movie.frame_count();
+    for frame in 0..frame_count {
+        let state = movie.port_state_mut(frame, 0).unwrap();
+        state.unset(ControllerButton::Start);
+        if (10..20).contains(&frame) || (30..31).contains(&frame) || frame >= frame_count - 5 {
+            state.set(ControllerButton::Start);
+        }
+    }
+
+    let timeline = button_timeline(&movie, 0).unwrap();
+    assert_eq!(timeline.frame_count, frame_count);
+    assert_eq!(
+        timeline.presses(ControllerButton::Start),
+        [
+            ButtonPress {
+                press_frame: 10,
+                release_frame: 20
+            },
+            ButtonPress {
+                press_frame: 30,
+                release_frame: 31
+            },
+            ButtonPress {
+                press_frame: frame_count - 5,
+                release_frame: frame_count
+            },
+        ]
+    );
+
+    let a_presses = timeline.presses(ControllerButton::A);
+    let a_frames: usize = a_presses
+        .iter()
+        .map(|press| press.release_frame - press.press_frame)
+        .sum();
+    assert_eq!(
+        a_frames,
+        movie.samples().iter().filter(|state| state.a_btn()).count()
+    );
+    assert!(timeline.presses(ControllerButton::Reserved01).is_empty());
+    assert!(button_timeline(&movie, 1).is_err());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_button_timeline_json() {
+    use m64_movie::analysis::ButtonTimeline;
+
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let timeline = button_timeline(&movie, 0).unwrap();
+    let json = timeline.to_json().unwrap();
+    assert!(json.contains("\"press_frame\""));
+    assert!(json.contains("\"A\": ["));
+    assert_eq!(ButtonTimeline::from_json(&json).unwrap(), timeline);
+}
+
+#[test]
+fn test_approx_comparison() {
+    let a = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut b = a.clone();
+    for frame in (0..b.frame_count()).step_by(3) {
+        let state = b.port_state_mut(frame, 0).unwrap();
+        let (x, y) = state.axis();
+        state.set_axis(x.saturating_add(1), y.saturating_sub(1));
+    }
+
+    assert_ne!(a, b);
+    assert!(a.inputs_approx_eq(&b, 1));
+    assert!(!a.inputs_approx_eq(&b, 0));
+    assert!(approx_differing_frames(&a, &b, 1).is_empty());
+
+    b.port_state_mut(500, 0)
+        .unwrap()
+        .toggle(ControllerButton::B);
+    b.port_state_mut(600, 0).unwrap().set_x_axis(-100);
+    let expected = if a.port_state(600, 0).unwrap().x_axis().abs_diff(-100) > 1 {
+        vec![500, 600]
+    } else {
+        vec![500]
+    };
+    assert_eq!(approx_differing_frames(&a, &b, 1), expected);
+
+    let mut shorter = a.clone();
+    shorter.remove_frames(7414..7416).unwrap();
+    assert!(!a.inputs_approx_eq(&shorter, 127));
+    assert_eq!(approx_differing_frames(&a, &shorter, 1), [7414, 7415]);
+}
+
+#[test]
+fn test_report() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    for frame in 1000..1100 {
+        *movie.port_state_mut(frame, 0).unwrap() = ControllerState::default();
+    }
+    movie.port_state_mut(2000, 0).unwrap().set_x_axis(-128);
+
+    let report = report(&movie);
+    assert_eq!(report.frame_count, 7416);
+    assert_eq!(report.vertical_interrupts, 15384);
+    assert!((report.duration_seconds - 256.4).abs() < 0.01);
+    assert!(
+        report
+            .idle_ranges
+            .iter()
+            .any(|range| range.start <= 1000 && range.end >= 1100)
+    );
+    assert!(
+        report
+            .idle_ranges
+            .iter()
+            .all(|range| range.len() >= REPORT_MIN_IDLE_FRAMES)
+    );
+    assert!(report.axis_issues.iter().any(|issue| issue.frame == 2000));
+
+    let a_presses = button_timeline(&movie, 0)
+        .unwrap()
+        .presses(ControllerButton::A)
+        .len();
+    assert_eq!(report.press_counts[&ControllerButton::A], a_presses);
+
+    let csv = report.to_csv();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("metric,value"));
+    assert_eq!(lines.next(), Some("frame_count,7416"));
+    assert!(csv.contains(&format!("presses.A,{a_presses}\n")));
+    assert!(csv.contains(&format!("axis_issues,{}\n", report.axis_issues.len())));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_report_json() {
+    use m64_movie::analysis::MovieReport;
+
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let report = report(&movie);
+    let json = report.to_json().unwrap();
+    assert!(json.contains("\"press_counts\""));
+    assert_eq!(MovieReport::from_json(&json).unwrap(), report);
+}
+
+#[test]
+fn test_drop_unused_controllers_without_controllers() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.remove_frames(0..movie.frame_count()).unwrap();
+    movie
+        .set_controller_flags(ControllerFlags::from(0))
+        .unwrap();
+
+    assert!(movie.drop_unused_controllers().is_empty());
+    assert_eq!(movie.controller_count(), 0);
+}
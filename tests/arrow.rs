@@ -0,0 +1,53 @@
+#![cfg(feature = "arrow")]
+
+use arrow_array::{Array, Int8Array, UInt16Array, UInt64Array};
+use m64_movie::{BinReadExt, Movie};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_movie_to_record_batch() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let batch = movie.to_record_batch().unwrap();
+
+    assert_eq!(batch.num_rows(), movie.frame_count());
+    assert_eq!(batch.schema(), m64_movie::arrow::schema());
+
+    let frames = batch
+        .column_by_name("frame")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<UInt64Array>()
+        .unwrap();
+    assert_eq!(frames.value(100), 100);
+
+    let state = movie.port_state(100, 0).unwrap();
+    let buttons = batch
+        .column(2)
+        .as_any()
+        .downcast_ref::<UInt16Array>()
+        .unwrap();
+    let x = batch
+        .column(3)
+        .as_any()
+        .downcast_ref::<Int8Array>()
+        .unwrap();
+    assert_eq!(buttons.value(100), u32::from(*state) as u16);
+    assert_eq!(x.value(100), state.x_axis());
+}
+
+#[test]
+fn test_movie_to_parquet_file() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    movie.to_parquet_file(file.path()).unwrap();
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file.reopen().unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+    assert_eq!(rows, movie.frame_count());
+}
@@ -0,0 +1,73 @@
+use m64_movie::{
+    BinReadExt, ControllerButton, ConvertError, Movie, MovieError, convert::bizhawk::InputLogLayout,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_input_log_roundtrip() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let log = movie.to_bizhawk_input_log();
+
+    assert!(log.starts_with("[Input]\nLogKey:#Reset|Power|#P1 X Axis|P1 Y Axis|P1 A Up|"));
+    assert!(log.ends_with("|\n[/Input]\n"));
+    assert_eq!(log.lines().count(), movie.frame_count() + 3);
+
+    let samples = InputLogLayout::n64(1).parse(&log).unwrap();
+    assert_eq!(samples, movie.samples());
+}
+
+#[test]
+fn test_input_log_parse_frames() {
+    let layout = InputLogLayout::n64(2);
+    let samples = layout
+        .parse(
+            "|..|   12, -128,UDLR.....Z.A..l...|    0,    0,..................|\n\
+             |..|    0,    0,..................|    0,  127,........S.......LR|",
+        )
+        .unwrap();
+
+    assert_eq!(samples.len(), 4);
+    assert_eq!(samples[0].axis(), (12, -128));
+    assert_eq!(
+        samples[0].get_pressed(),
+        [
+            ControllerButton::Z,
+            ControllerButton::A,
+            ControllerButton::CLeft
+        ]
+    );
+    assert_eq!(samples[3].y_axis(), 127);
+    assert_eq!(
+        samples[3].get_pressed(),
+        [
+            ControllerButton::Start,
+            ControllerButton::TriggerRight,
+            ControllerButton::TriggerLeft
+        ]
+    );
+}
+
+#[test]
+fn test_input_log_parse_errors() {
+    let layout = InputLogLayout::n64(1);
+    for line in [
+        "|..|    0,    0,.....|",
+        "|..|  200,    0,..................|",
+        "|..|    0,    0,..................|    0,    0,..................|",
+        "    0,    0,..................",
+    ] {
+        let text = format!("[Input]\n{line}\n[/Input]");
+        assert!(
+            matches!(
+                layout.parse(&text),
+                Err(MovieError::ConvertError(ConvertError::InvalidLine {
+                    line: 2,
+                    ..
+                }))
+            ),
+            "{line:?} should not parse"
+        );
+    }
+}
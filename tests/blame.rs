@@ -0,0 +1,123 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, ControllerButton,
+    blame::{Blame, BlameRange},
+    bundle::Bundle,
+    edit::MovieEditor,
+    raw::ControllerState,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_blame_ranges_and_text() {
+    let mut blame = Blame::new(100);
+    blame.attribute(10..20, "Alice");
+    blame.attribute(15..30, "bruteforcer");
+    blame.insert(0, 5, "Bob");
+    blame.remove(50..60);
+
+    assert_eq!(blame.len(), 95);
+    assert_eq!(blame.author_of(0), Some("Bob"));
+    assert_eq!(blame.author_of(5), None);
+    assert_eq!(blame.author_of(19), Some("Alice"));
+    assert_eq!(blame.author_of(20), Some("bruteforcer"));
+    assert_eq!(blame.author_of(95), None);
+    assert_eq!(
+        blame.ranges(12..40),
+        [
+            BlameRange {
+                frames: 12..15,
+                author: None,
+            },
+            BlameRange {
+                frames: 15..20,
+                author: Some("Alice"),
+            },
+            BlameRange {
+                frames: 20..35,
+                author: Some("bruteforcer"),
+            },
+            BlameRange {
+                frames: 35..40,
+                author: None,
+            },
+        ]
+    );
+
+    let text = blame.to_string();
+    assert_eq!(
+        text,
+        "# m64-movie blame\nframes 95\n0-4 Bob\n15-19 Alice\n20-34 bruteforcer\n"
+    );
+    assert_eq!(Blame::parse(&text).unwrap(), blame);
+
+    assert!(Blame::parse("frames 10\n5-20 Alice").is_err());
+    assert!(Blame::parse("frames ten").is_err());
+    assert!(Blame::parse("frames 10\nAlice").is_err());
+    assert!(Blame::parse("frames 100000000000000").is_err());
+    assert!(Blame::parse_bounded("frames 10", 9).is_err());
+    assert_eq!(Blame::parse_bounded("frames 10", 10).unwrap().len(), 10);
+}
+
+#[test]
+fn test_editor_tracks_blame() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), MOVIE_1KEY_BYTES).unwrap();
+
+    let mut editor = MovieEditor::open(file.path())
+        .unwrap()
+        .track_blame(Blame::new(0), "Alice");
+    let frame_count = editor.movie().frame_count();
+    assert_eq!(editor.blame().unwrap().len(), frame_count);
+
+    editor.frame_mut(frame_count - 1).unwrap()[0].set(ControllerButton::A);
+    editor.set_author("Bob");
+    editor
+        .insert_frames(100, &[ControllerState::default(); 3])
+        .unwrap();
+    editor
+        .port_state_mut(200, 0)
+        .unwrap()
+        .set(ControllerButton::B);
+    editor.remove_frames(0..50).unwrap();
+
+    let blame = editor.blame().unwrap();
+    assert_eq!(blame.len(), editor.movie().frame_count());
+    assert_eq!(blame.author_of(frame_count - 1 + 3 - 50), Some("Alice"));
+    assert_eq!(
+        blame.ranges(0..blame.len()),
+        [
+            BlameRange {
+                frames: 0..50,
+                author: None,
+            },
+            BlameRange {
+                frames: 50..53,
+                author: Some("Bob"),
+            },
+            BlameRange {
+                frames: 53..150,
+                author: None,
+            },
+            BlameRange {
+                frames: 150..151,
+                author: Some("Bob"),
+            },
+            BlameRange {
+                frames: 151..blame.len() - 1,
+                author: None,
+            },
+            BlameRange {
+                frames: blame.len() - 1..blame.len(),
+                author: Some("Alice"),
+            },
+        ]
+    );
+
+    // The blame is persisted in the project bundle.
+    let bundle = Bundle::new(editor.movie().clone()).with_blame(blame.clone());
+    let read = Bundle::from_bytes(&bundle.to_bytes().unwrap()).unwrap();
+    assert_eq!(read.blame.as_ref(), Some(blame));
+    assert_eq!(read, bundle);
+}
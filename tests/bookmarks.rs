@@ -0,0 +1,59 @@
+use m64_movie::{annotations::Annotations, bookmarks::Bookmarks, lag::LagMask, markers::Markers};
+
+#[test]
+fn test_bookmarks_nearest() {
+    let mut bookmarks: Bookmarks<u32> = [(10, 1), (20, 2), (30, 3)].into_iter().collect();
+
+    assert_eq!(bookmarks.nearest_before(9), None);
+    assert_eq!(bookmarks.nearest_before(10), Some((10, &1)));
+    assert_eq!(bookmarks.nearest_before(29), Some((20, &2)));
+    assert_eq!(bookmarks.nearest_after(21), Some((30, &3)));
+    assert_eq!(bookmarks.nearest_after(31), None);
+    assert_eq!(
+        bookmarks.range(15..=30).collect::<Vec<_>>(),
+        [(20, &2), (30, &3)]
+    );
+
+    *bookmarks.get_mut(20).unwrap() = 4;
+    let invalidated = bookmarks.split_off(20);
+    assert_eq!(invalidated.iter().collect::<Vec<_>>(), [(20, &4), (30, &3)]);
+    assert_eq!(bookmarks.frames().collect::<Vec<_>>(), [10]);
+}
+
+#[test]
+fn test_nearest_annotation_marker_and_lag() {
+    let annotations: Annotations = [(100, "door"), (250, "star")].into_iter().collect();
+    assert_eq!(annotations.nearest_before(249), Some((100, "door")));
+
+    let markers: Markers = [("bob", 500), ("wf", 1500), ("a", 1500)]
+        .into_iter()
+        .collect();
+    assert_eq!(markers.nearest_before(499), None);
+    assert_eq!(markers.nearest_before(2000).unwrap().label, "wf");
+
+    let lag: LagMask = [3, 4, 9].into_iter().collect();
+    assert_eq!(lag.last_lag_before(8), Some(4));
+    assert_eq!(lag.to_string(), "3-4,9");
+}
+
+#[test]
+fn test_markers_nearest_after_edits() {
+    let mut markers: Markers = [("bob", 500), ("wf", 1500)].into_iter().collect();
+
+    assert_eq!(markers.insert("wf", 400), Some(1500));
+    assert_eq!(markers.nearest_before(2000).unwrap().label, "bob");
+    assert_eq!(markers.nearest_before(450).unwrap().label, "wf");
+
+    markers.remove("bob");
+    assert_eq!(markers.nearest_before(2000).unwrap().frame, 400);
+    assert_eq!(
+        markers
+            .iter()
+            .map(|marker| marker.frame)
+            .collect::<Vec<_>>(),
+        [400]
+    );
+
+    markers.clear();
+    assert_eq!(markers.nearest_before(2000), None);
+}
@@ -0,0 +1,201 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, BundleError, Movie, MovieError,
+    blame::Blame,
+    bundle::{Bundle, Manifest, RomManifest},
+    verify::Companions,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+/// Builds a minimal big-endian ROM image.
+fn make_rom() -> Vec<u8> {
+    let mut rom = vec![0u8; 0x1000];
+    rom[0..4].copy_from_slice(&0x8037_1240u32.to_be_bytes());
+    rom[0x10..0x14].copy_from_slice(&0x4EAA_3D0Eu32.to_be_bytes());
+    rom[0x20..0x34].copy_from_slice(b"SUPER MARIO 64      ");
+    rom[0x3E] = b'J';
+    rom
+}
+
+#[test]
+fn test_bundle_roundtrip() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.markers_mut().insert("castle", 900);
+
+    let rom = make_rom();
+    let bundle = Bundle::new(movie)
+        .with_savestate(vec![1, 2, 3])
+        .with_save_file("SUPER MARIO 64.eep", vec![4; 512])
+        .with_rom(&rom)
+        .unwrap();
+
+    let read = Bundle::from_bytes(&bundle.to_bytes().unwrap()).unwrap();
+    assert_eq!(read, bundle);
+    assert_eq!(read.movie.markers().get("castle"), Some(900));
+    assert_eq!(read.companions().savestate, Some(&[1, 2, 3][..]));
+    assert_eq!(read.companions().save.map(<[u8]>::len), Some(512));
+
+    let manifest = read.rom.unwrap();
+    assert_eq!(manifest.crc32, read.movie.game_info.rom_crc32);
+    assert_eq!(manifest.name, "SUPER MARIO 64");
+    assert!(manifest.matches(&rom));
+
+    // A byte-swapped dump of the same ROM matches too.
+    let swapped: Vec<u8> = rom.chunks(2).flat_map(|w| [w[1], w[0]]).collect();
+    assert!(manifest.matches(&swapped));
+    assert!(!manifest.matches(&rom[..0x800]));
+}
+
+#[test]
+fn test_bundle_rejects_invalid_archives() {
+    let bundle = Bundle::new(Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap());
+    let bytes = bundle.to_bytes().unwrap();
+    assert_eq!(&bytes[..5], b"M64B\x1A");
+
+    // An archive with no entries.
+    let empty = [&bytes[..7], &0u32.to_le_bytes()[..]].concat();
+    assert!(matches!(
+        Bundle::from_bytes(&empty),
+        Err(MovieError::BundleError(BundleError::MissingMovie))
+    ));
+
+    // An archive with an extra entry.
+    let mut unknown = bytes.clone();
+    unknown[7..11].copy_from_slice(&2u32.to_le_bytes());
+    unknown.extend_from_slice(b"notes.txt\0");
+    unknown.extend_from_slice(&0u32.to_le_bytes());
+    assert!(matches!(
+        Bundle::from_bytes(&unknown),
+        Err(MovieError::BundleError(BundleError::UnknownEntry(name))) if name == "notes.txt"
+    ));
+
+    assert!(RomManifest::from_rom(&[0; 16]).is_err());
+}
+
+#[test]
+fn test_manifest() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let bundle = Bundle::new(movie)
+        .with_savestate(vec![1, 2, 3])
+        .with_rom(&make_rom())
+        .unwrap();
+
+    let manifest = bundle.manifest().unwrap();
+    assert_eq!(manifest.frame_count, 7416);
+    assert_eq!(manifest.rom.crc32, 0x0E3D_AA4E);
+    assert_eq!(manifest.rom.name, "SUPER MARIO 64");
+    assert_eq!(
+        manifest.rom.sha256,
+        Some(m64_movie::hash::sha256(&make_rom()))
+    );
+    assert_eq!(manifest.versions.format, 3);
+    assert_eq!(manifest.versions.input_plugin, "TAS Input");
+    assert_eq!(manifest.versions.m64_movie, env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        manifest
+            .files
+            .iter()
+            .map(|file| (file.name.as_str(), file.size))
+            .collect::<Vec<_>>(),
+        [
+            ("movie.m64", MOVIE_1KEY_BYTES.len()),
+            ("movie.st", 3),
+            (
+                "rom.manifest",
+                bundle.rom.as_ref().unwrap().to_string().len()
+            ),
+        ]
+    );
+    assert_eq!(
+        manifest.files[0].sha256,
+        m64_movie::hash::sha256(MOVIE_1KEY_BYTES)
+    );
+
+    let bare = manifest_of(&bundle.movie);
+    assert_eq!(bare.rom.sha256, None);
+    assert_eq!(bare.files.len(), 1);
+}
+
+#[test]
+fn test_manifest_hashes_every_entry() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.markers_mut().insert("start", 0);
+    let bundle = Bundle::new(movie)
+        .with_blame(Blame::new(7416))
+        .with_save_file("a.eep", vec![1; 4])
+        .with_save_file("b.sra", vec![2; 8]);
+
+    let manifest = bundle.manifest().unwrap();
+    assert_eq!(
+        manifest
+            .files
+            .iter()
+            .map(|file| file.name.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "movie.m64",
+            "movie.m64.sidecar",
+            "movie.blame",
+            "save/a.eep",
+            "save/b.sra"
+        ]
+    );
+    assert_eq!(manifest.files[4].size, 8);
+}
+
+/// Builds the manifest of a movie without companion files.
+fn manifest_of(movie: &Movie) -> Manifest {
+    m64_movie::bundle::manifest(movie, &Companions::default()).unwrap()
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_manifest_toml_roundtrip() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let manifest = manifest_of(&movie);
+    let toml = manifest.to_toml().unwrap();
+    assert_eq!(Manifest::from_toml(&toml).unwrap(), manifest);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_manifest_json_roundtrip() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let manifest = manifest_of(&movie);
+    let json = manifest.to_json().unwrap();
+    assert!(json.contains("\"crc32\""));
+    assert_eq!(Manifest::from_json(&json).unwrap(), manifest);
+}
+
+#[test]
+fn test_bundle_rejects_blame_longer_than_movie() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let bytes = Bundle::new(movie)
+        .with_blame(Blame::new(7417))
+        .to_bytes()
+        .unwrap();
+    assert!(matches!(
+        Bundle::from_bytes(&bytes),
+        Err(MovieError::BlameError(_))
+    ));
+}
+
+#[test]
+fn test_bundle_rejects_lag_past_the_end_of_the_movie() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.set_lag_mask(Some([7415, 7416].into_iter().collect()));
+    let bytes = Bundle::new(movie).to_bytes().unwrap();
+    assert!(matches!(
+        Bundle::from_bytes(&bytes),
+        Err(MovieError::SidecarError(_))
+    ));
+}
+
+#[test]
+fn test_manifest_digests_are_sha256() {
+    assert_eq!(
+        m64_movie::hash::sha256(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
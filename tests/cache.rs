@@ -0,0 +1,54 @@
+#![cfg(feature = "cache")]
+
+use m64_movie::{BinReadExt, BinWriteExt, Movie, cache::SummaryCache};
+
+static MOVIE_1KEY_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64");
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_summary_cache_hits() {
+    let mut cache = SummaryCache::new();
+    let summary = cache.summary(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(
+        summary,
+        Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap().summary()
+    );
+    assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+    assert_eq!(cache.summary_of_file(MOVIE_1KEY_PATH).unwrap(), summary);
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    assert_eq!(cache.len(), 1);
+
+    // Changed bytes are parsed again.
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.recording_info.add_rerecords(1).unwrap();
+    let edited = cache.summary(&movie.to_bytes().unwrap()).unwrap();
+    assert_eq!(edited.rerecord_count, summary.rerecord_count + 1);
+    assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+    assert!(cache.summary(&MOVIE_1KEY_BYTES[..0x200]).is_err());
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_summary_cache_persists() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("summaries.json");
+
+    let mut cache = SummaryCache::open(&path).unwrap();
+    assert!(cache.is_empty());
+    let collection = cache.collect([MOVIE_1KEY_PATH, MOVIE_1KEY_PATH]).unwrap();
+    assert_eq!(collection.len(), 2);
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    cache.save().unwrap();
+
+    let mut reopened = SummaryCache::open(&path).unwrap();
+    assert_eq!(reopened.len(), 1);
+    assert_eq!(
+        reopened.summary_of_file(MOVIE_1KEY_PATH).unwrap(),
+        collection.first().unwrap().summary
+    );
+    assert_eq!((reopened.hits(), reopened.misses()), (1, 0));
+}
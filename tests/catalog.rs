@@ -0,0 +1,128 @@
+use std::{fs, time::Duration};
+
+use m64_movie::{
+    BinReadExt, Movie,
+    catalog::{
+        Collection, IndexField, by_author, by_rom_crc, duration, index, rerecord_count,
+        shorter_than,
+    },
+};
+
+static MOVIE_120STAR_BYTES: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/movies/120 star tas (2012).m64"
+));
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+fn collection() -> Collection {
+    let mut collection = Collection::new();
+    for (path, bytes) in [
+        ("120 star tas (2012).m64", MOVIE_120STAR_BYTES),
+        ("1key.m64", MOVIE_1KEY_BYTES),
+    ] {
+        collection.push(path, Movie::from_bytes(bytes).unwrap().summary());
+    }
+
+    let mut edited = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    edited.game_info.rom_crc32 = 0x635A_2BFF;
+    edited.recording_info.set_rerecord_count(1).unwrap();
+    collection.push("1key (USA).m64", edited.summary());
+
+    collection
+}
+
+fn paths(collection: &Collection) -> Vec<&str> {
+    collection
+        .iter()
+        .map(|entry| entry.path.to_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_collection_filter_and_sort() {
+    let collection = collection();
+    assert_eq!(collection.len(), 3);
+
+    let sorted = collection.clone().sort_by(duration);
+    assert_eq!(sorted.first().unwrap().path.to_str(), Some("1key.m64"));
+    assert_eq!(
+        paths(&sorted.clone().reverse())[0],
+        "120 star tas (2012).m64"
+    );
+
+    let japanese = collection
+        .clone()
+        .filter(by_rom_crc(0x0E3D_AA4E))
+        .sort_by(rerecord_count);
+    assert!(
+        !japanese
+            .iter()
+            .any(|entry| entry.path.ends_with("1key (USA).m64"))
+    );
+
+    let short = collection
+        .clone()
+        .filter(shorter_than(Duration::from_secs(60 * 10)));
+    assert_eq!(paths(&short), ["1key.m64", "1key (USA).m64"]);
+
+    assert!(
+        collection
+            .clone()
+            .filter(by_author("\u{0}nobody"))
+            .is_empty()
+    );
+    assert_eq!(collection.take(1).len(), 1);
+}
+
+#[test]
+fn test_catalog_index_search() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("wip")).unwrap();
+    fs::write(dir.path().join("120 star.m64"), MOVIE_120STAR_BYTES).unwrap();
+    fs::write(dir.path().join("wip").join("1key.M64"), MOVIE_1KEY_BYTES).unwrap();
+    fs::write(dir.path().join("broken.m64"), &MOVIE_1KEY_BYTES[..0x100]).unwrap();
+    fs::write(dir.path().join("notes.txt"), "not a movie").unwrap();
+
+    let index = index(dir.path()).unwrap();
+    assert_eq!(index.len(), 2);
+    assert_eq!(index.skipped(), [dir.path().join("broken.m64")]);
+
+    let hits = index.search("CONSOLE timing");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].field, IndexField::Description);
+    assert!(hits[0].entry.path.ends_with("wip/1key.M64"));
+
+    let hits = index.search("mkdasher");
+    assert_eq!(hits.len(), 2);
+    assert!(hits.iter().all(|hit| hit.field == IndexField::Author));
+
+    let hits = index.search("direct3d8");
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].field, IndexField::Plugin);
+
+    let hits = index.search_fuzzy("rikuu saved");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].distance, 1);
+    assert!(hits[0].entry.description.contains("Rikku"));
+
+    let hits = index.search_fuzzy("kehne tyler");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].distance, 0);
+
+    assert!(index.search_fuzzy("blj").is_empty());
+    assert!(index.search_fuzzy("").is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_catalog_index_skips_directory_links() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("wip")).unwrap();
+    fs::write(dir.path().join("wip").join("1key.m64"), MOVIE_1KEY_BYTES).unwrap();
+    std::os::unix::fs::symlink(dir.path(), dir.path().join("wip").join("loop")).unwrap();
+
+    let index = index(dir.path()).unwrap();
+    assert_eq!(index.len(), 1);
+}
@@ -0,0 +1,56 @@
+use m64_movie::{
+    BinReadExt, ControllerButton, Movie, chord::Chord, raw::ControllerState,
+    selection::FrameSelection,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_chord_matches() {
+    let chord: Chord = [ControllerButton::A, ControllerButton::Z]
+        .into_iter()
+        .collect();
+    assert_eq!(chord.buttons(), [ControllerButton::Z, ControllerButton::A]);
+    assert!(chord.matches(&ControllerState::build().a().z().b().done()));
+    assert!(!chord.matches(&ControllerState::build().a().done()));
+
+    let exclusive = chord.clone().exclusive(true);
+    assert!(exclusive.matches(&ControllerState::build().a().z().stick(5, 5).done()));
+    assert!(!exclusive.matches(&ControllerState::build().a().z().b().done()));
+
+    let stick = Chord::new().x(-10..=10).y(100..=127);
+    assert!(stick.matches(&ControllerState::build().stick(0, 127).done()));
+    assert!(!stick.matches(&ControllerState::build().stick(11, 127).done()));
+    assert!(Chord::new().matches(&ControllerState::default()));
+}
+
+#[test]
+fn test_replace_states_in_selection() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let c_up_stick_up = ControllerState::build().c_up().stick(0, 100).done();
+    for frame in [10, 20, 30, 5000] {
+        *movie.port_state_mut(frame, 0).unwrap() = c_up_stick_up;
+    }
+
+    let matcher = Chord::new()
+        .with(ControllerButton::CUp)
+        .y(1..=i8::MAX)
+        .exclusive(true);
+    let replacement = ControllerState::build().c_up().done();
+    let selection = FrameSelection::from(0..25).union(&(4000..9000).into());
+
+    let original = movie.clone();
+    let replaced = movie.replace_states(&matcher, replacement, &selection);
+    let expected: Vec<usize> = (0..original.frame_count())
+        .filter(|&frame| {
+            selection.contains(frame) && matcher.matches(original.port_state(frame, 0).unwrap())
+        })
+        .collect();
+    assert_eq!(replaced, expected.len());
+    assert!(expected.contains(&10) && expected.contains(&5000));
+
+    assert_eq!(movie.port_state(10, 0), Some(&replacement));
+    assert_eq!(movie.port_state(5000, 0), Some(&replacement));
+    assert_eq!(movie.port_state(30, 0), Some(&c_up_stick_up));
+}
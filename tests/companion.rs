@@ -0,0 +1,71 @@
+use std::fs;
+
+use m64_movie::{
+    BinReadExt, Movie,
+    companion::{CompanionKind, StartRequirements},
+    raw::MovieStartType,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_start_requirements() {
+    let snapshot = StartRequirements::for_start_type(MovieStartType::Snapshot);
+    assert!(snapshot.is_satisfied_by(&[CompanionKind::Savestate]));
+    assert!(!snapshot.is_satisfied_by(&[CompanionKind::Eeprom]));
+
+    let eeprom = StartRequirements::for_start_type(MovieStartType::EEPROM);
+    assert!(eeprom.is_satisfied_by(&[CompanionKind::Sram]));
+    assert!(!eeprom.is_satisfied_by(&[]));
+
+    assert!(StartRequirements::for_start_type(MovieStartType::PowerOn).is_empty());
+
+    assert_eq!(
+        CompanionKind::Savestate.file_names("run", "SUPER MARIO 64"),
+        ["run.st", "run.savestate"]
+    );
+    assert_eq!(
+        CompanionKind::Eeprom.file_names("run", "SUPER MARIO 64 "),
+        ["SUPER MARIO 64.eep"]
+    );
+}
+
+#[test]
+fn test_movie_locate_companions() {
+    let dir = tempfile::tempdir().unwrap();
+    let movie_path = dir.path().join("run.m64");
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    let located = movie.locate_companions(&movie_path);
+    assert!(located.is_complete());
+    assert!(located.found.is_empty());
+
+    movie.recording_info.start_type = MovieStartType::Snapshot;
+    let located = movie.locate_companions(&movie_path);
+    assert_eq!(located.missing(), [CompanionKind::Savestate]);
+
+    fs::write(dir.path().join("run.savestate"), [0; 4]).unwrap();
+    let located = movie.locate_companions(&movie_path);
+    assert!(located.is_complete());
+    assert_eq!(
+        located.get(CompanionKind::Savestate),
+        Some(dir.path().join("run.savestate").as_path())
+    );
+
+    movie.recording_info.start_type = MovieStartType::EEPROM;
+    let located = movie.locate_companions(&movie_path);
+    assert_eq!(located.missing(), CompanionKind::SAVES);
+
+    fs::write(dir.path().join("SUPER MARIO 64.eep"), [0; 512]).unwrap();
+    let located = movie.locate_companions(&movie_path);
+    assert!(located.is_complete());
+    assert_eq!(located.found.len(), 1);
+    assert_eq!(located.found[0].kind, CompanionKind::Eeprom);
+
+    let mut flags = movie.controller_flags();
+    flags.set_controller_01_has_mempak(true);
+    movie.set_controller_flags(flags).unwrap();
+    let located = movie.locate_companions(&movie_path);
+    assert_eq!(located.missing(), [CompanionKind::Mempak]);
+}
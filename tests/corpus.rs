@@ -0,0 +1,25 @@
+#![cfg(feature = "corpus")]
+
+use m64_movie::corpus::{bundled_movie, iter_bundled_movies};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_bundled_movies_parse() {
+    let names: Vec<&str> = iter_bundled_movies().map(|movie| movie.name).collect();
+    assert_eq!(names, ["120 star tas (2012).m64", "1key.m64"]);
+
+    for bundled in iter_bundled_movies() {
+        let movie = bundled.parse().unwrap();
+        assert!(movie.frame_count() > 0, "{} has no frames", bundled.name);
+    }
+}
+
+#[test]
+fn test_bundled_movie_lookup() {
+    let bundled = bundled_movie("1key.m64").unwrap();
+    assert_eq!(bundled.bytes, MOVIE_1KEY_BYTES);
+    assert_eq!(bundled.parse().unwrap().frame_count(), 7416);
+    assert!(bundled_movie("missing.m64").is_none());
+}
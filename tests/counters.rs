@@ -0,0 +1,104 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, CounterError, Movie, MovieError,
+    counters::MAX_FRAME_COUNT,
+    parsed::ExtendedData,
+    raw::{ControllerState, RawMovie},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_recording_info_checked_counters() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let info = &mut movie.recording_info;
+
+    info.set_rerecord_count(u32::MAX as u64 - 2).unwrap();
+    assert_eq!(info.add_rerecords(2).unwrap(), u32::MAX);
+    assert!(matches!(
+        info.add_rerecords(1),
+        Err(CounterError::Overflow {
+            counter: "rerecord_count",
+            value: 0x1_0000_0000,
+        })
+    ));
+    assert_eq!(info.rerecord_count(), u32::MAX);
+    assert!(info.set_rerecord_count(u64::MAX).is_err());
+
+    assert_eq!(info.add_vertical_interrupts(16).unwrap(), 15400);
+    assert!(info.set_vertical_interrupts(1 << 32).is_err());
+    assert_eq!(info.vertical_interrupts(), 15400);
+}
+
+#[test]
+fn test_movie_rerecords_carry_into_high_word() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.metadata.extended_data = ExtendedData::ExtendedDataV0;
+    movie
+        .recording_info
+        .set_rerecord_count(u32::MAX as u64)
+        .unwrap();
+    assert!(movie.add_rerecords(1).is_err());
+    assert_eq!(movie.rerecord_count(), u32::MAX as u64);
+
+    movie.metadata.extended_version = 1;
+    movie.metadata.extended_data = ExtendedData::ExtendedDataV1 {
+        authorship_info: 0,
+        bruteforce_data: 0,
+        rerecord_count_high: 0,
+    };
+    assert_eq!(movie.add_rerecords(2).unwrap(), 0x1_0000_0001);
+    assert_eq!(movie.recording_info.rerecord_count(), 1);
+    assert!(matches!(
+        movie.metadata.extended_data,
+        ExtendedData::ExtendedDataV1 {
+            rerecord_count_high: 1,
+            ..
+        }
+    ));
+
+    let raw = RawMovie::from_bytes(&movie.to_bytes().unwrap()).unwrap();
+    assert_eq!(raw.rerecord_count, 1);
+    assert_eq!(raw.extended_data.rerecord_count_high, 1);
+
+    assert!(matches!(
+        movie.add_rerecords(u64::MAX),
+        Err(CounterError::Overflow {
+            counter: "extended_data.rerecord_count_high",
+            ..
+        })
+    ));
+    assert_eq!(movie.rerecord_count(), 0x1_0000_0001);
+
+    movie.set_rerecord_count(7).unwrap();
+    assert_eq!(movie.rerecord_count(), 7);
+    assert!(matches!(
+        movie.metadata.extended_data,
+        ExtendedData::ExtendedDataV1 {
+            rerecord_count_high: 0,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_movie_checked_frame_count() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    movie.add_samples(&[ControllerState::default(); 4]).unwrap();
+    assert_eq!(movie.frame_count(), 7420);
+
+    movie.set_frame_count(100).unwrap();
+    assert_eq!(movie.frame_count(), 100);
+    movie.set_frame_count(120).unwrap();
+    assert_eq!(movie.frame(119), Some(&[ControllerState::default()][..]));
+
+    assert!(matches!(
+        movie.set_frame_count(MAX_FRAME_COUNT + 1),
+        Err(MovieError::CounterError(CounterError::Overflow {
+            counter: "controller_input_samples",
+            ..
+        }))
+    ));
+    assert_eq!(movie.frame_count(), 120);
+}
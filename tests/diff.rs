@@ -0,0 +1,153 @@
+use m64_movie::{
+    BinReadExt, Movie,
+    diff::{DryRun, Edit, EditScript, MovieDiff},
+    raw::ControllerState,
+    track::InputTrack,
+    transform::reverse,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+/// Builds a track from x-axis values.
+fn track(values: &[i8]) -> InputTrack {
+    values
+        .iter()
+        .map(|&x| {
+            let mut state = ControllerState::default();
+            state.set_x_axis(x);
+            state
+        })
+        .collect()
+}
+
+/// Returns the length of the longest common subsequence of two tracks.
+fn lcs(a: &[ControllerState], b: &[ControllerState]) -> usize {
+    let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in 0..a.len() {
+        for j in 0..b.len() {
+            table[i + 1][j + 1] = if a[i] == b[j] {
+                table[i][j] + 1
+            } else {
+                table[i][j + 1].max(table[i + 1][j])
+            };
+        }
+    }
+    table[a.len()][b.len()]
+}
+
+#[test]
+fn test_edit_script_edits() {
+    let old = track(&[1, 2, 3, 4, 5, 6]);
+    let new = track(&[1, 9, 3, 4, 6, 7]);
+
+    let script = EditScript::between(&old, &new);
+    assert_eq!(
+        script.edits(),
+        [
+            Edit::Replace {
+                frames: 1..2,
+                states: track(&[9]).to_vec(),
+            },
+            Edit::Delete { frames: 4..5 },
+            Edit::Insert {
+                at: 6,
+                states: track(&[7]).to_vec(),
+            },
+        ]
+    );
+    assert_eq!(script.distance(), 4);
+    assert_eq!(script.apply(&old).unwrap(), new);
+}
+
+#[test]
+fn test_edit_script_is_minimal() {
+    // A small linear congruential generator, so the tracks share some frames.
+    let mut seed = 12345u32;
+    let mut next = || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        ((seed >> 16) % 4) as i8
+    };
+
+    for _ in 0..50 {
+        let a = track(&(0..next() as usize * 8).map(|_| next()).collect::<Vec<_>>());
+        let b = track(&(0..next() as usize * 8).map(|_| next()).collect::<Vec<_>>());
+
+        let script = EditScript::between(&a, &b);
+        let common = lcs(&a.to_vec(), &b.to_vec());
+        assert_eq!(script.distance(), a.len() + b.len() - 2 * common);
+        assert_eq!(script.apply(&a).unwrap(), b);
+    }
+}
+
+#[test]
+fn test_edit_script_movie() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let old = movie.track(0).unwrap();
+
+    movie.remove_frames(1000..1030).unwrap();
+    movie.insert_neutral_frame_run(4500, 6).unwrap();
+    let new = movie.track(0).unwrap();
+
+    let script = EditScript::between(&old, &new);
+    assert_eq!(script.apply(&old).unwrap(), new);
+    assert!(script.distance() <= 36);
+    assert!(EditScript::between(&old, &old).is_empty());
+    assert!(script.apply(&track(&[0])).is_err());
+}
+
+#[test]
+fn test_movie_preview() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    let diff = movie
+        .preview(|movie| movie.remove_frames(1000..1030))
+        .unwrap();
+    assert_eq!(movie.frame_count(), 7416);
+    assert_eq!(diff.old_frame_count, 7416);
+    assert_eq!(diff.new_frame_count, 7386);
+    assert_eq!(diff.header_fields, ["controller_input_samples"]);
+    assert_eq!(diff.tracks.len(), 1);
+    assert_eq!(diff.tracks[0].distance(), 30);
+    assert_eq!(
+        diff.tracks[0]
+            .apply(&movie.track(0).unwrap())
+            .unwrap()
+            .len(),
+        7386
+    );
+
+    let diff = movie
+        .preview(|movie| {
+            movie.recording_info.add_rerecords(1).unwrap();
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(diff.header_fields, ["rerecord_count"]);
+    assert!(diff.tracks[0].is_empty());
+    assert_eq!(diff.to_string(), "header: rerecord_count\n");
+
+    assert!(
+        movie
+            .preview(|movie| Ok(movie.frame_count()))
+            .unwrap()
+            .is_empty()
+    );
+    assert!(movie.preview(|movie| movie.remove_frames(0..8000)).is_err());
+}
+
+#[test]
+fn test_dry_run() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    let mut dry_run = DryRun::new(&movie);
+    reverse(&mut dry_run);
+    dry_run.insert_neutral_frame_run(0, 2).unwrap();
+    let diff = dry_run.diff().unwrap();
+    assert_eq!(diff.new_frame_count, 7418);
+    assert!(!diff.is_empty());
+
+    let edited = dry_run.into_movie();
+    assert_eq!(MovieDiff::between(&movie, &edited).unwrap(), diff);
+    assert_eq!(movie.frame_count(), 7416);
+}
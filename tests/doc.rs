@@ -0,0 +1,26 @@
+use m64_movie::{
+    doc::{FIELD_DOCS, explain},
+    layout::HEADER_FIELDS,
+};
+
+#[test]
+fn test_every_header_field_is_documented() {
+    assert_eq!(FIELD_DOCS.len(), HEADER_FIELDS.len());
+    for (field, doc) in HEADER_FIELDS.iter().zip(FIELD_DOCS) {
+        assert_eq!(field.name, doc.name);
+        assert_eq!(explain(field.name), Some(doc));
+    }
+}
+
+#[test]
+fn test_explain() {
+    let doc = explain("start_type").unwrap();
+    assert!(doc.valid_values.contains("power-on"));
+    assert!(
+        explain("extended_flags")
+            .unwrap()
+            .version_notes
+            .contains("extended version")
+    );
+    assert_eq!(explain("frame_rate"), None);
+}
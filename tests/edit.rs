@@ -0,0 +1,153 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, ControllerButton, FrameError, Movie, MovieError,
+    edit::MovieEditor,
+    journal::{JournalChange, parse_journal, replay_journal},
+    raw::ControllerState,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+/// Writes the test movie to a temporary file.
+fn temp_movie() -> tempfile::NamedTempFile {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), MOVIE_1KEY_BYTES).unwrap();
+    file
+}
+
+#[test]
+fn test_editor_saves_only_changes() {
+    let file = temp_movie();
+    let mut editor = MovieEditor::open(file.path()).unwrap();
+    assert!(!editor.is_dirty().unwrap());
+
+    editor
+        .edit_header(|header| {
+            header.recording_info.add_rerecords(1).unwrap();
+        })
+        .unwrap();
+    editor
+        .port_state_mut(100, 0)
+        .unwrap()
+        .toggle(ControllerButton::A);
+    assert!(editor.is_dirty().unwrap());
+
+    let report = editor.save().unwrap();
+    assert_eq!(report.header_fields, ["rerecord_count"]);
+    assert_eq!(report.frames, vec![100..101]);
+    assert_eq!(report.bytes_written, 8);
+    assert!(!editor.is_dirty().unwrap());
+
+    let written = std::fs::read(file.path()).unwrap();
+    assert_eq!(written, editor.movie().to_bytes().unwrap());
+
+    let report = editor.save().unwrap();
+    assert_eq!(report.bytes_written, 0);
+}
+
+#[test]
+fn test_editor_missing_frames_stay_clean() {
+    let file = temp_movie();
+    let mut editor = MovieEditor::open(file.path()).unwrap();
+
+    assert!(editor.frame_mut(7416).is_none());
+    assert!(editor.frame_mut(usize::MAX).is_none());
+    assert!(editor.port_state_mut(0, 1).is_none());
+    assert!(!editor.is_dirty().unwrap());
+    assert_eq!(editor.save().unwrap().bytes_written, 0);
+}
+
+#[test]
+fn test_editor_removes_frames() {
+    let file = temp_movie();
+    let mut editor = MovieEditor::open(file.path()).unwrap();
+
+    editor.remove_frames(7000..7010).unwrap();
+    let report = editor.save().unwrap();
+    assert_eq!(report.header_fields, ["controller_input_samples"]);
+    assert_eq!(report.frames, vec![7000..7406]);
+
+    let written = std::fs::read(file.path()).unwrap();
+    assert_eq!(written.len(), MOVIE_1KEY_BYTES.len() - 40);
+    assert_eq!(written, editor.movie().to_bytes().unwrap());
+}
+
+#[test]
+fn test_editor_journal_replays_edits() {
+    let file = temp_movie();
+    let journal = tempfile::NamedTempFile::new().unwrap();
+    let mut editor = MovieEditor::open(file.path())
+        .unwrap()
+        .journal_to(journal.path());
+
+    editor
+        .port_state_mut(100, 0)
+        .unwrap()
+        .toggle(ControllerButton::A);
+    // Borrowing a frame without changing it is not journaled.
+    editor.frame_mut(101).unwrap();
+    editor.remove_frames(200..202).unwrap();
+    editor
+        .insert_frames(10, &[ControllerState::from(0x0080)])
+        .unwrap();
+    editor.save().unwrap();
+
+    let text = std::fs::read_to_string(journal.path()).unwrap();
+    let entries = parse_journal(&text).unwrap();
+    assert_eq!(entries.len(), 3);
+    assert!(matches!(
+        entries[0].change,
+        JournalChange::Set {
+            frame: 100,
+            port: 0,
+            ..
+        }
+    ));
+    assert!(matches!(
+        &entries[1].change,
+        JournalChange::Remove { start: 200, states } if states.len() == 2
+    ));
+
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    replay_journal(&mut movie, &entries).unwrap();
+    assert_eq!(&movie, editor.movie());
+}
+
+#[test]
+fn test_parse_journal_rejects_invalid_lines() {
+    assert!(parse_journal("# comment\n\n1 set 2 0 0000_0000 0080_0000").is_ok());
+    assert!(parse_journal("1 swap 2").is_err());
+    assert!(parse_journal("1 set 2 0 0000_0000").is_err());
+    assert!(parse_journal("1 remove 2 0000_0000 extra").is_err());
+}
+
+#[test]
+fn test_replay_journal_rejects_diverged_movies() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let entries = parse_journal("0 remove 18446744073709551615 0000_0000").unwrap();
+    assert!(replay_journal(&mut movie, &entries).is_err());
+
+    let removed = movie.frame(200).unwrap()[0];
+    let entries = parse_journal(&format!("0 remove 200 {}", removed.to_hex())).unwrap();
+    let mut diverged = movie.clone();
+    diverged
+        .port_state_mut(200, 0)
+        .unwrap()
+        .toggle(ControllerButton::Z);
+    assert!(matches!(
+        replay_journal(&mut diverged, &entries),
+        Err(MovieError::FrameError(FrameError::StateMismatch {
+            frame: 200,
+            port: 0
+        }))
+    ));
+    assert_eq!(diverged.frame_count(), 7416);
+
+    let mut wrong = diverged.frame(5).unwrap()[0];
+    wrong.toggle(ControllerButton::A);
+    let set = format!("0 set 5 0 {} 0000_0000", wrong.to_hex());
+    assert!(replay_journal(&mut diverged, &parse_journal(&set).unwrap()).is_err());
+
+    replay_journal(&mut movie, &entries).unwrap();
+    assert_eq!(movie.frame_count(), 7415);
+}
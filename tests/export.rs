@@ -0,0 +1,71 @@
+use m64_movie::{
+    BinReadExt, Movie,
+    export::{Cue, SubtitleFormat, Subtitles},
+    region::Region,
+    timing::Timing,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_subtitles_srt() {
+    let timing = Timing::new(Region::Ntsc);
+    let subtitles = Subtitles::from_notes(timing, [(90, "Second"), (30, "First\nline")], 3630);
+
+    assert_eq!(
+        subtitles.cues()[0],
+        Cue {
+            start: 30,
+            end: 90,
+            text: "First\nline".to_owned()
+        }
+    );
+    assert_eq!(
+        subtitles.render(SubtitleFormat::Srt),
+        "1\n00:00:00,500 --> 00:00:01,500\nFirst\nline\n\n\
+         2\n00:00:01,500 --> 00:01:00,500\nSecond\n\n"
+    );
+}
+
+#[test]
+fn test_subtitles_ass() {
+    let timing = Timing::new(Region::Pal).with_vis_per_frame(2, 1);
+    let subtitles = Subtitles::from_notes(timing, [(25, "A\nB")], 50);
+
+    let ass = subtitles.render(SubtitleFormat::Ass);
+    assert!(ass.starts_with("[Script Info]\n"));
+    assert!(ass.ends_with("Dialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,A\\NB\n"));
+}
+
+#[test]
+fn test_subtitles_from_annotations() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.annotations_mut().insert(100, "Start");
+    movie.annotations_mut().insert(7000, "Star grab");
+
+    let subtitles = Subtitles::from_annotations(&movie);
+    let cues = subtitles.cues();
+    assert_eq!(cues.len(), 2);
+    assert_eq!((cues[0].start, cues[0].end), (100, 7000));
+    assert_eq!((cues[1].start, cues[1].end), (7000, 7416));
+}
+
+#[test]
+fn test_subtitles_from_markers() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.markers_mut().insert("castle", 900);
+    movie.markers_mut().insert("key", 7000);
+
+    let subtitles = Subtitles::from_markers(&movie);
+    let cues = subtitles.cues();
+    assert_eq!(cues.len(), 2);
+    assert_eq!(
+        (cues[0].start, cues[0].end, cues[0].text.as_str()),
+        (900, 7000, "castle")
+    );
+    assert_eq!(
+        (cues[1].start, cues[1].end, cues[1].text.as_str()),
+        (7000, 7416, "key")
+    );
+}
@@ -0,0 +1,58 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, ControllerButton, Movie,
+    generate::{AxisDistribution, RandomConfig, random_movie},
+    raw::ControllerFlags,
+    region::Region,
+};
+
+#[test]
+fn test_random_movie_is_reproducible() {
+    let config = RandomConfig::default();
+    let movie = random_movie(42, &config);
+
+    assert!((600..=3600).contains(&movie.frame_count()));
+    assert_eq!(movie.controller_count(), 1);
+    assert_eq!(random_movie(42, &config).samples(), movie.samples());
+    assert_ne!(random_movie(43, &config).samples(), movie.samples());
+
+    let bytes = movie.to_bytes().unwrap();
+    assert_eq!(
+        Movie::from_bytes(&bytes).unwrap().samples(),
+        movie.samples()
+    );
+}
+
+#[test]
+fn test_random_movie_config() {
+    let mut flags = ControllerFlags::default();
+    flags.set_controller_01_present(true);
+    flags.set_controller_03_present(true);
+
+    let config = RandomConfig::default()
+        .frames(100..=100)
+        .controller_flags(flags)
+        .button_probability(1.0)
+        .axis(AxisDistribution::Neutral)
+        .region(Region::Pal);
+    let movie = random_movie(7, &config);
+
+    assert_eq!(movie.frame_count(), 100);
+    assert_eq!(movie.controller_count(), 2);
+    assert_eq!(movie.recording_info.vis_per_second, 50);
+    assert!(movie.samples().iter().all(|state| {
+        state.is_set(ControllerButton::A)
+            && !state.is_set(ControllerButton::Reserved01)
+            && (state.x_axis(), state.y_axis()) == (0, 0)
+    }));
+
+    let config = config
+        .button_probability(0.0)
+        .axis(AxisDistribution::Uniform { min: 10, max: 20 });
+    let movie = random_movie(7, &config);
+    assert!(
+        movie
+            .samples()
+            .iter()
+            .all(|state| { state.get_pressed().is_empty() && (10..=20).contains(&state.x_axis()) })
+    );
+}
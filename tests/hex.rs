@@ -0,0 +1,49 @@
+use m64_movie::{BinReadExt, Movie, convert::hex::parse_hex_block, raw::ControllerState};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_controller_state_hex() {
+    let mut state = ControllerState::default();
+    state.set_a_btn(true);
+    state.set_x_axis(127);
+    state.set_y_axis(-1);
+
+    assert_eq!(state.to_hex(), "0080_7FFF");
+    assert_eq!(ControllerState::from_hex("0080_7fff").unwrap(), state);
+    assert_eq!(ControllerState::from_hex("00807FFF").unwrap(), state);
+    assert_eq!(
+        ControllerState::from_hex("0000_7F00").unwrap().x_axis(),
+        127
+    );
+    assert!(ControllerState::from_hex("0080_7F").is_err());
+    assert!(ControllerState::from_hex("0080_7FGG").is_err());
+}
+
+#[test]
+fn test_hex_block_roundtrip() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let block = movie.to_hex_block(100..103).unwrap();
+    assert!(block.starts_with("# frames 100..103\n100: "));
+    assert_eq!(block.lines().count(), 4);
+
+    let frames = parse_hex_block(&block).unwrap();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[1].0, 101);
+    assert_eq!(frames[1].1, movie.frame(101).unwrap());
+
+    let mut edited = movie.clone();
+    assert_eq!(
+        edited
+            .import_hex_block("\n# stick right\n5: 0000_7F00\n")
+            .unwrap(),
+        1
+    );
+    assert_eq!(edited.port_state(5, 0).unwrap().x_axis(), 127);
+
+    assert!(edited.import_hex_block("7416: 0000_0000").is_err());
+    assert!(edited.import_hex_block("6: 0000_0000 0000_0000").is_err());
+    assert!(edited.import_hex_block("6 0000_0000").is_err());
+    assert!(movie.to_hex_block(7400..7417).is_err());
+}
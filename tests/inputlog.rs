@@ -0,0 +1,56 @@
+use m64_movie::{
+    BinReadExt, ControllerButton, Movie,
+    convert::inputlog::{InputPoll, parse_input_log},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+/// Writes an input log of the first frames of a movie.
+fn log_of(movie: &Movie, frames: usize) -> String {
+    (0..frames)
+        .map(|frame| {
+            let state = movie.port_state(frame, 0).unwrap();
+            format!("{frame} 0 0x{:08X}\n", u32::from(*state))
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_input_log() {
+    let polls = parse_input_log("# frame port sample\n0 0 0x00000080\n1 1 FF000000\n").unwrap();
+    assert_eq!(polls.len(), 2);
+    assert_eq!(polls[0].frame, 0);
+    assert!(polls[0].state.is_set(ControllerButton::A));
+    assert_eq!((polls[1].port, polls[1].state.x_axis()), (1, 0));
+    assert_eq!(polls[1].state.y_axis(), -1);
+
+    assert!(parse_input_log("0 0").is_err());
+    assert!(parse_input_log("0 0 xyz").is_err());
+}
+
+#[test]
+fn test_first_log_mismatch() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let polls = parse_input_log(&log_of(&movie, 500)).unwrap();
+    assert_eq!(movie.first_log_mismatch(&polls), None);
+
+    let mut desynced = polls.clone();
+    desynced[300].state.toggle(ControllerButton::B);
+    desynced[400].state.toggle(ControllerButton::B);
+    let mismatch = movie.first_log_mismatch(&desynced).unwrap();
+    assert_eq!((mismatch.frame, mismatch.port), (300, 0));
+    assert_eq!(mismatch.expected, Some(polls[300].state));
+
+    // Neutral polls after the movie ends are expected.
+    let end = movie.frame_count();
+    let mut past_end = vec![InputPoll {
+        frame: end,
+        port: 0,
+        state: Default::default(),
+    }];
+    assert_eq!(movie.first_log_mismatch(&past_end), None);
+
+    past_end[0].state.toggle(ControllerButton::Start);
+    assert_eq!(movie.first_log_mismatch(&past_end).unwrap().expected, None);
+}
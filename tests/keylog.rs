@@ -0,0 +1,52 @@
+use m64_movie::{
+    ControllerButton,
+    convert::keylog::{KeyBinding, KeyBindings, parse_key_log},
+    region::Region,
+    timing::Timing,
+};
+
+/// Binds the arrow keys to the stick and X to A.
+fn bindings() -> KeyBindings {
+    KeyBindings::new()
+        .bind("Right", KeyBinding::Stick { x: 127, y: 0 })
+        .bind("Up", KeyBinding::Stick { x: 0, y: 127 })
+        .bind("X", KeyBinding::Button(ControllerButton::A))
+}
+
+#[test]
+fn test_key_log_to_states() {
+    let events = parse_key_log(
+        "# a short run\n\
+         0 down Right\n\
+         100 down X\n\
+         105 up X\n\
+         200 up Right\n\
+         200 down Up\n",
+    )
+    .unwrap();
+    assert_eq!(events.len(), 5);
+
+    // 60 frames per second, so a frame lasts about 16.7 ms.
+    let timing = Timing::new(Region::Ntsc);
+    let states = bindings().to_states(&events, &timing);
+    assert_eq!(states.len(), 13);
+
+    assert_eq!(states[0].x_axis(), 127);
+    assert!(!states[5].is_set(ControllerButton::A));
+    // The tap at 100 ms is shorter than a frame, but kept on frame 6.
+    assert!(states[6].is_set(ControllerButton::A));
+    assert!(!states[7].is_set(ControllerButton::A));
+    // Right is released as frame 12 starts, so it is still held on that frame.
+    assert_eq!((states[11].x_axis(), states[11].y_axis()), (127, 0));
+    assert_eq!((states[12].x_axis(), states[12].y_axis()), (127, 127));
+}
+
+#[test]
+fn test_key_log_errors() {
+    assert!(parse_key_log("100 down").is_err());
+    assert!(parse_key_log("100 hold X").is_err());
+    assert!(parse_key_log("soon down X").is_err());
+
+    let state = bindings().state(["Right", "Up", "Unbound"]);
+    assert_eq!((state.x_axis(), state.y_axis()), (127, 127));
+}
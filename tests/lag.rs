@@ -0,0 +1,57 @@
+use m64_movie::{BinReadExt, Movie, lag::LagMask, sidecar::Sidecar};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_lag_mask_from_log() {
+    let mask = LagMask::from_log("# lag frames\n12\n40-45, 90\n\n46").unwrap();
+
+    assert_eq!(mask.lag_count(), 9);
+    assert!(mask.is_lag(44));
+    assert!(!mask.is_lag(47));
+    assert_eq!(mask.ranges(), [12..=12, 40..=46, 90..=90]);
+    assert_eq!(mask.to_string(), "12,40-46,90");
+
+    assert!(LagMask::from_log("10-5").is_err());
+    assert!(LagMask::from_log("ten").is_err());
+}
+
+#[test]
+fn test_lag_mask_rejects_frames_past_the_end() {
+    assert!(LagMask::from_log("0-18446744073709551615").is_err());
+    assert!(LagMask::from_log("4294967295").is_err());
+
+    assert!(LagMask::from_log_bounded("10-99", 100).is_ok());
+    assert!(LagMask::from_log_bounded("10-100", 100).is_err());
+    assert!(Sidecar::parse_bounded("lag 5,100", 100).is_err());
+}
+
+#[test]
+fn test_movie_real_frames() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(movie.real_frames().count(), 7416);
+    assert_eq!(movie.duration_without_lag(), movie.duration());
+
+    movie.set_lag_mask(Some((0..100).collect()));
+    assert_eq!(movie.vi_frames().count(), 7416);
+    assert_eq!(movie.real_frames().count(), 7316);
+    assert_eq!(movie.real_frames().next(), Some(100));
+
+    let lag = movie.timing().frame_to_duration(100);
+    assert_eq!(movie.lag_duration(), lag);
+    assert_eq!(movie.duration_without_lag(), movie.duration() - lag);
+}
+
+#[test]
+fn test_lag_mask_persists_in_sidecar() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.set_lag_mask(Some([3, 4, 5, 9].into_iter().collect()));
+
+    let text = movie.sidecar().to_string();
+    assert!(text.contains("\nlag 3-5,9\n"));
+    assert_eq!(
+        Sidecar::parse(&text).unwrap().lag_mask,
+        movie.lag_mask().cloned()
+    );
+}
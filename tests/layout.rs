@@ -0,0 +1,88 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, ControllerButton, Movie,
+    layout::{ByteDiff, FieldRef, HEADER_FIELDS, InputPart, describe_byte_diff, describe_offset},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+static MOVIE_120STAR_BYTES: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/movies/120 star tas (2012).m64"
+));
+
+#[test]
+fn test_header_fields_cover_header() {
+    let mut offset = 0;
+    for field in HEADER_FIELDS {
+        assert_eq!(field.offset, offset, "{} is misplaced", field.name);
+        offset += field.len;
+    }
+    assert_eq!(offset, 0x400);
+}
+
+#[test]
+fn test_describe_offset() {
+    let Some(FieldRef::Header { field, index }) = describe_offset(0x225) else {
+        panic!("expected a header field");
+    };
+    assert_eq!((field.name, index), ("author_name", 3));
+    assert_eq!(describe_offset(0x0E4).unwrap().to_string(), "rom_crc32[0]");
+
+    let input = describe_offset(0x400 + 4 * 7 + 2).unwrap();
+    assert_eq!(
+        input,
+        FieldRef::Input {
+            sample: 7,
+            part: InputPart::XAxis
+        }
+    );
+    assert_eq!(input.frame_and_port(2), Some((3, 1)));
+}
+
+#[test]
+fn test_describe_byte_diff() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.recording_info.author_name = "Someone Else".parse().unwrap();
+    let state = movie.port_state_mut(100, 0).unwrap();
+    state.toggle(ControllerButton::A);
+    state.set_y_axis(state.y_axis().wrapping_add(1));
+
+    let edited = movie.to_bytes().unwrap();
+    let diffs = describe_byte_diff(MOVIE_1KEY_BYTES, &edited);
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs[0].to_string(), "author_name (0x222)");
+    assert_eq!(
+        diffs[1],
+        ByteDiff::Input {
+            frame: 100,
+            port: 0,
+            buttons: vec![ControllerButton::A],
+            x_axis: false,
+            y_axis: true,
+        }
+    );
+    assert_eq!(diffs[1].to_string(), "frame 100, port 0: A, y axis");
+
+    let diffs = describe_byte_diff(MOVIE_1KEY_BYTES, &MOVIE_1KEY_BYTES[..0x404]);
+    assert_eq!(
+        diffs,
+        [ByteDiff::Length {
+            left: MOVIE_1KEY_BYTES.len(),
+            right: 0x404
+        }]
+    );
+}
+
+#[test]
+fn test_describe_byte_diff_of_reencoded_movies() {
+    for bytes in [MOVIE_120STAR_BYTES, MOVIE_1KEY_BYTES] {
+        let reencoded = Movie::from_bytes(bytes).unwrap().to_bytes().unwrap();
+        let diffs = describe_byte_diff(bytes, &reencoded);
+        assert!(
+            diffs.is_empty(),
+            "{:?}",
+            diffs.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+    }
+}
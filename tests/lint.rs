@@ -0,0 +1,43 @@
+use m64_movie::{
+    BinReadExt, Movie,
+    lint::{AxisConstraints, AxisIssue, Constraints},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_nearest_safe() {
+    let constraints = AxisConstraints::new();
+    assert_eq!(constraints.nearest_safe(-128, 40), Some((-127, 40)));
+    assert_eq!(constraints.nearest_safe(-128, -128), Some((-127, -127)));
+    assert_eq!(constraints.nearest_safe(5, 5), Some((5, 5)));
+
+    let constraints = AxisConstraints::none().max_magnitude(80).forbid(80, 80);
+    assert_eq!(constraints.nearest_safe(127, 127), Some((80, 79)));
+    assert!(AxisConstraints::none().is_safe(-128, -128));
+}
+
+#[test]
+fn test_lint_and_fix_axes() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let constraints = AxisConstraints::new();
+    let existing = movie.lint_axes(&constraints).len();
+
+    let state = movie.port_state_mut(200, 0).unwrap();
+    state.set_x_axis(-128);
+    let y = state.y_axis();
+
+    let issues = movie.lint_axes(&constraints);
+    assert_eq!(issues.len(), existing + 1);
+    assert!(issues.contains(&AxisIssue {
+        frame: 200,
+        port: 0,
+        x: -128,
+        y,
+    }));
+
+    assert_eq!(movie.fix_axes(&constraints), existing + 1);
+    assert!(movie.lint_axes(&constraints).is_empty());
+    assert_eq!(movie.port_state(200, 0).unwrap().x_axis(), -127);
+}
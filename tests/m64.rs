@@ -1,13 +1,17 @@
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
 use binrw::{BinWrite, meta::WriteEndian};
 use m64_movie::{
-    BinReadExt, BinWriteExt, ControllerButton,
-    parsed::{self, m64::Movie},
-    raw::m64::{
-        ControllerFlags, ControllerState, ExtendedData, ExtendedFlags, MovieStartType, RawMovie,
+    BinReadExt, BinWriteExt, ControllerButton, FrameError, MovieError, MovieParseError,
+    parsed::{self, OldMovieInfo, m64::Movie},
+    patch,
+    raw::{
+        RawHeader, RawMovieRef,
+        m64::{
+            ControllerFlags, ControllerState, ExtendedData, ExtendedFlags, MovieStartType, RawMovie,
+        },
     },
-    shared::Reserved,
+    shared::{Padding, Reserved},
 };
 
 static MOVIE_120STAR_PATH: &str = concat!(
@@ -25,16 +29,6 @@ static MOVIE_1KEY_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key
 static MOVIE_1KEY_BYTES: &[u8] =
     include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
 
-/// Replaces a section of a byte slice with new bytes at the specified offset.
-fn replace_bytes(bytes: &mut [u8], offset: usize, new_bytes: &[u8]) -> Result<(), String> {
-    if offset + new_bytes.len() > bytes.len() {
-        return Err("Replacement exceeds byte slice length".to_string());
-    }
-
-    bytes[offset..offset + new_bytes.len()].copy_from_slice(new_bytes);
-    Ok(())
-}
-
 /// Asserts that the bytes of a BinWrite type match the expected byte slice.
 fn assert_bytes_equal<T: BinWrite + WriteEndian>(actual: &T, expected: &[u8])
 where
@@ -209,6 +203,66 @@ fn test_controller_state_get_pressed() {
     assert!(!pressed.contains(&ControllerButton::Z));
 }
 
+#[test]
+fn test_controller_button_masks() {
+    assert_eq!(ControllerButton::iter().count(), 16);
+    assert_eq!(ControllerButton::DPadRight.mask(), 0x0001);
+    assert_eq!(ControllerButton::A.mask(), 0x0080);
+    assert_eq!(ControllerButton::Reserved02.mask(), 0x8000);
+
+    for button in ControllerButton::iter() {
+        let mut state = ControllerState::default();
+        state.set(button);
+        assert_eq!(u32::from(state), button.mask() as u32);
+        assert_eq!(ControllerButton::try_from(button.mask()).unwrap(), button);
+    }
+
+    assert!(ControllerButton::try_from(0).is_err());
+    assert!(ControllerButton::try_from(0x0081).is_err());
+}
+
+#[test]
+fn test_legacy_data_in_reserved_regions() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(movie.metadata.legacy, None);
+
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes[0x0EA] = 0x12;
+    let movie = Movie::from_bytes(&bytes).unwrap();
+    let legacy = movie.metadata.legacy.as_ref().unwrap();
+    assert_eq!(legacy.reserved01, None);
+    assert_eq!(legacy.reserved02, None);
+    assert_eq!(
+        legacy.reserved03.as_deref().map(|b| (b.len(), b[0])),
+        Some((56, 0x12))
+    );
+    assert_eq!(legacy.old_info, None);
+}
+
+#[test]
+fn test_legacy_data_old_movie_info() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes[0x044..0x04A].copy_from_slice(b"Author");
+    bytes[0x074..0x080].copy_from_slice(b"Old notes   ");
+    let movie = Movie::from_bytes(&bytes).unwrap();
+    let legacy = movie.metadata.legacy.as_ref().unwrap();
+    assert_eq!(legacy.reserved02.as_ref().map(Vec::len), Some(128));
+    assert_eq!(
+        legacy.old_info,
+        Some(OldMovieInfo {
+            author: "Author".to_string(),
+            description: "Old notes".to_string(),
+        })
+    );
+
+    // From Mupen 1.1.9, the region is reserved.
+    bytes[0x016] = 1;
+    let movie = Movie::from_bytes(&bytes).unwrap();
+    let legacy = movie.metadata.legacy.as_ref().unwrap();
+    assert!(legacy.reserved02.is_some());
+    assert_eq!(legacy.old_info, None);
+}
+
 #[test]
 fn test_extended_flags() {
     let mut flags = ExtendedFlags::default();
@@ -348,6 +402,24 @@ fn test_invalid_version() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_input_sample_mismatch() {
+    let mut bytes = MOVIE_1KEY_BYTES[..0x400 + 4 * 10 + 2].to_vec();
+    let err = Movie::from_bytes(&bytes).unwrap_err();
+    assert!(matches!(
+        err,
+        MovieError::MovieParseError(MovieParseError::InputSampleMismatch {
+            expected: 7416,
+            found: 10,
+            input_bytes: 42,
+        })
+    ));
+
+    bytes.truncate(0x400 + 4 * 10);
+    bytes[0x18..0x1C].copy_from_slice(&10u32.to_le_bytes());
+    assert_eq!(Movie::from_bytes(&bytes).unwrap().frame_count(), 10);
+}
+
 #[test]
 fn test_movie_roundtrip() {
     let original_movie = RawMovie::from_bytes(MOVIE_120STAR_BYTES).unwrap();
@@ -387,11 +459,8 @@ fn test_ext_version_cases() -> Result<(), String> {
     assert_eq!(movie.extended_version, 0, "Initial ext_version should be 0");
 
     // Set the extended version to 1
-    replace_bytes(
-        &mut bytes,
-        0x16, // Offset for extended version
-        &1u8.to_le_bytes(),
-    )?;
+    patch::apply(&mut bytes, &[(0x16, &1u8.to_le_bytes())]) // Offset for extended version
+        .map_err(|e| format!("Failed to patch movie: {}", e))?;
 
     let movie =
         RawMovie::from_bytes(&bytes).map_err(|e| format!("Failed to parse movie: {}", e))?;
@@ -406,11 +475,8 @@ fn test_ext_version_cases() -> Result<(), String> {
         .to_bytes()
         .map_err(|e| format!("Failed to serialize ExtendedFlags: {}", e))?;
 
-    replace_bytes(
-        &mut bytes,
-        0x17, // Offset for extended flags
-        &ext_flags_bytes,
-    )?;
+    patch::apply(&mut bytes, &[(0x17, &ext_flags_bytes)]) // Offset for extended flags
+        .map_err(|e| format!("Failed to patch movie: {}", e))?;
 
     let movie =
         RawMovie::from_bytes(&bytes).map_err(|e| format!("Failed to parse movie: {}", e))?;
@@ -420,11 +486,8 @@ fn test_ext_version_cases() -> Result<(), String> {
     );
 
     // Now set the extended version to 0, which should not be valid.
-    replace_bytes(
-        &mut bytes,
-        0x16, // Offset for extended version
-        &0u8.to_le_bytes(),
-    )?;
+    patch::apply(&mut bytes, &[(0x16, &0u8.to_le_bytes())]) // Offset for extended version
+        .map_err(|e| format!("Failed to patch movie: {}", e))?;
 
     let movie = RawMovie::from_bytes(&bytes);
     assert!(
@@ -495,11 +558,11 @@ fn test_parsed_from_raw_movie() {
     );
     assert_eq!(parsed_movie.recording_info.uid, raw_movie.uid);
     assert_eq!(
-        parsed_movie.recording_info.vertical_interrupts,
+        parsed_movie.recording_info.vertical_interrupts(),
         raw_movie.vertical_interrupts
     );
     assert_eq!(
-        parsed_movie.recording_info.rerecord_count,
+        parsed_movie.recording_info.rerecord_count(),
         raw_movie.rerecord_count
     );
     assert_eq!(
@@ -507,19 +570,619 @@ fn test_parsed_from_raw_movie() {
         raw_movie.vis_per_second
     );
     assert_eq!(
-        parsed_movie.recording_info.controller_count,
-        raw_movie.controller_count
-    );
-    assert_eq!(
-        parsed_movie.recording_info.controller_input_samples,
-        raw_movie.controller_input_samples
+        parsed_movie.controller_count(),
+        raw_movie.controller_count as usize
     );
     assert_eq!(
-        parsed_movie.recording_info.controller_flags,
-        raw_movie.controller_flags
+        parsed_movie.frame_count(),
+        raw_movie.controller_input_samples as usize
     );
+    assert_eq!(parsed_movie.controller_flags(), raw_movie.controller_flags);
     assert_eq!(parsed_movie.recording_info.start_type, raw_movie.start_type);
 
     // Inputs checks
-    assert_eq!(parsed_movie.inputs, raw_movie.inputs);
+    assert_eq!(parsed_movie.samples(), raw_movie.inputs);
+}
+
+#[test]
+fn test_parsed_movie_controller_invariants() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+
+    // Declare two controllers while only one is flagged as present.
+    patch::apply(&mut bytes, &[(0x15, &2u8.to_le_bytes())]).unwrap();
+    assert!(RawMovie::from_bytes(&bytes).is_ok());
+    assert!(Movie::from_bytes(&bytes).is_err());
+
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut flags = movie.controller_flags();
+    flags.set_controller_01_has_rumblepak(true);
+    movie.set_controller_flags(flags).unwrap();
+
+    flags.set_controller_02_present(true);
+    assert!(movie.set_controller_flags(flags).is_err());
+
+    // Counters are always derived from the inputs.
+    movie.remove_frames(0..16).unwrap();
+    let raw = movie.into_raw();
+    assert_eq!(raw.controller_count, 1);
+    assert_eq!(raw.controller_input_samples, 7400);
+}
+
+#[test]
+fn test_insert_neutral_frames() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    let mut bulk = movie.clone();
+    bulk.insert_neutral_frames(&[7416, 100, 5, 100]).unwrap();
+    assert_eq!(bulk.frame_count(), 7420);
+
+    let mut single = movie.clone();
+    for at in [7416, 100, 100, 5] {
+        single.insert_neutral_frame_run(at, 1).unwrap();
+    }
+    assert_eq!(bulk.samples(), single.samples());
+
+    assert!(bulk.insert_neutral_frames(&[0, 7421]).is_err());
+    assert_eq!(bulk.frame_count(), 7420);
+}
+
+#[test]
+fn test_insert_neutral_frames_moves_frame_data() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.markers_mut().insert("door", 100);
+    movie.annotations_mut().insert(5, "start");
+    movie.set_lag_mask(Some([4, 5].into_iter().collect()));
+
+    movie.insert_neutral_frames(&[100, 5, 100]).unwrap();
+    assert_eq!(movie.markers().get("door"), Some(103));
+    assert_eq!(movie.annotations().get(6), Some("start"));
+    assert_eq!(movie.lag_mask().unwrap().ranges(), [4..=4, 6..=6]);
+}
+
+#[test]
+fn test_insert_neutral_frames_without_controllers() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.remove_frames(0..movie.frame_count()).unwrap();
+    movie
+        .set_controller_flags(ControllerFlags::from(0))
+        .unwrap();
+
+    assert!(matches!(
+        movie.insert_neutral_frames(&[0]),
+        Err(MovieError::FrameError(FrameError::PartialFrame { .. }))
+    ));
+}
+
+#[test]
+fn test_parsed_movie_to_raw() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let raw = movie.to_raw();
+
+    assert_eq!(raw, RawMovie::from(&movie));
+    assert_eq!(raw, movie.clone().into_raw());
+    assert_eq!(raw.to_bytes().unwrap(), MOVIE_1KEY_BYTES);
+}
+
+#[test]
+fn test_parsed_movie_write_to() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(movie.to_bytes().unwrap(), MOVIE_1KEY_BYTES);
+
+    movie.remove_frames(0..movie.frame_count()).unwrap();
+    let bytes = movie.to_bytes().unwrap();
+    assert_eq!(bytes.len(), 0x400);
+    assert_eq!(bytes, movie.to_raw().to_bytes().unwrap());
+}
+
+#[test]
+fn test_parsed_movie_from_reader() {
+    // A small buffer over a non-seekable reader splits samples across refills.
+    let reader = std::io::BufReader::with_capacity(7, MOVIE_1KEY_BYTES.chain(&[][..]));
+    let movie = Movie::from_reader(reader).unwrap();
+    assert_eq!(movie, Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap());
+
+    // Extra samples are reported like `from_bytes` does, and a trailing partial
+    // sample is ignored.
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes.extend_from_slice(&[0; 6]);
+    let err = Movie::from_reader(&bytes[..]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        Movie::from_bytes(&bytes).unwrap_err().to_string()
+    );
+    assert!(matches!(
+        err,
+        MovieError::MovieParseError(MovieParseError::InputSampleMismatch {
+            expected: 7416,
+            found: 7417,
+            input_bytes: 29670,
+        })
+    ));
+    assert_eq!(
+        Movie::from_reader(&bytes[..bytes.len() - 4]).unwrap(),
+        Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap()
+    );
+
+    assert!(Movie::from_reader(&MOVIE_1KEY_BYTES[..0x200]).is_err());
+}
+
+#[test]
+fn test_parsed_movie_write_stream() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    // `Vec<u8>` is a plain writer, without `Seek`.
+    let mut bytes = Vec::new();
+    movie.write_stream(&mut bytes).unwrap();
+    assert_eq!(bytes, MOVIE_1KEY_BYTES);
+
+    let mut edited = movie.clone();
+    edited.remove_frames(0..100).unwrap();
+    let mut bytes = Vec::new();
+    edited.write_stream(&mut bytes).unwrap();
+    assert_eq!(bytes, edited.to_bytes().unwrap());
+    assert_eq!(Movie::from_reader(&bytes[..]).unwrap(), edited);
+}
+
+#[test]
+fn test_parsed_movie_write_options() {
+    use m64_movie::{
+        WriteError,
+        parsed::{LegacyData, ReservedRegions, WriteOptions},
+    };
+
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(
+        movie.to_bytes_with(WriteOptions::default()).unwrap(),
+        movie.to_bytes().unwrap()
+    );
+    assert_eq!(
+        movie.to_bytes_with(WriteOptions::strict()).unwrap(),
+        MOVIE_1KEY_BYTES
+    );
+
+    movie.metadata.legacy = Some(LegacyData {
+        reserved01: Some(vec![0xAB, 0xCD]),
+        reserved02: None,
+        reserved03: Some(vec![1; 3]),
+        old_info: None,
+    });
+    let zeroed = movie.to_bytes_with(WriteOptions::strict()).unwrap();
+    assert_eq!(zeroed, MOVIE_1KEY_BYTES);
+
+    let preserved = movie
+        .to_bytes_with(WriteOptions::strict().reserved(ReservedRegions::Preserved))
+        .unwrap();
+    assert_eq!(&preserved[0x1E..0x20], &[0xAB, 0xCD]);
+    // A region of the wrong length is zeroed.
+    assert!(preserved[0xEA..0x122].iter().all(|&b| b == 0));
+    let reparsed = Movie::from_bytes(&preserved).unwrap();
+    assert_eq!(
+        reparsed.metadata.legacy.unwrap().reserved01,
+        Some(vec![0xAB, 0xCD])
+    );
+
+    movie.metadata.version = 2;
+    assert!(movie.to_bytes().is_err());
+    assert!(movie.to_bytes_with(WriteOptions::default()).is_err());
+    let v2 = movie
+        .to_bytes_with(WriteOptions::default().require_version_3(false))
+        .unwrap();
+    assert_eq!(&v2[4..8], &2u32.to_le_bytes());
+    assert_eq!(&v2[8..], &zeroed[8..]);
+    assert!(
+        movie
+            .to_bytes_with(WriteOptions::strict().require_version_3(false))
+            .is_ok()
+    );
+    let mut bytes = Vec::new();
+    let err = movie
+        .write_stream_with(&mut bytes, WriteOptions::strict())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        MovieError::WriteError(WriteError::UnsupportedVersion(2))
+    ));
+    assert!(bytes.is_empty());
+}
+
+#[test]
+fn test_parsed_movie_from_bytes_prefix() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes.extend_from_slice(b"signed by tool\n");
+    assert!(Movie::from_bytes(&bytes).is_err());
+
+    let (movie, rest) = Movie::from_bytes_prefix(&bytes).unwrap();
+    assert_eq!(movie, Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap());
+    assert_eq!(rest, b"signed by tool\n");
+
+    let err =
+        Movie::from_bytes_prefix(&MOVIE_1KEY_BYTES[..MOVIE_1KEY_BYTES.len() - 4]).unwrap_err();
+    assert!(matches!(
+        err,
+        MovieError::MovieParseError(MovieParseError::InputSampleMismatch {
+            expected: 7416,
+            found: 7415,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_parsed_movie_from_bytes_concatenated() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes.extend_from_slice(MOVIE_120STAR_BYTES);
+    bytes.extend_from_slice(b"trailer");
+
+    let concatenated = Movie::from_bytes_concatenated(&bytes).unwrap();
+    assert_eq!(concatenated.movies.len(), 2);
+    assert_eq!(
+        concatenated.movies[1],
+        Movie::from_bytes(MOVIE_120STAR_BYTES).unwrap()
+    );
+    assert_eq!(concatenated.trailing, b"trailer");
+
+    let single = Movie::from_bytes_concatenated(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(single.movies.len(), 1);
+    assert!(single.trailing.is_empty());
+
+    // A second header without enough inputs is an error.
+    bytes.truncate(MOVIE_1KEY_BYTES.len() + 2000);
+    assert!(Movie::from_bytes_concatenated(&bytes).is_err());
+}
+
+#[test]
+fn test_controller_state_builder() {
+    let state = ControllerState::build().a().z().stick(127, 0).done();
+    let mut expected = ControllerState::default();
+    expected.set(ControllerButton::A);
+    expected.set(ControllerButton::Z);
+    expected.set_axis(127, 0);
+    assert_eq!(state, expected);
+
+    let state: ControllerState = ControllerState::build()
+        .buttons([ControllerButton::CUp, ControllerButton::TriggerRight])
+        .l()
+        .d_left()
+        .x(-128)
+        .y(5)
+        .into();
+    assert_eq!(
+        state.get_pressed(),
+        [
+            ControllerButton::DPadLeft,
+            ControllerButton::CUp,
+            ControllerButton::TriggerRight,
+            ControllerButton::TriggerLeft,
+        ]
+    );
+    assert_eq!(state.axis(), (-128, 5));
+    assert_eq!(ControllerState::build().done(), ControllerState::default());
+}
+
+/// A container embedding a movie between other fields.
+#[derive(Debug, PartialEq, binrw::BinRead, BinWrite)]
+#[brw(little, magic = b"BNDL")]
+struct Container {
+    tag: u32,
+    movie: Movie,
+    trailer: u32,
+}
+
+#[test]
+fn test_parsed_movie_embedded_in_container() {
+    let container = Container {
+        tag: 7,
+        movie: Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap(),
+        trailer: 0xDEADBEEF,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    container.write(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert_eq!(&bytes[8..bytes.len() - 4], MOVIE_1KEY_BYTES);
+
+    let read = <Container as binrw::BinRead>::read(&mut Cursor::new(&bytes)).unwrap();
+    assert_eq!(read, container);
+}
+
+#[test]
+fn test_parsed_movie_byte_reader() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    let mut bytes = Vec::new();
+    std::io::Read::read_to_end(&mut movie.byte_reader(), &mut bytes).unwrap();
+    assert_eq!(bytes, MOVIE_1KEY_BYTES);
+
+    // Reads in odd-sized chunks straddle sample boundaries.
+    let mut reader = movie.byte_reader();
+    let mut chunked = Vec::new();
+    let mut buf = [0; 7];
+    loop {
+        let n = std::io::Read::read(&mut reader, &mut buf).unwrap();
+        if n == 0 {
+            break;
+        }
+        chunked.extend_from_slice(&buf[..n]);
+    }
+    assert_eq!(chunked, MOVIE_1KEY_BYTES);
+}
+
+#[test]
+fn test_serialized_len() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(movie.serialized_len(), MOVIE_1KEY_BYTES.len());
+    assert_eq!(movie.serialized_len(), 1024 + 4 * 7416);
+    assert_eq!(movie.to_raw().serialized_len(), MOVIE_1KEY_BYTES.len());
+
+    let bytes = movie.to_bytes().unwrap();
+    assert_eq!(bytes.capacity(), bytes.len());
+}
+
+#[test]
+fn test_full_length_string_fields() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    let author = "A".repeat(222);
+    patch::apply(&mut bytes, &[(0x222, author.as_bytes())]).unwrap();
+
+    let raw = RawMovie::from_bytes(&bytes).unwrap();
+    assert_eq!(raw.author_name.len(), 222);
+    assert_eq!(
+        raw.description.to_string(),
+        RawMovie::from_bytes(MOVIE_1KEY_BYTES)
+            .unwrap()
+            .description
+            .to_string()
+    );
+    assert_eq!(raw.to_bytes().unwrap(), bytes);
+
+    let movie = Movie::from_bytes(&bytes).unwrap();
+    assert_eq!(movie.recording_info.author_name.to_string(), author);
+    assert_eq!(movie.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn test_space_padded_string_fields() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    let mut rom_name = [b' '; 32];
+    rom_name[..14].copy_from_slice(b"SUPER MARIO 64");
+    patch::apply(&mut bytes, &[(0xC4, &rom_name)]).unwrap();
+
+    let mut movie = Movie::from_bytes(&bytes).unwrap();
+    assert_eq!(movie.game_info.rom_name, "SUPER MARIO 64");
+    assert_eq!(movie.game_info.rom_name.padding(), Padding::Space);
+    assert_eq!(movie.recording_info.author_name.padding(), Padding::Zero);
+    assert_eq!(movie.to_bytes().unwrap(), bytes);
+
+    let zero_padded = parsed::WriteOptions::default().padding(Some(Padding::Zero));
+    assert_eq!(movie.to_bytes_with(zero_padded).unwrap(), MOVIE_1KEY_BYTES);
+
+    movie.game_info.rom_name = "SUPER MARIO 64".parse().unwrap();
+    assert_eq!(movie.to_bytes().unwrap(), MOVIE_1KEY_BYTES);
+}
+
+#[test]
+fn test_space_padding_write_option() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let bytes = movie
+        .to_bytes_with(parsed::WriteOptions::default().padding(Some(Padding::Space)))
+        .unwrap();
+
+    let mut rom_name = [b' '; 32];
+    rom_name[..14].copy_from_slice(b"SUPER MARIO 64");
+    assert_eq!(&bytes[0xC4..0xE4], &rom_name);
+
+    let reparsed = Movie::from_bytes(&bytes).unwrap();
+    assert_eq!(reparsed, movie);
+    assert_eq!(reparsed.game_info.rom_name.padding(), Padding::Space);
+}
+
+#[test]
+fn test_string_padding_does_not_affect_equality() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    let mut rom_name = [b' '; 32];
+    rom_name[..14].copy_from_slice(b"SUPER MARIO 64");
+    patch::apply(&mut bytes, &[(0xC4, &rom_name)]).unwrap();
+
+    let zero_padded = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let space_padded = Movie::from_bytes(&bytes).unwrap();
+
+    assert_eq!(
+        space_padded.game_info.rom_name,
+        zero_padded.game_info.rom_name
+    );
+    assert_eq!(space_padded, zero_padded);
+    assert_ne!(
+        space_padded.to_bytes().unwrap(),
+        zero_padded.to_bytes().unwrap()
+    );
+}
+
+#[test]
+fn test_is_m64() {
+    assert_eq!(&MOVIE_1KEY_BYTES[..4], &m64_movie::M64_MAGIC);
+    assert!(m64_movie::is_m64(MOVIE_1KEY_BYTES));
+    assert!(m64_movie::is_m64(&MOVIE_1KEY_BYTES[..8]));
+    assert!(!m64_movie::is_m64(&MOVIE_1KEY_BYTES[..7]));
+
+    let mut bytes = MOVIE_1KEY_BYTES[..8].to_vec();
+    patch::apply(&mut bytes, &[(4, &4u32.to_le_bytes())]).unwrap();
+    assert!(!m64_movie::is_m64(&bytes));
+    assert!(!m64_movie::is_m64(b"PK\x03\x04\x14\x00\x00\x00"));
+}
+
+#[test]
+fn test_controller_state_approx_eq() {
+    let state = ControllerState::build().a().stick(10, -10).done();
+    assert!(state.approx_eq(&ControllerState::build().a().stick(11, -12).done(), 2));
+    assert!(!state.approx_eq(&ControllerState::build().a().stick(11, -12).done(), 1));
+    assert!(!state.approx_eq(&ControllerState::build().b().stick(10, -10).done(), 127));
+    assert!(
+        ControllerState::build()
+            .stick(-128, 0)
+            .done()
+            .approx_eq(&ControllerState::build().stick(127, 0).done(), 255)
+    );
+}
+
+#[test]
+fn test_raw_movie_read_header_only() {
+    let mut reader = MOVIE_1KEY_BYTES;
+    let header = RawMovie::read_header_only(&mut reader).unwrap();
+    assert_eq!(reader, &MOVIE_1KEY_BYTES[0x400..]);
+
+    let raw = RawMovie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(header, raw.header());
+    assert_eq!(header.controller_input_samples, 7416);
+    assert_eq!(header.declared_len(), MOVIE_1KEY_BYTES.len() as u64);
+    assert_eq!(header.clone().with_inputs(raw.inputs.clone()), raw);
+
+    assert_eq!(
+        RawHeader::from_bytes(&MOVIE_1KEY_BYTES[..0x400]).unwrap(),
+        header
+    );
+    assert_eq!(header.to_bytes().unwrap(), &MOVIE_1KEY_BYTES[..0x400]);
+    assert!(RawMovie::read_header_only(&MOVIE_1KEY_BYTES[..0x3FF]).is_err());
+}
+
+#[test]
+fn test_raw_header_write_at_offset() {
+    let header = RawMovie::read_header_only(MOVIE_1KEY_BYTES).unwrap();
+
+    let mut writer = Cursor::new(vec![0xFF; 4]);
+    writer.set_position(4);
+    header.write(&mut writer).unwrap();
+
+    let bytes = writer.into_inner();
+    assert_eq!(bytes.len(), 4 + 0x400);
+    assert_eq!(&bytes[4..], &MOVIE_1KEY_BYTES[..0x400]);
+}
+
+#[test]
+fn test_raw_movie_frames_from_reader() {
+    let raw = RawMovie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut stream = RawMovie::frames_from_reader(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(stream.header(), &raw.header());
+
+    let inputs: Vec<_> = stream.by_ref().collect::<Result<_, _>>().unwrap();
+    assert_eq!(inputs, raw.inputs);
+    assert_eq!(stream.samples_read(), 7416);
+
+    let truncated = &MOVIE_1KEY_BYTES[..MOVIE_1KEY_BYTES.len() - 1];
+    let mut stream = RawMovie::frames_from_reader(truncated).unwrap();
+    assert_eq!(
+        stream.by_ref().take(7415).filter(Result::is_ok).count(),
+        7415
+    );
+    assert!(matches!(stream.next(), Some(Err(MovieError::FileError(_)))));
+    assert!(stream.next().is_none());
+
+    assert!(RawMovie::frames_from_reader(&MOVIE_1KEY_BYTES[..0x200]).is_err());
+
+    let mut trailing = MOVIE_1KEY_BYTES.to_vec();
+    trailing.extend_from_slice(&[0xFF; 8]);
+    let stream = RawMovie::frames_from_reader(&trailing[..]).unwrap();
+    assert_eq!(stream.samples_declared(), 7416);
+    let inputs: Vec<_> = stream.collect::<Result<_, _>>().unwrap();
+    assert_eq!(inputs, raw.inputs);
+
+    let short = &MOVIE_1KEY_BYTES[..MOVIE_1KEY_BYTES.len() - 4];
+    let mut stream = RawMovie::frames_from_reader(short).unwrap();
+    assert_eq!(
+        stream.by_ref().take(7415).filter(Result::is_ok).count(),
+        7415
+    );
+    assert!(matches!(
+        stream.next(),
+        Some(Err(MovieError::MovieParseError(
+            MovieParseError::InputSampleMismatch {
+                expected: 7416,
+                found: 7415,
+                ..
+            }
+        )))
+    ));
+}
+
+#[test]
+fn test_raw_movie_ref() {
+    let raw = RawMovie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let view = RawMovieRef::parse(MOVIE_1KEY_BYTES).unwrap();
+
+    assert_eq!(view.header(), &raw.header());
+    assert_eq!(view.sample_count(), raw.inputs.len());
+    assert_eq!(view.sample(7415), raw.inputs.last().copied());
+    assert_eq!(view.sample(7416), None);
+    assert_eq!(view.sample(usize::MAX), None);
+    assert_eq!(view.sample(usize::MAX / 4), None);
+    assert!(view.samples().eq(raw.inputs.iter().copied()));
+    assert_eq!(
+        view.input_bytes().as_ptr(),
+        MOVIE_1KEY_BYTES[0x400..].as_ptr()
+    );
+    assert_eq!(view.to_owned_movie(), raw);
+
+    let mut partial = MOVIE_1KEY_BYTES.to_vec();
+    partial.push(0xFF);
+    assert_eq!(RawMovieRef::try_from(&partial[..]).unwrap(), view);
+    assert!(RawMovieRef::parse(&MOVIE_1KEY_BYTES[..0x3FF]).is_err());
+}
+
+#[test]
+fn test_movie_remove_frames_rejects_invalid_ranges() {
+    let mut movie = parsed::Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 8000..10;
+    assert!(matches!(
+        movie.remove_frames(reversed),
+        Err(MovieError::FrameError(FrameError::InvalidRange {
+            start: 8000,
+            end: 10
+        }))
+    ));
+    assert!(matches!(
+        movie.remove_frames(7000..7417),
+        Err(MovieError::FrameError(FrameError::OutOfBounds { .. }))
+    ));
+    assert_eq!(movie.frame_count(), 7416);
+
+    assert_eq!(movie.remove_frames(7410..7416).unwrap().len(), 6);
+    assert_eq!(movie.frame_count(), 7410);
+}
+
+#[test]
+fn test_movie_frame_data_follows_inserted_and_removed_frames() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.markers_mut().insert("door", 100);
+    movie.annotations_mut().insert(100, "door opens");
+    movie.annotations_mut().insert(5, "start");
+
+    movie.insert_neutral_frame_run(0, 10).unwrap();
+    assert_eq!(movie.markers().get("door"), Some(110));
+    assert_eq!(movie.annotations().get(110), Some("door opens"));
+    assert_eq!(movie.annotations().get(15), Some("start"));
+
+    movie.remove_frames(0..20).unwrap();
+    assert_eq!(movie.markers().get("door"), Some(90));
+    assert_eq!(movie.annotations().get(90), Some("door opens"));
+    assert_eq!(movie.annotations().len(), 1);
+
+    movie.remove_frames(80..100).unwrap();
+    assert_eq!(movie.markers().get("door"), None);
+    assert!(movie.annotations().is_empty());
+}
+
+#[test]
+fn test_movie_frame_lookup_does_not_overflow() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(movie.frame(usize::MAX), None);
+    assert_eq!(movie.frame_mut(usize::MAX / 2 + 1), None);
+    assert_eq!(movie.port_state(usize::MAX, 0), None);
+
+    movie.remove_frames(0..movie.frame_count()).unwrap();
+    movie
+        .set_controller_flags(ControllerFlags::from(0))
+        .unwrap();
+    assert_eq!(movie.frame(0), None);
+    assert_eq!(movie.frame(usize::MAX), None);
 }
@@ -0,0 +1,72 @@
+#![cfg(feature = "m64p")]
+
+use std::ffi::{CStr, c_char};
+
+use m64_movie::{
+    BinReadExt, Movie,
+    m64p::{Control, ControlInfo, InputPlugin, M64ERR_ALREADY_INIT, M64ERR_SUCCESS, PLUGIN_NONE},
+    raw::ControllerFlags,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+m64_movie::m64p_input_plugin!(c"Movie playback", || Movie::from_bytes(MOVIE_1KEY_BYTES));
+
+#[test]
+fn test_plugin_ports() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut flags = ControllerFlags::default();
+    flags.set_controller_02_present(true);
+    flags.set_controller_04_present(true);
+
+    let plugin = InputPlugin::new(movie.cursor(), flags);
+    assert_eq!(plugin.movie_port(0), None);
+    assert_eq!(plugin.movie_port(1), Some(0));
+    assert_eq!(plugin.movie_port(3), Some(1));
+    assert_eq!(plugin.movie_port(4), None);
+    assert_eq!(plugin.controls()[1].present, 1);
+    assert_eq!(plugin.controls()[2].present, 0);
+}
+
+#[test]
+fn test_exported_plugin() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let null = std::ptr::null_mut();
+
+    unsafe {
+        assert_eq!(PluginStartup(null, null, null), M64ERR_SUCCESS);
+        assert_eq!(PluginStartup(null, null, null), M64ERR_ALREADY_INIT);
+
+        let mut name: *const c_char = std::ptr::null();
+        let mut plugin_type = 0;
+        PluginGetVersion(
+            &mut plugin_type,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut name,
+            std::ptr::null_mut(),
+        );
+        assert_eq!(plugin_type, 4);
+        assert_eq!(CStr::from_ptr(name), c"Movie playback");
+
+        let mut controls = [Control::default(); 4];
+        InitiateControllers(ControlInfo {
+            controls: controls.as_mut_ptr(),
+        });
+        assert_eq!(controls[0].present, 1);
+        assert_eq!(controls[1].present, 0);
+        assert_eq!(controls[1].plugin, PLUGIN_NONE);
+
+        assert_eq!(RomOpen(), 1);
+        let mut keys = 0;
+        for frame in 0..3 {
+            GetKeys(0, &mut keys);
+            assert_eq!(keys, u32::from(*movie.port_state(frame, 0).unwrap()));
+        }
+        GetKeys(1, &mut keys);
+        assert_eq!(keys, 0);
+    }
+
+    assert_eq!(PluginShutdown(), M64ERR_SUCCESS);
+}
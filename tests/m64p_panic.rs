@@ -0,0 +1,27 @@
+#![cfg(feature = "m64p")]
+
+use m64_movie::{
+    Movie, MovieError,
+    m64p::{M64ERR_NOT_INIT, M64ERR_PLUGIN_FAIL},
+};
+
+m64_movie::m64p_input_plugin!(c"Broken playback", || -> Result<Movie, MovieError> {
+    panic!("no movie to load")
+});
+
+#[test]
+fn test_exported_plugin_survives_panicking_load() {
+    let null = std::ptr::null_mut();
+
+    unsafe {
+        assert_eq!(PluginStartup(null, null, null), M64ERR_PLUGIN_FAIL);
+        assert_eq!(PluginStartup(null, null, null), M64ERR_PLUGIN_FAIL);
+
+        let mut keys = 1;
+        GetKeys(0, &mut keys);
+        assert_eq!(keys, 0);
+    }
+
+    assert_eq!(RomOpen(), 1);
+    assert_eq!(PluginShutdown(), M64ERR_NOT_INIT);
+}
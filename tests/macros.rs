@@ -0,0 +1,114 @@
+use m64_movie::{
+    BinReadExt, ControllerButton, FrameError, Movie, MovieError,
+    macros::{ApplyMode, Macro, MacroLibrary, apply_at},
+    raw::ControllerState,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+/// Builds a controller state with the given buttons pressed.
+fn pressed(buttons: &[ControllerButton]) -> ControllerState {
+    let mut state = ControllerState::default();
+    for &button in buttons {
+        state.set(button);
+    }
+
+    state
+}
+
+/// A macro mashing A every other frame.
+fn mash_a(frames: usize) -> Macro {
+    let states = (0..frames)
+        .map(|i| {
+            if i % 2 == 0 {
+                pressed(&[ControllerButton::A])
+            } else {
+                ControllerState::default()
+            }
+        })
+        .collect();
+
+    Macro::new("mash A", states)
+}
+
+#[test]
+fn test_movie_frame_access() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(movie.controller_count(), 1);
+    assert_eq!(movie.frame_count(), 7416);
+    assert_eq!(movie.frame(7415).unwrap().len(), 1);
+    assert!(movie.frame(7416).is_none());
+
+    movie.insert_neutral_frame_run(0, 10).unwrap();
+    assert_eq!(movie.frame_count(), 7426);
+    assert_eq!(movie.to_raw().controller_input_samples, 7426);
+
+    let removed = movie.remove_frames(0..10).unwrap();
+    assert_eq!(removed, vec![ControllerState::default(); 10]);
+    assert_eq!(movie, Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap());
+
+    let result = movie.insert_neutral_frame_run(7417, 1);
+    assert!(matches!(
+        result,
+        Err(MovieError::FrameError(FrameError::OutOfBounds {
+            frame: 7417,
+            frame_count: 7416
+        }))
+    ));
+}
+
+#[test]
+fn test_macro_record() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let m = Macro::record("intro", &movie, 0, 100..110).unwrap();
+
+    assert_eq!(m.name(), "intro");
+    assert_eq!(m.len(), 10);
+    assert_eq!(m.states(), &movie.samples()[100..110]);
+
+    assert!(Macro::record("bad port", &movie, 1, 0..1).is_err());
+    assert!(Macro::record("too long", &movie, 0, 7000..8000).is_err());
+}
+
+#[test]
+fn test_macro_apply_overlay() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let m = mash_a(6);
+
+    apply_at(&mut movie, &m, 200, 0, ApplyMode::Overlay).unwrap();
+    assert_eq!(movie.frame_count(), 7416);
+    assert_eq!(&movie.samples()[200..206], m.states());
+
+    // Overlaying past the end extends the movie.
+    apply_at(&mut movie, &m, 7414, 0, ApplyMode::Overlay).unwrap();
+    assert_eq!(movie.frame_count(), 7420);
+    assert_eq!(&movie.samples()[7414..7420], m.states());
+}
+
+#[test]
+fn test_macro_apply_insert() {
+    let original = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut movie = original.clone();
+    let m = mash_a(4);
+
+    apply_at(&mut movie, &m, 50, 0, ApplyMode::Insert).unwrap();
+    assert_eq!(movie.frame_count(), original.frame_count() + 4);
+    assert_eq!(&movie.samples()[..50], &original.samples()[..50]);
+    assert_eq!(&movie.samples()[50..54], m.states());
+    assert_eq!(&movie.samples()[54..], &original.samples()[50..]);
+}
+
+#[test]
+fn test_macro_library() {
+    let library = [mash_a(4), Macro::new("pause buffer", vec![])]
+        .into_iter()
+        .collect::<MacroLibrary>();
+
+    assert_eq!(library.len(), 2);
+    assert_eq!(library.get("mash A").unwrap().len(), 4);
+    assert_eq!(
+        library.iter().map(Macro::name).collect::<Vec<_>>(),
+        ["mash A", "pause buffer"]
+    );
+}
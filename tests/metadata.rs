@@ -0,0 +1,147 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, EncodedFixedStrError, Movie, MovieError,
+    metadata::{AnonymizeOptions, MetadataPatch},
+    raw::MovieStartType,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_apply_metadata() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let original = movie.clone();
+
+    let patch = MetadataPatch {
+        author: Some("Team".to_string()),
+        input_plugin: Some("TAS Input Plugin 0.6".to_string()),
+        start_type: Some(MovieStartType::Snapshot),
+        ..Default::default()
+    };
+    movie.apply_metadata(&patch).unwrap();
+
+    assert_eq!(movie.recording_info.author_name.to_string(), "Team");
+    assert_eq!(
+        movie.plugin_info.input_plugin.to_string(),
+        "TAS Input Plugin 0.6"
+    );
+    assert_eq!(movie.recording_info.start_type, MovieStartType::Snapshot);
+
+    // Fields not set in the patch are left unchanged.
+    assert_eq!(
+        movie.recording_info.description,
+        original.recording_info.description
+    );
+    assert_eq!(
+        movie.plugin_info.video_plugin,
+        original.plugin_info.video_plugin
+    );
+}
+
+#[test]
+fn test_apply_metadata_is_atomic() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let original = movie.clone();
+
+    let patch = MetadataPatch {
+        author: Some("Team".to_string()),
+        video_plugin: Some("Glide64 ✓".to_string()),
+        ..Default::default()
+    };
+
+    let result = movie.apply_metadata(&patch);
+    assert!(matches!(
+        result,
+        Err(MovieError::FixedStrError(
+            EncodedFixedStrError::InvalidAscii(_)
+        ))
+    ));
+    assert_eq!(movie, original);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_metadata_patch_toml_roundtrip() {
+    let toml = r#"
+        author = "MKDasher, Nahoc"
+        description = "120 star TAS"
+        start_type = "power-on"
+    "#;
+
+    let patch = MetadataPatch::from_toml(toml).unwrap();
+    assert_eq!(patch.author.as_deref(), Some("MKDasher, Nahoc"));
+    assert_eq!(patch.start_type, Some(MovieStartType::PowerOn));
+    assert_eq!(patch.video_plugin, None);
+
+    let serialized = patch.to_toml().unwrap();
+    assert_eq!(MetadataPatch::from_toml(&serialized).unwrap(), patch);
+
+    assert!(MetadataPatch::from_toml("rom_name = \"x\"").is_err());
+}
+
+#[test]
+fn test_header_roundtrip() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut other = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    other.recording_info.set_rerecord_count(0).unwrap();
+
+    other.set_header(movie.header()).unwrap();
+    assert_eq!(other, movie);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_header_toml_roundtrip() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let toml = movie.header_to_toml().unwrap();
+
+    assert!(toml.contains("rom_name = \"SUPER MARIO 64\""));
+    assert!(toml.contains("start_type = \"power-on\""));
+
+    let edited = toml.replace("rerecord_count = 189571", "rerecord_count = 42");
+    movie.import_header_toml(&edited).unwrap();
+    assert_eq!(movie.recording_info.rerecord_count(), 42);
+
+    let expected = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(movie.samples(), expected.samples());
+    assert_eq!(movie.game_info, expected.game_info);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_header_toml_import_keeps_padding() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    let mut rom_name = [b' '; 32];
+    rom_name[..14].copy_from_slice(b"SUPER MARIO 64");
+    m64_movie::patch::apply(&mut bytes, &[(0xC4, &rom_name)]).unwrap();
+
+    let mut movie = Movie::from_bytes(&bytes).unwrap();
+    let toml = movie.header_to_toml().unwrap();
+    movie.import_header_toml(&toml).unwrap();
+    assert_eq!(movie.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn test_anonymize() {
+    let original = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert!(!original.recording_info.author_name.is_empty());
+
+    let mut movie = original.clone();
+    movie.anonymize();
+    assert!(movie.recording_info.author_name.is_empty());
+    assert!(movie.recording_info.description.is_empty());
+    assert_eq!(movie.recording_info.uid, 0);
+    assert_eq!(
+        movie.recording_info.rerecord_count(),
+        original.recording_info.rerecord_count()
+    );
+    assert_eq!(movie.game_info, original.game_info);
+    assert_eq!(movie.plugin_info, original.plugin_info);
+    assert_eq!(movie.samples(), original.samples());
+
+    movie.anonymize_with(AnonymizeOptions::default().clear_rerecords(true));
+    assert_eq!(movie.recording_info.rerecord_count(), 0);
+
+    let bytes = movie.to_bytes().unwrap();
+    assert!(bytes[0x222..0x400].iter().all(|&b| b == 0));
+}
@@ -0,0 +1,66 @@
+use m64_movie::{
+    NameError,
+    raw::{ControllerFlags, MovieStartType, PakType},
+    region::CountryCode,
+};
+
+#[test]
+fn test_start_type_names() {
+    for start_type in [
+        MovieStartType::Snapshot,
+        MovieStartType::PowerOn,
+        MovieStartType::EEPROM,
+    ] {
+        assert_eq!(
+            start_type.to_string().parse::<MovieStartType>().unwrap(),
+            start_type
+        );
+    }
+
+    assert_eq!(MovieStartType::PowerOn.to_string(), "power-on");
+    assert_eq!(
+        " EEPROM ".parse::<MovieStartType>().unwrap(),
+        MovieStartType::EEPROM
+    );
+    assert!(matches!(
+        "2".parse::<MovieStartType>(),
+        Err(NameError::Unknown {
+            kind: "start type",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_country_code_names() {
+    for code in 0..=0xFF {
+        let country = CountryCode::from(code);
+        assert_eq!(country.to_string().parse::<CountryCode>().unwrap(), country);
+    }
+
+    assert_eq!(CountryCode::Japan.to_string(), "japan");
+    assert_eq!(CountryCode::OtherEurope(b'Y').to_string(), "Y");
+    assert_eq!(CountryCode::Other(0x1234).to_string(), "0x1234");
+    assert_eq!(
+        "North-America".parse::<CountryCode>().unwrap(),
+        CountryCode::NorthAmerica
+    );
+    assert_eq!("P".parse::<CountryCode>().unwrap(), CountryCode::Europe);
+    assert_eq!("0x4A".parse::<CountryCode>().unwrap(), CountryCode::Japan);
+    assert!("atlantis".parse::<CountryCode>().is_err());
+}
+
+#[test]
+fn test_pak_types() {
+    let flags = &mut ControllerFlags::from(0b0001);
+    assert_eq!(flags.pak_type(0), Some(PakType::None));
+    assert_eq!(flags.pak_type(4), None);
+
+    flags.set_pak_type(1, "rumblepak".parse().unwrap());
+    assert_eq!(flags.pak_type(1), Some(PakType::Rumblepak));
+    assert!(flags.controller_02_has_rumblepak());
+
+    flags.set_controller_02_has_mempak(true);
+    assert_eq!(flags.pak_type(1), Some(PakType::Mempak));
+    assert_eq!(PakType::Mempak.to_string(), "mempak");
+}
@@ -0,0 +1,41 @@
+use m64_movie::{BinReadExt, Movie, paste::PasteMode};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_paste_hex_block() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let block = movie.to_hex_block(100..110).unwrap();
+
+    let mut pasted = movie.clone();
+    assert_eq!(pasted.paste_frames(7410, &block).unwrap(), 10);
+    assert_eq!(pasted.frame_count(), 7420);
+    assert_eq!(pasted.samples()[7410..], movie.samples()[100..110]);
+    assert_eq!(pasted.samples()[..7410], movie.samples()[..7410]);
+
+    let mut inserted = movie.clone();
+    inserted
+        .paste_frames_with(0, &block, PasteMode::Insert)
+        .unwrap();
+    assert_eq!(inserted.frame_count(), 7426);
+    assert_eq!(inserted.samples()[..10], movie.samples()[100..110]);
+}
+
+#[test]
+fn test_paste_input_log() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let log = movie.to_bizhawk_input_log();
+
+    let mut pasted = movie.clone();
+    assert_eq!(pasted.paste_frames(0, &log).unwrap(), 7416);
+    assert_eq!(pasted.samples(), movie.samples());
+
+    let line = log.lines().find(|line| line.starts_with('|')).unwrap();
+    assert_eq!(pasted.paste_frames(3, line).unwrap(), 1);
+    assert_eq!(pasted.frame(3), movie.frame(0));
+
+    assert!(pasted.paste_frames(7417, line).is_err());
+    assert!(pasted.paste_frames(0, "not a block").is_err());
+    assert_eq!(pasted.frame_count(), 7416);
+}
@@ -0,0 +1,67 @@
+use m64_movie::{
+    BinReadExt, Movie, MovieError, PatchError,
+    patch::{self, PatchOptions},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_patch_apply() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    let warnings = patch::apply(&mut bytes, &[(0x222, b"Someone"), (0x229, &[0])]).unwrap();
+    assert!(warnings.is_empty());
+
+    let movie = Movie::from_bytes(&bytes).unwrap();
+    assert_eq!(movie.recording_info.author_name, "Someone");
+}
+
+#[test]
+fn test_patch_out_of_bounds_applies_nothing() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    let len = bytes.len();
+    let result = patch::apply(&mut bytes, &[(0x222, b"Someone"), (len - 1, &[0, 0])]);
+
+    assert!(matches!(
+        result,
+        Err(MovieError::PatchError(PatchError::OutOfBounds {
+            len: 2,
+            ..
+        }))
+    ));
+    assert_eq!(bytes, MOVIE_1KEY_BYTES);
+    assert!(patch::apply(&mut bytes, &[(usize::MAX, &[0])]).is_err());
+}
+
+#[test]
+fn test_patch_field_boundary_warning() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    let warnings = patch::apply(&mut bytes, &[(0x14, &[60, 1])]).unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    let names: Vec<_> = warnings[0].fields.iter().map(|field| field.name).collect();
+    assert_eq!(names, ["vis_per_second", "controller_count"]);
+    assert_eq!(
+        warnings[0].to_string(),
+        "patch of 2 bytes at 0x014 spans fields vis_per_second, controller_count"
+    );
+}
+
+#[test]
+fn test_patch_validation() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    let options = PatchOptions::default().validate(true);
+
+    // Two controllers while only one is flagged as present.
+    assert!(patch::apply_with(&mut bytes, &[(0x15, &[2])], options).is_err());
+    assert_eq!(bytes, MOVIE_1KEY_BYTES);
+
+    patch::apply_with(&mut bytes, &[(0x10, &7u32.to_le_bytes())], options).unwrap();
+    assert_eq!(
+        Movie::from_bytes(&bytes)
+            .unwrap()
+            .recording_info
+            .rerecord_count(),
+        7
+    );
+}
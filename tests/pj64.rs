@@ -0,0 +1,58 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, Movie,
+    convert::pj64::PJM_MAGIC,
+    parsed::{ExtendedData, ExtendedFlags},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_pjm_roundtrip() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let pjm = movie.to_pjm_bytes().unwrap();
+
+    assert_eq!(pjm[..4], PJM_MAGIC);
+    assert_eq!(pjm[4..8], 1u32.to_le_bytes());
+    assert_eq!(pjm[8..], MOVIE_1KEY_BYTES[8..]);
+
+    let converted = Movie::from_pjm_bytes(&pjm).unwrap();
+    assert_eq!(converted, movie);
+    assert_eq!(converted.to_bytes().unwrap(), MOVIE_1KEY_BYTES);
+}
+
+#[test]
+fn test_pjm_drops_extended_data() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.metadata.extended_version = 1;
+    movie.metadata.extended_flags = ExtendedFlags::ExtendedFlagsV1 {
+        wiivc_emulation_mode: true,
+    };
+    movie.metadata.extended_data = ExtendedData::ExtendedDataV1 {
+        authorship_info: 1,
+        bruteforce_data: 2,
+        rerecord_count_high: 3,
+    };
+
+    let pjm = movie.to_pjm_bytes().unwrap();
+    assert!(pjm[0x24..0x44].iter().all(|&b| b == 0));
+    let converted = Movie::from_pjm_bytes(&pjm).unwrap();
+    assert_eq!(converted.metadata.extended_version, 0);
+    assert_eq!(
+        converted.metadata.extended_data,
+        ExtendedData::ExtendedDataV0
+    );
+}
+
+#[test]
+fn test_pjm_rejects_other_files() {
+    assert!(Movie::from_pjm_bytes(MOVIE_1KEY_BYTES).is_err());
+    assert!(Movie::from_pjm_bytes(b"PJM ").is_err());
+
+    let mut pjm = Movie::from_bytes(MOVIE_1KEY_BYTES)
+        .unwrap()
+        .to_pjm_bytes()
+        .unwrap();
+    pjm[4] = 2;
+    assert!(Movie::from_pjm_bytes(&pjm).is_err());
+}
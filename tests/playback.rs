@@ -0,0 +1,57 @@
+use m64_movie::{BinReadExt, Movie, playback::InputProvider, raw::ControllerState};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_cursor_advance() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut cursor = movie.cursor();
+
+    assert_eq!(cursor.current_frame(), 0);
+    assert_eq!(cursor.remaining(), 7416);
+    assert_eq!(cursor.advance(), movie.frame(0));
+    assert_eq!(cursor.current_frame(), 1);
+    assert_eq!(cursor.port(0), movie.port_state(1, 0).copied());
+    assert_eq!(cursor.port(1), None);
+    assert_eq!(cursor.count(), 7415);
+}
+
+#[test]
+fn test_cursor_seek() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut cursor = movie.cursor();
+
+    cursor.seek(5000).unwrap();
+    assert_eq!(cursor.current(), movie.frame(5000));
+    assert_eq!(cursor.remaining(), 2416);
+
+    cursor.seek(7416).unwrap();
+    assert!(cursor.is_finished());
+    assert_eq!(cursor.advance(), None);
+    assert!(cursor.seek(7417).is_err());
+    assert_eq!(cursor.current_frame(), 7416);
+}
+
+/// Runs a frontend for a number of frames, collecting the inputs of the first port.
+fn run<P: InputProvider>(provider: &mut P, frames: usize) -> Vec<ControllerState> {
+    (0..frames)
+        .map(|_| {
+            let state = provider.poll(0);
+            provider.frame_advanced();
+            state
+        })
+        .collect()
+}
+
+#[test]
+fn test_cursor_input_provider() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut cursor = movie.cursor();
+    cursor.seek(7410).unwrap();
+
+    let states = run(&mut cursor, 10);
+    assert_eq!(states[..6], movie.samples()[7410..]);
+    assert_eq!(states[6..], [ControllerState::default(); 4]);
+    assert_eq!(cursor.poll(3), ControllerState::default());
+}
@@ -0,0 +1,59 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, Movie, parsed::ExtendedData, raw::ControllerState,
+    recording::RecordingSession,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_recording_session_reproduces_movie() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut session = RecordingSession::new(movie.header());
+    assert_eq!(session.frame_count(), 0);
+    assert_eq!(session.vertical_interrupts(), 0);
+
+    for frame in movie.cursor() {
+        session.push_frame(frame).unwrap();
+    }
+    for _ in 0..movie.recording_info.vertical_interrupts() {
+        session.push_vertical_interrupt();
+    }
+    session.set_rerecords(movie.rerecord_count()).unwrap();
+
+    assert_eq!(session.frame_count(), 7416);
+    assert_eq!(session.vertical_interrupts(), 15384);
+    assert_eq!(
+        session.finalize().to_bytes().unwrap(),
+        movie.to_bytes().unwrap()
+    );
+}
+
+#[test]
+fn test_recording_session_rejects_partial_frames() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut session = RecordingSession::new(movie.header());
+
+    let states = [ControllerState::default(); 2];
+    assert!(session.push_frame(&states).is_err());
+    assert!(session.push_frame(&states[..1]).is_ok());
+    assert_eq!(session.movie().frame_count(), 1);
+}
+
+#[test]
+fn test_recording_session_resets_whole_rerecord_count() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.metadata.extended_version = 1;
+    movie.metadata.extended_data = ExtendedData::ExtendedDataV1 {
+        authorship_info: 0,
+        bruteforce_data: 0,
+        rerecord_count_high: 3,
+    };
+
+    let mut session = RecordingSession::new(movie.header());
+    assert_eq!(session.rerecords(), 0);
+
+    session.set_rerecords(0x2_0000_0005).unwrap();
+    assert_eq!(session.rerecords(), 0x2_0000_0005);
+    assert_eq!(session.movie().recording_info.rerecord_count(), 5);
+}
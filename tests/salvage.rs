@@ -0,0 +1,86 @@
+use m64_movie::{
+    BinReadExt, Movie, RawMovie,
+    raw::{MovieStartType, SalvageIssue},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_forced_parse_of_valid_movie_is_clean() {
+    let salvaged = RawMovie::from_bytes_forced(MOVIE_1KEY_BYTES).unwrap();
+    assert!(salvaged.is_clean());
+    assert_eq!(
+        salvaged.movie,
+        RawMovie::from_bytes(MOVIE_1KEY_BYTES).unwrap()
+    );
+}
+
+#[test]
+fn test_forced_parse_repairs_header() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes[0x01] = b'N'; // magic
+    bytes[0x04] = 0x07; // version
+    bytes[0x1C] = 0x03; // start type
+    bytes[0xC4] = 0xFF; // ROM name
+    assert!(RawMovie::from_bytes(&bytes).is_err());
+
+    let salvaged = RawMovie::from_bytes_forced(&bytes).unwrap();
+    assert_eq!(
+        salvaged.issues,
+        [
+            SalvageIssue::BadMagic(*b"MN4\x1A"),
+            SalvageIssue::UnsupportedVersion(7),
+            SalvageIssue::UnknownStartType(3),
+            SalvageIssue::NonAsciiString("ROM name"),
+        ]
+    );
+
+    let movie = Movie::try_from(salvaged.movie).unwrap();
+    assert_eq!(movie.recording_info.start_type, MovieStartType::PowerOn);
+    assert_eq!(movie.game_info.rom_name.as_str()[..1], *"?");
+    assert_eq!(movie.frame_count(), 7416);
+}
+
+#[test]
+fn test_forced_parse_of_truncated_file() {
+    let mut bytes = MOVIE_1KEY_BYTES[..0x400 + 4 * 10 + 3].to_vec();
+    bytes[0x16] = 0x09; // extended version
+
+    let salvaged = RawMovie::from_bytes_forced(&bytes).unwrap();
+    assert_eq!(
+        salvaged.issues,
+        [
+            SalvageIssue::PartialSample(3),
+            SalvageIssue::UnsupportedExtendedVersion(9),
+            SalvageIssue::SampleCountMismatch {
+                expected: 7416,
+                actual: 10
+            },
+        ]
+    );
+    assert_eq!(salvaged.movie.inputs.len(), 10);
+    assert_eq!(salvaged.movie.controller_input_samples, 10);
+
+    let salvaged = RawMovie::from_bytes_forced(&[]).unwrap();
+    assert!(salvaged.issues.contains(&SalvageIssue::TruncatedHeader(0)));
+    assert!(salvaged.movie.inputs.is_empty());
+}
+
+#[test]
+fn test_forced_parse_repairs_sample_count() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes[0x18..0x1C].copy_from_slice(&7000u32.to_le_bytes());
+    assert!(Movie::from_bytes(&bytes).is_err());
+
+    let salvaged = RawMovie::from_bytes_forced(&bytes).unwrap();
+    assert_eq!(
+        salvaged.issues,
+        [SalvageIssue::SampleCountMismatch {
+            expected: 7000,
+            actual: 7416
+        }]
+    );
+    let movie = Movie::try_from(salvaged.movie).unwrap();
+    assert_eq!(movie, Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap());
+}
@@ -0,0 +1,152 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, FrameError, Movie, MovieError, SegmentError,
+    raw::{ControllerFlags, ControllerState},
+    segment::Segment,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_segment_record_and_roundtrip() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut segment = Segment::record("door entry", &movie, 500..560).unwrap();
+    segment.provenance_mut().source = "1key.m64".to_string();
+
+    assert_eq!(segment.frame_count(), 60);
+    assert_eq!(segment.samples(), &movie.samples()[500..560]);
+    assert_eq!(segment.provenance().source_frame, 500);
+    assert_eq!(segment.provenance().rom_crc32, 0x0E3DAA4E);
+
+    let bytes = segment.to_bytes().unwrap();
+    assert!(bytes.starts_with(b"M64S\x1A"));
+    assert_eq!(Segment::from_bytes(&bytes).unwrap(), segment);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("door.seg");
+    segment.to_file(&path).unwrap();
+    assert_eq!(Segment::from_file(&path).unwrap(), segment);
+}
+
+#[test]
+fn test_movie_insert_segment() {
+    let original = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let segment = Segment::record("star dance skip", &original, 0..30).unwrap();
+
+    let mut movie = original.clone();
+    movie.insert_segment(1000, &segment).unwrap();
+    assert_eq!(movie.frame_count(), original.frame_count() + 30);
+    assert_eq!(&movie.samples()[1000..1030], segment.samples());
+
+    let mut flags = ControllerFlags::default();
+    flags.set_controller_01_present(true);
+    flags.set_controller_02_present(true);
+    let two_player = Segment::new("co-op", flags, vec![ControllerState::default(); 4]).unwrap();
+    assert_eq!(two_player.frame_count(), 2);
+
+    let result = movie.insert_segment(0, &two_player);
+    assert!(matches!(
+        result,
+        Err(MovieError::SegmentError(SegmentError::ControllerMismatch {
+            required: 0b0011,
+            present: 0b0001
+        }))
+    ));
+
+    assert!(Segment::new("partial", flags, vec![ControllerState::default(); 3]).is_err());
+}
+
+#[test]
+fn test_movie_split_at_markers() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let markers = [("lobby", 1000), ("bowser", 5000), ("key", 5000)]
+        .into_iter()
+        .collect();
+
+    let segments = movie.split_at_markers(&markers).unwrap();
+    let names = segments.iter().map(Segment::name).collect::<Vec<_>>();
+    assert_eq!(names, ["", "lobby", "bowser", "key"]);
+    let frames = segments
+        .iter()
+        .map(Segment::frame_count)
+        .collect::<Vec<_>>();
+    assert_eq!(frames, [1000, 4000, 0, 2416]);
+    assert_eq!(segments[3].provenance().source_frame, 5000);
+    assert_eq!(segments[3].samples(), &movie.samples()[5000..]);
+
+    let markers = [("start", 0)].into_iter().collect();
+    let segments = movie.split_at_markers(&markers).unwrap();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].samples(), movie.samples());
+
+    let markers = [("past end", 8000)].into_iter().collect();
+    assert!(movie.split_at_markers(&markers).is_err());
+}
+
+#[test]
+fn test_movie_from_segments() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let markers = [("lobby", 1000), ("bowser", 5000)].into_iter().collect();
+    let segments = movie.split_at_markers(&markers).unwrap();
+
+    let assembled = Movie::from_segments(&movie, &segments).unwrap();
+    assert_eq!(assembled, movie);
+
+    let assembled = Movie::from_segments(&movie, &segments[1..]).unwrap();
+    assert_eq!(assembled.frame_count(), movie.frame_count() - 1000);
+    assert_eq!(
+        assembled.recording_info.vertical_interrupts() as u64,
+        movie.timing().frame_to_vi(assembled.frame_count())
+    );
+
+    let mut foreign = segments[0].clone();
+    foreign.provenance_mut().rom_crc32 = 0x635A_2BFF;
+    assert!(matches!(
+        Movie::from_segments(&movie, &[foreign]),
+        Err(MovieError::SegmentError(SegmentError::RomMismatch {
+            found: 0x635A_2BFF,
+            ..
+        }))
+    ));
+
+    let mut flags = ControllerFlags::default();
+    flags.set_controller_01_present(true);
+    flags.set_controller_02_present(true);
+    let two_player = Segment::new("co-op", flags, vec![ControllerState::default(); 4]).unwrap();
+    assert!(matches!(
+        Movie::from_segments(&movie, &[segments[0].clone(), two_player]),
+        Err(MovieError::SegmentError(
+            SegmentError::ControllerMismatch { .. }
+        ))
+    ));
+}
+
+#[test]
+fn test_movie_from_segments_drops_template_frame_data() {
+    let mut template = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    template.markers_mut().insert("lobby", 1000);
+    template.annotations_mut().insert(1000, "lobby");
+    template.set_lag_mask(Some([1000].into_iter().collect()));
+    let segments = template.split_at_markers(template.markers()).unwrap();
+
+    let assembled = Movie::from_segments(&template, &segments[1..]).unwrap();
+    assert!(assembled.markers().is_empty());
+    assert!(assembled.annotations().is_empty());
+    assert_eq!(assembled.lag_mask(), None);
+}
+
+#[test]
+fn test_segment_record_rejects_invalid_ranges() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 9000..10;
+    assert!(matches!(
+        Segment::record("x", &movie, reversed),
+        Err(MovieError::FrameError(FrameError::InvalidRange { .. }))
+    ));
+    assert!(matches!(
+        Segment::record("x", &movie, 7000..7417),
+        Err(MovieError::FrameError(FrameError::OutOfBounds { .. }))
+    ));
+}
@@ -0,0 +1,186 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, ControllerButton, Movie,
+    export::Subtitles,
+    lag::LagMask,
+    lint::AxisConstraints,
+    selection::FrameSelection,
+    transform::{RemapProfile, remap_buttons_in},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_frame_selection_insert_merges() {
+    let mut selection = FrameSelection::new();
+    selection.insert(10..20);
+    selection.insert(30..40);
+    selection.insert(20..25);
+    selection.insert(5..5);
+    assert_eq!(selection.ranges(), &[10..25, 30..40]);
+
+    selection.insert(0..35);
+    assert_eq!(selection, FrameSelection::from(0..40));
+    assert_eq!(selection.len(), 40);
+}
+
+#[test]
+fn test_frame_selection_set_operations() {
+    let a: FrameSelection = [100..201, 5000..5101].into_iter().collect();
+    let b = FrameSelection::from(150..5050);
+
+    assert_eq!(a.union(&b), FrameSelection::from(100..5101));
+    assert_eq!(a.intersect(&b).ranges(), &[150..201, 5000..5050]);
+    assert_eq!(a.difference(&b).ranges(), &[100..150, 5050..5101]);
+    assert_eq!(a.invert(6000).ranges(), &[0..100, 201..5000, 5101..6000]);
+    assert_eq!(a.invert(150), FrameSelection::from(0..100));
+    assert_eq!(a.invert(6000).invert(6000), a);
+    assert!(FrameSelection::new().intersect(&a).is_empty());
+}
+
+#[test]
+fn test_frame_selection_contains_and_display() {
+    let selection = FrameSelection::from(100..201)
+        .union(&(5000..5101).into())
+        .difference(&(5050..5051).into());
+
+    assert!(selection.contains(100));
+    assert!(selection.contains(200));
+    assert!(!selection.contains(201));
+    assert!(!selection.contains(5050));
+    assert_eq!(selection.len(), 101 + 100);
+    assert_eq!(selection.frames().nth(101), Some(5000));
+    assert_eq!(selection.to_string(), "100-200,5000-5049,5051-5100");
+    assert_eq!(FrameSelection::from(7..8).to_string(), "7");
+}
+
+#[test]
+fn test_remap_buttons_in_selection() {
+    let original = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut movie = original.clone();
+    let profile = RemapProfile::new().with(ControllerButton::A, ControllerButton::B);
+    let selection = FrameSelection::from(0..100).union(&(7400..8000).into());
+
+    remap_buttons_in(&mut movie, 0, &profile, &selection).unwrap();
+    for frame in 0..movie.frame_count() {
+        let expected = original.port_state(frame, 0).unwrap();
+        let expected = match selection.contains(frame) {
+            true => profile.apply(expected),
+            false => *expected,
+        };
+        assert_eq!(movie.port_state(frame, 0), Some(&expected));
+    }
+}
+
+#[test]
+fn test_hex_block_in_selection() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let selection: FrameSelection = [100..102, 200..201].into_iter().collect();
+
+    let block = movie.to_hex_block_in(&selection).unwrap();
+    let lines: Vec<&str> = block.lines().collect();
+    assert_eq!(lines[0], "# frames 100..102, 200..201");
+    assert_eq!(lines.len(), 4);
+    assert!(lines[3].starts_with("200: "));
+
+    let mut copy = movie.clone();
+    assert_eq!(copy.import_hex_block(&block).unwrap(), 3);
+    assert_eq!(copy, movie);
+
+    assert!(movie.to_hex_block_in(&(7000..7417).into()).is_err());
+}
+
+#[test]
+fn test_subtitles_from_annotations_in_selection() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.annotations_mut().insert(100, "Start");
+    movie.annotations_mut().insert(3000, "Skipped");
+    movie.annotations_mut().insert(7000, "Star grab");
+
+    let selection: FrameSelection = [0..500, 6900..7416].into_iter().collect();
+    let subtitles = Subtitles::from_annotations_in(&movie, &selection);
+    let cues = subtitles.cues();
+    assert_eq!(cues.len(), 2);
+    assert_eq!((cues[0].start, cues[0].end), (100, 500));
+    assert_eq!((cues[1].start, cues[1].end), (7000, 7416));
+}
+
+#[test]
+fn test_lint_and_fix_axes_in_selection() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let constraints = AxisConstraints::new();
+    movie.fix_axes(&constraints);
+    movie.port_state_mut(200, 0).unwrap().set_x_axis(-128);
+    movie.port_state_mut(5050, 0).unwrap().set_x_axis(-128);
+
+    let selection = FrameSelection::from(100..300).union(&(5000..5101).into());
+    let selection = selection.difference(&(5050..5051).into());
+
+    let issues = movie.lint_axes_in(&constraints, &selection);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].frame, 200);
+
+    assert_eq!(movie.fix_axes_in(&constraints, &selection), 1);
+    assert_eq!(movie.lint_axes(&constraints).len(), 1);
+    assert_eq!(movie.port_state(5050, 0).unwrap().x_axis(), -128);
+}
+
+#[test]
+fn test_extract_selection() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let selection = FrameSelection::from(100..201)
+        .union(&(5000..5101).into())
+        .difference(&(5050..5051).into());
+
+    let extract = movie.extract(&selection);
+    assert_eq!(extract.frame_count(), selection.len());
+    assert_eq!(extract.frame(0), movie.frame(100));
+    assert_eq!(extract.frame(101), movie.frame(5000));
+    assert_eq!(extract.frame(151), movie.frame(5051));
+    assert_eq!(extract.game_info, movie.game_info);
+
+    let timing = movie.timing();
+    assert_eq!(
+        extract.recording_info.vertical_interrupts() as u64,
+        timing.frame_to_vi(selection.len())
+    );
+
+    let bytes = extract.to_bytes().unwrap();
+    assert_eq!(Movie::from_bytes(&bytes).unwrap(), extract);
+
+    let whole = movie.extract(&(0..10_000).into());
+    assert_eq!(whole.samples(), movie.samples());
+    assert_eq!(
+        whole.recording_info.vertical_interrupts() as u64,
+        timing.frame_to_vi(movie.frame_count())
+    );
+    assert_eq!(movie.extract(&FrameSelection::new()).frame_count(), 0);
+}
+
+#[test]
+fn test_extract_remaps_frame_data() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.annotations_mut().insert(150, "kept");
+    movie.annotations_mut().insert(5050, "deselected");
+    movie.markers_mut().insert("second range", 5060);
+    movie.markers_mut().insert("past the end", 7000);
+    movie.set_lag_mask(Some([100, 300, 5100].into_iter().collect()));
+
+    let selection = FrameSelection::from(100..201)
+        .union(&(5000..5101).into())
+        .difference(&(5050..5051).into());
+    assert_eq!(selection.position(5060), Some(160));
+    assert_eq!(selection.position(5050), None);
+
+    let extract = movie.extract(&selection);
+    assert_eq!(
+        extract.annotations().iter().collect::<Vec<_>>(),
+        [(50, "kept")]
+    );
+    assert_eq!(extract.markers().get("second range"), Some(160));
+    assert_eq!(extract.markers().get("past the end"), None);
+    assert_eq!(
+        extract.lag_mask(),
+        Some(&[0, 200].into_iter().collect::<LagMask>())
+    );
+}
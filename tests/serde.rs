@@ -0,0 +1,67 @@
+#![cfg(feature = "serde")]
+
+use m64_movie::{ControllerButton, raw::ControllerState};
+use serde_test::{Configure, Token, assert_tokens};
+
+/// A controller state with A and Z pressed and the stick pushed up and to the left.
+fn state() -> ControllerState {
+    let mut state = ControllerState::default();
+    state.set(ControllerButton::A);
+    state.set(ControllerButton::Z);
+    state.set_axis(-20, 127);
+    state
+}
+
+#[test]
+fn test_controller_state_readable() {
+    assert_tokens(
+        &state().readable(),
+        &[
+            Token::Struct {
+                name: "ControllerStateRepr",
+                len: 3,
+            },
+            Token::Str("buttons"),
+            Token::Seq { len: Some(2) },
+            Token::UnitVariant {
+                name: "ControllerButton",
+                variant: "Z",
+            },
+            Token::UnitVariant {
+                name: "ControllerButton",
+                variant: "A",
+            },
+            Token::SeqEnd,
+            Token::Str("x"),
+            Token::I8(-20),
+            Token::Str("y"),
+            Token::I8(127),
+            Token::StructEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_controller_state_compact() {
+    assert_tokens(&state().compact(), &[Token::U32(u32::from(state()))]);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_controller_state_with_helper() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Frame {
+        input: ControllerState,
+        #[serde(with = "m64_movie::raw::serde_with::compact")]
+        packed: ControllerState,
+    }
+
+    let frame = Frame {
+        input: state(),
+        packed: state(),
+    };
+    let toml = toml::to_string(&frame).unwrap();
+    assert!(toml.contains("buttons = [\"Z\", \"A\"]"));
+    assert!(toml.contains(&format!("packed = {}", u32::from(state()))));
+    assert_eq!(toml::from_str::<Frame>(&toml).unwrap(), frame);
+}
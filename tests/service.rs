@@ -0,0 +1,139 @@
+#![cfg(feature = "service")]
+
+use std::io::ErrorKind;
+
+use m64_movie::{
+    BinReadExt, BinWriteExt, Movie,
+    service::{MAX_REQUEST_LEN, serve, serve_tcp},
+};
+use serde_json::{Value, json};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+/// Sends requests to a service, returning the responses.
+fn run(requests: &[Value]) -> Vec<Value> {
+    let input: String = requests.iter().map(|r| format!("{r}\n")).collect();
+    let mut output = Vec::new();
+    serve(input.as_bytes(), &mut output).unwrap();
+
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_service_edits_movie() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("1key.m64");
+    let out = dir.path().join("edited.m64");
+    std::fs::write(&path, MOVIE_1KEY_BYTES).unwrap();
+
+    let state = json!({ "buttons": ["A"], "x": 10, "y": -10 });
+    let responses = run(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "open", "params": { "path": path } }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "setFrame", "params": { "frame": 5, "states": [state] } }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "getFrame", "params": { "frame": 5 } }),
+        json!({ "jsonrpc": "2.0", "method": "splice", "params": { "start": 0, "deleteCount": 2 } }),
+        json!({ "jsonrpc": "2.0", "id": 4, "method": "save", "params": { "path": out } }),
+    ]);
+
+    assert_eq!(responses.len(), 4);
+    assert_eq!(
+        responses[0]["result"],
+        json!({ "frameCount": 7416, "controllerCount": 1 })
+    );
+    assert_eq!(responses[1]["result"], Value::Null);
+    assert_eq!(responses[2]["result"], json!([state]));
+    assert_eq!(responses[3]["id"], 4);
+
+    let edited = Movie::from_file(&out).unwrap();
+    assert_eq!(edited.frame_count(), 7414);
+    assert_eq!(edited.port_state(3, 0).unwrap().x_axis(), 10);
+}
+
+#[test]
+fn test_service_errors() {
+    let responses = run(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "getFrame", "params": { "frame": 0 } }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "rewind" }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "open", "params": {} }),
+    ]);
+
+    assert_eq!(responses[0]["error"]["code"], -32000);
+    assert_eq!(responses[1]["error"]["code"], -32601);
+    assert_eq!(responses[2]["error"]["code"], -32602);
+
+    let mut output = Vec::new();
+    serve("not json\n".as_bytes(), &mut output).unwrap();
+    let response: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(response["error"]["code"], -32700);
+
+    let mut output = Vec::new();
+    serve(
+        &b"\xFF\xFE\n{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"rewind\"}\n"[..],
+        &mut output,
+    )
+    .unwrap();
+    let responses: Vec<Value> = output
+        .split(|&byte| byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_slice(line).unwrap())
+        .collect();
+    assert_eq!(responses[0]["error"]["code"], -32700);
+    assert_eq!(responses[1]["error"]["code"], -32601);
+}
+
+#[test]
+fn test_service_rejects_long_requests() {
+    let mut input = vec![b'x'; MAX_REQUEST_LEN + 1];
+    input.extend_from_slice(b"\n{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"rewind\"}\n");
+
+    let mut output = Vec::new();
+    serve(&input[..], &mut output).unwrap();
+    let responses: Vec<Value> = output
+        .split(|&byte| byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_slice(line).unwrap())
+        .collect();
+    assert_eq!(responses[0]["error"]["code"], -32700);
+    assert_eq!(responses[1]["error"]["code"], -32601);
+}
+
+#[test]
+fn test_service_failed_splice_keeps_movie() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut flags = movie.controller_flags();
+    flags.set_controller_02_present(true);
+    movie.remove_frames(0..movie.frame_count()).unwrap();
+    movie.set_controller_flags(flags).unwrap();
+    movie.insert_neutral_frame_run(0, 4).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("co-op.m64");
+    std::fs::write(&path, movie.to_bytes().unwrap()).unwrap();
+
+    let state = json!({ "buttons": [], "x": 0, "y": 0 });
+    let responses = run(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "open", "params": { "path": path } }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "splice", "params": { "start": 1, "deleteCount": 2, "states": [state] } }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "save" }),
+    ]);
+
+    assert_eq!(responses[1]["error"]["code"], -32000);
+    assert!(
+        responses[1]["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("controller")
+    );
+    assert_eq!(Movie::from_file(&path).unwrap(), movie);
+}
+
+#[test]
+fn test_serve_tcp_rejects_non_loopback_addresses() {
+    let err = serve_tcp("0.0.0.0:0").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+}
@@ -1,5 +1,9 @@
 use binrw::NullString;
-use m64_movie::{EncodedFixedStrError, MovieError, shared::EncodedFixedStr};
+use m64_movie::{
+    EncodedFixedStrError, MovieError, ascii_fixed,
+    shared::{Ascii, EncodedFixedStr, RawString, Reserved, Utf8},
+    utf8_fixed,
+};
 
 #[test]
 fn test_encoded_fixed_str_ascii() {
@@ -42,3 +46,71 @@ fn test_encoded_fixed_str_utf8_into_null_string() {
 
     assert_eq!(s.to_string(), "こんにちは、世界！");
 }
+
+#[test]
+fn test_encoded_fixed_str_full_length() {
+    let encoded = EncodedFixedStr::<4, _>::from_ascii_str("ABCD").unwrap();
+    assert_eq!(encoded.as_str(), "ABCD");
+    assert_eq!(encoded.len(), 4);
+
+    let raw: RawString<4> = encoded.into();
+    assert_eq!(raw.raw_bytes(), b"ABCD");
+    assert_eq!(raw.as_bytes(), b"ABCD");
+
+    assert!(matches!(
+        EncodedFixedStr::<4, _>::from_ascii_str("ABCDE"),
+        Err(MovieError::FixedStrError(
+            EncodedFixedStrError::FixedStrError(_)
+        ))
+    ));
+}
+
+#[test]
+fn test_raw_string_terminator() {
+    let raw = RawString::new(*b"AB\0D");
+    assert_eq!(raw.as_bytes(), b"AB");
+    assert_eq!(raw.to_string(), "AB");
+    assert_eq!(RawString::<4>::from("ABCDEF").as_bytes(), b"ABCD");
+}
+
+#[test]
+fn test_encoded_fixed_str_compare_and_parse() {
+    let name = EncodedFixedStr::<32, Ascii>::from_ascii_str("SUPER MARIO 64").unwrap();
+    assert_eq!(name, "SUPER MARIO 64");
+    assert_eq!("SUPER MARIO 64", name);
+    assert_eq!(name, "SUPER MARIO 64".to_string());
+    assert_ne!(name, "SUPER MARIO 64 ");
+
+    let author = "Author".parse::<EncodedFixedStr<222, Utf8>>().unwrap();
+    assert_eq!(author, "Author");
+    assert!("世界".parse::<EncodedFixedStr<32, Ascii>>().is_err());
+}
+
+#[test]
+fn test_encoded_fixed_str_const() {
+    const VIDEO: EncodedFixedStr<64, Ascii> = ascii_fixed!("Jabo's Direct3D8 1.6");
+    assert_eq!(
+        VIDEO,
+        EncodedFixedStr::<64, Ascii>::from_ascii_str("Jabo's Direct3D8 1.6").unwrap()
+    );
+
+    let full = ascii_fixed!(4, "ABCD");
+    assert_eq!(full.len(), 4);
+
+    let author: EncodedFixedStr<222, Utf8> = utf8_fixed!("こんにちは");
+    assert_eq!(author, "こんにちは");
+}
+
+#[test]
+fn test_reserved_bytes() {
+    let mut reserved = Reserved::<4>::default();
+    assert!(reserved.is_zeroed());
+
+    reserved.set_bytes([0, 1, 0, 2]);
+    assert!(!reserved.is_zeroed());
+    assert_eq!(reserved.as_bytes(), &[0, 1, 0, 2]);
+    assert_eq!(reserved, Reserved::new([0, 1, 0, 2]));
+
+    reserved.clear();
+    assert!(reserved.is_zeroed());
+}
@@ -0,0 +1,81 @@
+use m64_movie::{
+    BinReadExt, Movie, MovieError, SidecarError, annotations::Annotations, sidecar::Sidecar,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_annotations_range_queries() {
+    let mut annotations = Annotations::new();
+    annotations.insert(100, "Lag spike");
+    annotations.insert(250, "BLJ");
+    annotations.insert(400, "Star dance");
+
+    assert_eq!(annotations.len(), 3);
+    assert_eq!(annotations.get(250), Some("BLJ"));
+    assert_eq!(
+        annotations.range(100..400).collect::<Vec<_>>(),
+        [(100, "Lag spike"), (250, "BLJ")]
+    );
+
+    assert_eq!(
+        annotations.insert(250, "BLJ (fast)"),
+        Some("BLJ".to_string())
+    );
+    assert_eq!(annotations.remove(100), Some("Lag spike".to_string()));
+    assert_eq!(annotations.range(..300).count(), 1);
+}
+
+#[test]
+fn test_sidecar_roundtrip() {
+    let annotations = [(10, "first"), (20, "multi\nline \\ note"), (30, "")]
+        .into_iter()
+        .collect::<Annotations>();
+    let sidecar = Sidecar {
+        annotations,
+        ..Default::default()
+    };
+
+    let text = sidecar.to_string();
+    assert_eq!(Sidecar::parse(&text).unwrap(), sidecar);
+}
+
+#[test]
+fn test_sidecar_parse_errors() {
+    let result = Sidecar::parse("# comment\nannotation x note");
+    assert!(matches!(
+        result,
+        Err(MovieError::SidecarError(SidecarError::InvalidFrame {
+            line: 2,
+            ..
+        }))
+    ));
+
+    let result = Sidecar::parse("bogus 1");
+    assert!(matches!(
+        result,
+        Err(MovieError::SidecarError(SidecarError::UnknownEntry {
+            line: 1,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn test_movie_annotations_persist_in_sidecar() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.annotations_mut().insert(1200, "Key grab");
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = Sidecar::path_for(dir.path().join("1key.m64"));
+    assert!(path.ends_with("1key.m64.sidecar"));
+    movie.save_sidecar(&path).unwrap();
+
+    let mut reloaded = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert!(reloaded.annotations().is_empty());
+
+    reloaded.load_sidecar(&path).unwrap();
+    assert_eq!(reloaded.annotations().get(1200), Some("Key grab"));
+    assert_eq!(reloaded, movie);
+}
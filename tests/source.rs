@@ -0,0 +1,127 @@
+use m64_movie::{
+    BinReadExt, BinWriteExt, Movie,
+    hash::fnv1a,
+    parsed::{ParseOptions, WriteOptions},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+/// Offset of the 128 reserved bytes after the ROM CRC and country code.
+const RESERVED02_OFFSET: usize = 0x44;
+
+/// Offset of the author name field.
+const AUTHOR_OFFSET: usize = 0x222;
+
+#[test]
+fn test_source_is_opt_in() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert!(movie.source().is_none());
+    assert_eq!(movie.is_modified(), None);
+
+    let movie = Movie::from_bytes_with(MOVIE_1KEY_BYTES, ParseOptions::default()).unwrap();
+    assert!(movie.source().is_none());
+}
+
+#[test]
+fn test_source_detects_edits() {
+    let options = ParseOptions::default().retain_source(true);
+    let mut movie = Movie::from_bytes_with(MOVIE_1KEY_BYTES, options).unwrap();
+
+    let source = movie.source().unwrap();
+    assert_eq!(source.len(), MOVIE_1KEY_BYTES.len());
+    assert_eq!(source.header(), &MOVIE_1KEY_BYTES[..0x400]);
+    assert_eq!(source.hash(), fnv1a(MOVIE_1KEY_BYTES));
+    assert_eq!(movie.is_modified(), Some(false));
+
+    movie.recording_info.author_name = "Someone Else".parse().unwrap();
+    assert_eq!(movie.is_modified(), Some(true));
+}
+
+#[test]
+fn test_source_restores_untouched_bytes() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes[RESERVED02_OFFSET] = 0xAB;
+
+    let options = ParseOptions::default().retain_source(true);
+    let mut movie = Movie::from_bytes_with(&bytes, options).unwrap();
+
+    // Reserved bytes are not kept by `Movie`, but are restored from the source.
+    assert_eq!(movie.to_bytes().unwrap()[RESERVED02_OFFSET], 0);
+    assert_eq!(movie.to_bytes_preserving_source().unwrap(), bytes);
+    assert_eq!(movie.is_modified(), Some(false));
+
+    movie.recording_info.author_name = "Someone Else".parse().unwrap();
+    let written = movie.to_bytes_preserving_source().unwrap();
+    assert_eq!(written[RESERVED02_OFFSET], 0xAB);
+    assert_eq!(&written[AUTHOR_OFFSET..AUTHOR_OFFSET + 12], b"Someone Else");
+    assert_eq!(
+        Movie::from_bytes(&written)
+            .unwrap()
+            .recording_info
+            .author_name,
+        "Someone Else"
+    );
+}
+
+#[test]
+fn test_raw_header_keeps_original_values() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes[RESERVED02_OFFSET] = 0xAB;
+
+    let movie = Movie::from_bytes(&bytes).unwrap();
+    let header = movie.raw_header();
+    assert!(header.reserved02.is_zeroed());
+
+    let options = ParseOptions::default().retain_source(true);
+    let mut movie = Movie::from_bytes_with(&bytes, options).unwrap();
+    movie.recording_info.set_rerecord_count(42).unwrap();
+
+    let header = movie.raw_header();
+    assert!(!header.reserved02.is_zeroed());
+    assert_eq!(header.reserved02.as_bytes()[0], 0xAB);
+    assert_eq!(header.rerecord_count, 42);
+    assert_eq!(header.controller_input_samples, movie.frame_count() as u32);
+}
+
+#[test]
+fn test_trailing_data_round_trip() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes.extend_from_slice(b"# converted by tool v2\n");
+
+    let options = ParseOptions::default().retain_trailing_data(true);
+    let movie = Movie::from_bytes_with(&bytes, options).unwrap();
+    assert_eq!(movie.trailing_data(), b"# converted by tool v2\n");
+    assert_eq!(movie.to_bytes().unwrap(), MOVIE_1KEY_BYTES);
+    assert_eq!(
+        movie
+            .to_bytes_with(WriteOptions::default().trailing_data(true))
+            .unwrap(),
+        bytes
+    );
+
+    let movie = Movie::from_bytes_with(&bytes, options.retain_source(true)).unwrap();
+    assert_eq!(movie.to_bytes_preserving_source().unwrap(), bytes);
+    assert_eq!(movie.is_modified(), Some(false));
+
+    assert!(Movie::from_bytes_with(&bytes, ParseOptions::default()).is_err());
+    let plain = Movie::from_bytes_with(MOVIE_1KEY_BYTES, options).unwrap();
+    assert!(plain.trailing_data().is_empty());
+}
+
+#[test]
+fn test_source_does_not_leak_into_edited_fields() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes[AUTHOR_OFFSET..AUTHOR_OFFSET + 222].fill(0);
+    bytes[AUTHOR_OFFSET..AUTHOR_OFFSET + 7].copy_from_slice(b"ABC\0XYZ");
+
+    let options = ParseOptions::default().retain_source(true);
+    let mut movie = Movie::from_bytes_with(&bytes, options).unwrap();
+    assert_eq!(movie, Movie::from_bytes(&bytes).unwrap());
+    assert_eq!(movie.to_bytes_preserving_source().unwrap(), bytes);
+
+    movie.recording_info.author_name = "ABCDE".parse().unwrap();
+    let written = movie.to_bytes_preserving_source().unwrap();
+    assert_eq!(&written[AUTHOR_OFFSET..AUTHOR_OFFSET + 7], b"ABCDE\0\0");
+    assert_eq!(movie.raw_header().author_name.as_bytes(), b"ABCDE");
+}
@@ -0,0 +1,86 @@
+use m64_movie::{
+    BinReadExt, Movie,
+    parsed::ParseOptions,
+    strings::{FieldPadding, StringEncoding, string_fields_report},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_string_fields_report_clean() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let report = movie.string_fields_report();
+
+    let fields: Vec<&str> = report.iter().map(|field| field.field).collect();
+    assert_eq!(
+        fields,
+        [
+            "rom_name",
+            "video_plugin",
+            "sound_plugin",
+            "input_plugin",
+            "rsp_plugin",
+            "author_name",
+            "description"
+        ]
+    );
+    assert_eq!(report[0].text, "SUPER MARIO 64");
+    assert_eq!(report[0].encoding, StringEncoding::Ascii);
+    assert!(report.iter().all(|field| !field.changes_on_reencode));
+}
+
+#[test]
+fn test_string_fields_report_irregular_header() {
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    // Garbage after the terminator of the ROM name.
+    bytes[0x0C4 + 31] = b'X';
+    // A space-padded video plugin.
+    bytes[0x122..0x122 + 64].fill(b' ');
+    bytes[0x122..0x122 + 4].copy_from_slice(b"Jabo");
+    // An author in Latin-1.
+    bytes[0x222..0x222 + 222].fill(0);
+    bytes[0x222..0x222 + 4].copy_from_slice(b"Jos\xE9");
+    // A description in UTF-8.
+    bytes[0x300..0x300 + 256].fill(0);
+    bytes[0x300..0x300 + 5].copy_from_slice("caf\u{e9}".as_bytes());
+
+    let report = string_fields_report(&bytes);
+
+    assert_eq!(report[0].padding, FieldPadding::Zero);
+    assert!(report[0].nonstandard_padding);
+    assert!(report[0].changes_on_reencode);
+
+    assert_eq!(report[1].text, "Jabo");
+    assert_eq!(report[1].padding, FieldPadding::Space);
+    assert!(report[1].nonstandard_padding);
+    assert!(!report[1].changes_on_reencode);
+
+    assert_eq!(report[5].encoding, StringEncoding::Unknown);
+    assert_eq!(report[5].text, "Jos\u{FFFD}");
+    assert!(report[5].changes_on_reencode);
+    assert_eq!(
+        report[5].to_string(),
+        "author_name: \"Jos\u{FFFD}\" (unknown, expected UTF-8, changes on re-encode)"
+    );
+
+    assert_eq!(report[6].encoding, StringEncoding::Utf8);
+    assert!(report[6].is_clean());
+
+    // The same garbage is reported from a retained source.
+    bytes[0x222..0x222 + 4].copy_from_slice(b"Jose");
+    let movie =
+        Movie::from_bytes_with(&bytes, ParseOptions::default().retain_source(true)).unwrap();
+    let report = movie.string_fields_report();
+    assert!(report[0].changes_on_reencode);
+    assert!(!report[5].changes_on_reencode);
+    assert!(Movie::from_bytes(&bytes).unwrap().string_fields_report()[0].is_clean());
+}
+
+#[test]
+fn test_string_fields_report_truncated_header() {
+    let report = string_fields_report(&MOVIE_1KEY_BYTES[..0x100]);
+    assert_eq!(report.len(), 7);
+    assert_eq!(report[0].text, "SUPER MARIO 64");
+    assert!(report[6].text.is_empty());
+}
@@ -0,0 +1,36 @@
+use m64_movie::{BinReadExt, Movie, hash::fnv1a, summary::MovieSummary};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_summary_from_movie() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let summary = movie.summary();
+
+    assert_eq!(summary.rom_crc32, 0x0E3D_AA4E);
+    assert_eq!(summary.rom_country, 0x4A);
+    assert_eq!(summary.controller_count, 1);
+    assert_eq!(summary.frame_count, 7416);
+    assert_eq!(summary.vertical_interrupts, 15384);
+    assert_eq!(summary.duration, movie.duration());
+    assert_eq!(summary.author, movie.recording_info.author_name.to_string());
+    assert_eq!(summary.input_hash, Some(fnv1a(&MOVIE_1KEY_BYTES[0x400..])));
+}
+
+#[test]
+fn test_summary_from_header_bytes() {
+    let summary = MovieSummary::from_header_bytes(&MOVIE_1KEY_BYTES[..0x400]).unwrap();
+    let full = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap().summary();
+
+    assert_eq!(summary.input_hash, None);
+    assert_eq!(
+        summary,
+        MovieSummary {
+            input_hash: None,
+            ..full
+        }
+    );
+
+    assert!(MovieSummary::from_header_bytes(&MOVIE_1KEY_BYTES[..0x100]).is_err());
+}
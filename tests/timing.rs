@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use m64_movie::{
+    BinReadExt, Movie,
+    region::{CountryCode, Region},
+    timing::Timing,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_country_code_region() {
+    assert_eq!(CountryCode::from(0x4A), CountryCode::Japan);
+    assert_eq!(CountryCode::from(0x50).region(), Region::Pal);
+    assert_eq!(CountryCode::from(0x45).region(), Region::Ntsc);
+    assert_eq!(CountryCode::from(0x59), CountryCode::OtherEurope(0x59));
+    assert_eq!(CountryCode::from(0x1234), CountryCode::Other(0x1234));
+
+    for code in 0..=0x100 {
+        assert_eq!(u16::from(CountryCode::from(code)), code);
+    }
+}
+
+#[test]
+fn test_timing_conversions() {
+    let timing = Timing::new(Region::Pal).with_vis_per_frame(2, 1);
+    assert_eq!(timing.vis_per_second(), 50);
+    assert!(!timing.is_off_region());
+
+    assert_eq!(timing.frame_to_vi(25), 50);
+    assert_eq!(timing.vi_to_frame(51), 25);
+    assert_eq!(timing.frame_to_duration(25), Duration::from_secs(1));
+    assert_eq!(timing.duration_to_frame(Duration::from_millis(1010)), 25);
+    assert_eq!(timing.vi_to_duration(5), Duration::from_millis(100));
+    assert_eq!(timing.duration_to_vi(Duration::from_millis(119)), 5);
+
+    let timing = timing.with_vis_per_second(60);
+    assert!(timing.is_off_region());
+    assert_eq!(timing.frame_to_duration(30), Duration::from_secs(1));
+}
+
+#[test]
+fn test_movie_timing() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let timing = movie.timing();
+
+    assert_eq!(timing.region(), Region::Ntsc);
+    assert_eq!(timing.vis_per_second(), 60);
+    assert_eq!(timing.frame_to_vi(7416), 15384);
+    assert_eq!(movie.duration(), Duration::from_millis(256_400));
+
+    let times = movie.frame_times().collect::<Vec<_>>();
+    assert_eq!(times.len(), 7416);
+    assert_eq!(times[0], Duration::ZERO);
+    assert!(times.is_sorted());
+    assert!(*times.last().unwrap() < movie.duration());
+}
+
+#[test]
+fn test_movie_frame_at() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    for (frame, time) in movie.frame_times().enumerate() {
+        assert_eq!(movie.frame_at(time), Some(frame));
+    }
+
+    assert_eq!(movie.frame_at(Duration::from_secs(1)), Some(28));
+    assert_eq!(movie.frame_at(movie.duration()), None);
+}
+
+#[test]
+fn test_movie_slice_by_time() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let times = movie.frame_times().collect::<Vec<_>>();
+
+    let slice = movie.slice_by_time(times[100]..times[200]).unwrap();
+    assert_eq!(slice.frame_count(), 100);
+    assert_eq!(slice.samples(), &movie.samples()[100..200]);
+    assert_eq!(
+        slice.recording_info.vertical_interrupts() as u64,
+        movie.timing().frame_to_vi(200) - movie.timing().frame_to_vi(100)
+    );
+
+    // Partially covered frames are kept.
+    let slice = movie
+        .slice_by_time(times[100] + Duration::from_millis(1)..times[200] + Duration::from_millis(1))
+        .unwrap();
+    assert_eq!(slice.samples(), &movie.samples()[100..201]);
+
+    let tail = movie
+        .slice_by_time(times[7400]..Duration::from_secs(3600))
+        .unwrap();
+    assert_eq!(tail.frame_count(), 16);
+    assert_eq!(
+        movie
+            .slice_by_time(times[10]..times[10])
+            .unwrap()
+            .frame_count(),
+        0
+    );
+}
+
+#[test]
+fn test_movie_slice_by_time_moves_frame_data() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.annotations_mut().insert(50, "before");
+    movie.annotations_mut().insert(150, "inside");
+    movie.markers_mut().insert("start", 100);
+    movie.markers_mut().insert("end", 200);
+    movie.set_lag_mask(Some([120, 7000].into_iter().collect()));
+
+    let times = movie.frame_times().collect::<Vec<_>>();
+    let slice = movie.slice_by_time(times[100]..times[200]).unwrap();
+    assert_eq!(
+        slice.annotations().iter().collect::<Vec<_>>(),
+        [(50, "inside")]
+    );
+    assert_eq!(slice.markers().get("start"), Some(0));
+    assert_eq!(slice.markers().get("end"), None);
+    assert_eq!(slice.lag_mask().unwrap().iter().collect::<Vec<_>>(), [20]);
+}
@@ -0,0 +1,228 @@
+use m64_movie::{
+    BinReadExt, ControllerButton, Movie,
+    hash::fnv1a,
+    raw::{AxisMerge, ButtonMerge, ControllerState, MergePolicy},
+    track::InputTrack,
+    transform::ResampleStrategy,
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+/// Presses A on even frames.
+fn mash_a(frame: usize) -> ControllerState {
+    let mut state = ControllerState::default();
+    if frame.is_multiple_of(2) {
+        state.set(ControllerButton::A);
+    }
+
+    state
+}
+
+#[test]
+fn test_input_track_generate() {
+    let track = InputTrack::generate(600, mash_a);
+
+    assert_eq!(track.len(), 600);
+    assert_eq!(track.iter().filter(|state| state.a_btn()).count(), 300);
+    assert!(track.get(0).unwrap().a_btn());
+    assert!(!track.get(1).unwrap().a_btn());
+    assert_eq!(track.get(600), None);
+}
+
+#[test]
+fn test_input_track_set() {
+    let mut track = InputTrack::generate(2, mash_a);
+
+    let previous = track.set(0, ControllerState::default());
+    assert!(previous.unwrap().a_btn());
+    assert!(!track.get(0).unwrap().a_btn());
+    assert_eq!(track.set(2, ControllerState::default()), None);
+}
+
+#[test]
+fn test_movie_extend_with() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let frame_count = movie.frame_count();
+
+    movie.extend_with(600, |frame, _port| mash_a(frame - frame_count));
+    assert_eq!(movie.frame_count(), frame_count + 600);
+    assert_eq!(
+        movie.to_raw().controller_input_samples as usize,
+        frame_count + 600
+    );
+
+    let track = movie.track(0).unwrap();
+    let generated = InputTrack::generate(600, mash_a);
+    assert!(track.iter().skip(frame_count).eq(generated.iter()));
+    assert!(movie.track(1).is_err());
+}
+
+#[test]
+fn test_controller_state_merge() {
+    let mut base = ControllerState::default();
+    base.set(ControllerButton::B);
+    base.set_axis(10, -20);
+
+    let mut layer = ControllerState::default();
+    layer.set(ControllerButton::A);
+    layer.set_axis(0, 127);
+
+    let merged = base.merge(&layer, MergePolicy::OVERLAY);
+    assert_eq!(
+        merged.get_pressed(),
+        [ControllerButton::B, ControllerButton::A]
+    );
+    assert_eq!(merged.axis(), (10, 127));
+
+    assert_eq!(base.merge(&layer, MergePolicy::REPLACE), layer);
+
+    let policy = MergePolicy {
+        buttons: ButtonMerge::Xor,
+        x_axis: AxisMerge::Keep,
+        y_axis: AxisMerge::Add,
+    };
+    let merged = base.merge(&base, policy);
+    assert!(merged.get_pressed().is_empty());
+    assert_eq!(merged.axis(), (10, -40));
+}
+
+#[test]
+fn test_movie_overlay() {
+    let original = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut movie = original.clone();
+    let correction = InputTrack::generate(10, mash_a).to_vec();
+
+    movie
+        .overlay(100, 0, &correction, MergePolicy::OVERLAY)
+        .unwrap();
+    for frame in 100..110 {
+        let expected =
+            original.samples()[frame].merge(&correction[frame - 100], MergePolicy::OVERLAY);
+        assert_eq!(movie.samples()[frame], expected);
+    }
+
+    let frame_count = movie.frame_count();
+    assert!(
+        movie
+            .overlay(frame_count - 5, 0, &correction, MergePolicy::OVERLAY)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_input_track_resample() {
+    let track = InputTrack::generate(10, mash_a);
+
+    let halved = track.resample(0.5, ResampleStrategy::HoldLatest);
+    assert_eq!(halved.len(), 5);
+    assert!(halved.iter().all(|state| state.is_set(ControllerButton::A)));
+
+    let doubled = track.resample(2.0, ResampleStrategy::Nearest);
+    assert_eq!(doubled.len(), 20);
+    assert_eq!(doubled.get(2), track.get(1));
+    assert_eq!(doubled.get(3), track.get(2));
+
+    let held = track.resample(2.0, ResampleStrategy::HoldLatest);
+    assert_eq!(held.get(3), track.get(1));
+    assert_eq!(track.resample(1.0, ResampleStrategy::Nearest), track);
+}
+
+#[test]
+#[should_panic]
+fn test_input_track_resample_rejects_zero() {
+    InputTrack::new().resample(0.0, ResampleStrategy::Nearest);
+}
+
+#[test]
+fn test_input_track_compress() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let plain = movie.track(0).unwrap();
+
+    let mut track = plain.clone();
+    track.compress();
+    assert!(track.is_compressed());
+    assert_eq!(track, plain);
+    assert_eq!(track.len(), plain.len());
+    assert_eq!(track.run_count(), plain.run_count());
+    assert!(track.run_count() < track.len());
+    assert!((0..plain.len()).all(|frame| track.get(frame) == plain.get(frame)));
+    assert_eq!(track.get(plain.len()), None);
+
+    track.decompress();
+    assert!(!track.is_compressed());
+    assert_eq!(track, plain);
+}
+
+#[test]
+fn test_input_track_compressed_edits() {
+    let mut plain = InputTrack::generate(12, |frame| mash_a(frame / 4));
+    let mut track = plain.clone();
+    track.compress();
+    assert_eq!(track.run_count(), 3);
+
+    let mut a = ControllerState::default();
+    a.set(ControllerButton::A);
+    let edits = [
+        (5, a),
+        (4, a),
+        (7, a),
+        (6, a),
+        (0, ControllerState::default()),
+        (11, a),
+        (12, a),
+    ];
+    for (frame, state) in edits {
+        assert_eq!(track.set(frame, state), plain.set(frame, state));
+        assert_eq!(track, plain);
+        assert_eq!(track.run_count(), plain.run_count());
+    }
+
+    for state in [a, a, ControllerState::default()] {
+        track.push(state);
+        plain.push(state);
+    }
+    track.extend([a, a]);
+    plain.extend([a, a]);
+    assert_eq!(track.to_vec(), plain.to_vec());
+    assert_eq!(Vec::from(track), plain.to_vec());
+}
+
+#[test]
+fn test_input_track_hash_prefix() {
+    let mut track = InputTrack::generate(100, mash_a);
+    let bytes: Vec<u8> = track
+        .iter()
+        .take(40)
+        .flat_map(|state| u32::from(state).to_le_bytes())
+        .collect();
+    assert_eq!(track.hash_prefix(40), Some(fnv1a(&bytes)));
+    assert_eq!(track.hash_prefix(0), Some(fnv1a(&[])));
+    assert_eq!(track.hash_prefix(101), None);
+
+    let before: Vec<_> = (0..=100).map(|frame| track.hash_prefix(frame)).collect();
+    let mut compressed = track.clone();
+    compressed.compress();
+
+    for track in [&mut track, &mut compressed] {
+        track.set(60, ControllerState::default());
+        for (frame, &before) in before.iter().enumerate() {
+            let unchanged = track.hash_prefix(frame) == before;
+            assert_eq!(unchanged, frame <= 60, "frame {frame}");
+        }
+
+        // Setting a frame to the state it holds keeps every hash.
+        let after: Vec<_> = (0..=100).map(|frame| track.hash_prefix(frame)).collect();
+        track.set(10, mash_a(10));
+        assert!(
+            after
+                .iter()
+                .enumerate()
+                .all(|(frame, &after)| track.hash_prefix(frame) == after)
+        );
+    }
+
+    assert_eq!(track.hash_prefix(100), compressed.hash_prefix(100));
+    track.push(ControllerState::default());
+    assert_eq!(track.hash_prefix(100), compressed.hash_prefix(100));
+}
@@ -0,0 +1,221 @@
+use m64_movie::{
+    BinReadExt, ControllerButton, Movie,
+    raw::ControllerState,
+    region::Region,
+    transform::{
+        RemapProfile, ResampleStrategy, palindrome, quantize_axes, remap_buttons, retime, reverse,
+        reverse_port,
+    },
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_remap_profile_apply() {
+    let profile = RemapProfile::new()
+        .swap(ControllerButton::A, ControllerButton::B)
+        .with(ControllerButton::Z, ControllerButton::TriggerLeft);
+
+    let state = ControllerState::build()
+        .a()
+        .z()
+        .start()
+        .stick(-5, 60)
+        .done();
+
+    let remapped = profile.apply(&state);
+    assert!(remapped.is_set(ControllerButton::B));
+    assert!(remapped.is_set(ControllerButton::TriggerLeft));
+    assert!(remapped.is_set(ControllerButton::Start));
+    assert!(!remapped.is_set(ControllerButton::A));
+    assert!(!remapped.is_set(ControllerButton::Z));
+    assert_eq!(remapped.axis(), (-5, 60));
+}
+
+#[test]
+fn test_remap_buttons_swap_twice_is_identity() {
+    let original = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut movie = original.clone();
+    let profile = RemapProfile::new().swap(ControllerButton::A, ControllerButton::B);
+
+    remap_buttons(&mut movie, 0, &profile).unwrap();
+    assert_ne!(movie.samples(), original.samples());
+    assert_eq!(
+        movie.samples().iter().filter(|s| s.a_btn()).count(),
+        original.samples().iter().filter(|s| s.b_btn()).count()
+    );
+
+    remap_buttons(&mut movie, 0, &profile).unwrap();
+    assert_eq!(movie, original);
+
+    assert!(remap_buttons(&mut movie, 3, &profile).is_err());
+}
+
+#[test]
+fn test_retime_ntsc_to_pal() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    let mut pal = movie.clone();
+    retime(
+        &mut pal,
+        Region::Ntsc,
+        Region::Pal,
+        ResampleStrategy::HoldLatest,
+    );
+    assert_eq!(pal.frame_count(), 6180);
+    assert_eq!(pal.recording_info.vertical_interrupts(), 12820);
+    assert_eq!(pal.recording_info.vis_per_second, 50);
+    assert_eq!(pal.game_info.rom_country, 0x50);
+    // Every sixth NTSC frame is dropped.
+    assert_eq!(pal.frame(5), movie.frame(6));
+
+    let mut nearest = movie.clone();
+    retime(
+        &mut nearest,
+        Region::Ntsc,
+        Region::Pal,
+        ResampleStrategy::Nearest,
+    );
+    assert_eq!(nearest.frame(2), movie.frame(2));
+    assert_eq!(nearest.frame(3), movie.frame(4));
+
+    retime(
+        &mut pal,
+        Region::Pal,
+        Region::Ntsc,
+        ResampleStrategy::HoldLatest,
+    );
+    assert_eq!(pal.frame_count(), 7416);
+    assert_eq!(pal.game_info.rom_country, 0x45);
+}
+
+#[test]
+fn test_retime_moves_frame_data() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.markers_mut().insert("door", 6);
+    movie.markers_mut().insert("end", 7416);
+    movie.annotations_mut().insert(12, "jump");
+    movie.set_lag_mask(Some([5, 6].into_iter().collect()));
+
+    retime(
+        &mut movie,
+        Region::Ntsc,
+        Region::Pal,
+        ResampleStrategy::HoldLatest,
+    );
+    assert_eq!(movie.markers().get("door"), Some(5));
+    assert_eq!(movie.markers().get("end"), Some(6180));
+    assert_eq!(movie.annotations().get(10), Some("jump"));
+    assert_eq!(movie.lag_mask().unwrap().ranges(), [5..=5]);
+}
+
+#[test]
+fn test_reverse_twice_is_identity() {
+    let original = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut movie = original.clone();
+
+    reverse(&mut movie);
+    assert_ne!(movie, original);
+    assert_eq!(movie.frame_count(), original.frame_count());
+    assert_eq!(movie.frame(0), original.frame(original.frame_count() - 1));
+
+    reverse(&mut movie);
+    assert_eq!(movie, original);
+}
+
+#[test]
+fn test_reverse_mirrors_frame_data() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.markers_mut().insert("door", 100);
+    movie.annotations_mut().insert(0, "start");
+    movie.set_lag_mask(Some([7414, 7415].into_iter().collect()));
+
+    reverse(&mut movie);
+    assert_eq!(movie.markers().get("door"), Some(7315));
+    assert_eq!(movie.annotations().get(7415), Some("start"));
+    assert_eq!(movie.lag_mask().unwrap().ranges(), [0..=1]);
+
+    palindrome(&mut movie);
+    assert_eq!(movie.markers().get("door"), Some(7315));
+    assert_eq!(movie.annotations().len(), 1);
+    assert_eq!(movie.lag_mask().unwrap().ranges(), [0..=1]);
+}
+
+#[test]
+fn test_reverse_port() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let samples = movie.remove_frames(0..movie.frame_count()).unwrap();
+    let mut flags = movie.controller_flags();
+    flags.set_controller_02_present(true);
+    movie.set_controller_flags(flags).unwrap();
+    movie.extend_with(samples.len(), |frame, port| {
+        samples[if port == 0 {
+            frame
+        } else {
+            samples.len() - 1 - frame / 2
+        }]
+    });
+    let original = movie.clone();
+
+    reverse_port(&mut movie, 1).unwrap();
+    assert_eq!(movie.track(0).unwrap(), original.track(0).unwrap());
+    assert!(
+        movie
+            .track(1)
+            .unwrap()
+            .iter()
+            .eq(original.track(1).unwrap().to_vec().into_iter().rev())
+    );
+
+    assert!(reverse_port(&mut movie, 2).is_err());
+}
+
+#[test]
+fn test_palindrome() {
+    let original = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let mut movie = original.clone();
+
+    palindrome(&mut movie);
+    let frame_count = original.frame_count();
+    assert_eq!(movie.frame_count(), 2 * frame_count);
+    assert_eq!(
+        movie.recording_info.vertical_interrupts(),
+        2 * original.recording_info.vertical_interrupts()
+    );
+    assert!(
+        (0..frame_count)
+            .all(|frame| movie.frame(frame) == movie.frame(2 * frame_count - 1 - frame))
+    );
+}
+
+#[test]
+fn test_quantize_axes() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let values = [(0, 0), (3, 4), (5, -4), (-5, 12), (126, -128), (127, -127)];
+    for (frame, &(x, y)) in values.iter().enumerate() {
+        movie.port_state_mut(frame, 0).unwrap().set_axis(x, y);
+    }
+
+    let mut quantized = movie.clone();
+    let changed = quantize_axes(&mut quantized, 8);
+    assert!(changed > 0);
+    let snapped: Vec<(i8, i8)> = (0..values.len())
+        .map(|frame| quantized.port_state(frame, 0).unwrap().axis())
+        .collect();
+    assert_eq!(
+        snapped,
+        [(0, 0), (0, 0), (8, 0), (-8, 8), (120, -128), (120, -128)]
+    );
+    assert!(
+        quantized
+            .samples()
+            .iter()
+            .all(|state| state.x_axis() % 8 == 0 && state.y_axis() % 8 == 0)
+    );
+    assert_eq!(quantize_axes(&mut quantized, 8), 0);
+
+    let mut unchanged = movie.clone();
+    assert_eq!(quantize_axes(&mut unchanged, 1), 0);
+    assert_eq!(unchanged, movie);
+}
@@ -0,0 +1,151 @@
+use m64_movie::{
+    BinReadExt, Movie, MovieError, RomError,
+    raw::MovieStartType,
+    verify::{CheckStatus, Companions, RomFormat, RomHeader, verify},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+/// Builds a minimal big-endian ROM image with the given header values.
+fn make_rom(crc1_be: u32, name: &str, country: u8) -> Vec<u8> {
+    let mut rom = vec![0u8; 0x1000];
+    rom[0..4].copy_from_slice(&0x8037_1240u32.to_be_bytes());
+    rom[0x10..0x14].copy_from_slice(&crc1_be.to_be_bytes());
+
+    let mut padded = [b' '; 20];
+    padded[..name.len()].copy_from_slice(name.as_bytes());
+    rom[0x20..0x34].copy_from_slice(&padded);
+    rom[0x3E] = country;
+
+    rom
+}
+
+#[test]
+fn test_rom_header_matches_movie_representation() {
+    let rom = make_rom(0x4EAA_3D0E, "SUPER MARIO 64", b'J');
+    let header = RomHeader::from_bytes(&rom).unwrap();
+
+    assert_eq!(header.crc32, 0x0E3D_AA4E);
+    assert_eq!(header.name, "SUPER MARIO 64");
+    assert_eq!(header.country, b'J' as u16);
+}
+
+#[test]
+fn test_verify_byte_order_dumps() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let rom = make_rom(0x4EAA_3D0E, "SUPER MARIO 64", b'J');
+
+    for format in [RomFormat::ByteSwapped, RomFormat::LittleEndian] {
+        // Both conversions are their own inverse, so this also produces the dump.
+        let dump = format.to_big_endian(&rom);
+        assert_eq!(RomFormat::detect(&dump), Some(format));
+        assert_eq!(format.to_big_endian(&dump), rom);
+
+        let header = RomHeader::from_bytes(&dump).unwrap();
+        assert_eq!(header.format, format);
+        assert_eq!(header.crc32, 0x0E3D_AA4E);
+        assert_eq!(header.name, "SUPER MARIO 64");
+
+        let report = verify(&movie, &dump, &Companions::default()).unwrap();
+        assert!(report.passed(), "{}", report);
+        let warned = report
+            .with_status(CheckStatus::Warn)
+            .map(|check| check.name)
+            .collect::<Vec<_>>();
+        assert_eq!(warned, ["ROM format"]);
+    }
+}
+
+#[test]
+fn test_verify_matching_rom() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    assert_eq!(movie.recording_info.start_type, MovieStartType::PowerOn);
+
+    let rom = make_rom(0x4EAA_3D0E, "SUPER MARIO 64", b'J');
+    let report = verify(&movie, &rom, &Companions::default()).unwrap();
+
+    assert!(report.passed(), "{}", report);
+    assert_eq!(report.with_status(CheckStatus::Fail).count(), 0);
+}
+
+#[test]
+fn test_verify_wrong_rom() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    // The USA revision of the same game.
+    let rom = make_rom(0x635A_2BFF, "SUPER MARIO 64", b'E');
+    let report = verify(&movie, &rom, &Companions::default()).unwrap();
+
+    assert!(!report.passed());
+    let failed = report
+        .with_status(CheckStatus::Fail)
+        .map(|check| check.name)
+        .collect::<Vec<_>>();
+    assert_eq!(failed, ["ROM CRC", "ROM country"]);
+}
+
+#[test]
+fn test_verify_snapshot_requires_savestate() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.recording_info.start_type = MovieStartType::Snapshot;
+
+    let rom = make_rom(0x4EAA_3D0E, "SUPER MARIO 64", b'J');
+    let report = verify(&movie, &rom, &Companions::default()).unwrap();
+    assert!(!report.passed());
+
+    let savestate = [0x1F, 0x8B, 0x08];
+    let companions = Companions {
+        savestate: Some(&savestate),
+        save: None,
+    };
+    let report = verify(&movie, &rom, &companions).unwrap();
+    assert!(report.passed(), "{}", report);
+}
+
+#[test]
+fn test_verify_rejects_unknown_rom_format() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+
+    let result = verify(&movie, &[0u8; 0x40], &Companions::default());
+    assert!(matches!(
+        result,
+        Err(MovieError::RomError(RomError::UnknownFormat(0)))
+    ));
+
+    let result = verify(&movie, &[0u8; 4], &Companions::default());
+    assert!(matches!(
+        result,
+        Err(MovieError::RomError(RomError::TooShort(4)))
+    ));
+}
+
+#[test]
+fn test_verify_roundtrip() {
+    let report = m64_movie::verify_roundtrip(MOVIE_1KEY_BYTES).unwrap();
+    assert!(report.is_lossless(), "{report}");
+    assert_eq!(report.original_len, report.written_len);
+
+    let mut bytes = MOVIE_1KEY_BYTES.to_vec();
+    bytes[0x44] = 0xAB;
+    bytes.extend_from_slice(&[1, 2]);
+
+    let report = m64_movie::verify_roundtrip(&bytes).unwrap();
+    assert!(!report.is_lossless());
+    assert_eq!(report.written_len, MOVIE_1KEY_BYTES.len());
+    assert_eq!(
+        report
+            .diffs
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        [
+            "reserved02 (0x044)".to_string(),
+            format!(
+                "length differs: {} vs {} bytes",
+                bytes.len(),
+                MOVIE_1KEY_BYTES.len()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,50 @@
+use m64_movie::{
+    BinReadExt, Movie,
+    parsed::{ExtendedData, ExtendedFlags},
+    version::{MovieVersion, VersionInfo},
+};
+
+static MOVIE_1KEY_BYTES: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/movies/1key.m64"));
+
+#[test]
+fn test_version_info_of_v3_movie() {
+    let movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    let info = movie.version_info();
+
+    assert!(matches!(info, VersionInfo::V3(_)));
+    assert_eq!(info.version(), 3);
+    assert_eq!(
+        info.extended_version(),
+        Some(movie.metadata.extended_version)
+    );
+}
+
+#[test]
+fn test_version_info_extended_fields() {
+    let mut movie = Movie::from_bytes(MOVIE_1KEY_BYTES).unwrap();
+    movie.metadata.extended_version = 0;
+    movie.metadata.extended_flags = ExtendedFlags::ExtendedFlagsV0;
+    movie.metadata.extended_data = ExtendedData::ExtendedDataV0;
+
+    let info = movie.version_info();
+    assert_eq!(info.wiivc_emulation_mode(), None);
+    assert_eq!(info.rerecord_count_high(), None);
+
+    movie.metadata.extended_version = 1;
+    movie.metadata.extended_flags = ExtendedFlags::ExtendedFlagsV1 {
+        wiivc_emulation_mode: true,
+    };
+    movie.metadata.extended_data = ExtendedData::ExtendedDataV1 {
+        authorship_info: 1,
+        bruteforce_data: 2,
+        rerecord_count_high: 3,
+    };
+
+    let info = movie.version_info();
+    assert_eq!(info.extended_version(), Some(1));
+    assert_eq!(info.wiivc_emulation_mode(), Some(true));
+    assert_eq!(info.authorship_info(), Some(1));
+    assert_eq!(info.bruteforce_data(), Some(2));
+    assert_eq!(info.rerecord_count_high(), Some(3));
+}